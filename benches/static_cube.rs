@@ -0,0 +1,49 @@
+//! Benchmarks simulating the static cube scene, where every surface is static.
+//! Run with `cargo bench --bench static_cube`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use demo::{
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::SceneData,
+    scene_builder::static_cube_scene,
+    simulation_config::{SimulationConfig, TraversalMode},
+    DEFAULT_SAMPLE_RATE,
+};
+
+/// The number of rays simulated per iteration.
+const RAYS: u32 = 50;
+
+fn simulate_static_cube(c: &mut Criterion) {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(static_cube_scene()).unwrap();
+    let mut group = c.benchmark_group("static_cube");
+    group.sample_size(10);
+    for (name, traversal_mode) in [
+        ("chunked", TraversalMode::Chunked),
+        ("brute_force", TraversalMode::BruteForce),
+    ] {
+        let config = SimulationConfig {
+            traversal_mode,
+            threads: Some(1),
+            ..SimulationConfig::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                scene_data
+                    .simulate_at_time(
+                        0,
+                        RAYS,
+                        DEFAULT_PROPAGATION_SPEED,
+                        DEFAULT_SAMPLE_RATE,
+                        false,
+                        true,
+                        &config,
+                    )
+                    .unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, simulate_static_cube);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+use std::io::Write;
+
+use nalgebra::Vector3;
+
+use crate::{
+    error::DemoError,
+    interpolation::Interpolation,
+    scene::{Emitter, Scene},
+};
+
+/// The receiver's movement at a single sample.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ReceiverKinematics {
+    /// The time in samples.
+    pub time: u32,
+    /// The receiver's position at `time`.
+    pub position: Vector3<f64>,
+    /// The receiver's velocity in meters per second,
+    /// calculated from its positions at `time` and `time + 1`.
+    pub velocity: Vector3<f64>,
+    /// The expected Doppler factor for sound travelling directly from the emitter to the receiver,
+    /// i.e. the ratio of received to emitted frequency.
+    pub doppler_factor: f64,
+}
+
+/// Calculate the receiver's kinematics for each of the first `number_of_samples` samples.
+/// The positions are interpolated just like for a snapshot of the scene,
+/// so they are loop-aware and match what rays are checked against.
+/// Velocities are finite differences between consecutive samples,
+/// both for the receiver and for the emitter when calculating the Doppler factor.
+///
+/// # Arguments
+/// * `scene`: The scene to analyse.
+/// * `sample_rate`: The sample rate, used to convert velocities to meters per second.
+/// * `propagation_speed`: The speed of sound in meters per second.
+/// * `number_of_samples`: The number of samples to analyse, starting at 0.
+///
+/// # Errors
+///
+/// * If the scene isn't valid, see `Scene::validate`.
+///
+/// # Panics
+///
+/// * If interpolating the receiver or emitter somehow doesn't return coordinates. This shouldn't be able to happen.
+pub fn receiver_kinematics(
+    scene: &Scene,
+    sample_rate: f64,
+    propagation_speed: f64,
+    number_of_samples: u32,
+) -> Result<Vec<ReceiverKinematics>, DemoError> {
+    scene.validate()?;
+    let receiver_position = |time: u32| {
+        let looped_time = scene.looped_time(scene.receiver.loop_duration(), time);
+        let Some(coords) = scene.receiver.at_time(looped_time).center() else {
+            panic!("at_time() somehow returned a non-interpolated receiver. This shouldn't happen.")
+        };
+        coords
+    };
+    let emitter_position = |time: u32| {
+        let Emitter::Interpolated(coords, _time, _emission_type) =
+            scene.emitter.at_time(scene.looped_time(None, time))
+        else {
+            panic!("at_time() somehow returned a non-interpolated emitter. This shouldn't happen.")
+        };
+        coords
+    };
+    Ok((0..number_of_samples)
+        .map(|time| {
+            let position = receiver_position(time);
+            let velocity = (receiver_position(time + 1) - position) * sample_rate;
+            let emitter = emitter_position(time);
+            let emitter_velocity = (emitter_position(time + 1) - emitter) * sample_rate;
+            ReceiverKinematics {
+                time,
+                position,
+                velocity,
+                doppler_factor: doppler_factor(
+                    position,
+                    velocity,
+                    emitter,
+                    emitter_velocity,
+                    propagation_speed,
+                ),
+            }
+        })
+        .collect())
+}
+
+/// Calculate the Doppler factor for sound travelling from the emitter to the receiver.
+/// If both are at the same position, there is no direction to the emitter and 1 is returned.
+fn doppler_factor(
+    receiver_position: Vector3<f64>,
+    receiver_velocity: Vector3<f64>,
+    emitter_position: Vector3<f64>,
+    emitter_velocity: Vector3<f64>,
+    propagation_speed: f64,
+) -> f64 {
+    let Some(to_emitter) = (emitter_position - receiver_position).try_normalize(0f64) else {
+        return 1f64;
+    };
+    (propagation_speed + receiver_velocity.dot(&to_emitter))
+        / (propagation_speed + emitter_velocity.dot(&to_emitter))
+}
+
+/// Write the given kinematics to the given writer in CSV format.
+/// Each row holds the time, the receiver's position and velocity x/y/z values and the Doppler factor.
+///
+/// # Errors
+///
+/// * If writing to `writer` fails.
+pub fn write_kinematics_csv<W: Write>(
+    kinematics: &[ReceiverKinematics],
+    writer: &mut W,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "time;position_x;position_y;position_z;velocity_x;velocity_y;velocity_z;doppler_factor"
+    )?;
+    for row in kinematics {
+        writeln!(
+            writer,
+            "{};{};{};{};{};{};{};{}",
+            row.time,
+            row.position.x,
+            row.position.y,
+            row.position.z,
+            row.velocity.x,
+            row.velocity.y,
+            row.velocity.z,
+            row.doppler_factor
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,410 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use wav::BitDepth;
+
+/// The format to write simulation results in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// A WAV file with the same bit depth as the input.
+    #[default]
+    Same,
+    /// A 32-bit float WAV file, regardless of the input's bit depth.
+    Float32,
+    /// Raw little-endian `f64` samples, plus a JSON sidecar file describing them.
+    Float64Raw,
+}
+
+impl OutputFormat {
+    /// Get the output format with the given command line name
+    /// (`same`, `f32` or `f64-raw`), if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "same" => Some(Self::Same),
+            "f32" => Some(Self::Float32),
+            "f64-raw" => Some(Self::Float64Raw),
+            _ => None,
+        }
+    }
+}
+
+/// Audio read from an input file.
+/// Regardless of the input format, the data is represented as a `wav::BitDepth`
+/// so it can be passed to the simulation directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioInput {
+    pub sample_rate: u32,
+    pub channel_count: u16,
+    pub data: BitDepth,
+}
+
+impl AudioInput {
+    /// Create a WAV header matching this input's sample rate, channel count and bit depth.
+    /// This is used to write the simulation result back out.
+    pub fn wav_header(&self) -> wav::Header {
+        let (format, bits_per_sample) = match self.data {
+            BitDepth::Eight(_) => (wav::WAV_FORMAT_PCM, 8),
+            BitDepth::TwentyFour(_) => (wav::WAV_FORMAT_PCM, 24),
+            BitDepth::ThirtyTwoFloat(_) => (wav::WAV_FORMAT_IEEE_FLOAT, 32),
+            BitDepth::Sixteen(_) | BitDepth::Empty => (wav::WAV_FORMAT_PCM, 16),
+        };
+        wav::Header::new(
+            format,
+            self.channel_count,
+            self.sample_rate,
+            bits_per_sample,
+        )
+    }
+}
+
+/// Errors that can occur while reading an audio file.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum AudioIoError {
+    /// The file couldn't be opened or read.
+    Io(std::io::Error),
+    /// The FLAC decoder failed.
+    Flac(claxon::Error),
+    /// The OGG/Vorbis decoder failed.
+    Vorbis(lewton::VorbisError),
+    /// The file extension doesn't belong to a supported format.
+    UnsupportedFormat(String),
+}
+
+impl Display for AudioIoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't read audio file: {err}"),
+            Self::Flac(err) => write!(f, "couldn't decode FLAC file: {err}"),
+            Self::Vorbis(err) => write!(f, "couldn't decode OGG/Vorbis file: {err}"),
+            Self::UnsupportedFormat(extension) => {
+                write!(f, "unsupported audio format \"{extension}\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AudioIoError {}
+
+impl From<std::io::Error> for AudioIoError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<claxon::Error> for AudioIoError {
+    fn from(err: claxon::Error) -> Self {
+        Self::Flac(err)
+    }
+}
+
+impl From<lewton::VorbisError> for AudioIoError {
+    fn from(err: lewton::VorbisError) -> Self {
+        Self::Vorbis(err)
+    }
+}
+
+/// Errors that can occur while reading the simulation's input.
+#[derive(Debug)]
+pub enum InputError {
+    /// The input file couldn't be opened or decoded.
+    Audio(AudioIoError),
+    /// The input file doesn't contain any samples.
+    Empty,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Audio(err) => write!(f, "{err}"),
+            Self::Empty => write!(f, "the input file doesn't contain any samples"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<AudioIoError> for InputError {
+    fn from(err: AudioIoError) -> Self {
+        Self::Audio(err)
+    }
+}
+
+/// Read the simulation input from the audio file at the given path, see `open_audio`.
+/// Returns a WAV header describing the input alongside its data.
+///
+/// # Errors
+///
+/// * If the file can't be opened or decoded.
+/// * If the file doesn't contain any samples.
+pub fn read_input(path: &Path) -> Result<(wav::Header, BitDepth), InputError> {
+    let input = open_audio(path)?;
+    if sample_count(&input.data) == 0 {
+        return Err(InputError::Empty);
+    }
+    Ok((input.wav_header(), input.data))
+}
+
+/// Create an input consisting of a single full scale sample at the given sample rate.
+/// Simulating it results in the scene's impulse response.
+pub fn unit_impulse_input(sample_rate: u32) -> (wav::Header, BitDepth) {
+    (
+        wav::Header::new(wav::WAV_FORMAT_IEEE_FLOAT, 1, sample_rate, 32),
+        BitDepth::ThirtyTwoFloat(vec![1f32]),
+    )
+}
+
+/// Get the number of samples (across all channels) in the given data.
+pub const fn sample_count(data: &BitDepth) -> usize {
+    match data {
+        BitDepth::Eight(data) => data.len(),
+        BitDepth::Sixteen(data) => data.len(),
+        BitDepth::TwentyFour(data) => data.len(),
+        BitDepth::ThirtyTwoFloat(data) => data.len(),
+        BitDepth::Empty => 0,
+    }
+}
+
+/// Describe the given input's sample rate, channel count and duration.
+pub fn describe_input(header: &wav::Header, data: &BitDepth) -> String {
+    let samples = sample_count(data);
+    let frames = samples / usize::from(header.channel_count.max(1));
+    format!(
+        "Input: {} Hz, {} channel(s), {:.3}s ({samples} samples)",
+        header.sampling_rate,
+        header.channel_count,
+        frames as f64 / f64::from(header.sampling_rate)
+    )
+}
+
+/// Get a note explaining the effects of the given sample rate,
+/// if it differs from `DEFAULT_SAMPLE_RATE`.
+/// Keyframe times and ray velocities are given per sample,
+/// so scenes built for a different sample rate behave differently per sample.
+pub fn sample_rate_note(sample_rate: u32) -> Option<String> {
+    (f64::from(sample_rate) != crate::DEFAULT_SAMPLE_RATE).then(|| {
+        format!(
+            "Note: the input's sample rate of {sample_rate} Hz differs from the default of {} Hz. Scene keyframe times and the rays' velocity per sample are based on {sample_rate} samples per second.",
+            crate::DEFAULT_SAMPLE_RATE
+        )
+    })
+}
+
+/// Open the audio file at the given path.
+/// The format is determined by the file extension (case-insensitive):
+/// * `.wav`: read via the `wav` crate, keeping the original bit depth.
+/// * `.flac`: decoded via `claxon`. Files with up to 16 bits per sample are returned as `i16`,
+///   files with more bits per sample as `f32`.
+/// * `.ogg`/`.oga`: decoded via `lewton`, returned as `i16`.
+///
+/// # Errors
+///
+/// * If the file can't be opened or decoded.
+/// * If the file extension is missing or unsupported.
+pub fn open_audio(path: &Path) -> Result<AudioInput, AudioIoError> {
+    let extension = path
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "wav" => open_wav(path),
+        "flac" => open_flac(path),
+        "ogg" | "oga" => open_vorbis(path),
+        _ => Err(AudioIoError::UnsupportedFormat(extension)),
+    }
+}
+
+/// Read a WAV file without converting its data.
+fn open_wav(path: &Path) -> Result<AudioInput, AudioIoError> {
+    let mut file = File::open(path)?;
+    let (header, data) = wav::read(&mut file)?;
+    Ok(AudioInput {
+        sample_rate: header.sampling_rate,
+        channel_count: header.channel_count,
+        data,
+    })
+}
+
+/// Decode a FLAC file.
+/// Samples with up to 16 bits are scaled up to `i16`, samples with more bits
+/// are normalised into `f32` between -1 and 1.
+fn open_flac(path: &Path) -> Result<AudioInput, AudioIoError> {
+    let mut reader = claxon::FlacReader::open(path)?;
+    let info = reader.streaminfo();
+    let bits_per_sample = info.bits_per_sample;
+    let data = if bits_per_sample <= 16 {
+        let shift = 16 - bits_per_sample;
+        BitDepth::Sixteen(
+            reader
+                .samples()
+                .map(|sample| sample.map(|value| (value << shift) as i16))
+                .collect::<Result<_, _>>()?,
+        )
+    } else {
+        let max_value = f64::from(1u32 << (bits_per_sample - 1));
+        BitDepth::ThirtyTwoFloat(
+            reader
+                .samples()
+                .map(|sample| sample.map(|value| (f64::from(value) / max_value) as f32))
+                .collect::<Result<_, _>>()?,
+        )
+    };
+    Ok(AudioInput {
+        sample_rate: info.sample_rate,
+        channel_count: info.channels as u16,
+        data,
+    })
+}
+
+/// Decode an OGG/Vorbis file into interleaved `i16` samples.
+fn open_vorbis(path: &Path) -> Result<AudioInput, AudioIoError> {
+    let file = File::open(path)?;
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(file)?;
+    let mut data: Vec<i16> = vec![];
+    while let Some(packet) = reader.read_dec_packet_itl()? {
+        data.extend_from_slice(&packet);
+    }
+    Ok(AudioInput {
+        sample_rate: reader.ident_hdr.audio_sample_rate,
+        channel_count: u16::from(reader.ident_hdr.audio_channels),
+        data: BitDepth::Sixteen(data),
+    })
+}
+
+/// Cast the given samples to the bit depth of `template`.
+/// Samples exceeding that bit depth's range are clipped.
+/// Returns the result and whether any samples had to be clipped.
+pub fn to_bit_depth_of(buffer: &[f64], template: &BitDepth) -> (BitDepth, bool) {
+    match template {
+        BitDepth::Eight(_) => {
+            let (data, had_to_clip) = cast_clipping(buffer);
+            (BitDepth::Eight(data), had_to_clip)
+        }
+        BitDepth::Sixteen(_) => {
+            let (data, had_to_clip) = cast_clipping(buffer);
+            (BitDepth::Sixteen(data), had_to_clip)
+        }
+        BitDepth::TwentyFour(_) => {
+            let (data, had_to_clip) = cast_clipping(buffer);
+            (BitDepth::TwentyFour(data), had_to_clip)
+        }
+        BitDepth::ThirtyTwoFloat(_) => {
+            let (data, had_to_clip) = cast_clipping(buffer);
+            (BitDepth::ThirtyTwoFloat(data), had_to_clip)
+        }
+        BitDepth::Empty => (BitDepth::Empty, false),
+    }
+}
+
+/// Cast the given samples to `T`, clipping them to `T`'s range where necessary.
+fn cast_clipping<T: num::NumCast + num::Bounded>(buffer: &[f64]) -> (Vec<T>, bool) {
+    let mut had_to_clip = false;
+    let data = buffer
+        .iter()
+        .map(|val| {
+            num::cast::<f64, T>(*val).unwrap_or_else(|| {
+                had_to_clip = true;
+                if *val > 0f64 {
+                    T::max_value()
+                } else {
+                    T::min_value()
+                }
+            })
+        })
+        .collect();
+    (data, had_to_clip)
+}
+
+/// Get the value that corresponds to full scale (i.e. 1.0 as a float sample) for the given bit depth.
+/// 8-bit WAV data is unsigned, its offset of 128 is not removed.
+const fn full_scale(template: &BitDepth) -> f64 {
+    match template {
+        BitDepth::Eight(_) => 128f64,
+        BitDepth::Sixteen(_) => 32768f64,
+        BitDepth::TwentyFour(_) => 8_388_608f64,
+        BitDepth::ThirtyTwoFloat(_) | BitDepth::Empty => 1f64,
+    }
+}
+
+/// Get the samples of the given channel of the input, normalised so full scale corresponds to 1.
+/// Unlike for `full_scale`, the offset of 128 of 8-bit WAV data is removed.
+/// Returns an empty vector if the input doesn't have the channel.
+pub fn normalised_channel(input: &AudioInput, channel: usize) -> Vec<f64> {
+    let channel_count = usize::from(input.channel_count.max(1));
+    if channel >= channel_count {
+        return vec![];
+    }
+    let full_scale = full_scale(&input.data);
+    let samples: Vec<f64> = match &input.data {
+        BitDepth::Eight(data) => channel_samples(data, channel, channel_count)
+            .into_iter()
+            .map(|value| value - 128f64)
+            .collect(),
+        BitDepth::Sixteen(data) => channel_samples(data, channel, channel_count),
+        BitDepth::TwentyFour(data) => channel_samples(data, channel, channel_count),
+        BitDepth::ThirtyTwoFloat(data) => channel_samples(data, channel, channel_count),
+        BitDepth::Empty => vec![],
+    };
+    samples
+        .into_iter()
+        .map(|value| value / full_scale)
+        .collect()
+}
+
+/// Get the samples of the given channel from interleaved data.
+fn channel_samples<T: Copy + Into<f64>>(
+    data: &[T],
+    channel: usize,
+    channel_count: usize,
+) -> Vec<f64> {
+    data.iter()
+        .skip(channel)
+        .step_by(channel_count)
+        .map(|value| (*value).into())
+        .collect()
+}
+
+/// Convert samples in the value range of `template`'s bit depth to `f32` samples,
+/// where full scale corresponds to 1.
+/// Nothing is clipped, so samples may exceed 1.
+pub fn to_normalised_f32(buffer: &[f64], template: &BitDepth) -> Vec<f32> {
+    let full_scale = full_scale(template);
+    buffer.iter().map(|val| (val / full_scale) as f32).collect()
+}
+
+/// Write the given samples (in the value range of `template`'s bit depth) normalised to full scale
+/// as raw little-endian `f64` values to the given path.
+/// Alongside it, a JSON sidecar file (the path with ".json" appended) holding the sample rate,
+/// length (in samples) and channel count is written.
+///
+/// # Errors
+///
+/// * If either file can't be created or written to.
+pub fn write_f64_raw(
+    path: &Path,
+    buffer: &[f64],
+    template: &BitDepth,
+    sample_rate: u32,
+    channel_count: u16,
+) -> std::io::Result<()> {
+    let full_scale = full_scale(template);
+    let mut writer = BufWriter::new(File::create(path)?);
+    for val in buffer {
+        writer.write_all(&(val / full_scale).to_le_bytes())?;
+    }
+    writer.flush()?;
+
+    let mut sidecar_path = path.as_os_str().to_owned();
+    sidecar_path.push(".json");
+    let mut sidecar = File::create(sidecar_path)?;
+    writeln!(
+        sidecar,
+        "{{\"sample_rate\": {sample_rate}, \"length\": {}, \"channels\": {channel_count}, \"format\": \"f64le\"}}",
+        buffer.len()
+    )
+}
@@ -5,19 +5,60 @@ use rand::random;
 pub enum EmissionType {
     Random,
     Directed(Vector3<f64>),
+    /// Rays are launched in uniformly random directions within the cone around `direction`
+    /// with the given half-angle in radians, like a loudspeaker with a finite aperture.
+    DirectedCone {
+        direction: Vector3<f64>,
+        half_angle: f64,
+    },
+    /// The `number_of_rays` rays launched at once are spread evenly across the sphere
+    /// on a spherical Fibonacci lattice, see `spherical_fibonacci_direction`.
+    /// `jitter` (0 to 1) randomly offsets each direction by up to half a lattice cell,
+    /// so repeated runs don't all launch the same directions. 0 makes the directions deterministic.
+    Stratified {
+        jitter: f64,
+    },
 }
 
 impl EmissionType {
-    /// Get the direction to bounce from, depending on this emission type.
+    /// Get the direction to launch the ray with the given index out of `number_of_rays` rays in,
+    /// depending on this emission type. Only stratified emission uses the index and the number of rays.
     /// The returned value is *NOT* guaranteed to be a unit vector!
-    pub fn get_direction(&self) -> Vector3<f64> {
+    pub fn get_direction(&self, ray_index: u32, number_of_rays: u32) -> Vector3<f64> {
         match self {
             Self::Random => random_direction(),
             Self::Directed(dir) => *dir,
+            Self::DirectedCone {
+                direction,
+                half_angle,
+            } => random_direction_in_cone(direction, *half_angle),
+            Self::Stratified { jitter } => {
+                spherical_fibonacci_direction(ray_index, number_of_rays, *jitter)
+            }
         }
     }
 }
 
+/// Get the point with the given index out of `count` points on a spherical Fibonacci lattice,
+/// which covers the sphere with nearly equal spacing: the points are spread evenly in height
+/// and successively rotated by the golden angle around the z axis.
+/// `jitter` (0 to 1) randomly offsets the point's height and azimuth by up to half a lattice cell each.
+/// The returned value is guaranteed to be a unit vector.
+pub fn spherical_fibonacci_direction(index: u32, count: u32, jitter: f64) -> Vector3<f64> {
+    let golden_angle = std::f64::consts::PI * (3f64 - 5f64.sqrt());
+    let count = <f64 as From<u32>>::from(count.max(1));
+    let index = <f64 as From<u32>>::from(index);
+    // the side length of a lattice cell, as each point covers 4π / count steradians
+    let cell_width = (4f64 * std::f64::consts::PI / count).sqrt();
+
+    let height_offset = jitter * (random::<f64>() - 0.5);
+    let z = (1f64 - 2f64 * (index + 0.5 + height_offset) / count).clamp(-1f64, 1f64);
+    let radius = z.mul_add(-z, 1f64).sqrt();
+    let azimuth_offset = jitter * (random::<f64>() - 0.5) * cell_width / radius.max(cell_width);
+    let phi = golden_angle.mul_add(index, azimuth_offset);
+    Vector3::new(radius * phi.cos(), radius * phi.sin(), z)
+}
+
 /// Get a `Vector3` pointing in a random direction.
 /// The returned value is *NOT* guaranteed to be a unit vector!
 pub fn random_direction() -> Vector3<f64> {
@@ -28,6 +69,44 @@ pub fn random_direction() -> Vector3<f64> {
     )
 }
 
+/// Get the probability density per steradian of `random_direction` pointing in the given direction.
+/// Normalising points in the cube favours directions towards its corners,
+/// which are up to 3√3 times as likely as directions along the axes.
+pub fn random_direction_density(direction: &Vector3<f64>) -> f64 {
+    let max_component = direction.normalize().amax();
+    1f64 / (24f64 * max_component.powi(3))
+}
+
+/// Get the solid angle in steradians of a cone with the given half-angle in radians.
+pub fn cone_solid_angle(half_angle: f64) -> f64 {
+    std::f64::consts::TAU * (1f64 - half_angle.cos())
+}
+
+/// Get the energy weight of a ray launched in `direction` when a fraction `importance` of rays
+/// is launched uniformly within the cone around `cone_direction` with the given half-angle
+/// and the rest using `random_direction`.
+/// The weight is the ratio of `random_direction`'s density to the density of this mixture,
+/// so the weighted rays are an unbiased estimate of launching all rays using `random_direction`.
+/// Inside narrow cones, it's roughly the cone's share of all directions divided by `importance`,
+/// outside the cone it's `1 / (1 - importance)`.
+/// To keep the weights of the directions of interest from jumping between those values,
+/// the cone should be wider than what these directions cover, see `scene::RECEIVER_CONE_WIDENING`.
+pub fn importance_weight(
+    direction: &Vector3<f64>,
+    cone_direction: &Vector3<f64>,
+    half_angle: f64,
+    importance: f64,
+) -> f64 {
+    let density = random_direction_density(direction);
+    let in_cone = direction.normalize().dot(&cone_direction.normalize()) >= half_angle.cos();
+    let cone_density = if in_cone {
+        importance / cone_solid_angle(half_angle)
+    } else {
+        0f64
+    };
+    density / (1f64 - importance).mul_add(density, cone_density)
+}
+
 /// Get a `Vector3` pointing in a random direction.
 /// The returned value is guaranteed to be a unit vector.
 pub fn random_unit_direction() -> Vector3<f64> {
@@ -36,22 +115,102 @@ pub fn random_unit_direction() -> Vector3<f64> {
     res
 }
 
-/// Get a `Vector3` pointing in a random direction inside the hemisphere
+/// Get a uniformly random point inside the given triangle.
+pub fn random_point_in_triangle(triangle: &[Vector3<f64>; 3]) -> Vector3<f64> {
+    // the square root keeps the points from bunching up at the first corner
+    let first = random::<f64>().sqrt();
+    let second = random::<f64>();
+    triangle[0] * (1f64 - first)
+        + triangle[1] * (first * (1f64 - second))
+        + triangle[2] * (first * second)
+}
+
+/// The minimum cosine of the angle between a diffusely bounced ray and the surface normal.
+/// To avoid errors, diffuse bounces avoid overly flat angles.
+pub const MIN_DIFFUSE_COSINE: f64 = 0.05;
+
+/// Normals with a squared length below this, e.g. those of zero-area triangles, don't have a direction.
+pub const MIN_NORMAL_NORM_SQUARED: f64 = 1e-10;
+
+/// Check whether the given normal is too short (or not finite) to have a direction,
+/// see `MIN_NORMAL_NORM_SQUARED`. Applies to directions just the same, e.g. those of bounced rays.
+pub fn is_degenerate_normal(normal: &Vector3<f64>) -> bool {
+    let norm_squared = normal.norm_squared();
+    !norm_squared.is_finite() || norm_squared < MIN_NORMAL_NORM_SQUARED
+}
+
+/// Get a `Vector3` pointing in a uniformly random direction inside the hemisphere
 /// where the given `normal` is the vec from the center to the tip.
-/// 
-/// To avoid errors, this will avoid overly flat angles.
+///
+/// To avoid errors, this will avoid overly flat angles, see `MIN_DIFFUSE_COSINE`.
+/// If the normal is degenerate (see `is_degenerate_normal`), the direction is sampled from the whole sphere instead.
 /// The returned value is guaranteed to be a unit vector.
 pub fn random_direction_in_hemisphere(normal: &Vector3<f64>) -> Vector3<f64> {
-    let mut result = random_unit_direction();
-    while result.dot(normal) <= 0.05f64 {
-        result = random_unit_direction();
+    if is_degenerate_normal(normal) {
+        return random_unit_direction();
+    }
+    random_direction_in_cone(normal, MIN_DIFFUSE_COSINE.acos())
+}
+
+/// Get a `Vector3` pointing in a random direction inside the hemisphere
+/// where the given `normal` is the vec from the center to the tip,
+/// with directions weighted by the cosine of their angle to the normal like Lambertian reflection.
+/// This uses Malley's method: a point is sampled uniformly on the unit disk and projected up onto the hemisphere.
+///
+/// To avoid errors, this will avoid overly flat angles, see `MIN_DIFFUSE_COSINE`.
+/// Like `random_direction_in_hemisphere`, degenerate normals fall back to the whole sphere.
+/// The returned value is guaranteed to be a unit vector.
+pub fn random_cosine_weighted_direction_in_hemisphere(normal: &Vector3<f64>) -> Vector3<f64> {
+    if is_degenerate_normal(normal) {
+        return random_unit_direction();
+    }
+    // the disk is shrunk to the radius whose projection has the minimum cosine
+    let radius_squared = random::<f64>() * MIN_DIFFUSE_COSINE.mul_add(-MIN_DIFFUSE_COSINE, 1f64);
+    let cos_theta = (1f64 - radius_squared).sqrt();
+    let phi = random::<f64>() * std::f64::consts::TAU;
+    direction_around_axis(&normal.normalize(), cos_theta, phi)
+}
+
+/// Get a `Vector3` pointing in a uniformly random direction inside the cone around the given `direction`
+/// with the given half-angle in radians.
+/// The direction's angle to the cone's axis is sampled directly so that narrow cones don't need
+/// many attempts.
+/// If the half-angle is 0, `direction` is returned as is,
+/// otherwise the returned value is guaranteed to be a unit vector.
+pub fn random_direction_in_cone(direction: &Vector3<f64>, half_angle: f64) -> Vector3<f64> {
+    if half_angle == 0f64 {
+        return *direction;
     }
-    result
+    // cosines are uniformly distributed for uniformly distributed directions on the sphere
+    let cos_theta = random::<f64>().mul_add(half_angle.cos() - 1f64, 1f64);
+    let phi = random::<f64>() * std::f64::consts::TAU;
+    direction_around_axis(&direction.normalize(), cos_theta, phi)
+}
+
+/// Get the unit vector with the given cosine of its angle to the given unit `axis`,
+/// rotated around the axis by `phi` radians.
+fn direction_around_axis(axis: &Vector3<f64>, cos_theta: f64, phi: f64) -> Vector3<f64> {
+    // any vector that isn't parallel to the axis can be used to build an orthonormal basis around it
+    let helper = if axis.x.abs() < 0.9f64 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let first_tangent = axis.cross(&helper).normalize();
+    let second_tangent = axis.cross(&first_tangent);
+
+    let sin_theta = cos_theta.mul_add(-cos_theta, 1f64).max(0f64).sqrt();
+    let result =
+        axis * cos_theta + (first_tangent * phi.cos() + second_tangent * phi.sin()) * sin_theta;
+    result.normalize()
 }
 
 /// Bounce the direction vector off a surface described by the given normal.
 /// Assumes that both the direction and normal are unit vectors.
 #[allow(clippy::module_name_repetitions)]
-pub fn bounce_off_surface_with_normal(direction: Vector3<f64>, normal: &Vector3<f64>) -> Vector3<f64> {
+pub fn bounce_off_surface_with_normal(
+    direction: Vector3<f64>,
+    normal: &Vector3<f64>,
+) -> Vector3<f64> {
     direction - 2f64 * (direction.dot(normal)) * normal
 }
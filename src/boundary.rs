@@ -0,0 +1,91 @@
+use nalgebra::Vector3;
+
+use crate::materials::Material;
+
+/// A face of the axis-aligned box spanned by a scene's maximum bounds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BoundaryFace {
+    MinX,
+    MaxX,
+    MinY,
+    MaxY,
+    MinZ,
+    MaxZ,
+}
+
+impl BoundaryFace {
+    /// Get the face a ray leaves the bounds through when moving along the given axis (0 to 2),
+    /// in positive direction if `positive` is set.
+    pub const fn from_axis(axis: usize, positive: bool) -> Self {
+        match (axis, positive) {
+            (0, false) => Self::MinX,
+            (0, true) => Self::MaxX,
+            (1, false) => Self::MinY,
+            (1, true) => Self::MaxY,
+            (_, false) => Self::MinZ,
+            (_, true) => Self::MaxZ,
+        }
+    }
+
+    /// Get the axis (0 to 2) this face is perpendicular to.
+    pub const fn axis(self) -> usize {
+        match self {
+            Self::MinX | Self::MaxX => 0,
+            Self::MinY | Self::MaxY => 1,
+            Self::MinZ | Self::MaxZ => 2,
+        }
+    }
+
+    /// Get this face's unit normal, pointing into the bounds.
+    pub fn inward_normal(self) -> Vector3<f64> {
+        let mut normal = Vector3::zeros();
+        normal[self.axis()] = match self {
+            Self::MinX | Self::MinY | Self::MinZ => 1f64,
+            Self::MaxX | Self::MaxY | Self::MaxZ => -1f64,
+        };
+        normal
+    }
+
+    const fn index(self) -> usize {
+        match self {
+            Self::MinX => 0,
+            Self::MaxX => 1,
+            Self::MinY => 2,
+            Self::MaxY => 3,
+            Self::MinZ => 4,
+            Self::MaxZ => 5,
+        }
+    }
+}
+
+/// What happens to rays reaching a face of the scene's maximum bounds.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BoundaryBehaviour {
+    /// The ray leaves the scene, as if into open air. In scenes with surfaces, this counts as a leak.
+    #[default]
+    Open,
+    /// The ray is absorbed at the boundary without counting as a leak.
+    Absorb,
+    /// The ray bounces off the boundary like off a surface with the given material,
+    /// e.g. to model an infinite ground plane.
+    Reflect(Material),
+}
+
+/// The behaviour of each face of a scene's maximum bounds.
+/// By default, all faces are open.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct SceneBoundary {
+    faces: [BoundaryBehaviour; 6],
+}
+
+impl SceneBoundary {
+    /// Get the behaviour of the given face.
+    pub const fn face(&self, face: BoundaryFace) -> BoundaryBehaviour {
+        self.faces[face.index()]
+    }
+
+    /// Set the behaviour of the given face.
+    pub const fn set_face(&mut self, face: BoundaryFace, behaviour: BoundaryBehaviour) {
+        self.faces[face.index()] = behaviour;
+    }
+}
@@ -2,41 +2,105 @@ use generic_array::{ArrayLength, GenericArray};
 use itertools::Itertools;
 use nalgebra::Vector3;
 use num::integer::Average;
+use rayon::prelude::*;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::ops::Mul;
 use typenum::{operator_aliases::Cube, Unsigned};
 
 use crate::{
-    interpolation,
-    scene::{CoordinateKeyframe, Receiver, Scene, Surface, SurfaceKeyframe},
-    scene_bounds,
+    intersection::Intersectable,
+    scene::{Receiver, Scene, Surface, SurfaceVariant},
     scene_bounds::MaximumBounds,
     test_utils,
+    time::{SampleDuration, SampleTime},
 };
 
 /// A single chunk entry. Chunk entries are either static
 /// (i.e. they just hold an object index that stays in this chunk for
 /// the entirety of the scene), dynamic (i.e. they also hold timestamps
-/// for when the object enters/exits the chunk) or final (i.e. they only hold
-/// a timestamp for when the object enters the chunk). The timestamp is inclusive,
-/// meaning that at the last timestamp, the object still is within the chunk).
+/// for when the object enters/exits the chunk), final (i.e. they only hold
+/// a timestamp for when the object enters the chunk) or periodic (i.e. like dynamic, but
+/// for objects with their own loop duration, which the timestamps are within and which is held as well).
+/// The timestamp is inclusive, meaning that at the last timestamp, the object still is within the chunk).
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TimedChunkEntry {
-    Dynamic(usize, u32, u32),
+    Dynamic(usize, SampleTime, SampleTime),
     Static(usize),
-    Final(usize, u32),
+    Final(usize, SampleTime),
+    Periodic(usize, SampleTime, SampleTime, SampleDuration),
 }
 
 impl TimedChunkEntry {
     const fn object_index(&self) -> usize {
         match self {
-            Self::Dynamic(index, _entry, _exit) => *index,
+            Self::Dynamic(index, _entry, _exit) | Self::Periodic(index, _entry, _exit, _) => *index,
             Self::Static(index) => *index,
             Self::Final(index, _entry) => *index,
         }
     }
 }
 
+/// The part of an object's loop a time span covers, see `SceneChunk::objects_at_time`.
+#[derive(Clone, Copy, Debug)]
+enum LoopWindow {
+    /// The time span lies within a single loop iteration, or the object doesn't loop,
+    /// and covers the given (looped) times.
+    Within(SampleTime, SampleTime),
+    /// The time span covers the whole loop.
+    Everything,
+    /// The time span crosses a single loop boundary, covering the offsets from the first time
+    /// until the end of the loop and from the start of the loop until the second time.
+    Wrapping(SampleTime, SampleTime),
+}
+
+impl LoopWindow {
+    /// Get the part of a loop with the given duration the given time span covers.
+    /// If it spans exactly one loop boundary, it covers the offsets `[time_entry, duration - 1]`
+    /// of the first iteration and `[0, time_exit]` of the second one.
+    /// Together, these cover the whole loop if and only if no offset lies between them,
+    /// i.e. if `time_exit + 1 >= time_entry`. Spans crossing two or more loop boundaries always cover
+    /// at least one whole loop.
+    const fn new(
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
+    ) -> Self {
+        let Some(duration) = loop_duration else {
+            return Self::Within(time_entry, time_exit);
+        };
+        let duration = duration.samples();
+        let (loop_entry, loop_exit) = (
+            time_entry.samples() / duration,
+            time_exit.samples() / duration,
+        );
+        let (time_entry, time_exit) = (
+            SampleTime(time_entry.samples() % duration),
+            SampleTime(time_exit.samples() % duration),
+        );
+        if loop_entry == loop_exit {
+            Self::Within(time_entry, time_exit)
+        } else if loop_exit - loop_entry >= 2 || time_exit.samples() + 1 >= time_entry.samples() {
+            Self::Everything
+        } else {
+            Self::Wrapping(time_entry, time_exit)
+        }
+    }
+
+    /// Get the given entry's object index if the object is in the chunk during this window.
+    const fn filter_map_entry(self, entry: &TimedChunkEntry) -> Option<usize> {
+        match self {
+            Self::Within(time_entry, time_exit) => {
+                filter_map_entry_within_time(entry, time_entry, time_exit)
+            }
+            Self::Everything => Some(entry.object_index()),
+            Self::Wrapping(time_entry, time_exit) => {
+                filter_map_entry_within_time_with_loop(entry, time_entry, time_exit)
+            }
+        }
+    }
+}
+
 /// A chunk within the scene. Chunks hold a vector of `TimedChunkEntry` entries for
 /// surfaces and receivers that are inside the chunk at some point in the scene.
 #[derive(Clone, Debug)]
@@ -51,67 +115,35 @@ impl SceneChunk {
     ///
     /// For the receivers (the first vector), the index doesn't mean anything as of current
     /// as there can only be one receiver.
+    ///
+    /// For looping scenes, the time span is split into loop iterations and offsets within the loop,
+    /// see `LoopWindow::new`. Periodic entries are split using their object's own loop duration instead.
     fn objects_at_time(
         &self,
-        time_entry: u32,
-        time_exit: u32,
-        loop_duration: Option<u32>,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
     ) -> (Vec<usize>, Vec<usize>) {
-        let (loop_entry, loop_exit, time_entry, time_exit) =
-            loop_duration.map_or((0, 0, time_entry, time_exit), |duration| {
-                (
-                    time_entry / duration,
-                    time_exit / duration,
-                    time_entry % duration,
-                    time_exit % duration,
-                )
-            });
-        if loop_entry == loop_exit {
-            // everything takes place in the same loop iteration or we don't loop at all
-            (
-                self.receivers
-                    .iter()
-                    .filter_map(|entry| filter_map_entry_within_time(entry, time_entry, time_exit))
-                    .unique()
-                    .collect(),
-                self.surfaces
-                    .iter()
-                    .filter_map(|entry| filter_map_entry_within_time(entry, time_entry, time_exit))
-                    .unique()
-                    .collect(),
-            )
-        } else if loop_exit - loop_entry >= 2 || time_exit >= time_entry {
-            // if we run through the full loop all in one go, just return every object we have
-            (
-                self.receivers
-                    .iter()
-                    .map(TimedChunkEntry::object_index)
-                    .unique()
-                    .collect(),
-                self.surfaces
-                    .iter()
-                    .map(TimedChunkEntry::object_index)
-                    .unique()
-                    .collect(),
-            )
-        } else {
-            (
-                self.receivers
-                    .iter()
-                    .filter_map(|entry| {
-                        filter_map_entry_within_time_with_loop(entry, time_entry, time_exit)
-                    })
-                    .unique()
-                    .collect(),
-                self.surfaces
-                    .iter()
-                    .filter_map(|entry| {
-                        filter_map_entry_within_time_with_loop(entry, time_entry, time_exit)
-                    })
-                    .unique()
-                    .collect(),
-            )
-        }
+        let window = LoopWindow::new(time_entry, time_exit, loop_duration);
+        let filter_map_entry = |entry: &TimedChunkEntry| match entry {
+            TimedChunkEntry::Periodic(_index, _entry, _exit, own_loop_duration) => {
+                LoopWindow::new(time_entry, time_exit, Some(*own_loop_duration))
+                    .filter_map_entry(entry)
+            }
+            _ => window.filter_map_entry(entry),
+        };
+        (
+            self.receivers
+                .iter()
+                .filter_map(filter_map_entry)
+                .unique()
+                .collect(),
+            self.surfaces
+                .iter()
+                .filter_map(filter_map_entry)
+                .unique()
+                .collect(),
+        )
     }
 }
 
@@ -120,20 +152,22 @@ impl SceneChunk {
 /// accordingly.
 const fn filter_map_entry_within_time(
     entry: &TimedChunkEntry,
-    time_entry: u32,
-    time_exit: u32,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
 ) -> Option<usize> {
     match entry {
         TimedChunkEntry::Static(index) => Some(*index),
         TimedChunkEntry::Final(index, entry) => {
-            if *entry <= time_entry {
+            // the object stays in the chunk once it entered it
+            if entry.samples() <= time_exit.samples() {
                 Some(*index)
             } else {
                 None
             }
         }
-        TimedChunkEntry::Dynamic(index, entry, exit) => {
-            if *entry <= time_exit && *exit >= time_entry {
+        TimedChunkEntry::Dynamic(index, entry, exit)
+        | TimedChunkEntry::Periodic(index, entry, exit, _) => {
+            if entry.samples() <= time_exit.samples() && exit.samples() >= time_entry.samples() {
                 Some(*index)
             } else {
                 None
@@ -150,22 +184,20 @@ const fn filter_map_entry_within_time(
 /// In this case, we only need to check if one of these conditions applies:
 /// a. the object has entered the chunk between 0 and `time_exit` (=> it shows up in the new loop)
 /// b. the object hasn't exited the chunk between 0 and `time_entry` (=> it shows up in the old loop)
+///
+/// Objects that stay in the chunk until the end of the loop once they entered it always show up in the old loop.
 const fn filter_map_entry_within_time_with_loop(
     entry: &TimedChunkEntry,
-    time_entry: u32,
-    time_exit: u32,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
 ) -> Option<usize> {
     match entry {
-        TimedChunkEntry::Static(index) => Some(*index),
-        TimedChunkEntry::Final(index, time_object_entry) => {
-            if *time_object_entry <= time_entry {
-                Some(*index)
-            } else {
-                None
-            }
-        }
-        TimedChunkEntry::Dynamic(index, time_object_entry, time_object_exit) => {
-            if *time_object_entry <= time_exit || *time_object_exit >= time_entry {
+        TimedChunkEntry::Static(index) | TimedChunkEntry::Final(index, _) => Some(*index),
+        TimedChunkEntry::Dynamic(index, time_object_entry, time_object_exit)
+        | TimedChunkEntry::Periodic(index, time_object_entry, time_object_exit, _) => {
+            if time_object_entry.samples() <= time_exit.samples()
+                || time_object_exit.samples() >= time_entry.samples()
+            {
                 Some(*index)
             } else {
                 None
@@ -180,6 +212,50 @@ impl PartialEq for SceneChunk {
     }
 }
 
+/// How the entries of a scene's chunks are distributed, see `Chunks::statistics`.
+/// A chunk's entries are all of its surface and receiver `TimedChunkEntry` records.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ChunkStatistics {
+    /// The total number of chunks, set or not.
+    pub total_chunks: usize,
+    /// The number of set chunks, see `Chunks::is_chunk_set`.
+    pub set_chunks: usize,
+    /// The fewest entries a set chunk holds, or 0 if no chunk is set.
+    pub min_entries: usize,
+    /// The mean number of entries per set chunk, or 0 if no chunk is set.
+    pub mean_entries: f64,
+    /// The most entries a set chunk holds, or 0 if no chunk is set.
+    pub max_entries: usize,
+    /// The number of set chunks holding each number of entries, indexed by the number of entries.
+    pub histogram: Vec<usize>,
+    /// The number of entries across all chunks.
+    pub total_entries: usize,
+}
+
+impl Display for ChunkStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Set chunks: {} of {}",
+            self.set_chunks, self.total_chunks
+        )?;
+        writeln!(f, "Chunk entries: {}", self.total_entries)?;
+        writeln!(
+            f,
+            "Entries per set chunk: {} min, {:.3} mean, {} max",
+            self.min_entries, self.mean_entries, self.max_entries
+        )?;
+        write!(f, "{:>8} | {:>8}", "Entries", "Chunks")?;
+        for (entries, chunks) in self.histogram.iter().enumerate() {
+            if *chunks > 0 {
+                write!(f, "\n{entries:>8} | {chunks:>8}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Data necessary to describe a scene as a set of chunks.
 /// Keys for the `set_chunks` array as well as the `chunks` map
 /// are calculated as (x << 16 + y << 8 + z), with x/y/z each being
@@ -235,6 +311,46 @@ where
         coords_to_chunk_index(coords, self)
     }
 
+    /// Get the spatial extent of the whole chunk grid.
+    /// This is an alias for `maximum_bounds`.
+    pub fn world_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
+        self.maximum_bounds()
+    }
+
+    /// Check whether the given coordinates lie within the chunk grid.
+    ///
+    /// # Example
+    /// ```
+    /// use typenum::U10;
+    /// use demo::chunk::Chunks;
+    /// use std::collections::HashMap;
+    /// use generic_array::GenericArray;
+    /// use nalgebra::Vector3;
+    ///
+    /// let chunks: Chunks<U10> = Chunks {
+    ///     set_chunks: GenericArray::default(),
+    ///     chunks: HashMap::new(),
+    ///     size_x: 0.1f64,
+    ///     size_y: 0.1f64,
+    ///     size_z: 0.1f64,
+    ///     chunk_starts: Vector3::new(0f64, 0f64, 0f64),
+    /// };
+    /// assert!(chunks.is_in_bounds(&Vector3::new(0f64, 0.5f64, 0.99f64)));
+    /// assert!(!chunks.is_in_bounds(&Vector3::new(-0.01f64, 0.5f64, 0.5f64)));
+    /// assert!(!chunks.is_in_bounds(&Vector3::new(0.5f64, 1f64, 0.5f64)));
+    /// ```
+    pub fn is_in_bounds(&self, coords: &Vector3<f64>) -> bool {
+        let number_of_chunks = <f64 as From<u32>>::from(C::to_u32());
+        let relative = coords - self.chunk_starts;
+        [
+            relative.x / self.size_x,
+            relative.y / self.size_y,
+            relative.z / self.size_z,
+        ]
+        .iter()
+        .all(|index| (0f64..number_of_chunks).contains(index))
+    }
+
     /// Get the array/map key for the chunk corresponding to the given coordinates.
     /// The key is calculated as x * C^2 + y * C + z, with x, y and z being the chunk indices
     /// corresponding to the coordinates.
@@ -287,18 +403,29 @@ where
     /// assert_eq!(111, chunks.key_for_index(1, 1, 1));
     /// assert_eq!(999, chunks.key_for_index(9, 9, 9));
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// * In debug builds, if any of the indices is outside `[0, C)`.
     pub fn key_for_index(&self, x: u32, y: u32, z: u32) -> u32 {
+        debug_assert!(
+            x < C::to_u32() && y < C::to_u32() && z < C::to_u32(),
+            "Chunk index ({x}, {y}, {z}) exceeds the {} chunks per dimension!",
+            C::to_u32()
+        );
         x * C::to_u32() * C::to_u32() + y * C::to_u32() + z
     }
 
     /// Add an object with the given index to the chunk at the given key position.
     /// This will set the according `set_chunks` bit to true and, if necessary,
     /// add the chunk to the `chunks` map.
+    /// Redundant entries aren't added, see `add_entry_without_duplicates`.
     ///
     /// # Example
     /// ```
     /// use typenum::U10;
     /// use demo::chunk::{Chunks, SceneChunk, TimedChunkEntry};
+    /// use demo::time::SampleTime;
     /// use std::collections::HashMap;
     /// use generic_array::GenericArray;
     /// use nalgebra::Vector3;
@@ -313,16 +440,16 @@ where
     /// };
     ///
     /// chunks.add_surface_at(0, 0, 0, 1, None);
-    /// chunks.add_surface_at(0, 0, 0, 2, Some((10, Some(4000))));
-    /// chunks.add_surface_at(0, 0, 0, 3, Some((500, None)));
+    /// chunks.add_surface_at(0, 0, 0, 2, Some((SampleTime(10), Some(SampleTime(4000)))));
+    /// chunks.add_surface_at(0, 0, 0, 3, Some((SampleTime(500), None)));
     /// assert_eq!(true, chunks.set_chunks[0]);
     /// let chunk = chunks.chunks.get(&0).unwrap();
     /// assert_eq!(&SceneChunk {
     ///     receivers: vec![],
     ///     surfaces: vec![
     ///         TimedChunkEntry::Static(1),
-    ///         TimedChunkEntry::Dynamic(2, 10, 4000),
-    ///         TimedChunkEntry::Final(3, 500),
+    ///         TimedChunkEntry::Dynamic(2, SampleTime(10), SampleTime(4000)),
+    ///         TimedChunkEntry::Final(3, SampleTime(500)),
     ///     ]
     /// }, chunk);
     /// ```
@@ -332,14 +459,18 @@ where
         y: u32,
         z: u32,
         index: usize,
-        time: Option<(u32, Option<u32>)>,
+        time: Option<(SampleTime, Option<SampleTime>)>,
     ) {
+        self.add_surface_entry_at(x, y, z, create_chunk_entry(index, time));
+    }
+
+    /// Add the given surface entry to the chunk at the given key position, see `add_surface_at`.
+    fn add_surface_entry_at(&mut self, x: u32, y: u32, z: u32, entry: TimedChunkEntry) {
         let key = self.key_for_index(x, y, z);
         self.set_chunks[key as usize] = true;
-        let entry = create_chunk_entry(index, time);
         let chunk = self.chunks.get_mut(&key);
         if let Some(chunk) = chunk {
-            chunk.surfaces.push(entry);
+            add_entry_without_duplicates(&mut chunk.surfaces, entry);
         } else {
             self.chunks.insert(
                 key,
@@ -354,11 +485,13 @@ where
     /// Add a receiver with the given index to the chunk at the given key position.
     /// This will set the according `set_chunks` bit to true and, if necessary,
     /// add the chunk to the `chunks` map.
+    /// Redundant entries aren't added, see `add_entry_without_duplicates`.
     ///
     /// # Example
     /// ```
     /// use typenum::U10;
     /// use demo::chunk::{Chunks, SceneChunk, TimedChunkEntry};
+    /// use demo::time::SampleTime;
     /// use std::collections::HashMap;
     /// use generic_array::GenericArray;
     /// use nalgebra::Vector3;
@@ -373,8 +506,8 @@ where
     /// };
     ///
     /// chunks.add_receiver_at(0, 0, 0, 1, None);
-    /// chunks.add_receiver_at(0, 1, 1, 2, Some((10, Some(4000))));
-    /// chunks.add_receiver_at(0, 1, 1, 3, Some((700, None)));
+    /// chunks.add_receiver_at(0, 1, 1, 2, Some((SampleTime(10), Some(SampleTime(4000)))));
+    /// chunks.add_receiver_at(0, 1, 1, 3, Some((SampleTime(700), None)));
     /// assert_eq!(true, chunks.set_chunks[0]);
     /// let chunk = chunks.chunks.get(&0).unwrap();
     /// assert_eq!(&SceneChunk {
@@ -387,8 +520,8 @@ where
     /// assert_eq!(&SceneChunk {
     ///     surfaces: vec![],
     ///     receivers: vec![
-    ///         TimedChunkEntry::Dynamic(2, 10, 4000),
-    ///         TimedChunkEntry::Final(3, 700),
+    ///         TimedChunkEntry::Dynamic(2, SampleTime(10), SampleTime(4000)),
+    ///         TimedChunkEntry::Final(3, SampleTime(700)),
     ///     ]
     /// }, chunk);
     /// ```
@@ -398,14 +531,18 @@ where
         y: u32,
         z: u32,
         index: usize,
-        time: Option<(u32, Option<u32>)>,
+        time: Option<(SampleTime, Option<SampleTime>)>,
     ) {
+        self.add_receiver_entry_at(x, y, z, create_chunk_entry(index, time));
+    }
+
+    /// Add the given receiver entry to the chunk at the given key position, see `add_receiver_at`.
+    fn add_receiver_entry_at(&mut self, x: u32, y: u32, z: u32, entry: TimedChunkEntry) {
         let key = self.key_for_index(x, y, z);
         self.set_chunks[key as usize] = true;
-        let entry = create_chunk_entry(index, time);
         let chunk = self.chunks.get_mut(&key);
         if let Some(chunk) = chunk {
-            chunk.receivers.push(entry);
+            add_entry_without_duplicates(&mut chunk.receivers, entry);
         } else {
             self.chunks.insert(
                 key,
@@ -445,24 +582,103 @@ where
         self.set_chunks[key]
     }
 
+    /// Get statistics on how many entries the set chunks hold, e.g. to tune the number of chunks.
+    pub fn statistics(&self) -> ChunkStatistics {
+        let entries: Vec<usize> = self
+            .set_chunks
+            .iter()
+            .enumerate()
+            .filter(|(_key, is_set)| **is_set)
+            .map(|(key, _is_set)| {
+                self.chunks
+                    .get(&(key as u32))
+                    .map_or(0, |chunk| chunk.surfaces.len() + chunk.receivers.len())
+            })
+            .collect();
+        let max_entries = entries.iter().copied().max().unwrap_or(0);
+        let mut histogram = vec![0; max_entries + 1];
+        for count in &entries {
+            histogram[*count] += 1;
+        }
+        let total_entries = self
+            .chunks
+            .values()
+            .map(|chunk| chunk.surfaces.len() + chunk.receivers.len())
+            .sum();
+        ChunkStatistics {
+            total_chunks: self.set_chunks.len(),
+            set_chunks: entries.len(),
+            min_entries: entries.iter().copied().min().unwrap_or(0),
+            mean_entries: if entries.is_empty() {
+                0f64
+            } else {
+                entries.iter().sum::<usize>() as f64 / entries.len() as f64
+            },
+            max_entries,
+            histogram,
+            total_entries,
+        }
+    }
+
     /// Retrieve all receiver and surface indices within the chunk with the given key
     /// at the given time.
     pub fn objects_at_key_and_time(
         &self,
         key: u32,
-        time_entry: u32,
-        time_exit: u32,
-        loop_duration: Option<u32>,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
     ) -> (Vec<usize>, Vec<usize>) {
         self.chunks.get(&key).map_or_else(
             || (vec![], vec![]),
             |chunk| chunk.objects_at_time(time_entry, time_exit, loop_duration),
         )
     }
+
+    /// Add all entries of the given chunks, which need to have the same sizes and starts, to these chunks.
+    /// Redundant entries aren't added, see `add_entry_without_duplicates`.
+    fn merge(&mut self, other: Self) {
+        for (set, other_set) in self.set_chunks.iter_mut().zip(other.set_chunks) {
+            *set |= other_set;
+        }
+        for (key, other_chunk) in other.chunks {
+            let chunk = self.chunks.entry(key).or_insert_with(|| SceneChunk {
+                surfaces: vec![],
+                receivers: vec![],
+            });
+            for entry in other_chunk.surfaces {
+                add_entry_without_duplicates(&mut chunk.surfaces, entry);
+            }
+            for entry in other_chunk.receivers {
+                add_entry_without_duplicates(&mut chunk.receivers, entry);
+            }
+        }
+    }
+}
+
+/// Add the given entry to a chunk's entries, unless it's redundant:
+/// if an identical entry or a static entry for the same object already exists, nothing is added.
+/// A static entry makes all timed entries for the same object redundant, so they are removed when it's added.
+/// This way, the entries don't depend on the order they are added in.
+fn add_entry_without_duplicates(entries: &mut Vec<TimedChunkEntry>, entry: TimedChunkEntry) {
+    let index = entry.object_index();
+    if entries
+        .iter()
+        .any(|existing| *existing == entry || *existing == TimedChunkEntry::Static(index))
+    {
+        return;
+    }
+    if entry == TimedChunkEntry::Static(index) {
+        entries.retain(|existing| existing.object_index() != index);
+    }
+    entries.push(entry);
 }
 
 /// Create the `TimedChunkEntry` for the given index and time.
-const fn create_chunk_entry(index: usize, time: Option<(u32, Option<u32>)>) -> TimedChunkEntry {
+const fn create_chunk_entry(
+    index: usize,
+    time: Option<(SampleTime, Option<SampleTime>)>,
+) -> TimedChunkEntry {
     match time {
         Some((enter, exit)) => match exit {
             Some(exit) => TimedChunkEntry::Dynamic(index, enter, exit),
@@ -472,6 +688,21 @@ const fn create_chunk_entry(index: usize, time: Option<(u32, Option<u32>)>) -> T
     }
 }
 
+/// Create the `TimedChunkEntry` for the given index and time, see `create_chunk_entry`.
+/// If the object has its own loop duration, its dynamic entries are periodic with that duration.
+const fn create_chunk_entry_with_period(
+    index: usize,
+    time: Option<(SampleTime, Option<SampleTime>)>,
+    own_loop_duration: Option<SampleDuration>,
+) -> TimedChunkEntry {
+    match (create_chunk_entry(index, time), own_loop_duration) {
+        (TimedChunkEntry::Dynamic(index, enter, exit), Some(duration)) => {
+            TimedChunkEntry::Periodic(index, enter, exit, duration)
+        }
+        (entry, _) => entry,
+    }
+}
+
 impl Scene {
     /// Calculate the chunks for this scene.
     ///
@@ -487,6 +718,57 @@ impl Scene {
     /// which chunks they are in and when.
     /// This avoids excessive chunking in cases where, for example, a surface moves along an L-shaped path.
     pub fn chunks<C>(&self) -> Chunks<C>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let mut result = self.empty_chunks();
+        for (index, surface) in self.surfaces.iter().enumerate() {
+            add_surface_variant_to_chunks(surface, &mut result, index, self);
+        }
+        add_receiver_to_chunks(&self.receiver, &mut result, self);
+
+        result
+    }
+
+    /// Calculate the chunks for this scene like `chunks`, but spread the surfaces across rayon's thread pool.
+    /// Each thread fills its own chunks, which are merged at the end, so the result is the same as `chunks`'
+    /// up to the order of each chunk's entries.
+    /// This only pays off for scenes with many surfaces.
+    pub fn parallel_chunks<C>(&self) -> Chunks<C>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        // calculating the bounds can be expensive, so only do it once
+        let empty_chunks = self.empty_chunks();
+        let mut result = self
+            .surfaces
+            .par_iter()
+            .enumerate()
+            .fold(
+                || empty_chunks.clone(),
+                |mut chunks, (index, surface)| {
+                    add_surface_variant_to_chunks(surface, &mut chunks, index, self);
+                    chunks
+                },
+            )
+            .reduce(
+                || empty_chunks.clone(),
+                |mut chunks, other| {
+                    chunks.merge(other);
+                    chunks
+                },
+            );
+        add_receiver_to_chunks(&self.receiver, &mut result, self);
+
+        result
+    }
+
+    /// Create chunks without any entries, spanning this scene's padded maximum bounds, see `chunks`.
+    fn empty_chunks<C>(&self) -> Chunks<C>
     where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
@@ -499,21 +781,31 @@ impl Scene {
         let (x_chunk_size, y_chunk_size, z_chunk_size) =
             calculate_chunk_size(&min_bounds, &max_bounds, number_of_chunks);
 
-        let mut result: Chunks<C> = Chunks {
+        Chunks {
             set_chunks: GenericArray::default(),
             chunks: HashMap::new(),
             size_x: x_chunk_size,
             size_y: y_chunk_size,
             size_z: z_chunk_size,
             chunk_starts: min_bounds,
-        };
-
-        for (index, surface) in self.surfaces.iter().enumerate() {
-            add_surface_to_chunks(surface, &mut result, index, self);
         }
-        add_receiver_to_chunks(&self.receiver, &mut result, self);
+    }
+}
 
-        result
+/// Add the surface with the given index to the chunks, whichever shape it has.
+fn add_surface_variant_to_chunks<C>(
+    surface: &SurfaceVariant,
+    chunks: &mut Chunks<C>,
+    index: usize,
+    scene: &Scene,
+) where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    match surface {
+        SurfaceVariant::Triangle(surface) => add_surface_to_chunks(surface, chunks, index, scene),
+        SurfaceVariant::Quad(surface) => add_surface_to_chunks(surface, chunks, index, scene),
     }
 }
 
@@ -541,6 +833,15 @@ fn single_chunk_size(min: f64, max: f64, number: u16) -> f64 {
     result
 }
 
+/// Which kind of chunk entry an object is added to the chunks as.
+#[derive(Clone, Copy)]
+enum ChunkObject {
+    /// A surface with the given index.
+    Surface(usize),
+    /// The scene's receiver.
+    Receiver,
+}
+
 /// Add the given surface to the chunks.
 ///
 /// For already interpolated surfaces, this will simply add it to each chunk touched by the
@@ -548,6 +849,10 @@ fn single_chunk_size(min: f64, max: f64, number: u16) -> f64 {
 ///
 /// For keyframe surfaces, this will iterate over each pair of keyframes and add them to the according
 /// chunks following the logic from `add_keyframe_pair_to_chunks`.
+///
+/// Keyframe surfaces with their own loop duration that differs from the scene's can't be
+/// described with timestamps within the scene's loop, so their entries are periodic with their own
+/// loop duration instead, see `TimedChunkEntry::Periodic`.
 fn add_surface_to_chunks<const N: usize, C>(
     surface: &Surface<N>,
     chunks: &mut Chunks<C>,
@@ -558,34 +863,23 @@ fn add_surface_to_chunks<const N: usize, C>(
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    match surface {
-        Surface::Interpolated(coordinates, _time, _material) => {
-            add_coordinate_slice_to_chunks(coordinates, index, chunks, None);
-        }
-        Surface::Keyframes(keyframes, _material) => {
-            let first_keyframe = &keyframes[0];
-            if first_keyframe.time != 0 {
-                add_coordinate_slice_to_chunks(
-                    &first_keyframe.coords,
-                    index,
-                    chunks,
-                    Some((0, Some(first_keyframe.time))),
-                );
-            }
-            keyframes.windows(2).for_each(|pair| {
-                add_surface_keyframe_pair_to_chunks(pair[0], &pair[1], chunks, index);
-            });
-            let last_keyframe = keyframes.last().unwrap();
-            // when looping, the last keyframe counts until the end of the scene. Otherwise, it's a final keyframe
-            let last_time = scene.loop_duration;
-            add_coordinate_slice_to_chunks(
-                &last_keyframe.coords,
-                index,
-                chunks,
-                Some((last_keyframe.time, last_time)),
-            );
-        }
-    }
+    let keyframe_times: Option<Vec<u32>> = match surface {
+        Surface::Interpolated(..) => None,
+        Surface::Keyframes(keyframes, _material, _loop_duration) => Some(
+            keyframes
+                .iter()
+                .map(|keyframe| keyframe.time.samples())
+                .collect(),
+        ),
+    };
+    add_intersectable_to_chunks(
+        surface,
+        keyframe_times.as_deref(),
+        surface.loop_duration(),
+        ChunkObject::Surface(index),
+        chunks,
+        scene,
+    );
 }
 
 /// Add the given receiver to the chunks.
@@ -595,217 +889,244 @@ fn add_surface_to_chunks<const N: usize, C>(
 ///
 /// For keyframe receivers, this will iterate over each pair of keyframes and add them to the according
 /// chunks following the logic from `add_keyframe_pair_to_chunks`.
+///
+/// Like for surfaces, the entries of keyframe receivers with their own loop duration that differs from
+/// the scene's repeat with that loop duration.
 fn add_receiver_to_chunks<C>(receiver: &Receiver, chunks: &mut Chunks<C>, scene: &Scene)
 where
     C: Unsigned + Mul<C>,
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    match receiver {
-        Receiver::Interpolated(coordinates, radius, _time) => {
-            add_sphere_to_chunks(coordinates, *radius, 0, chunks, None);
-        }
-        Receiver::Keyframes(keyframes, radius) => {
-            let first_keyframe = &keyframes[0];
-            if first_keyframe.time != 0 {
-                add_sphere_to_chunks(
-                    &first_keyframe.coords,
-                    *radius,
-                    0,
-                    chunks,
-                    Some((0, Some(first_keyframe.time))),
-                );
-            }
-            keyframes.windows(2).for_each(|pair| {
-                add_sphere_keyframe_pair_to_chunks(pair[0], &pair[1], *radius, chunks, 0);
-            });
-            let last_keyframe = keyframes.last().unwrap();
-            // when looping, the last keyframe counts until the end of the scene. Otherwise, it's a final keyframe
-            let last_time = scene.loop_duration;
-            add_sphere_to_chunks(
-                &last_keyframe.coords,
-                *radius,
-                0,
-                chunks,
-                Some((last_keyframe.time, last_time)),
-            );
-        }
-    }
+    let keyframe_times: Option<Vec<u32>> = match receiver {
+        Receiver::Interpolated(..) | Receiver::Ellipsoid(..) => None,
+        Receiver::Keyframes(keyframes, ..) | Receiver::EllipsoidKeyframes(keyframes, ..) => Some(
+            keyframes
+                .iter()
+                .map(|keyframe| keyframe.time.samples())
+                .collect(),
+        ),
+    };
+    add_intersectable_to_chunks(
+        receiver,
+        keyframe_times.as_deref(),
+        receiver.loop_duration(),
+        ChunkObject::Receiver,
+        chunks,
+        scene,
+    );
 }
 
-/// Calculate when the object described by the two given keyframes first and last enters
-/// which chunks, then add it to them accordingly.
-///
-/// This works by starting out in the middle between the first and second keyframe
-/// and halving the distance to the first keyframe until the first and middle keyframe
-/// fill the same chunks. Then the middle keyframe's time is incremented until it no longer fits within the same chunk boundaries,
-/// and the resulting time and chunks are written accordingly.
-///
-/// This process is repeated until the second keyframe's time is reached.
-fn add_surface_keyframe_pair_to_chunks<const N: usize, C>(
-    mut first: SurfaceKeyframe<N>,
-    second: &SurfaceKeyframe<N>,
+/// Add the given object to the chunks, using its bounds from `Intersectable::chunk_bounds_at`.
+/// `keyframe_times` holds the times of the object's keyframes, or `None` if it doesn't move.
+/// `own_loop_duration` is the object's own loop duration, if it has one.
+fn add_intersectable_to_chunks<T: Intersectable, C>(
+    object: &T,
+    keyframe_times: Option<&[u32]>,
+    own_loop_duration: Option<SampleDuration>,
+    chunk_object: ChunkObject,
     chunks: &mut Chunks<C>,
-    index: usize,
+    scene: &Scene,
 ) where
     C: Unsigned + Mul<C>,
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    let mut chunks_at_first = chunk_bounds(&first.coords, chunks);
-    let mut time = first.time;
-    while time < second.time {
-        time = time.average_floor(&second.time);
-        let mut keyframe_middle =
-            interpolation::interpolate_two_surface_keyframes(&first, second, time).unwrap();
-
-        let mut chunks_at_middle = chunk_bounds(&keyframe_middle, chunks);
-        while chunks_at_middle != chunks_at_first {
-            time = time.average_floor(&first.time);
-            keyframe_middle =
-                interpolation::interpolate_two_surface_keyframes(&first, second, time).unwrap();
-            chunks_at_middle = chunk_bounds(&keyframe_middle, chunks);
-        }
-
-        // potential optimisation: if we step here often, do increments by 10 or 100, then decrement again by an order of magnitude lower
-        while chunks_at_middle == chunks_at_first && time < second.time {
-            time += 1;
-            keyframe_middle =
-                interpolation::interpolate_two_surface_keyframes(&first, second, time).unwrap();
-            chunks_at_middle = chunk_bounds(&keyframe_middle, chunks);
-        }
-
-        add_coordinate_slice_to_chunks(
-            &first.coords,
-            index,
+    let Some(keyframe_times) = keyframe_times else {
+        add_bounds_to_chunks(&object.chunk_bounds_at(0), chunk_object, chunks, None, None);
+        return;
+    };
+    // objects looping on their own need their entries to repeat with their own loop duration
+    let period =
+        own_loop_duration.filter(|loop_duration| scene.loop_duration != Some(*loop_duration));
+
+    let first_time = keyframe_times[0];
+    if first_time != 0 {
+        add_bounds_to_chunks(
+            &object.chunk_bounds_at(first_time),
+            chunk_object,
             chunks,
-            Some((first.time, Some(time - 1))),
+            Some((SampleTime::ZERO, Some(SampleTime(first_time)))),
+            period,
         );
-
-        first = SurfaceKeyframe {
-            coords: keyframe_middle,
-            time,
-        };
-        chunks_at_first = chunks_at_middle;
     }
+    keyframe_times.windows(2).for_each(|pair| {
+        add_keyframe_pair_to_chunks(object, pair[0], pair[1], chunk_object, chunks, period);
+    });
+    let last_time = keyframe_times[keyframe_times.len() - 1];
+    // when looping, the last keyframe counts until the end of the loop. Otherwise, it's a final keyframe
+    add_bounds_to_chunks(
+        &object.chunk_bounds_at(last_time),
+        chunk_object,
+        chunks,
+        Some((
+            SampleTime(last_time),
+            period
+                .or(scene.loop_duration)
+                .map(|duration| SampleTime(duration.samples())),
+        )),
+        period,
+    );
 }
 
-/// Calculate when the receiver described by the two given keyframes first and last enters
+/// Calculate when the object moving between the two given keyframe times first and last enters
 /// which chunks, then add it to them accordingly.
+/// `period` is the object's own loop duration if its entries need to repeat with it,
+/// see `create_chunk_entry_with_period`.
 ///
 /// This works by starting out in the middle between the first and second keyframe
 /// and halving the distance to the first keyframe until the first and middle keyframe
-/// fill the same chunks. Then the middle keyframe's time is incremented until it no longer fits within the same chunk boundaries,
-/// and the resulting time and chunks are written accordingly.
+/// fill the same chunks. From there, the first time at which the object no longer fills these chunks
+/// is searched for, see `find_exit_time`, and the chunks are written for the time span up to it.
 ///
-/// This process is repeated until the second keyframe's time is reached.
+/// This process is repeated from the exit time until the second keyframe's time is reached.
 ///
-/// The chunk boundaries are simplified as a box around the receiver's sphere - in most practical uses, the receiver will be orders of magnitude
-/// smaller than the chunks, so there's no major accuracy loss by simplifying to a box.
-fn add_sphere_keyframe_pair_to_chunks<C>(
-    mut first: CoordinateKeyframe,
-    second: &CoordinateKeyframe,
-    radius: f64,
+/// The receiver's bounds move linearly, so it can't return to chunks it left and its exit time is found
+/// using binary search up to the second keyframe. A surface's bounds may not move linearly, so its exit time
+/// is first narrowed down by probing with exponentially growing steps, then found using binary search
+/// between the last two probes. This assumes a surface doesn't leave and re-enter its chunks between two probes.
+fn add_keyframe_pair_to_chunks<T: Intersectable, C>(
+    object: &T,
+    mut first_time: u32,
+    second_time: u32,
+    chunk_object: ChunkObject,
     chunks: &mut Chunks<C>,
-    index: usize,
+    period: Option<SampleDuration>,
 ) where
     C: Unsigned + Mul<C>,
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    let mut chunks_at_first = sphere_chunk_bounds(&first.coords, radius, chunks);
-    let mut time = first.time;
-    while time < second.time {
-        time = time.average_floor(&second.time);
-        let mut keyframe_middle =
-            interpolation::interpolate_two_coordinate_keyframes(&first, second, time).unwrap();
-
-        let mut chunks_at_middle = sphere_chunk_bounds(&keyframe_middle, radius, chunks);
+    let mut chunks_at_first = box_chunk_bounds(&object.chunk_bounds_at(first_time), chunks);
+    let mut time = first_time;
+    while time < second_time {
+        time = time.average_floor(&second_time);
+        let mut chunks_at_middle = box_chunk_bounds(&object.chunk_bounds_at(time), chunks);
         while chunks_at_middle != chunks_at_first {
-            time = time.average_floor(&first.time);
-            keyframe_middle =
-                interpolation::interpolate_two_coordinate_keyframes(&first, second, time).unwrap();
-            chunks_at_middle = sphere_chunk_bounds(&keyframe_middle, radius, chunks);
+            time = time.average_floor(&first_time);
+            chunks_at_middle = box_chunk_bounds(&object.chunk_bounds_at(time), chunks);
         }
 
-        // potential optimisation: if we step here often, do increments by 10 or 100, then decrement again by an order of magnitude lower
-        while chunks_at_middle == chunks_at_first && time < second.time {
-            time += 1;
-            keyframe_middle =
-                interpolation::interpolate_two_coordinate_keyframes(&first, second, time).unwrap();
-            chunks_at_middle = sphere_chunk_bounds(&keyframe_middle, radius, chunks);
-        }
+        let (lower, upper) = match chunk_object {
+            ChunkObject::Receiver => (time, second_time),
+            ChunkObject::Surface(_) => {
+                // double the step size until the surface has left the current chunks
+                let mut lower = time;
+                let mut step = 1;
+                loop {
+                    let upper = lower.saturating_add(step).min(second_time);
+                    if upper == second_time
+                        || box_chunk_bounds(&object.chunk_bounds_at(upper), chunks)
+                            != chunks_at_first
+                    {
+                        break (lower, upper);
+                    }
+                    lower = upper;
+                    step = step.saturating_mul(2);
+                }
+            }
+        };
+        time = find_exit_time(object, chunks_at_first, lower, upper, chunks);
+        chunks_at_middle = box_chunk_bounds(&object.chunk_bounds_at(time), chunks);
 
-        add_sphere_to_chunks(
-            &first.coords,
-            radius,
-            index,
+        add_chunk_indices_to_chunks(
+            chunks_at_first,
+            chunk_object,
             chunks,
-            Some((first.time, Some(time - 1))),
+            Some((SampleTime(first_time), Some(SampleTime(time - 1)))),
+            period,
         );
 
-        first = CoordinateKeyframe {
-            coords: keyframe_middle,
-            time,
-        };
+        first_time = time;
         chunks_at_first = chunks_at_middle;
     }
 }
 
-/// Add the object described by the given index to all chunks touched by the
-/// box formed by the given coordinate slice's maximum bounds.
-fn add_coordinate_slice_to_chunks<C>(
-    coordinates: &[Vector3<f64>],
-    index: usize,
-    chunks: &mut Chunks<C>,
-    time: Option<(u32, Option<u32>)>,
-) where
+/// Binary search for the first time after `lower` at which the given object no longer fills `chunks_at_lower`.
+/// The object needs to fill `chunks_at_lower` at `lower` and the exit time needs to be in `(lower, upper]`.
+/// If the object never leaves `chunks_at_lower`, `upper` is returned.
+fn find_exit_time<T: Intersectable, C>(
+    object: &T,
+    chunks_at_lower: ((u32, u32, u32), (u32, u32, u32)),
+    mut lower: u32,
+    mut upper: u32,
+    chunks: &Chunks<C>,
+) -> u32
+where
     C: Unsigned + Mul<C>,
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    // possible optimisation: move along surface rather than creating a box around it
-    let (min_index, max_index) = chunk_bounds(coordinates, chunks);
-
-    for x in min_index.0..=max_index.0 {
-        for y in min_index.1..=max_index.1 {
-            for z in min_index.2..=max_index.2 {
-                chunks.add_surface_at(x, y, z, index, time);
-            }
+    while lower + 1 < upper {
+        let middle = lower.average_floor(&upper);
+        if box_chunk_bounds(&object.chunk_bounds_at(middle), chunks) == chunks_at_lower {
+            lower = middle;
+        } else {
+            upper = middle;
         }
     }
+    upper
 }
 
-/// Add the object described by the given index to all chunks touched by the
-/// box formed by the given coordinate slice's maximum bounds.
-fn add_sphere_to_chunks<C>(
-    coordinates: &Vector3<f64>,
-    radius: f64,
-    index: usize,
+/// Add the given object to all chunks touched by the given box.
+fn add_bounds_to_chunks<C>(
+    bounds: &(Vector3<f64>, Vector3<f64>),
+    chunk_object: ChunkObject,
     chunks: &mut Chunks<C>,
-    time: Option<(u32, Option<u32>)>,
+    time: Option<(SampleTime, Option<SampleTime>)>,
+    period: Option<SampleDuration>,
 ) where
     C: Unsigned + Mul<C>,
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    // possible optimisation: move along surface rather than creating a box around it
-    let (min_index, max_index) = sphere_chunk_bounds(coordinates, radius, chunks);
+    add_chunk_indices_to_chunks(
+        box_chunk_bounds(bounds, chunks),
+        chunk_object,
+        chunks,
+        time,
+        period,
+    );
+}
 
+/// Add the given object to all chunks between the given minimum and maximum chunk indices.
+fn add_chunk_indices_to_chunks<C>(
+    (min_index, max_index): ((u32, u32, u32), (u32, u32, u32)),
+    chunk_object: ChunkObject,
+    chunks: &mut Chunks<C>,
+    time: Option<(SampleTime, Option<SampleTime>)>,
+    period: Option<SampleDuration>,
+) where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    // possible optimisation: move along surface rather than creating a box around it
     for x in min_index.0..=max_index.0 {
         for y in min_index.1..=max_index.1 {
             for z in min_index.2..=max_index.2 {
-                chunks.add_receiver_at(x, y, z, index, time);
+                match chunk_object {
+                    ChunkObject::Surface(index) => chunks.add_surface_entry_at(
+                        x,
+                        y,
+                        z,
+                        create_chunk_entry_with_period(index, time, period),
+                    ),
+                    ChunkObject::Receiver => chunks.add_receiver_entry_at(
+                        x,
+                        y,
+                        z,
+                        create_chunk_entry_with_period(0, time, period),
+                    ),
+                }
             }
         }
     }
 }
 
-/// Calculate the box formed around the given sphere
-/// bounds, represented as its boundaries' chunk indices.
-fn sphere_chunk_bounds<C>(
-    coordinates: &Vector3<f64>,
-    radius: f64,
+/// Calculate the given box's boundaries' chunk indices.
+fn box_chunk_bounds<C>(
+    (minimum_bounds, maximum_bounds): &(Vector3<f64>, Vector3<f64>),
     chunks: &Chunks<C>,
 ) -> ((u32, u32, u32), (u32, u32, u32))
 where
@@ -813,35 +1134,15 @@ where
     <C as Mul>::Output: Mul<C>,
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
-    let mut minimum_bounds = *coordinates;
-    minimum_bounds.add_scalar_mut(-radius);
-    let mut maximum_bounds = *coordinates;
-    maximum_bounds.add_scalar_mut(radius);
     (
-        coords_to_chunk_index(&minimum_bounds, chunks),
-        coords_to_chunk_index(&maximum_bounds, chunks),
-    )
-}
-
-/// Calculate the box formed by the given coordinates' maximum
-/// bounds, represented as its boundaries' chunk indices.
-fn chunk_bounds<C>(
-    coordinates: &[Vector3<f64>],
-    chunks: &Chunks<C>,
-) -> ((u32, u32, u32), (u32, u32, u32))
-where
-    C: Unsigned + Mul<C>,
-    <C as Mul>::Output: Mul<C>,
-    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
-{
-    let coords_at_second = scene_bounds::maximum_bounds(coordinates);
-    (
-        coords_to_chunk_index(&coords_at_second.0, chunks),
-        coords_to_chunk_index(&coords_at_second.1, chunks),
+        coords_to_chunk_index(minimum_bounds, chunks),
+        coords_to_chunk_index(maximum_bounds, chunks),
     )
 }
 
 /// Convert the given coordinates into their related chunk indices.
+/// Coordinates exactly at the upper bound belong to the last chunk,
+/// and coordinates outside the grid are clamped to its first or last chunk.
 fn coords_to_chunk_index<C>(coords: &Vector3<f64>, chunks: &Chunks<C>) -> (u32, u32, u32)
 where
     C: Unsigned + Mul<C>,
@@ -849,13 +1150,29 @@ where
     <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
 {
     (
-        ((coords.x - chunks.chunk_starts.x) / chunks.size_x).floor() as u32,
-        ((coords.y - chunks.chunk_starts.y) / chunks.size_y).floor() as u32,
-        ((coords.z - chunks.chunk_starts.z) / chunks.size_z).floor() as u32,
+        clamp_chunk_index(
+            ((coords.x - chunks.chunk_starts.x) / chunks.size_x).floor(),
+            C::to_u32(),
+        ),
+        clamp_chunk_index(
+            ((coords.y - chunks.chunk_starts.y) / chunks.size_y).floor(),
+            C::to_u32(),
+        ),
+        clamp_chunk_index(
+            ((coords.z - chunks.chunk_starts.z) / chunks.size_z).floor(),
+            C::to_u32(),
+        ),
     )
 }
 
+/// Limit the given (floored) chunk index to the `max` chunks per dimension.
+/// Negative indices are clamped to 0 explicitly rather than relying on the cast saturating.
+fn clamp_chunk_index(val: f64, max: u32) -> u32 {
+    val.clamp(0f64, f64::from(max - 1)) as u32
+}
+
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod tests {
     use std::collections::HashMap;
 
@@ -863,9 +1180,16 @@ mod tests {
     use nalgebra::Vector3;
     use typenum::U10;
 
-    use crate::chunk::{
-        chunk_bounds, coords_to_chunk_index, create_chunk_entry, single_chunk_size,
-        sphere_chunk_bounds, TimedChunkEntry,
+    use crate::{
+        chunk::{
+            box_chunk_bounds, coords_to_chunk_index, create_chunk_entry, single_chunk_size,
+            SceneChunk, TimedChunkEntry,
+        },
+        intersection::Intersectable,
+        scene::Receiver,
+        scene_bounds::maximum_bounds,
+        test_utils::assert_vector_abs_diff_eq,
+        time::{SampleDuration, SampleTime},
     };
 
     use super::{calculate_chunk_size, Chunks};
@@ -881,6 +1205,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn final_entries_are_found_after_entering_chunk() {
+        let chunk = SceneChunk {
+            surfaces: vec![
+                TimedChunkEntry::Final(3, SampleTime(50)),
+                TimedChunkEntry::Dynamic(4, SampleTime(10), SampleTime(20)),
+                TimedChunkEntry::Static(5),
+            ],
+            receivers: vec![],
+        };
+        assert_eq!(
+            vec![4, 5],
+            chunk.objects_at_time(SampleTime(0), SampleTime(30), None).1
+        );
+        assert_eq!(
+            vec![3, 5],
+            chunk
+                .objects_at_time(SampleTime(40), SampleTime(60), None)
+                .1
+        );
+        assert_eq!(
+            vec![3, 5],
+            chunk
+                .objects_at_time(SampleTime(60), SampleTime(70), Some(SampleDuration(100)))
+                .1
+        );
+        // wrapping around from the end of one loop to the start of the next
+        assert_eq!(
+            vec![3, 5],
+            chunk
+                .objects_at_time(SampleTime(90), SampleTime(105), Some(SampleDuration(100)))
+                .1
+        );
+        assert_eq!(
+            vec![3, 4, 5],
+            chunk
+                .objects_at_time(SampleTime(90), SampleTime(112), Some(SampleDuration(100)))
+                .1
+        );
+        // exiting one sample before the entry offset covers the whole loop
+        assert_eq!(
+            vec![3, 4, 5],
+            chunk
+                .objects_at_time(SampleTime(60), SampleTime(159), Some(SampleDuration(100)))
+                .1
+        );
+    }
+
     #[test]
     fn static_chunk_entry_object_index() {
         let entry = TimedChunkEntry::Static(1094);
@@ -889,27 +1261,26 @@ mod tests {
 
     #[test]
     fn dynamic_chunk_entry_object_index() {
-        let entry = TimedChunkEntry::Dynamic(299, 1000, 6000);
+        let entry = TimedChunkEntry::Dynamic(299, SampleTime(1000), SampleTime(6000));
         assert_eq!(299, entry.object_index());
     }
 
     #[test]
     fn final_chunk_entry_object_index() {
-        let entry = TimedChunkEntry::Final(4901, 6000);
+        let entry = TimedChunkEntry::Final(4901, SampleTime(6000));
         assert_eq!(4901, entry.object_index());
     }
 
-
     #[test]
     fn create_chunk_entry_static_dynamic_and_final() {
         assert_eq!(TimedChunkEntry::Static(12), create_chunk_entry(12, None));
         assert_eq!(
-            TimedChunkEntry::Dynamic(12, 0, 1000),
-            create_chunk_entry(12, Some((0, Some(1000))))
+            TimedChunkEntry::Dynamic(12, SampleTime(0), SampleTime(1000)),
+            create_chunk_entry(12, Some((SampleTime(0), Some(SampleTime(1000)))))
         );
         assert_eq!(
-            TimedChunkEntry::Final(12, 19000),
-            create_chunk_entry(12, Some((19000, None)))
+            TimedChunkEntry::Final(12, SampleTime(19000)),
+            create_chunk_entry(12, Some((SampleTime(19000), None)))
         );
     }
 
@@ -953,26 +1324,32 @@ mod tests {
     }
 
     // TODO
-    // add_surface_keyframe_pair_to_chunks
-    // add_sphere_keyframe_pair_to_chunks
-    // add_coordinate_slice_to_chunks
-    // add_sphere_to_chunks
+    // add_keyframe_pair_to_chunks
+    // add_bounds_to_chunks
 
     #[test]
-    fn sphere_chunk_bounds_full_chunk() {
+    fn receiver_chunk_bounds_full_chunk() {
         let chunks = empty_chunks();
         assert_eq!(
             ((0, 0, 0), (9, 9, 9)),
-            sphere_chunk_bounds(&Vector3::new(0f64, 0f64, 0f64), 0.9f64, &chunks)
+            box_chunk_bounds(
+                &Receiver::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0.9f64, 0, false)
+                    .chunk_bounds_at(0),
+                &chunks
+            )
         );
     }
 
     #[test]
-    fn sphere_chunk_bounds_partial() {
+    fn receiver_chunk_bounds_partial() {
         let chunks = empty_chunks();
         assert_eq!(
             ((3, 2, 3), (4, 4, 4)),
-            sphere_chunk_bounds(&Vector3::new(-0.2f64, -0.3f64, -0.2f64), 0.15f64, &chunks)
+            box_chunk_bounds(
+                &Receiver::Interpolated(Vector3::new(-0.2f64, -0.3f64, -0.2f64), 0.15f64, 0, false)
+                    .chunk_bounds_at(0),
+                &chunks
+            )
         );
     }
 
@@ -981,11 +1358,11 @@ mod tests {
         let chunks = empty_chunks();
         assert_eq!(
             ((0, 0, 0), (9, 9, 9)),
-            chunk_bounds(
-                &[
+            box_chunk_bounds(
+                &maximum_bounds(&[
                     Vector3::new(-1f64, -1f64, -1f64),
                     Vector3::new(0.9f64, 0.99f64, 0.999f64)
-                ],
+                ]),
                 &chunks
             )
         );
@@ -996,11 +1373,11 @@ mod tests {
         let chunks = empty_chunks();
         assert_eq!(
             ((6, 5, 5), (9, 5, 6)),
-            chunk_bounds(
-                &[
+            box_chunk_bounds(
+                &maximum_bounds(&[
                     Vector3::new(0.3f64, 0.2f64, 0.3f64),
                     Vector3::new(0.9f64, 0.1f64, 0.2f64)
-                ],
+                ]),
                 &chunks
             )
         );
@@ -1012,7 +1389,95 @@ mod tests {
         assert_eq!(
             (0, 0, 0),
             coords_to_chunk_index(&Vector3::new(-1f64, -1f64, -1f64), &chunks)
-        )
+        );
+    }
+
+    #[test]
+    fn redundant_entries_are_not_added() {
+        let mut chunks = empty_chunks();
+        chunks.add_surface_at(1, 2, 3, 4, Some((SampleTime(10), Some(SampleTime(20)))));
+        chunks.add_surface_at(1, 2, 3, 4, Some((SampleTime(10), Some(SampleTime(20)))));
+        chunks.add_surface_at(1, 2, 3, 5, None);
+        chunks.add_surface_at(1, 2, 3, 5, Some((SampleTime(30), None)));
+        chunks.add_surface_at(1, 2, 3, 5, None);
+        chunks.add_receiver_at(1, 2, 3, 0, Some((SampleTime(0), None)));
+        chunks.add_receiver_at(1, 2, 3, 0, Some((SampleTime(0), None)));
+        let chunk = &chunks.chunks[&chunks.key_for_index(1, 2, 3)];
+        assert_eq!(
+            vec![
+                TimedChunkEntry::Dynamic(4, SampleTime(10), SampleTime(20)),
+                TimedChunkEntry::Static(5)
+            ],
+            chunk.surfaces
+        );
+        assert_eq!(
+            vec![TimedChunkEntry::Final(0, SampleTime(0))],
+            chunk.receivers
+        );
+    }
+
+    #[test]
+    fn static_entries_replace_earlier_timed_entries() {
+        let mut chunks = empty_chunks();
+        chunks.add_surface_at(1, 2, 3, 4, Some((SampleTime(10), Some(SampleTime(20)))));
+        chunks.add_surface_at(1, 2, 3, 5, Some((SampleTime(15), Some(SampleTime(25)))));
+        chunks.add_surface_at(1, 2, 3, 4, Some((SampleTime(30), None)));
+        chunks.add_surface_at(1, 2, 3, 4, None);
+        chunks.add_receiver_at(1, 2, 3, 0, Some((SampleTime(0), Some(SampleTime(5)))));
+        chunks.add_receiver_at(1, 2, 3, 0, None);
+        let chunk = &chunks.chunks[&chunks.key_for_index(1, 2, 3)];
+        assert_eq!(
+            vec![
+                TimedChunkEntry::Dynamic(5, SampleTime(15), SampleTime(25)),
+                TimedChunkEntry::Static(4)
+            ],
+            chunk.surfaces
+        );
+        assert_eq!(vec![TimedChunkEntry::Static(0)], chunk.receivers);
+    }
+
+    #[test]
+    fn out_of_bounds_coords_to_chunk_index() {
+        let chunks = empty_chunks();
+        assert_eq!(
+            (0, 0, 0),
+            coords_to_chunk_index(&Vector3::new(-1.01f64, -1.5f64, -100f64), &chunks)
+        );
+        assert_eq!(
+            (9, 9, 9),
+            coords_to_chunk_index(&Vector3::new(1.01f64, 1.5f64, 100f64), &chunks)
+        );
+        assert_eq!(
+            ((0, 5, 5), (9, 5, 5)),
+            box_chunk_bounds(
+                &maximum_bounds(&[
+                    Vector3::new(-5f64, 0f64, 0f64),
+                    Vector3::new(5f64, 0f64, 0f64)
+                ]),
+                &chunks
+            )
+        );
+    }
+
+    #[test]
+    fn world_bounds_cover_all_chunks() {
+        let chunks = empty_chunks();
+        let (min_coords, max_coords) = chunks.world_bounds();
+        assert_vector_abs_diff_eq(Vector3::new(-1f64, -1f64, -1f64), min_coords);
+        assert_vector_abs_diff_eq(Vector3::new(1f64, 1f64, 1f64), max_coords);
+    }
+
+    #[test]
+    fn upper_bound_coords_to_chunk_index() {
+        let chunks = empty_chunks();
+        assert_eq!(
+            (9, 9, 9),
+            coords_to_chunk_index(&Vector3::new(1f64, 1f64, 1f64), &chunks)
+        );
+        assert_eq!(
+            999,
+            chunks.key_for_coordinates(&Vector3::new(1f64, 1f64, 1f64))
+        );
     }
 
     #[test]
@@ -1021,7 +1486,7 @@ mod tests {
         assert_eq!(
             (5, 5, 5),
             coords_to_chunk_index(&Vector3::new(0f64, 0f64, 0f64), &chunks)
-        )
+        );
     }
 
     #[test]
@@ -1030,7 +1495,7 @@ mod tests {
         assert_eq!(
             (3, 6, 5),
             coords_to_chunk_index(&Vector3::new(-0.3f64, 0.4f64, 0.1f64), &chunks)
-        )
+        );
     }
 
     #[test]
@@ -1042,6 +1507,6 @@ mod tests {
                 &Vector3::new(0.9999f64, 0.9999999f64, 0.9999999f64),
                 &chunks
             )
-        )
+        );
     }
 }
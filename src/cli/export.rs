@@ -0,0 +1,145 @@
+use std::io::{BufWriter, Write};
+
+use nalgebra::Vector3;
+
+use crate::{
+    interpolation::Interpolation,
+    scene::{Emitter, Receiver, Scene, Surface, SurfaceVariant},
+    DEFAULT_SAMPLE_RATE,
+};
+
+use super::{
+    options::{parse_value, required_value, split_arg, SceneOptions},
+    CliError,
+};
+
+/// Options for the `export` subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// The scene to export.
+    pub scene: SceneOptions,
+    /// The time to export the scene at, in samples.
+    pub time: u32,
+    /// The sample rate the scene is built for.
+    pub sample_rate: u32,
+    /// The file to write the scene to.
+    pub out_fname: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            scene: SceneOptions::default(),
+            time: 0,
+            sample_rate: DEFAULT_SAMPLE_RATE as u32,
+            out_fname: "scene.obj".to_string(),
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Parse the given arguments.
+    ///
+    /// # Errors
+    ///
+    /// * If an argument is unknown or has an invalid value.
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        for arg in args {
+            let (name, value) = split_arg(arg);
+            if options.scene.parse_arg(name, value)? {
+                continue;
+            }
+            match name {
+                "--time" => options.time = parse_value(name, value, "a number")?,
+                "--sample-rate" => options.sample_rate = parse_value(name, value, "a number")?,
+                "--outfile" => options.out_fname = required_value(name, value)?.to_string(),
+                _ => return Err(CliError::UnknownArgument(name.to_string())),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Export the selected scene's geometry at the given time as a Wavefront OBJ file.
+///
+/// # Errors
+///
+/// * If the arguments are invalid, see `ExportOptions::parse`.
+/// * If the scene is invalid.
+/// * If the file can't be written.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = ExportOptions::parse(args)?;
+    let scene = options.scene.build(options.sample_rate)?;
+    scene.validate()?;
+    let write = || {
+        let mut out_file = BufWriter::new(std::fs::File::create(&options.out_fname)?);
+        write_obj(&scene, options.time, &mut out_file)?;
+        out_file.flush()
+    };
+    write().map_err(|err| CliError::io(&options.out_fname, err))?;
+    println!(
+        "Exported {} surfaces to \"{}\".",
+        scene.surfaces.len(),
+        options.out_fname
+    );
+    Ok(())
+}
+
+/// Write the given scene's geometry at the given time to the given writer in Wavefront OBJ format.
+/// Every surface becomes one face, the receiver and emitter positions are written as comments.
+///
+/// # Errors
+///
+/// * If writing fails.
+///
+/// # Panics
+///
+/// * If the scene isn't valid, see `Scene::validate`.
+pub fn write_obj<W: Write>(scene: &Scene, time: u32, writer: &mut W) -> std::io::Result<()> {
+    let scene = scene.at_time(time);
+    let Some(receiver_coords) = scene.receiver.center() else {
+        panic!("at_time() somehow returned a non-interpolated receiver. This shouldn't happen.")
+    };
+    let shape = match scene.receiver {
+        Receiver::Ellipsoid(_coords, semi_axes, _orientation, _time) => {
+            format!("semi-axes {} {} {}", semi_axes.x, semi_axes.y, semi_axes.z)
+        }
+        _ => format!("radius {}", scene.receiver.bounding_radius()),
+    };
+    let Emitter::Interpolated(emitter_coords, _time, _emission_type) = scene.emitter else {
+        panic!("at_time() somehow returned a non-interpolated emitter. This shouldn't happen.")
+    };
+    writeln!(writer, "# scene at time {time}")?;
+    writeln!(
+        writer,
+        "# receiver {} {} {} {shape}",
+        receiver_coords.x, receiver_coords.y, receiver_coords.z
+    )?;
+    writeln!(
+        writer,
+        "# emitter {} {} {}",
+        emitter_coords.x, emitter_coords.y, emitter_coords.z
+    )?;
+
+    let mut vertex_count = 0;
+    for surface in &scene.surfaces {
+        let coords: &[Vector3<f64>] = match surface {
+            SurfaceVariant::Triangle(Surface::Interpolated(coords, _data)) => coords,
+            SurfaceVariant::Quad(Surface::Interpolated(coords, _data)) => coords,
+            _ => panic!(
+                "at_time() somehow returned a non-interpolated surface. This shouldn't happen."
+            ),
+        };
+        for vertex in coords {
+            writeln!(writer, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+        }
+        // OBJ vertex indices start at 1
+        let indices: Vec<String> = (vertex_count + 1..=vertex_count + coords.len())
+            .map(|index| index.to_string())
+            .collect();
+        writeln!(writer, "f {}", indices.join(" "))?;
+        vertex_count += coords.len();
+    }
+    Ok(())
+}
@@ -0,0 +1,377 @@
+use std::io::{BufWriter, Write};
+
+use nalgebra::Rotation3;
+
+use crate::{
+    parameter_sweep::{self, SweepParameter, SweepResult},
+    scene::SceneData,
+    sound_field::{GridResult, ReceiverGrid},
+    telemetry::TraversalCounters,
+    DEFAULT_SAMPLE_RATE,
+};
+
+use super::{
+    options::{parse_value, required_value, split_arg, SceneOptions, SimulationOptions},
+    simulate::print_estimate,
+    CliError,
+};
+
+/// Options for the `ir` subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IrOptions {
+    /// The scene to simulate.
+    pub scene: SceneOptions,
+    /// How to simulate the impulse response.
+    pub simulation: SimulationOptions,
+    /// The time to simulate the impulse response at, in samples.
+    pub time: u32,
+    /// The sample rate to simulate with.
+    pub sample_rate: u32,
+    /// The file to write the impulse response to.
+    pub ir_fname: String,
+    /// If set, a first-order ambisonic impulse response is written to this file as a 4-channel WAV file.
+    pub ambisonic_fname: Option<String>,
+    /// If set, the receiver is swept across this grid instead of simulating a single impulse response.
+    pub sweep_grid: Option<ReceiverGrid>,
+    /// The file to write the grid points to when sweeping a grid.
+    pub grid_fname: String,
+    /// If set, a heat map of the grid points' energies is written to this file when sweeping a grid.
+    pub grid_pgm_fname: Option<String>,
+    /// If set, the scene is simulated with the parameter set to each of the values instead of simulating a single impulse response.
+    pub parameter_sweep: Option<(SweepParameter, Vec<f64>)>,
+    /// The file to write the acoustic metrics to when sweeping a parameter.
+    pub sweep_fname: String,
+}
+
+impl Default for IrOptions {
+    fn default() -> Self {
+        Self {
+            scene: SceneOptions::default(),
+            simulation: SimulationOptions::default(),
+            time: 0,
+            sample_rate: DEFAULT_SAMPLE_RATE as u32,
+            ir_fname: "ir.csv".to_string(),
+            ambisonic_fname: None,
+            sweep_grid: None,
+            grid_fname: "grid.csv".to_string(),
+            grid_pgm_fname: None,
+            parameter_sweep: None,
+            sweep_fname: "sweep.csv".to_string(),
+        }
+    }
+}
+
+impl IrOptions {
+    /// Parse the given arguments.
+    ///
+    /// # Errors
+    ///
+    /// * If an argument is unknown or has an invalid value.
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        for arg in args {
+            let (name, value) = split_arg(arg);
+            if options.scene.parse_arg(name, value)? || options.simulation.parse_arg(name, value)? {
+                continue;
+            }
+            match name {
+                "--time" => options.time = parse_value(name, value, "a number")?,
+                "--sample-rate" => options.sample_rate = parse_value(name, value, "a number")?,
+                "--irfile" => options.ir_fname = required_value(name, value)?.to_string(),
+                "--ambisonic" => {
+                    options.ambisonic_fname = Some(required_value(name, value)?.to_string());
+                }
+                "--sweep-grid" => {
+                    options.sweep_grid = Some(parse_receiver_grid(required_value(name, value)?)?);
+                }
+                "--gridfile" => options.grid_fname = required_value(name, value)?.to_string(),
+                "--grid-pgm" => {
+                    options.grid_pgm_fname = Some(required_value(name, value)?.to_string());
+                }
+                "--sweep" => {
+                    options.parameter_sweep =
+                        Some(parse_parameter_sweep(required_value(name, value)?)?);
+                }
+                "--sweepfile" => options.sweep_fname = required_value(name, value)?.to_string(),
+                _ => return Err(CliError::UnknownArgument(name.to_string())),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Parse a receiver grid given as "z=HEIGHT,x=MIN..MAX,y=MIN..MAX,step=STEP", in any order.
+///
+/// # Errors
+///
+/// * If the value isn't in the right format, a part is missing or the step isn't positive.
+pub fn parse_receiver_grid(value: &str) -> Result<ReceiverGrid, CliError> {
+    let invalid = || {
+        CliError::InvalidArgument(
+            "\"--sweep-grid\" needs to be passed \"z=HEIGHT,x=MIN..MAX,y=MIN..MAX,step=STEP\" with a positive step!"
+                .to_string(),
+        )
+    };
+    let parse_range = |range: &str| {
+        let (min, max) = range.split_once("..")?;
+        let (min, max) = (min.parse::<f64>().ok()?, max.parse::<f64>().ok()?);
+        (min <= max).then_some((min, max))
+    };
+    let (mut z, mut x_range, mut y_range, mut step) = (None, None, None, None);
+    for part in value.split(',') {
+        match part.split_once('=').ok_or_else(invalid)? {
+            ("z", height) => z = Some(height.parse::<f64>().map_err(|_| invalid())?),
+            ("x", range) => x_range = Some(parse_range(range).ok_or_else(invalid)?),
+            ("y", range) => y_range = Some(parse_range(range).ok_or_else(invalid)?),
+            ("step", distance) => step = Some(distance.parse::<f64>().map_err(|_| invalid())?),
+            _ => return Err(invalid()),
+        }
+    }
+    match (z, x_range, y_range, step) {
+        (Some(z), Some(x_range), Some(y_range), Some(step)) if step > 0f64 => Ok(ReceiverGrid {
+            z,
+            x_range,
+            y_range,
+            step,
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a parameter sweep given as "NAME:MIN..MAX:COUNT", see `SweepParameter::name` and `parameter_sweep::sweep_values`.
+///
+/// # Errors
+///
+/// * If the value isn't in the right format, the parameter is unknown or the count isn't positive.
+pub fn parse_parameter_sweep(value: &str) -> Result<(SweepParameter, Vec<f64>), CliError> {
+    let invalid = || {
+        let names: Vec<&str> = SweepParameter::ALL
+            .iter()
+            .map(|parameter| parameter.name())
+            .collect();
+        CliError::InvalidArgument(format!(
+            "\"--sweep\" needs to be passed \"NAME:MIN..MAX:COUNT\" with a positive count! Supported parameters: {}",
+            names.join(", ")
+        ))
+    };
+    let mut parts = value.split(':');
+    let (Some(name), Some(range), Some(count), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let parameter = SweepParameter::from_name(name).ok_or_else(invalid)?;
+    let (min, max) = range.split_once("..").ok_or_else(invalid)?;
+    let (min, max) = (
+        min.parse::<f64>().map_err(|_| invalid())?,
+        max.parse::<f64>().map_err(|_| invalid())?,
+    );
+    let count = count
+        .parse::<usize>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(invalid)?;
+    Ok((parameter, parameter_sweep::sweep_values(min, max, count)))
+}
+
+/// Simulate a single impulse response at the given time without applying it to any audio,
+/// then write it to the given file.
+/// The impulse response starts at the given time.
+/// If a grid to sweep was passed, the receiver is placed at each of its points instead,
+/// and their energies and arrival times are written, see `write_grid_result`.
+/// If a parameter sweep was passed, the scene is simulated for each of its values instead,
+/// and their acoustic metrics are written, see `write_sweep_results`.
+///
+/// # Errors
+///
+/// * If the arguments are invalid, see `IrOptions::parse`.
+/// * If the scene is invalid or can't be simulated.
+/// * If the impulse response can't be written.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = IrOptions::parse(args)?;
+    let scene = options.scene.build(options.sample_rate)?;
+    if let Some((parameter, values)) = &options.parameter_sweep {
+        let simulation = &options.simulation;
+        let results = parameter_sweep::run_sweep::<typenum::U10>(
+            &|value| parameter.apply(&scene, value),
+            values,
+            options.time,
+            simulation.number_of_rays,
+            simulation.speed_of_sound,
+            f64::from(options.sample_rate),
+            &simulation.config,
+        )?;
+        println!("Swept {} values of {}", results.len(), parameter.name());
+        return write_sweep_results(&results, parameter.name(), &options.sweep_fname);
+    }
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene)?;
+    let simulation = &options.simulation;
+    if simulation.chunk_stats {
+        println!("Chunk statistics:\n{}", scene_data.chunks.statistics());
+    }
+    if simulation.estimate_only {
+        return print_estimate(&scene_data, simulation, 1, options.sample_rate);
+    }
+    if let Some(grid) = &options.sweep_grid {
+        let grid_result = scene_data.sweep_receiver_grid(
+            grid,
+            options.time,
+            simulation.number_of_rays,
+            simulation.speed_of_sound,
+            f64::from(options.sample_rate),
+            &simulation.config,
+        )?;
+        println!("Swept {} grid points", grid_result.points.len());
+        return write_grid_result(
+            &grid_result,
+            &options.grid_fname,
+            options.grid_pgm_fname.as_deref(),
+        );
+    }
+
+    if simulation.chunk_stats {
+        TraversalCounters::start();
+    }
+    let (mut impulse_response, telemetry) = scene_data.simulate_at_time_with_telemetry(
+        options.time,
+        simulation.number_of_rays,
+        simulation.speed_of_sound,
+        f64::from(options.sample_rate),
+        simulation.do_snapshot_method,
+        true,
+        &simulation.config,
+    )?;
+    impulse_response.drain(..(options.time as usize).min(impulse_response.len()));
+    let impulse_response = simulation.post_processing.apply(impulse_response);
+    if simulation.verbose {
+        println!("Timing breakdown:\n{telemetry}");
+    }
+    if simulation.chunk_stats {
+        println!("Traversal counts:\n{}", TraversalCounters::finish());
+    }
+    println!(
+        "Impulse response length: {}s",
+        impulse_response.len() as f64 / f64::from(options.sample_rate)
+    );
+    write_impulse_response(&options.ir_fname, &impulse_response)?;
+
+    if let Some(fname) = &options.ambisonic_fname {
+        write_ambisonic_impulse_response(
+            &scene_data,
+            simulation,
+            options.time,
+            options.sample_rate,
+            fname,
+        )?;
+    }
+    Ok(())
+}
+
+/// Simulate a first-order ambisonic impulse response at the given time for a receiver facing along the X axis,
+/// then write it to the given file as a 4-channel, 32-bit float WAV file with the channels in W, X, Y, Z order.
+/// The impulse response starts at the given time.
+///
+/// # Errors
+///
+/// * If the simulation can't be run or the file can't be written.
+pub fn write_ambisonic_impulse_response(
+    scene_data: &SceneData<typenum::U10>,
+    simulation: &SimulationOptions,
+    time: u32,
+    sample_rate: u32,
+    fname: &str,
+) -> Result<(), CliError> {
+    let mut ambisonic_impulse_response = scene_data.simulate_ambisonic_at_time(
+        time,
+        simulation.number_of_rays,
+        simulation.speed_of_sound,
+        f64::from(sample_rate),
+        &Rotation3::identity(),
+        true,
+        &simulation.config,
+    )?;
+    let start = (time as usize).min(ambisonic_impulse_response.w.len());
+    for channel in [
+        &mut ambisonic_impulse_response.w,
+        &mut ambisonic_impulse_response.x,
+        &mut ambisonic_impulse_response.y,
+        &mut ambisonic_impulse_response.z,
+    ] {
+        channel.drain(..start);
+    }
+    let header = wav::Header::new(wav::WAV_FORMAT_IEEE_FLOAT, 4, sample_rate, 32);
+    let data = wav::BitDepth::ThirtyTwoFloat(
+        ambisonic_impulse_response
+            .interleaved()
+            .into_iter()
+            .map(|value| value as f32)
+            .collect(),
+    );
+    let write = || {
+        let mut out_file = std::fs::File::create(fname)?;
+        wav::write(header, &data, &mut out_file)
+    };
+    write().map_err(|err| CliError::io(fname, err))
+}
+
+/// Write the given grid result to the given file in CSV format, see `GridResult::write_csv`,
+/// and, if given, a heat map to the given PGM file, see `GridResult::write_pgm`.
+///
+/// # Errors
+///
+/// * If either file can't be created or written to.
+pub fn write_grid_result(
+    grid_result: &GridResult,
+    fname: &str,
+    pgm_fname: Option<&str>,
+) -> Result<(), CliError> {
+    let write_csv = || {
+        let mut grid_file = BufWriter::new(std::fs::File::create(fname)?);
+        grid_result.write_csv(&mut grid_file)?;
+        grid_file.flush()
+    };
+    write_csv().map_err(|err| CliError::io(fname, err))?;
+    if let Some(pgm_fname) = pgm_fname {
+        let write_pgm = || {
+            let mut pgm_file = BufWriter::new(std::fs::File::create(pgm_fname)?);
+            grid_result.write_pgm(&mut pgm_file)?;
+            pgm_file.flush()
+        };
+        write_pgm().map_err(|err| CliError::io(pgm_fname, err))?;
+    }
+    Ok(())
+}
+
+/// Write the given sweep results to the given file in CSV format, see `parameter_sweep::write_sweep_csv`.
+///
+/// # Errors
+///
+/// * If the file can't be created or written to.
+pub fn write_sweep_results(
+    results: &[SweepResult],
+    parameter_name: &str,
+    fname: &str,
+) -> Result<(), CliError> {
+    let write = || {
+        let mut sweep_file = BufWriter::new(std::fs::File::create(fname)?);
+        parameter_sweep::write_sweep_csv(results, parameter_name, &mut sweep_file)?;
+        sweep_file.flush()
+    };
+    write().map_err(|err| CliError::io(fname, err))
+}
+
+/// Write the given impulse response to the given file, with each value followed by a semicolon.
+///
+/// # Errors
+///
+/// * If the file can't be created or written to.
+pub fn write_impulse_response(fname: &str, impulse_response: &[f64]) -> Result<(), CliError> {
+    let write = || {
+        let mut ir_file = BufWriter::new(std::fs::File::create(fname)?);
+        for value in impulse_response {
+            write!(ir_file, "{value};")?;
+        }
+        ir_file.flush()
+    };
+    write().map_err(|err| CliError::io(fname, err))
+}
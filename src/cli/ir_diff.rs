@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use crate::{
+    audio_io::{self, AudioIoError},
+    impulse_response::{self, ImpulseResponse},
+    ir_diff::diff_impulse_responses,
+    DEFAULT_SAMPLE_RATE,
+};
+
+use super::{
+    options::{parse_value, split_arg},
+    CliError,
+};
+
+/// Options for the `ir-diff` subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IrDiffOptions {
+    /// The files holding the impulse responses to compare.
+    pub fnames: Vec<String>,
+    /// The largest absolute difference of a single sample that still counts as matching.
+    pub tolerance: f64,
+    /// The sample rate of impulse responses read from text files.
+    pub sample_rate: u32,
+}
+
+impl Default for IrDiffOptions {
+    fn default() -> Self {
+        Self {
+            fnames: vec![],
+            tolerance: 0f64,
+            sample_rate: DEFAULT_SAMPLE_RATE as u32,
+        }
+    }
+}
+
+impl IrDiffOptions {
+    /// Parse the given arguments. Arguments not starting with "--" are the files to compare.
+    ///
+    /// # Errors
+    ///
+    /// * If an argument is unknown or has an invalid value.
+    /// * `CliError::MissingArgument` if there aren't exactly two files to compare.
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        for arg in args {
+            if !arg.starts_with("--") {
+                options.fnames.push(arg.clone());
+                continue;
+            }
+            let (name, value) = split_arg(arg);
+            match name {
+                "--tolerance" => {
+                    options.tolerance = parse_value::<f64>(name, value, "a non-negative number")
+                        .ok()
+                        .filter(|tolerance| *tolerance >= 0f64)
+                        .ok_or_else(|| {
+                            CliError::InvalidArgument(
+                                "\"--tolerance\" needs to be passed a non-negative number!"
+                                    .to_string(),
+                            )
+                        })?;
+                }
+                "--sample-rate" => options.sample_rate = parse_value(name, value, "a number")?,
+                _ => return Err(CliError::UnknownArgument(name.to_string())),
+            }
+        }
+        if options.fnames.len() != 2 {
+            return Err(CliError::MissingArgument(
+                "Please pass exactly two impulse response files to compare!".to_string(),
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// Read the impulse response from the given file.
+/// Audio files (see `audio_io::open_audio`) are read as their first channel, normalised to full scale,
+/// at their own sample rate. Other files are read as text, see `impulse_response::read_text`,
+/// at `default_sample_rate`.
+/// Returns the impulse response and its sample rate.
+///
+/// # Errors
+///
+/// * `CliError::Read` if the file can't be read or decoded.
+pub fn read_impulse_response(
+    fname: &str,
+    default_sample_rate: u32,
+) -> Result<(ImpulseResponse, u32), CliError> {
+    let path = Path::new(fname);
+    let is_audio = path
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|extension| {
+            matches!(
+                extension.to_lowercase().as_str(),
+                "wav" | "flac" | "ogg" | "oga"
+            )
+        });
+    if is_audio {
+        let input = audio_io::open_audio(path).map_err(|err| CliError::read(fname, err))?;
+        Ok((audio_io::normalised_channel(&input, 0), input.sample_rate))
+    } else {
+        let impulse_response = impulse_response::read_text(path)
+            .map_err(|err| CliError::read(fname, AudioIoError::Io(err)))?;
+        Ok((impulse_response, default_sample_rate))
+    }
+}
+
+/// Compare two impulse responses and print their differences, see `ir_diff::diff_impulse_responses`.
+///
+/// # Errors
+///
+/// * If the arguments are invalid, see `IrDiffOptions::parse`.
+/// * If either file can't be read.
+/// * `CliError::InvalidArgument` if the impulse responses have different sample rates.
+/// * `CliError::ToleranceExceeded` if a single sample differs by more than the tolerance.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = IrDiffOptions::parse(args)?;
+    let (first, first_sample_rate) =
+        read_impulse_response(&options.fnames[0], options.sample_rate)?;
+    let (second, second_sample_rate) =
+        read_impulse_response(&options.fnames[1], options.sample_rate)?;
+    if first_sample_rate != second_sample_rate {
+        return Err(CliError::InvalidArgument(format!(
+            "Can't compare impulse responses with different sample rates ({first_sample_rate} Hz and {second_sample_rate} Hz)!"
+        )));
+    }
+
+    let diff = diff_impulse_responses(&first, &second, f64::from(first_sample_rate));
+    print!("{diff}");
+    if diff.l_infinity > options.tolerance {
+        return Err(CliError::ToleranceExceeded {
+            difference: diff.l_infinity,
+            tolerance: options.tolerance,
+        });
+    }
+    Ok(())
+}
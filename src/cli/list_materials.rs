@@ -0,0 +1,34 @@
+use crate::materials::MATERIAL_PRESETS;
+
+use super::CliError;
+
+/// Describe all material presets usable with "--material-override", one line per material.
+pub fn material_list() -> Vec<String> {
+    MATERIAL_PRESETS
+        .iter()
+        .map(|(name, material)| {
+            format!(
+                "\t{name} - absorption {}, diffusion {}, transmission {}",
+                material.absorption_coefficient,
+                material.diffusion_coefficient,
+                material.transmission_coefficient
+            )
+        })
+        .collect()
+}
+
+/// Print out all material presets.
+///
+/// # Errors
+///
+/// * `CliError::UnknownArgument` if any arguments are passed.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if let Some(arg) = args.first() {
+        return Err(CliError::UnknownArgument(arg.clone()));
+    }
+    println!("The following materials are supported:");
+    for line in material_list() {
+        println!("{line}");
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::scene_builder::available_scenes;
+
+use super::CliError;
+
+/// Describe all predefined scenes with their index, slug, name and description, one line per scene.
+pub fn scene_list() -> Vec<String> {
+    available_scenes()
+        .iter()
+        .enumerate()
+        .map(|(index, scene)| {
+            format!(
+                "\t{index} - {} ({}): {}",
+                scene.slug, scene.name, scene.description
+            )
+        })
+        .collect()
+}
+
+/// Print out all predefined scenes.
+///
+/// # Errors
+///
+/// * `CliError::UnknownArgument` if any arguments are passed.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    if let Some(arg) = args.first() {
+        return Err(CliError::UnknownArgument(arg.clone()));
+    }
+    println!("The following scenes are supported, select them by index or slug with \"--scene\":");
+    for line in scene_list() {
+        println!("{line}");
+    }
+    Ok(())
+}
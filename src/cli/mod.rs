@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+use crate::{
+    audio_io::{AudioIoError, InputError},
+    error::DemoError,
+};
+
+pub mod export;
+pub mod ir;
+pub mod ir_diff;
+pub mod list_materials;
+pub mod list_scenes;
+pub mod options;
+pub mod simulate;
+
+/// Errors that can occur while running a subcommand.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum CliError {
+    /// The given subcommand doesn't exist.
+    UnknownSubcommand(String),
+    /// The given argument isn't supported by the subcommand.
+    UnknownArgument(String),
+    /// An argument was passed an invalid value. Holds a message describing the expected value.
+    InvalidArgument(String),
+    /// A required argument wasn't passed. Holds a message describing the argument.
+    MissingArgument(String),
+    /// The input file doesn't contain any samples and there's no fallback.
+    EmptyInput,
+    /// The input file couldn't be read.
+    Input(InputError),
+    /// The scene is invalid or couldn't be simulated.
+    Demo(DemoError),
+    /// An output file couldn't be written.
+    Io {
+        /// The file that couldn't be written.
+        path: String,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+    /// A file other than the simulation's input couldn't be read.
+    Read {
+        /// The file that couldn't be read.
+        path: String,
+        /// The underlying error.
+        source: AudioIoError,
+    },
+    /// Two compared impulse responses differ by more than the tolerance.
+    ToleranceExceeded {
+        /// The largest absolute difference of a single sample.
+        difference: f64,
+        /// The tolerance it was compared against.
+        tolerance: f64,
+    },
+}
+
+impl CliError {
+    /// The exit code the binary should return for this error:
+    /// 1 for invalid usage, 2 for invalid scenes, 3 for simulations that couldn't be run,
+    /// 4 for files that couldn't be read or written and 5 for impulse responses differing by more than the tolerance.
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::UnknownSubcommand(_)
+            | Self::UnknownArgument(_)
+            | Self::InvalidArgument(_)
+            | Self::MissingArgument(_) => 1,
+            Self::Demo(DemoError::Interpolation(_) | DemoError::SceneBuild(_)) => 2,
+            Self::Demo(DemoError::Simulation(_)) => 3,
+            Self::EmptyInput | Self::Input(_) | Self::Io { .. } | Self::Read { .. } => 4,
+            Self::ToleranceExceeded { .. } => 5,
+        }
+    }
+
+    /// Create an `Io` error for the given path.
+    pub(crate) fn io(path: &str, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    /// Create a `Read` error for the given path.
+    pub(crate) fn read(path: &str, source: AudioIoError) -> Self {
+        Self::Read {
+            path: path.to_string(),
+            source,
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSubcommand(name) => write!(
+                f,
+                "Unknown subcommand \"{name}\"! Supported subcommands: {}",
+                SUBCOMMANDS.join(", ")
+            ),
+            Self::UnknownArgument(arg) => write!(f, "Unknown argument {arg}"),
+            Self::InvalidArgument(message) | Self::MissingArgument(message) => {
+                write!(f, "{message}")
+            }
+            Self::EmptyInput => write!(f, "The input file doesn't contain any samples, so there is nothing to simulate. Pass \"--single-ir\" to only calculate the impulse response."),
+            Self::Input(err) => write!(f, "An error occurred while reading the input file: {err}"),
+            Self::Demo(err) => write!(f, "{err}"),
+            Self::Io { path, source } => write!(f, "Couldn't write \"{path}\": {source}"),
+            Self::Read { path, source } => write!(f, "Couldn't read \"{path}\": {source}"),
+            Self::ToleranceExceeded {
+                difference,
+                tolerance,
+            } => write!(
+                f,
+                "The impulse responses differ by up to {difference}, more than the tolerance of {tolerance}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<InputError> for CliError {
+    fn from(err: InputError) -> Self {
+        match err {
+            InputError::Empty => Self::EmptyInput,
+            err @ InputError::Audio(_) => Self::Input(err),
+        }
+    }
+}
+
+impl From<DemoError> for CliError {
+    fn from(err: DemoError) -> Self {
+        Self::Demo(err)
+    }
+}
+
+/// The names of all supported subcommands.
+pub const SUBCOMMANDS: [&str; 6] = [
+    "simulate",
+    "ir",
+    "ir-diff",
+    "export",
+    "list-scenes",
+    "list-materials",
+];
+
+/// Run the subcommand named by the first argument with the remaining arguments.
+/// Each subcommand has its own module with a `run` function, so it can also be called directly.
+/// If the first argument is a flag or there are no arguments, `simulate` is run with all arguments,
+/// so the flat flags from before subcommands were introduced keep working.
+///
+/// # Errors
+///
+/// * If the subcommand doesn't exist or fails, see the subcommands' `run` functions.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let Some(subcommand) = args.first().filter(|arg| !arg.starts_with("--")) else {
+        return simulate::run(args);
+    };
+    let args = &args[1..];
+    match subcommand.as_str() {
+        "simulate" => simulate::run(args),
+        "ir" => ir::run(args),
+        "ir-diff" => ir_diff::run(args),
+        "export" => export::run(args),
+        "list-scenes" => list_scenes::run(args),
+        "list-materials" => list_materials::run(args),
+        _ => Err(CliError::UnknownSubcommand(subcommand.clone())),
+    }
+}
@@ -0,0 +1,298 @@
+use std::str::FromStr;
+
+use crate::{
+    impulse_response::{PostProcessing, PressureSign},
+    materials::{self, Material},
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::Scene,
+    scene_builder,
+    simulation_config::{DiffuseSampling, IrPrecision, SimulationConfig, TraversalMode},
+};
+
+use super::CliError;
+
+/// The default number of rays simulated per impulse response.
+pub const DEFAULT_NUMBER_OF_RAYS: u32 = 100000;
+
+/// Get the predefined scene with the given index, see `scene_builder::available_scenes`.
+/// Scenes that move are built for the given sample rate.
+pub fn scene_by_index(index: u32, sample_rate: u32) -> Option<Scene> {
+    scene_builder::available_scenes()
+        .get(index as usize)
+        .map(|descriptor| (descriptor.build)(sample_rate))
+}
+
+/// Parse a scene given by its slug or index, see `scene_builder::find_scene`.
+/// Indices are returned as they are, so invalid ones are only rejected once the scene is built.
+///
+/// # Errors
+///
+/// * If no value was passed or there is no scene with the given slug.
+pub fn parse_scene(name: &str, value: Option<&str>) -> Result<u32, CliError> {
+    let value = required_value(name, value)?;
+    if let Ok(index) = value.parse::<u32>() {
+        return Ok(index);
+    }
+    scene_builder::find_scene(value)
+        .map(|(index, _descriptor)| index)
+        .ok_or_else(|| {
+            CliError::InvalidArgument(format!(
+                "Unknown scene \"{value}\"! Run \"list-scenes\" to see the supported scenes."
+            ))
+        })
+}
+
+/// Split the given argument into its name and value, e.g. "--rays=100" into "--rays" and "100".
+pub fn split_arg(arg: &str) -> (&str, Option<&str>) {
+    arg.split_once('=')
+        .map_or((arg, None), |(name, value)| (name, Some(value)))
+}
+
+/// Get the value passed to the argument with the given name.
+///
+/// # Errors
+///
+/// * `CliError::MissingArgument` if no value was passed.
+pub fn required_value<'a>(name: &str, value: Option<&'a str>) -> Result<&'a str, CliError> {
+    value
+        .ok_or_else(|| CliError::MissingArgument(format!("\"{name}\" needs to be passed a value!")))
+}
+
+/// Parse the value passed to the argument with the given name.
+/// `expected` describes valid values for the error message, e.g. "a number".
+///
+/// # Errors
+///
+/// * If no value was passed or it can't be parsed.
+pub fn parse_value<T: FromStr>(
+    name: &str,
+    value: Option<&str>,
+    expected: &str,
+) -> Result<T, CliError> {
+    required_value(name, value)?.parse::<T>().map_err(|_| {
+        CliError::InvalidArgument(format!("\"{name}\" needs to be passed {expected}!"))
+    })
+}
+
+/// Options selecting the scene to work with.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SceneOptions {
+    /// The index of the predefined scene, see `scene_builder::available_scenes`.
+    pub scene_index: Option<u32>,
+    /// Materials replacing those of the surfaces with the given indices.
+    pub material_overrides: Vec<(usize, Material)>,
+}
+
+impl SceneOptions {
+    /// Apply the given argument if it's a scene option.
+    /// Returns whether the argument was a scene option.
+    ///
+    /// # Errors
+    ///
+    /// * If the argument's value is invalid.
+    pub fn parse_arg(&mut self, name: &str, value: Option<&str>) -> Result<bool, CliError> {
+        match name {
+            "--scene" => self.scene_index = Some(parse_scene(name, value)?),
+            "--material-override" => self
+                .material_overrides
+                .push(parse_material_override(required_value(name, value)?)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Build the selected scene for the given sample rate and apply the material overrides.
+    ///
+    /// # Errors
+    ///
+    /// * If no scene or an unknown scene was selected.
+    /// * If a material override refers to a surface the scene doesn't have.
+    pub fn build(&self, sample_rate: u32) -> Result<Scene, CliError> {
+        let Some(scene_index) = self.scene_index else {
+            return Err(CliError::MissingArgument(
+                "Please provide a valid scene using \"--scene=SLUG\" or \"--scene=INDEX\"! Run \"list-scenes\" to see the supported scenes.".to_string(),
+            ));
+        };
+        let mut scene = scene_by_index(scene_index, sample_rate).ok_or_else(|| {
+            CliError::InvalidArgument(format!(
+                "Invalid scene index {scene_index}! Run \"list-scenes\" to see the supported scenes."
+            ))
+        })?;
+        for (surface_index, material) in &self.material_overrides {
+            if *surface_index >= scene.surfaces.len() {
+                return Err(CliError::InvalidArgument(format!(
+                    "Can't override the material of surface {surface_index}, the scene only has {} surfaces!",
+                    scene.surfaces.len()
+                )));
+            }
+            scene.set_material(*surface_index, *material);
+        }
+        Ok(scene)
+    }
+}
+
+/// Options controlling how impulse responses are simulated.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SimulationOptions {
+    /// The number of rays simulated per impulse response.
+    pub number_of_rays: u32,
+    /// The speed sound propagates at, in meters per second.
+    pub speed_of_sound: f64,
+    /// Whether to simulate using the snapshot rather than the interpolated method.
+    pub do_snapshot_method: bool,
+    /// The post-processing applied to each impulse response.
+    pub post_processing: PostProcessing,
+    /// How the simulation is run.
+    pub config: SimulationConfig,
+    /// Whether to only print an estimate of the simulation's output and cost instead of running it.
+    pub estimate_only: bool,
+    /// Whether to print a breakdown of where the simulation spent its time.
+    pub verbose: bool,
+    /// Whether to print the scene's chunk statistics and count the rays' chunk visits and intersection tests.
+    pub chunk_stats: bool,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            number_of_rays: DEFAULT_NUMBER_OF_RAYS,
+            speed_of_sound: DEFAULT_PROPAGATION_SPEED,
+            do_snapshot_method: false,
+            post_processing: PostProcessing::default(),
+            config: SimulationConfig::default(),
+            estimate_only: false,
+            verbose: false,
+            chunk_stats: false,
+        }
+    }
+}
+
+impl SimulationOptions {
+    /// Apply the given argument if it's a simulation option.
+    /// Returns whether the argument was a simulation option.
+    ///
+    /// # Errors
+    ///
+    /// * If the argument's value is invalid.
+    pub fn parse_arg(&mut self, name: &str, value: Option<&str>) -> Result<bool, CliError> {
+        match name {
+            "--rays" => self.number_of_rays = parse_value(name, value, "a number")?,
+            "--speed-of-sound" => self.speed_of_sound = parse_positive(name, value)?,
+            "--energy-threshold" => {
+                self.config.relative_energy_threshold = parse_positive(name, value)?;
+            }
+            "--snapshot-method" => self.do_snapshot_method = true,
+            "--ir-pressure" => {
+                self.post_processing.pressure = match value {
+                    None | Some("random") => Some(PressureSign::Random),
+                    Some("alternating") => Some(PressureSign::Alternating),
+                    Some(_) => {
+                        return Err(CliError::InvalidArgument(
+                            "\"--ir-pressure\" needs to be passed either \"random\" or \"alternating\"!"
+                                .to_string(),
+                        ))
+                    }
+                };
+            }
+            "--ir-normalise" => {
+                self.post_processing.normalise_peak = Some(match value {
+                    None => 1f64,
+                    Some(_) => parse_value(name, value, "a number")?,
+                });
+            }
+            "--threads" => self.config.threads = Some(parse_value(name, value, "a number")?),
+            "--capture-decay-db" => {
+                self.config.capture_decay_db = Some(parse_positive(name, value)?);
+            }
+            "--samples-per-task" => {
+                self.config.samples_per_task =
+                    parse_value::<usize>(name, value, "a positive number")
+                        .ok()
+                        .filter(|samples| *samples > 0)
+                        .ok_or_else(|| {
+                            CliError::InvalidArgument(
+                                "\"--samples-per-task\" needs to be passed a positive number!"
+                                    .to_string(),
+                            )
+                        })?;
+            }
+            "--brute-force" => self.config.traversal_mode = TraversalMode::BruteForce,
+            "--uniform-diffuse" => self.config.diffuse_sampling = DiffuseSampling::Uniform,
+            "--area-weighted-emission" => self.config.use_area_weighted_emission = true,
+            "--receiver-importance" => {
+                self.config.receiver_importance =
+                    parse_value::<f64>(name, value, "a number from 0 to below 1")
+                        .ok()
+                        .filter(|importance| (0f64..1f64).contains(importance))
+                        .ok_or_else(|| {
+                            CliError::InvalidArgument(
+                        "\"--receiver-importance\" needs to be passed a number from 0 to below 1!"
+                            .to_string(),
+                    )
+                        })?;
+            }
+            "--ir-precision" => {
+                self.config.ir_precision = match value {
+                    Some("f64") => IrPrecision::F64,
+                    Some("f32") => IrPrecision::F32,
+                    _ => {
+                        return Err(CliError::InvalidArgument(
+                            "\"--ir-precision\" needs to be passed either \"f64\" or \"f32\"!"
+                                .to_string(),
+                        ))
+                    }
+                };
+            }
+            "--launch-jitter" => self.config.launch_jitter = true,
+            "--estimate" => self.estimate_only = true,
+            "--verbose" => self.verbose = true,
+            "--chunk-stats" => self.chunk_stats = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Parse the value passed to the argument with the given name as a positive number.
+///
+/// # Errors
+///
+/// * If no value was passed or it isn't a positive number.
+pub fn parse_positive(name: &str, value: Option<&str>) -> Result<f64, CliError> {
+    parse_value::<f64>(name, value, "a positive number")
+        .ok()
+        .filter(|number| *number > 0f64)
+        .ok_or_else(|| {
+            CliError::InvalidArgument(format!("\"{name}\" needs to be passed a positive number!"))
+        })
+}
+
+/// Parse a material override given as "INDEX:NAME".
+///
+/// # Errors
+///
+/// * If the value isn't in the right format, the index isn't a number or there is no material preset with the given name.
+pub fn parse_material_override(value: &str) -> Result<(usize, Material), CliError> {
+    let Some((index, name)) = value.split_once(':') else {
+        return Err(CliError::InvalidArgument(
+            "\"--material-override\" needs to be passed \"INDEX:NAME\"!".to_string(),
+        ));
+    };
+    let index = index.parse::<usize>().map_err(|_| {
+        CliError::InvalidArgument(
+            "\"--material-override\" needs to be passed a surface index!".to_string(),
+        )
+    })?;
+    let material = materials::material_by_name(name).ok_or_else(|| {
+        let names: Vec<&str> = materials::MATERIAL_PRESETS
+            .iter()
+            .map(|(name, _material)| *name)
+            .collect();
+        CliError::InvalidArgument(format!(
+            "Unknown material \"{name}\"! Supported materials: {}",
+            names.join(", ")
+        ))
+    })?;
+    Ok((index, material))
+}
@@ -0,0 +1,443 @@
+use std::path::Path;
+use std::time::Instant;
+
+use crate::{
+    analysis,
+    audio_io::{self, InputError, OutputFormat},
+    impulse_response::ImpulseResponse,
+    leak_detection::LeakReport,
+    scene::SceneData,
+    scene_builder,
+    simulation_config::{IrPrecision, SimulationConfig},
+    telemetry::{Telemetry, TraversalCounters},
+    DEFAULT_SAMPLE_RATE,
+};
+
+use super::{
+    ir::{write_ambisonic_impulse_response, write_impulse_response},
+    options::{parse_value, required_value, split_arg, SceneOptions, SimulationOptions},
+    CliError,
+};
+
+/// The default factor the auralized audio's amplitude is scaled up by.
+pub const DEFAULT_SCALING_FACTOR: f64 = 10000f64;
+/// The maximum number of leaked rays written to the leak CSV file.
+const MAX_LEAK_CSV_ROWS: usize = 1000;
+
+/// Options for the `simulate` subcommand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulateOptions {
+    /// The scene to simulate.
+    pub scene: SceneOptions,
+    /// How to simulate the impulse responses.
+    pub simulation: SimulationOptions,
+    /// The audio file to apply the impulse responses to.
+    pub input_fname: Option<String>,
+    /// The factor the auralized audio's amplitude is scaled up by.
+    pub scaling_factor: f64,
+    /// Whether to only simulate a single impulse response at the start time
+    /// (see `SimulationConfig::time_offset`) and apply it to the entire input.
+    pub single_ir: bool,
+    /// The file to write the resulting audio to.
+    pub out_fname: String,
+    /// The format to write the resulting audio in.
+    pub output_format: OutputFormat,
+    /// If set, the impulse response is written to this file.
+    pub ir_fname: Option<String>,
+    /// If set, a first-order ambisonic impulse response at time 0 is written to this file as a 4-channel WAV file.
+    pub ambisonic_fname: Option<String>,
+    /// Whether to count the rays of the first input sample leaking out of the scene while simulating.
+    pub detect_leaks: bool,
+    /// The file to write leaked rays to.
+    pub leak_fname: String,
+    /// If set, the receiver's kinematics are written to this file.
+    pub kinematics_fname: Option<String>,
+}
+
+impl Default for SimulateOptions {
+    fn default() -> Self {
+        Self {
+            scene: SceneOptions::default(),
+            simulation: SimulationOptions::default(),
+            input_fname: None,
+            scaling_factor: DEFAULT_SCALING_FACTOR,
+            single_ir: false,
+            out_fname: "result.wav".to_string(),
+            output_format: OutputFormat::Same,
+            ir_fname: None,
+            ambisonic_fname: None,
+            detect_leaks: false,
+            leak_fname: "leaks.csv".to_string(),
+            kinematics_fname: None,
+        }
+    }
+}
+
+impl SimulateOptions {
+    /// Parse the given arguments.
+    ///
+    /// # Errors
+    ///
+    /// * If an argument is unknown or has an invalid value.
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        for arg in args {
+            let (name, value) = split_arg(arg);
+            if options.scene.parse_arg(name, value)? || options.simulation.parse_arg(name, value)? {
+                continue;
+            }
+            match name {
+                "--fname" => options.input_fname = Some(required_value(name, value)?.to_string()),
+                "--scaling-factor" => {
+                    options.scaling_factor = parse_value(name, value, "a number")?;
+                }
+                "--single-ir" => options.single_ir = true,
+                "--start-time-samples" => {
+                    options.simulation.config.time_offset =
+                        parse_value(name, value, "a number of samples")?;
+                }
+                "--dry-wet" => {
+                    options.simulation.config.dry_wet =
+                        parse_value::<f64>(name, value, "a number between 0 and 1")
+                            .ok()
+                            .filter(|dry_wet| (0f64..=1f64).contains(dry_wet))
+                            .ok_or_else(|| {
+                                CliError::InvalidArgument(
+                                    "\"--dry-wet\" needs to be passed a number between 0 and 1!"
+                                        .to_string(),
+                                )
+                            })?;
+                }
+                "--input-gain" => {
+                    options.simulation.config.input_gain_db =
+                        parse_value(name, value, "a number of dB")?;
+                }
+                "--outfile" => options.out_fname = required_value(name, value)?.to_string(),
+                "--out-format" => {
+                    options.output_format = OutputFormat::from_name(required_value(name, value)?)
+                        .ok_or_else(|| {
+                        CliError::InvalidArgument(
+                            "\"--out-format\" needs to be passed \"same\", \"f32\" or \"f64-raw\"!"
+                                .to_string(),
+                        )
+                    })?;
+                }
+                "--irfile" => options.ir_fname = Some(required_value(name, value)?.to_string()),
+                "--ambisonic" => {
+                    options.ambisonic_fname = Some(required_value(name, value)?.to_string());
+                }
+                "--detect-leaks" => options.detect_leaks = true,
+                "--leakfile" => options.leak_fname = required_value(name, value)?.to_string(),
+                "--dump-kinematics" => {
+                    options.kinematics_fname = Some(required_value(name, value)?.to_string());
+                }
+                _ => return Err(CliError::UnknownArgument(name.to_string())),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Read the input file.
+    /// If `single_ir` is set, a missing or empty input is replaced by a unit impulse.
+    ///
+    /// # Errors
+    ///
+    /// * If no input file was given or it can't be read.
+    pub fn read_input(&self) -> Result<(wav::Header, wav::BitDepth), CliError> {
+        match self
+            .input_fname
+            .as_deref()
+            .map(|fname| audio_io::read_input(Path::new(fname)))
+        {
+            Some(Ok(input)) => Ok(input),
+            None | Some(Err(InputError::Empty)) if self.single_ir => {
+                println!("WARNING: No input samples were given, using a unit impulse instead.");
+                Ok(audio_io::unit_impulse_input(DEFAULT_SAMPLE_RATE as u32))
+            }
+            None => Err(CliError::MissingArgument(
+                "Please provide a file name using \"--fname=FILENAME\"!".to_string(),
+            )),
+            Some(Err(err)) => Err(err.into()),
+        }
+    }
+}
+
+/// Print which criterion decided until when receiver hits were accepted, see `SimulationConfig::capture_decay_db`.
+fn print_capture_criterion(config: &SimulationConfig) {
+    match config.capture_decay_db {
+        Some(decay_db) => println!(
+            "Decay capture criterion: receiver hits were accepted until the Schroeder integral dropped {decay_db} dB below the direct sound"
+        ),
+        None => println!(
+            "Decay capture criterion: receiver hits were accepted until each ray's energy dropped below {} of its initial energy",
+            config.relative_energy_threshold
+        ),
+    }
+}
+
+/// Simulate the selected scene for every sample of the input file and write the auralized result.
+/// Optionally, the impulse response, leaked rays and receiver kinematics are written as well.
+///
+/// # Errors
+///
+/// * If the arguments are invalid, see `SimulateOptions::parse`.
+/// * If the input can't be read, see `SimulateOptions::read_input`.
+/// * If the scene is invalid or can't be simulated.
+/// * If an output file can't be written.
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let options = SimulateOptions::parse(args)?;
+    let simulation = &options.simulation;
+    let config = &simulation.config;
+
+    let (header, input_data) = options.read_input()?;
+    println!("{}", audio_io::describe_input(&header, &input_data));
+    if let Some(note) = audio_io::sample_rate_note(header.sampling_rate) {
+        println!("{note}");
+    }
+    let input_data_len = audio_io::sample_count(&input_data);
+    let input_sound_len: usize = if options.single_ir { 1 } else { input_data_len };
+
+    let scene = options.scene.build(header.sampling_rate)?;
+    if let Some(scene_index) = options.scene.scene_index {
+        println!(
+            "Selected scene #{scene_index}: \"{}\".",
+            scene_builder::available_scenes()[scene_index as usize].name
+        );
+    }
+    if let Some(fname) = &options.kinematics_fname {
+        let kinematics = analysis::receiver_kinematics(
+            &scene,
+            f64::from(header.sampling_rate),
+            simulation.speed_of_sound,
+            u32::try_from(input_data_len).unwrap_or(u32::MAX),
+        )?;
+        let write = || {
+            let mut kinematics_file = std::io::BufWriter::new(std::fs::File::create(fname)?);
+            analysis::write_kinematics_csv(&kinematics, &mut kinematics_file)
+        };
+        write().map_err(|err| CliError::io(fname, err))?;
+    }
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene)?;
+    if simulation.chunk_stats {
+        println!("Chunk statistics:\n{}", scene_data.chunks.statistics());
+    }
+    if simulation.estimate_only {
+        return print_estimate(
+            &scene_data,
+            simulation,
+            u32::try_from(input_sound_len).unwrap_or(u32::MAX),
+            header.sampling_rate,
+        );
+    }
+
+    println!(
+        "Calculating and applying {input_sound_len} impulse responses with {} rays each, this will take a loooong while...",
+        simulation.number_of_rays
+    );
+    if simulation.chunk_stats {
+        TraversalCounters::start();
+    }
+    let time_start = Instant::now();
+    let (result, impulse_response, telemetry, leak_report) =
+        simulate(&scene_data, &options, &input_data, header.sampling_rate)?;
+    let elapsed = time_start.elapsed().as_secs();
+    println!(
+        "Finished calculation in {}:{:02}:{:02}",
+        elapsed / 3600,
+        (elapsed % 3600) / 60,
+        elapsed % 60
+    );
+    if simulation.verbose {
+        println!("Timing breakdown:\n{telemetry}");
+    }
+    if simulation.chunk_stats {
+        println!("Traversal counts:\n{}", TraversalCounters::finish());
+    }
+
+    print_capture_criterion(config);
+    println!(
+        "Impulse response length: {}s",
+        impulse_response.len() as f64 / f64::from(header.sampling_rate)
+    );
+
+    write_output(
+        &options.out_fname,
+        options.output_format,
+        &result,
+        &input_data,
+        header,
+    )?;
+
+    if let Some(fname) = &options.ir_fname {
+        write_impulse_response(fname, &impulse_response)?;
+    }
+    if let Some(fname) = &options.ambisonic_fname {
+        write_ambisonic_impulse_response(&scene_data, simulation, 0, header.sampling_rate, fname)?;
+    }
+
+    if let Some(leak_report) = leak_report {
+        write_leak_report(&leak_report, &options.leak_fname)?;
+    }
+    Ok(())
+}
+
+/// Simulate the input with the given options, see `SceneData::simulate_for_time_span_raw`.
+/// If `options.detect_leaks` is set, the rays leaking out of the scene are collected
+/// along the way, see `SceneData::simulate_for_time_span_detecting_leaks`.
+///
+/// # Errors
+///
+/// * If the simulation can't be run.
+fn simulate(
+    scene_data: &SceneData<typenum::U10>,
+    options: &SimulateOptions,
+    input_data: &wav::BitDepth,
+    sample_rate: u32,
+) -> Result<(Vec<f64>, ImpulseResponse, Telemetry, Option<LeakReport>), CliError> {
+    let simulation = &options.simulation;
+    if options.detect_leaks {
+        let (result, impulse_response, telemetry, leak_report) = scene_data
+            .simulate_for_time_span_detecting_leaks(
+                input_data,
+                simulation.number_of_rays,
+                simulation.speed_of_sound,
+                f64::from(sample_rate),
+                options.scaling_factor,
+                simulation.do_snapshot_method,
+                options.single_ir,
+                &simulation.post_processing,
+                &simulation.config,
+            )?;
+        return Ok((result, impulse_response, telemetry, Some(leak_report)));
+    }
+    let (result, impulse_response, telemetry) = scene_data.simulate_for_time_span_raw(
+        input_data,
+        simulation.number_of_rays,
+        simulation.speed_of_sound,
+        f64::from(sample_rate),
+        options.scaling_factor,
+        simulation.do_snapshot_method,
+        options.single_ir,
+        &simulation.post_processing,
+        &simulation.config,
+    )?;
+    Ok((result, impulse_response, telemetry, None))
+}
+
+/// Estimate the output and cost of simulating the given number of impulse responses
+/// and print the report, see `SceneData::estimate`.
+///
+/// # Errors
+///
+/// * If the pilot simulation can't be run.
+pub fn print_estimate(
+    scene_data: &SceneData<typenum::U10>,
+    simulation: &SimulationOptions,
+    number_of_impulse_responses: u32,
+    sample_rate: u32,
+) -> Result<(), CliError> {
+    let estimate = scene_data.estimate(
+        simulation.number_of_rays,
+        number_of_impulse_responses,
+        simulation.speed_of_sound,
+        f64::from(sample_rate),
+        simulation.do_snapshot_method,
+        &simulation.config,
+    )?;
+    let estimated_secs = estimate.estimated_duration.as_secs();
+    println!(
+        "Estimate for {number_of_impulse_responses} impulse responses with {} rays each, based on {} pilot rays:",
+        simulation.number_of_rays, estimate.pilot_rays
+    );
+    println!(
+        "\tImpulse response length: {}s ({} samples, {:.2} MiB each)",
+        f64::from(estimate.impulse_response_length) / f64::from(sample_rate),
+        estimate.impulse_response_length,
+        estimate.impulse_response_bytes() as f64 / 1024f64 / 1024f64
+    );
+    if simulation.config.ir_precision == IrPrecision::F32 {
+        println!(
+            "\t\t{:.2} MiB each while applied in f32",
+            estimate.applied_impulse_response_bytes(IrPrecision::F32) as f64 / 1024f64 / 1024f64
+        );
+    }
+    println!("\tMean bounces per ray: {:.2}", estimate.mean_bounces);
+    println!("\tLeaked rays: {:.2}%", estimate.leaked_fraction * 100f64);
+    println!(
+        "\tEstimated calculation time: {}:{:02}:{:02} (the pilot took {:.2}s)",
+        estimated_secs / 3600,
+        (estimated_secs % 3600) / 60,
+        estimated_secs % 60,
+        estimate.pilot_duration.as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Print how many of the first input sample's rays leaked out of the scene
+/// and write a sample of them to the given leak file.
+///
+/// # Errors
+///
+/// * If the leak file can't be written.
+fn write_leak_report(leak_report: &LeakReport, fname: &str) -> Result<(), CliError> {
+    println!(
+        "Leaked rays: {} of {} ({:.2}%)",
+        leak_report.count(),
+        leak_report.number_of_rays,
+        leak_report.percentage()
+    );
+    let write = || {
+        let mut leak_file = std::fs::File::create(fname)?;
+        leak_report.write_csv(&mut leak_file, MAX_LEAK_CSV_ROWS)
+    };
+    write().map_err(|err| CliError::io(fname, err))
+}
+
+/// Write the simulation result to the given file in the given format.
+/// `input_data` and `header` describe the input the simulation was run on.
+///
+/// # Errors
+///
+/// * If the file can't be created or written to.
+pub fn write_output(
+    out_fname: &str,
+    output_format: OutputFormat,
+    result: &[f64],
+    input_data: &wav::BitDepth,
+    header: wav::Header,
+) -> Result<(), CliError> {
+    let path = Path::new(out_fname);
+    let (header, data) = match output_format {
+        OutputFormat::Same => {
+            let (data, had_to_clip) = audio_io::to_bit_depth_of(result, input_data);
+            if had_to_clip {
+                println!("WARNING: Part of the resulting audio had to be clipped because it exceeded the file format's range. Please try a bigger scaling factor.");
+            }
+            (header, data)
+        }
+        OutputFormat::Float32 => (
+            wav::Header::new(
+                wav::WAV_FORMAT_IEEE_FLOAT,
+                header.channel_count,
+                header.sampling_rate,
+                32,
+            ),
+            wav::BitDepth::ThirtyTwoFloat(audio_io::to_normalised_f32(result, input_data)),
+        ),
+        OutputFormat::Float64Raw => {
+            return audio_io::write_f64_raw(
+                path,
+                result,
+                input_data,
+                header.sampling_rate,
+                header.channel_count,
+            )
+            .map_err(|err| CliError::io(out_fname, err));
+        }
+    };
+    let write = || {
+        let mut output_file = std::fs::File::create(path)?;
+        wav::write(header, &data, &mut output_file)
+    };
+    write().map_err(|err| CliError::io(out_fname, err))
+}
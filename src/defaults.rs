@@ -0,0 +1,55 @@
+/// The standard conditions simulations run at unless told otherwise.
+/// The associated constants hold the standard values, which the crate's `DEFAULT_*` constants refer to.
+/// An instance holds a consistent set of values, e.g. for a different temperature, see `Defaults::at_temperature`,
+/// and can be turned into a config with `SimulationConfig::from_defaults`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Defaults {
+    /// The sample rate, in Hz.
+    pub sample_rate: f64,
+    /// The speed sound propagates at, in m/s.
+    pub propagation_speed: f64,
+    /// Rays get discarded once their energy drops below this fraction of their initial energy.
+    pub relative_energy_threshold: f64,
+}
+
+impl Defaults {
+    /// The standard sample rate of 44.1 `KHz`.
+    pub const SAMPLE_RATE: f64 = 44100f64;
+    /// The temperature the standard propagation speed applies to, in °C.
+    pub const TEMPERATURE: f64 = 20f64;
+    /// The speed of sound in air at the standard temperature, in m/s.
+    pub const PROPAGATION_SPEED: f64 = 343.2;
+    /// How much the speed of sound in air changes per °C, in m/s.
+    /// This is the slope of the usual linear approximation `331.3 + 0.606 * celsius`.
+    pub const PROPAGATION_SPEED_PER_DEGREE: f64 = 0.606;
+    /// The standard fraction of a ray's initial energy below which it gets discarded.
+    pub const RELATIVE_ENERGY_THRESHOLD: f64 = 0.000001;
+
+    /// Get the standard conditions with the speed of sound in air at the given temperature in °C.
+    /// The speed is approximated linearly around the standard temperature,
+    /// which is accurate to within about 0.5% between -20 °C and 40 °C.
+    pub fn at_temperature(celsius: f64) -> Self {
+        Self {
+            propagation_speed: (celsius - Self::TEMPERATURE)
+                .mul_add(Self::PROPAGATION_SPEED_PER_DEGREE, Self::PROPAGATION_SPEED),
+            ..Self::default()
+        }
+    }
+
+    /// Get the distance sound travels per sample, in meters, like `Ray::velocity`.
+    /// The simulation functions (e.g. `SceneData::simulate_at_time`) take `propagation_speed`
+    /// and `sample_rate` instead and calculate this themselves.
+    pub fn velocity(&self) -> f64 {
+        self.propagation_speed / self.sample_rate
+    }
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            sample_rate: Self::SAMPLE_RATE,
+            propagation_speed: Self::PROPAGATION_SPEED,
+            relative_energy_threshold: Self::RELATIVE_ENERGY_THRESHOLD,
+        }
+    }
+}
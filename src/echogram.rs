@@ -0,0 +1,74 @@
+use std::fmt::Write;
+
+use nalgebra::Vector3;
+
+/// A single event along a ray's path, i.e. a bounce off a surface or a receiver hit.
+/// Collected when `SimulationConfig::record_echogram` is set, see `SceneData::simulate_at_time_with_echogram`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayBounceRecord {
+    /// The time of the event, in samples.
+    pub hit_time: f64,
+    /// The index of the surface the ray bounced off, or `None` for receiver hits.
+    pub surface_index: Option<usize>,
+    /// The ray's energy when reaching the surface or receiver.
+    pub energy: f64,
+    /// Where the event happened.
+    pub position: Vector3<f64>,
+}
+
+/// Convert the given records into CSV format, sorted by their hit time.
+/// Each row holds the hit time in samples and seconds, the surface index (empty for receiver hits),
+/// the energy and the position's x/y/z values.
+pub fn echogram_to_csv(records: &[RayBounceRecord], sample_rate: f64) -> String {
+    let mut sorted = records.to_vec();
+    sorted.sort_by(|first, second| first.hit_time.total_cmp(&second.hit_time));
+    let mut csv =
+        "time_samples;time_seconds;surface_index;energy;position_x;position_y;position_z\n"
+            .to_string();
+    for record in sorted {
+        writeln!(
+            csv,
+            "{};{};{};{};{};{};{}",
+            record.hit_time,
+            record.hit_time / sample_rate,
+            record
+                .surface_index
+                .map_or_else(String::new, |index| index.to_string()),
+            record.energy,
+            record.position.x,
+            record.position.y,
+            record.position.z
+        )
+        .unwrap();
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+
+    use super::{echogram_to_csv, RayBounceRecord};
+
+    #[test]
+    fn csv_is_sorted_by_time() {
+        let records = [
+            RayBounceRecord {
+                hit_time: 20f64,
+                surface_index: None,
+                energy: 0.5f64,
+                position: Vector3::new(1f64, 0f64, 0f64),
+            },
+            RayBounceRecord {
+                hit_time: 10f64,
+                surface_index: Some(3),
+                energy: 1f64,
+                position: Vector3::new(0f64, 2f64, 0f64),
+            },
+        ];
+        assert_eq!(
+            "time_samples;time_seconds;surface_index;energy;position_x;position_y;position_z\n10;0.5;3;1;0;2;0\n20;1;;0.5;1;0;0\n",
+            echogram_to_csv(&records, 20f64)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use crate::time::{SampleDuration, SampleTime};
+
+/// Errors that can occur when using the library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum DemoError {
+    /// An object's keyframes couldn't be interpolated.
+    Interpolation(InterpolationError),
+    /// A scene couldn't be built, or isn't valid for simulation.
+    SceneBuild(SceneBuildError),
+    /// A simulation couldn't be run.
+    Simulation(SimulationError),
+}
+
+/// Reasons keyframes can't be interpolated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum InterpolationError {
+    /// The object doesn't have any keyframes.
+    NoKeyframes,
+    /// The object's keyframes aren't sorted by time.
+    UnsortedKeyframes,
+}
+
+/// Reasons a scene can't be built or simulated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum SceneBuildError {
+    /// Neither coordinates nor keyframes were set for the receiver.
+    MissingReceiver,
+    /// Neither coordinates nor keyframes were set for the emitter.
+    MissingEmitter,
+    /// The scene or one of its objects has a loop duration of 0.
+    ZeroLoopDuration,
+    /// The surface with the given index doesn't touch any other surface,
+    /// see `SceneBuilder::check_connectivity`.
+    DisconnectedSurface(usize),
+    /// Two merged scenes loop with the given different durations, see `Scene::merge`.
+    LoopDurationMismatch(SampleDuration, SampleDuration),
+    /// The emitter's last keyframe at the given time lies beyond the scene's loop duration,
+    /// see `Scene::validate_emitter_loop_alignment`.
+    EmitterBeyondLoop(SampleTime, SampleDuration),
+    /// A rotating object's rotation axis is too short (or not finite) to have a direction,
+    /// see `bounce::is_degenerate_normal`.
+    DegenerateRotationAxis,
+}
+
+/// Reasons a simulation can't be run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum SimulationError {
+    /// The thread pool requested by `SimulationConfig::threads` couldn't be built.
+    ThreadPool(String),
+}
+
+impl Display for DemoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Interpolation(err) => write!(f, "couldn't interpolate keyframes: {err}"),
+            Self::SceneBuild(err) => write!(f, "invalid scene: {err}"),
+            Self::Simulation(err) => write!(f, "couldn't run the simulation: {err}"),
+        }
+    }
+}
+
+impl Display for InterpolationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoKeyframes => write!(f, "an object doesn't have any keyframes"),
+            Self::UnsortedKeyframes => write!(f, "an object's keyframes aren't sorted by time"),
+        }
+    }
+}
+
+impl Display for SceneBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingReceiver => write!(f, "the receiver doesn't have any coordinates"),
+            Self::MissingEmitter => write!(f, "the emitter doesn't have any coordinates"),
+            Self::ZeroLoopDuration => write!(f, "loop durations need to be positive"),
+            Self::DegenerateRotationAxis => {
+                write!(f, "a rotating object's rotation axis doesn't have a direction")
+            }
+            Self::DisconnectedSurface(index) => {
+                write!(f, "surface {index} doesn't touch any other surface")
+            }
+            Self::EmitterBeyondLoop(time, loop_duration) => write!(
+                f,
+                "the emitter's last keyframe at {time} lies beyond the loop duration of {loop_duration}"
+            ),
+            Self::LoopDurationMismatch(first, second) => write!(
+                f,
+                "the merged scenes loop with different durations ({first} and {second})"
+            ),
+        }
+    }
+}
+
+impl Display for SimulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ThreadPool(err) => write!(f, "couldn't build the thread pool: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DemoError {}
+
+impl From<InterpolationError> for DemoError {
+    fn from(err: InterpolationError) -> Self {
+        Self::Interpolation(err)
+    }
+}
+
+impl From<SceneBuildError> for DemoError {
+    fn from(err: SceneBuildError) -> Self {
+        Self::SceneBuild(err)
+    }
+}
+
+impl From<SimulationError> for DemoError {
+    fn from(err: SimulationError) -> Self {
+        Self::Simulation(err)
+    }
+}
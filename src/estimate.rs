@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use crate::{ray::RayStatistics, simulation_config::IrPrecision, time::SampleDuration};
+
+/// The total number of rays traced for an estimate, spread across all pilot times.
+pub const PILOT_RAYS: u32 = 500;
+/// The maximum number of times within the simulated time span a pilot batch is launched at.
+pub const PILOT_TIMES: u32 = 4;
+
+/// A single pilot ray's launch time, receiver hits and statistics.
+pub type PilotResult = (u32, Vec<(f64, u32)>, RayStatistics);
+
+/// An estimate of a simulation's output and cost, based on a small pilot batch of rays,
+/// see `SceneData::estimate`.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct SimulationEstimate {
+    /// The number of rays traced for the estimate.
+    pub pilot_rays: u32,
+    /// The estimated impulse response length in samples,
+    /// i.e. the latest observed arrival relative to its ray's launch time.
+    pub impulse_response_length: u32,
+    /// The mean number of times a ray bounced off a surface.
+    pub mean_bounces: f64,
+    /// The fraction (0 to 1) of rays that leaked out of the scene.
+    pub leaked_fraction: f64,
+    /// The time it took to trace the pilot rays.
+    pub pilot_duration: Duration,
+    /// The extrapolated time it takes to trace all rays of the full simulation.
+    pub estimated_duration: Duration,
+}
+
+impl SimulationEstimate {
+    /// Create an estimate from the pilot results.
+    /// The pilot timing is scaled up to the full simulation's `total_rays`.
+    pub fn from_pilot_results(
+        results: &[PilotResult],
+        pilot_duration: Duration,
+        total_rays: u64,
+    ) -> Self {
+        if results.is_empty() {
+            return Self::default();
+        }
+        let pilot_rays = results.len() as f64;
+        let impulse_response_length = results
+            .iter()
+            .flat_map(|(launch_time, hits, _statistics)| {
+                hits.iter()
+                    .map(move |(_energy, time)| time.saturating_sub(*launch_time) + 1)
+            })
+            .max()
+            .unwrap_or(0);
+        let total_bounces: f64 = results
+            .iter()
+            .map(|(_launch_time, _hits, statistics)| f64::from(statistics.bounces))
+            .sum();
+        let leaked = results
+            .iter()
+            .filter(|(_launch_time, _hits, statistics)| statistics.leaked)
+            .count();
+        Self {
+            pilot_rays: results.len() as u32,
+            impulse_response_length,
+            mean_bounces: total_bounces / pilot_rays,
+            leaked_fraction: leaked as f64 / pilot_rays,
+            pilot_duration,
+            estimated_duration: pilot_duration.mul_f64(total_rays as f64 / pilot_rays),
+        }
+    }
+
+    /// Get the estimated memory an impulse response of the estimated length takes up, in bytes.
+    pub const fn impulse_response_bytes(&self) -> usize {
+        self.impulse_response_length as usize * std::mem::size_of::<f64>()
+    }
+
+    /// Get the estimated memory an impulse response of the estimated length takes up
+    /// while it's applied with the given precision, in bytes.
+    pub const fn applied_impulse_response_bytes(&self, precision: IrPrecision) -> usize {
+        self.impulse_response_length as usize * precision.bytes_per_sample()
+    }
+}
+
+/// Get the times to launch pilot batches at, spread evenly across the first `number_of_impulse_responses` samples.
+/// For looping scenes, only the first loop is covered, because later loops repeat it.
+pub fn pilot_times(
+    number_of_impulse_responses: u32,
+    loop_duration: Option<SampleDuration>,
+) -> Vec<u32> {
+    let span = loop_duration.map_or(number_of_impulse_responses, |duration| {
+        duration.samples().min(number_of_impulse_responses)
+    });
+    let count = PILOT_TIMES.min(span).max(1);
+    (0..count).map(|idx| span / count * idx).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{pilot_times, SimulationEstimate};
+    use crate::{ray::RayStatistics, simulation_config::IrPrecision, time::SampleDuration};
+
+    #[test]
+    fn pilot_times_cover_first_loop() {
+        assert_eq!(vec![0], pilot_times(1, None));
+        assert_eq!(vec![0], pilot_times(0, None));
+        assert_eq!(vec![0, 250, 500, 750], pilot_times(1000, None));
+        assert_eq!(
+            vec![0, 25, 50, 75],
+            pilot_times(1000, Some(SampleDuration(100)))
+        );
+        assert_eq!(vec![0, 1], pilot_times(2, Some(SampleDuration(100))));
+    }
+
+    #[test]
+    fn estimate_from_pilot_results() {
+        let results = vec![
+            (
+                10,
+                vec![(1f64, 20), (0.5f64, 40)],
+                RayStatistics {
+                    bounces: 3,
+                    leaked: false,
+                },
+            ),
+            (
+                100,
+                vec![(1f64, 110)],
+                RayStatistics {
+                    bounces: 1,
+                    leaked: true,
+                },
+            ),
+        ];
+        let estimate =
+            SimulationEstimate::from_pilot_results(&results, Duration::from_millis(10), 20);
+        assert_eq!(2, estimate.pilot_rays);
+        assert_eq!(31, estimate.impulse_response_length);
+        assert_eq!(248, estimate.impulse_response_bytes());
+        assert_eq!(
+            124,
+            estimate.applied_impulse_response_bytes(IrPrecision::F32)
+        );
+        assert!((estimate.mean_bounces - 2f64).abs() < f64::EPSILON);
+        assert!((estimate.leaked_fraction - 0.5f64).abs() < f64::EPSILON);
+        assert_eq!(Duration::from_millis(100), estimate.estimated_duration);
+    }
+}
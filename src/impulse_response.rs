@@ -1,113 +1,819 @@
+use std::{fmt::Debug, ops::AddAssign, path::Path};
+
+use nalgebra::Vector3;
+use rand::Rng;
+
 pub type ImpulseResponse = Vec<f64>;
+/// An impulse response converted to `f32` for applying it, see `IrSample`.
+pub type ImpulseResponse32 = Vec<f32>;
+
+/// A sample type impulse responses can be applied to audio in, see `SimulationConfig::ir_precision`.
+/// Impulse responses are always accumulated as `f64` and converted once afterwards,
+/// so only applying them (and the output buffers of parallel tasks) use the narrower type.
+pub trait IrSample: num::Float + AddAssign + Send + Sync + Debug {
+    /// Convert the given `f64` value, rounding it to the nearest representable value.
+    fn narrow(value: f64) -> Self;
+
+    /// Convert this value to `f64` without losing precision.
+    fn widen(self) -> f64;
+
+    /// Convert the given impulse response, keeping it as is if `Self` is `f64`.
+    fn from_impulse_response(impulse_response: ImpulseResponse) -> Vec<Self>;
+
+    /// Convert the given samples to `f64`, keeping them as they are if `Self` is `f64`.
+    fn into_f64_vec(samples: Vec<Self>) -> Vec<f64>;
+}
+
+impl IrSample for f64 {
+    fn narrow(value: f64) -> Self {
+        value
+    }
+
+    fn widen(self) -> f64 {
+        self
+    }
+
+    fn from_impulse_response(impulse_response: ImpulseResponse) -> Vec<Self> {
+        impulse_response
+    }
+
+    fn into_f64_vec(samples: Vec<Self>) -> Vec<f64> {
+        samples
+    }
+}
+
+impl IrSample for f32 {
+    fn narrow(value: f64) -> Self {
+        value as Self
+    }
+
+    fn widen(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn from_impulse_response(impulse_response: ImpulseResponse) -> Vec<Self> {
+        impulse_response.into_iter().map(Self::narrow).collect()
+    }
+
+    fn into_f64_vec(samples: Vec<Self>) -> Vec<f64> {
+        samples.into_iter().map(Self::widen).collect()
+    }
+}
+
+/// How the summed energies are normalised in `to_impulse_response`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NormalisationMode {
+    /// Divide by the number of launched rays.
+    #[default]
+    PerRayCount,
+    /// Divide by the sum of all received energies, so the impulse response sums up to 1.
+    /// This avoids underestimating the received energy in scenes where many rays miss the receiver.
+    PerReceivedEnergy,
+    /// Keep the raw summed energies.
+    None,
+}
 
 /// Convert a set of intersection events into an impulse response.
 /// Each event (described as a combination of the energy and time)
-/// is stored in the IR buffer at its relevant time.
+/// is stored in the IR buffer at its relevant time,
+/// then the buffer is normalised according to `normalisation_mode`.
+/// The events are consumed in a single pass, growing the buffer as needed,
+/// so they don't need to be collected beforehand.
 #[allow(clippy::module_name_repetitions)]
-pub fn to_impulse_response(results: &[(f64, u32)], number_of_rays: u32) -> ImpulseResponse {
-    let buf_size = results
-        .iter()
-        .max_by_key(|result| result.1)
-        .unwrap_or(&(0f64, 0))
-        .1 as usize
-        + 1;
-    let mut impulse_response_buffer = vec![0f64; buf_size];
-    for result in results {
-        impulse_response_buffer[result.1 as usize] += result.0;
-    }
-    let number_of_rays_float = f64::from(number_of_rays);
+pub fn to_impulse_response(
+    results: impl IntoIterator<Item = (f64, u32)>,
+    number_of_rays: u32,
+    normalisation_mode: NormalisationMode,
+) -> ImpulseResponse {
+    // the impulse response always covers at least time 0
+    let mut impulse_response_buffer = vec![0f64];
+    let mut received_energy = 0f64;
+    for (energy, time) in results {
+        add_to_impulse_response(&mut impulse_response_buffer, energy, time);
+        received_energy += energy;
+    }
+    normalise_impulse_response(
+        impulse_response_buffer,
+        received_energy,
+        number_of_rays,
+        normalisation_mode,
+    )
+}
+
+/// Add an intersection event's energy to the unnormalised IR buffer at the given time,
+/// growing the buffer as needed.
+pub fn add_to_impulse_response(
+    impulse_response_buffer: &mut ImpulseResponse,
+    energy: f64,
+    time: u32,
+) {
+    let time = time as usize;
+    if impulse_response_buffer.len() <= time {
+        impulse_response_buffer.resize(time + 1, 0f64);
+    }
+    impulse_response_buffer[time] += energy;
+}
+
+/// Add two unnormalised IR buffers, e.g. ones accumulated on different threads.
+/// The result is as long as the longer one.
+pub fn merge_impulse_responses(first: ImpulseResponse, second: ImpulseResponse) -> ImpulseResponse {
+    let (mut longer, shorter) = if first.len() >= second.len() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    for (value, other) in longer.iter_mut().zip(shorter) {
+        *value += other;
+    }
+    longer
+}
+
+/// Normalise an unnormalised IR buffer according to `normalisation_mode`,
+/// given the total energy of the events summed up in it and the number of launched rays.
+pub fn normalise_impulse_response(
+    impulse_response_buffer: ImpulseResponse,
+    received_energy: f64,
+    number_of_rays: u32,
+    normalisation_mode: NormalisationMode,
+) -> ImpulseResponse {
+    let divisor = match normalisation_mode {
+        NormalisationMode::PerRayCount => f64::from(number_of_rays),
+        NormalisationMode::PerReceivedEnergy => received_energy,
+        NormalisationMode::None => return impulse_response_buffer,
+    };
+    if divisor == 0f64 {
+        // nothing was received, so there's nothing to normalise
+        return impulse_response_buffer;
+    }
     impulse_response_buffer
         .iter()
-        .map(|val| val / number_of_rays_float)
+        .map(|val| val / divisor)
         .collect()
 }
 
-/// Internal logic to apply a set of impulse responses to a set of `data` points.
-/// This assumes that there are at least as many `impulse_response` entries as there are `data` points.
-/// Each data point has the impulse response at the same time applied to it.
-/// 
+/// A statistical model of the late reverb, appended to simulated impulse responses to make up for
+/// the sparse late arrivals of too few rays, see `Scene::statistical_tail` and `add_statistical_tail`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatisticalTail {
+    /// The time in seconds the tail's energy takes to decay by 60 dB. This needs to be positive.
+    pub t60: f64,
+    /// The tail's mean energy per sample at its onset in dB, relative to the normalised impulse response,
+    /// e.g. relative to the emitted energy for `NormalisationMode::PerRayCount`.
+    pub level_db: f64,
+    /// The time in seconds after the emission the tail starts at.
+    pub onset_time: f64,
+}
+
+/// Add a statistical tail to the given (normalised) impulse response, starting `tail.onset_time` seconds
+/// after `start_sample`, the sample the rays were emitted at.
+/// The tail is Gaussian noise under an exponentially decaying envelope. Since impulse responses hold energies,
+/// each sample adds the squared noise times the envelope, so its mean energy follows the envelope.
+/// The tail ends once the envelope has decayed by 60 dB, extending the impulse response if needed.
+/// Tails without a positive T60 add nothing.
+pub fn add_statistical_tail<R: Rng + ?Sized>(
+    impulse_response: &mut ImpulseResponse,
+    tail: &StatisticalTail,
+    start_sample: usize,
+    sample_rate: f64,
+    rng: &mut R,
+) {
+    if tail.t60.is_nan() || tail.t60 <= 0f64 {
+        return;
+    }
+    let onset_sample = start_sample + (tail.onset_time.max(0f64) * sample_rate).round() as usize;
+    let length = (tail.t60 * sample_rate).ceil() as usize;
+    if impulse_response.len() < onset_sample + length {
+        impulse_response.resize(onset_sample + length, 0f64);
+    }
+    let level = 10f64.powf(tail.level_db / 10f64);
+    // the energy drops by 60 dB, i.e. a factor of 10^-6, over T60
+    let decay_per_sample = 10f64.powf(-6f64 / (tail.t60 * sample_rate));
+    let mut envelope = level;
+    for sample in &mut impulse_response[onset_sample..onset_sample + length] {
+        let noise = standard_normal(rng);
+        *sample = (noise * noise).mul_add(envelope, *sample);
+        envelope *= decay_per_sample;
+    }
+}
+
+/// Draw a standard normally distributed number using the Box-Muller transform.
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    // 1 - [0, 1) lies within (0, 1], so the logarithm stays finite
+    let radius = (-2f64 * (1f64 - rng.gen::<f64>()).ln()).sqrt();
+    radius * (std::f64::consts::TAU * rng.gen::<f64>()).cos()
+}
+
+/// A first-order ambisonic (B-format) impulse response.
+/// `w` holds the omnidirectional energy, while `x`, `y` and `z` weight each arrival's energy
+/// with the direction cosines of the direction it arrives from, relative to the receiver's orientation.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct AmbisonicImpulseResponse {
+    pub w: ImpulseResponse,
+    pub x: ImpulseResponse,
+    pub y: ImpulseResponse,
+    pub z: ImpulseResponse,
+}
+
+impl AmbisonicImpulseResponse {
+    /// Get the channels in W, X, Y, Z order.
+    pub const fn channels(&self) -> [&ImpulseResponse; 4] {
+        [&self.w, &self.x, &self.y, &self.z]
+    }
+
+    /// Interleave the channels in W, X, Y, Z order, e.g. for writing a 4-channel audio file.
+    pub fn interleaved(&self) -> Vec<f64> {
+        (0..self.w.len())
+            .flat_map(|idx| self.channels().map(|channel| channel[idx]))
+            .collect()
+    }
+}
+
+/// Convert a set of intersection events with directions into a first-order ambisonic impulse response.
+/// Each event is described as a combination of the energy, time and the direction the ray travelled in
+/// when hitting the receiver, relative to the receiver's orientation, see `Ray::launch_with_directions`.
+/// The sound arrives from the opposite direction, whose direction cosines weight the energy for X, Y and Z.
+/// All channels are divided by the number of launched rays, like `NormalisationMode::PerRayCount`.
+pub fn to_ambisonic_impulse_response(
+    results_with_direction: impl IntoIterator<Item = (f64, u32, Vector3<f64>)>,
+    number_of_rays: u32,
+) -> AmbisonicImpulseResponse {
+    // the impulse response always covers at least time 0
+    let mut result = AmbisonicImpulseResponse {
+        w: vec![0f64],
+        x: vec![0f64],
+        y: vec![0f64],
+        z: vec![0f64],
+    };
+    for (energy, time, direction) in results_with_direction {
+        let time = time as usize;
+        if result.w.len() <= time {
+            for channel in [&mut result.w, &mut result.x, &mut result.y, &mut result.z] {
+                channel.resize(time + 1, 0f64);
+            }
+        }
+        let arrival_direction = -direction.normalize();
+        result.w[time] += energy;
+        result.x[time] += energy * arrival_direction.x;
+        result.y[time] += energy * arrival_direction.y;
+        result.z[time] += energy * arrival_direction.z;
+    }
+    if number_of_rays == 0 {
+        return result;
+    }
+    let divisor = f64::from(number_of_rays);
+    for channel in [&mut result.w, &mut result.x, &mut result.y, &mut result.z] {
+        for val in channel.iter_mut() {
+            *val /= divisor;
+        }
+    }
+    result
+}
+
+/// Find the time after which no more intersection events need to be accepted
+/// to capture `decay_db` dB of the decay.
+/// `energy_histogram` holds the energy received at each sample, like an unnormalised IR buffer,
+/// so it can be accumulated batch by batch with `add_to_impulse_response`.
+/// This is the last sample receiving energy at which the Schroeder integral (the energy received from then on)
+/// is still at most `decay_db` dB below its value at the direct sound, i.e. the total received energy.
+/// Returns `None` if the events don't decay far enough (or don't contain any energy),
+/// meaning later events still need to be accepted.
+pub fn decay_cutoff_time(energy_histogram: &[f64], decay_db: f64) -> Option<u32> {
+    let total_energy: f64 = energy_histogram.iter().sum();
+    if total_energy <= 0f64 {
+        return None;
+    }
+    let threshold = total_energy * 10f64.powf(-decay_db / 10f64);
+
+    let mut remaining_energy = total_energy;
+    let mut cutoff_time = None;
+    for (time, energy) in energy_histogram.iter().enumerate() {
+        if *energy == 0f64 {
+            continue;
+        }
+        if remaining_energy < threshold {
+            return cutoff_time;
+        }
+        cutoff_time = Some(time as u32);
+        remaining_energy -= energy;
+    }
+    None
+}
+
+/// Add the given impulse response, applied to a single sample value and scaled by
+/// `scaling_factor`, into `buffer`, starting at `offset`.
+/// The buffer is extended if it's too short to hold the result.
+/// All other `apply_*` functions use this, so scaling is handled identically for each of them.
+/// The calculation is done in the impulse response's sample type, see `IrSample`.
+fn add_to_buffer<F: IrSample>(
+    buffer: &mut Vec<F>,
+    impulse_response: &[F],
+    sample: F,
+    offset: usize,
+    scaling_factor: F,
+) {
+    let end = offset + impulse_response.len();
+    if buffer.len() < end {
+        buffer.resize(end, F::zero());
+    }
+    buffer[offset..end]
+        .iter_mut()
+        .zip(impulse_response)
+        .for_each(|(val, ir_value)| *val += sample * *ir_value * scaling_factor);
+}
+
+/// Convert a data point to the impulse responses' sample type, treating unconvertible values as 0.
+fn to_ir_sample<T: num::NumCast, F: IrSample>(sample: T) -> F {
+    num::cast::<T, F>(sample).unwrap_or_else(F::zero)
+}
+
+/// Mix the unprocessed `dry` input into the convolved `buffer`,
+/// so the convolved signal makes up the fraction `dry_wet` (0 to 1) of the result.
+/// Both signals are scaled by the input gain in dB.
+/// The dry signal isn't delayed, so `dry[idx]` is mixed into `buffer[idx]`.
+pub fn mix_dry_signal<T: num::Num + num::NumCast + Clone + Copy>(
+    buffer: &mut [f64],
+    dry: &[T],
+    dry_wet: f64,
+    input_gain_db: f64,
+) {
+    let gain = 10f64.powf(input_gain_db / 20f64);
+    let wet_factor = dry_wet * gain;
+    let dry_factor = (1f64 - dry_wet) * gain;
+    for (idx, value) in buffer.iter_mut().enumerate() {
+        let dry_sample = dry
+            .get(idx)
+            .and_then(|sample| num::cast::<T, f64>(*sample))
+            .unwrap_or(0f64);
+        *value = value.mul_add(wet_factor, dry_sample * dry_factor);
+    }
+}
+
+/// Apply a set of impulse responses to a set of consecutive `data` points,
+/// the first of which is at `start_index` in the whole input.
+/// Each data point has the impulse response with the same index applied to it,
+/// so there need to be at least as many `impulse_responses` as there are `data` points.
+/// Like `apply_to_sample`, the result starts at the output's beginning, so it has the standard convolution length
+/// of `start_index + data.len() + max_ir_len - 1` for the longest impulse response's length `max_ir_len`,
+/// or is empty if there are no data points or all impulse responses are empty.
+///
 /// # Panics
-/// 
-/// * If `T` can't be cast to f64 or vice versa
-pub fn apply_to_data<T: num::Num + num::NumCast + Clone + Copy>(
-    impulse_response: &[ImpulseResponse],
+///
+/// * If there are fewer impulse responses than data points.
+pub fn apply_to_data<T: num::Num + num::NumCast + Clone + Copy, F: IrSample>(
+    impulse_responses: &[Vec<F>],
     data: &[T],
-) -> Vec<T> {
-    let max_t60 = impulse_response
-        .iter()
-        .max_by_key(|result| result.len())
-        .unwrap_or(&vec![])
-        .len()
-        + 1;
-    let mut buffer = vec![T::zero(); max_t60 + data.len()];
-    for (index, sample) in data.iter().enumerate() {
-        let response = &impulse_response[index];
-        for (idx, value) in response.iter().enumerate() {
-            buffer[index + idx] = buffer[index + idx]
-                + num::cast::<f64, T>(num::cast::<T, f64>(*sample).unwrap() * value).unwrap();
-        }
+    start_index: usize,
+    scaling_factor: f64,
+) -> Vec<F> {
+    assert!(
+        impulse_responses.len() >= data.len(),
+        "Every data point needs an impulse response"
+    );
+    let max_ir_len = impulse_responses.iter().map(Vec::len).max().unwrap_or(0);
+    if data.is_empty() || max_ir_len == 0 {
+        return vec![];
+    }
+    let mut buffer = vec![F::zero(); start_index + data.len() + max_ir_len - 1];
+    for (index, (sample, impulse_response)) in data.iter().zip(impulse_responses).enumerate() {
+        add_to_buffer(
+            &mut buffer,
+            impulse_response,
+            to_ir_sample(*sample),
+            start_index + index,
+            F::narrow(scaling_factor),
+        );
     }
     buffer
 }
 
-/// Apply a single impulse response to a single data point.
-pub fn apply_to_sample<T: num::Num + num::NumCast + Clone + Copy>(
-    impulse_response: &[f64],
+/// Apply a single impulse response to a single data point at the given `index`.
+/// The result starts at the output's beginning, so it can be added to the output buffer as is.
+/// Like the other `apply_*` functions, it has the standard convolution length for the `index + 1` samples
+/// up to the data point, `index + impulse_response.len()`, or is empty if the impulse response is.
+pub fn apply_to_sample<T: num::Num + num::NumCast + Clone + Copy, F: IrSample>(
+    impulse_response: &[F],
     sample: T,
     index: usize,
     scaling_factor: f64,
-) -> Vec<f64> {
-    let mut buffer = vec![0f64; impulse_response.len() + index + 1];
-    for (idx, value) in impulse_response.iter().enumerate() {
-        buffer[idx] = num::cast::<T, f64>(sample).unwrap_or(0f64) * value * scaling_factor;
+) -> Vec<F> {
+    if impulse_response.is_empty() {
+        return vec![];
     }
+    let mut buffer = vec![F::zero(); impulse_response.len() + index];
+    add_to_buffer(
+        &mut buffer,
+        impulse_response,
+        to_ir_sample(sample),
+        index,
+        F::narrow(scaling_factor),
+    );
     buffer
 }
 
-/// Apply a single impulse response to several data points from a looping scene.
-/// This assumes the samples are sorted by index.
-pub fn apply_to_many_samples<T: num::Num + num::NumCast + Clone + Copy>(
-    impulse_response: &[f64],
+/// Apply a single impulse response to several consecutive data points.
+/// The result has the standard convolution length of
+/// `impulse_response.len() + samples.len() - 1`, or is empty if either input is empty.
+pub fn apply_to_many_samples<T: num::Num + num::NumCast + Clone + Copy, F: IrSample>(
+    impulse_response: &[F],
     samples: &[T],
     scaling_factor: f64,
-) -> Vec<f64> {
-    let mut buffer =
-        vec![0f64; impulse_response.len() + samples.len() + 1];
-    for (idx, value) in impulse_response.iter().enumerate() {
-        for (sample_num, sample) in samples.iter().enumerate() {
-            buffer[idx + sample_num] += num::cast::<T, f64>(*sample).unwrap_or(0f64) * value * scaling_factor;
-        }
+) -> Vec<F> {
+    if impulse_response.is_empty() || samples.is_empty() {
+        return vec![];
+    }
+    let mut buffer = vec![F::zero(); impulse_response.len() + samples.len() - 1];
+    for (sample_num, sample) in samples.iter().enumerate() {
+        add_to_buffer(
+            &mut buffer,
+            impulse_response,
+            to_ir_sample(*sample),
+            sample_num,
+            F::narrow(scaling_factor),
+        );
     }
     buffer
 }
 
 /// Apply a single impulse response to several data points from a looping scene.
+/// Each sample's impulse response starts at the sample's own index,
+/// so all samples need to be at the same time within the loop.
 /// This assumes the samples are sorted by index.
-pub fn apply_looped_to_many_samples<T: num::Num + num::NumCast + Clone + Copy>(
-    impulse_response: &[f64],
+/// The buffer only extends up to the last output index that can be non-zero,
+/// see `max_output_index`, and is empty if there are no samples.
+pub fn apply_looped_to_many_samples<T: num::Num + num::NumCast + Clone + Copy, F: IrSample>(
+    impulse_response: &[F],
     samples: &[(usize, T)],
     scaling_factor: f64,
     loop_duration: usize,
-) -> Vec<f64> {
-    let mut buffer =
-        vec![0f64; impulse_response.len() + samples.last().unwrap_or(&(0, T::zero())).0 + 1];
-    for (idx, value) in impulse_response.iter().enumerate() {
-        for (sample_num, sample) in samples.iter().enumerate() {
-            buffer[idx + sample_num * loop_duration] += num::cast::<T, f64>(sample.1).unwrap_or(0f64) * value * scaling_factor;
-        }
+) -> Vec<F> {
+    debug_assert!(
+        samples
+            .windows(2)
+            .all(|pair| pair[0].0 % loop_duration == pair[1].0 % loop_duration),
+        "Samples aren't at the same time within the loop!"
+    );
+    let Some((last_sample_offset, _value)) = samples.last() else {
+        return vec![];
+    };
+    let Some(max_index) = max_output_index(impulse_response, *last_sample_offset) else {
+        return vec![];
+    };
+    let mut buffer = vec![F::zero(); max_index + 1];
+    let relevant_len = max_index + 1 - *last_sample_offset;
+    for sample in samples {
+        add_to_buffer(
+            &mut buffer,
+            &impulse_response[..relevant_len],
+            to_ir_sample(sample.1),
+            sample.0,
+            F::narrow(scaling_factor),
+        );
     }
     buffer
 }
 
+/// Get the last output index that can hold a non-zero value when applying the given
+/// impulse response to a sample placed at `last_sample_offset`.
+/// Trailing zeros in the impulse response can't contribute to the output and are ignored.
+/// Returns `None` if the impulse response doesn't contain any non-zero values.
+pub fn max_output_index<F: IrSample>(
+    impulse_response: &[F],
+    last_sample_offset: usize,
+) -> Option<usize> {
+    impulse_response
+        .iter()
+        .rposition(|value| !value.is_zero())
+        .map(|idx| idx + last_sample_offset)
+}
+
+/// How signs are assigned to the values of a pressure impulse response
+/// in `convert_energy_to_pressure`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PressureSign {
+    /// Non-zero values alternate between positive and negative, starting with positive.
+    Alternating,
+    /// Each non-zero value gets a random sign.
+    Random,
+}
+
+/// Post-processing steps applied to each simulated impulse response
+/// before it is applied to the input audio.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct PostProcessing {
+    /// If set, convert the energetic response into a pressure response with the given
+    /// sign mode and remove its DC offset afterwards.
+    pub pressure: Option<PressureSign>,
+    /// If set, normalise the impulse response so its peak has this absolute value.
+    pub normalise_peak: Option<f64>,
+}
+
+impl PostProcessing {
+    /// Apply all configured post-processing steps to the given impulse response.
+    /// The pressure conversion happens before normalisation.
+    pub fn apply(&self, impulse_response: ImpulseResponse) -> ImpulseResponse {
+        let mut result = impulse_response;
+        if let Some(sign) = self.pressure {
+            result = remove_dc(&convert_energy_to_pressure(&result, sign));
+        }
+        if let Some(target) = self.normalise_peak {
+            result = normalise_peak(&result, target);
+        }
+        result
+    }
+}
+
+/// Remove the DC offset from the given impulse response by subtracting its mean from every value.
+pub fn remove_dc(impulse_response: &[f64]) -> ImpulseResponse {
+    if impulse_response.is_empty() {
+        return vec![];
+    }
+    let mean = impulse_response.iter().sum::<f64>() / impulse_response.len() as f64;
+    impulse_response.iter().map(|value| value - mean).collect()
+}
+
+/// Scale the given impulse response so the value with the highest absolute value
+/// ends up with an absolute value of `target`.
+/// Impulse responses without any non-zero values are returned unchanged.
+pub fn normalise_peak(impulse_response: &[f64], target: f64) -> ImpulseResponse {
+    let peak = impulse_response
+        .iter()
+        .fold(0f64, |peak, value| peak.max(value.abs()));
+    if peak == 0f64 {
+        return impulse_response.to_vec();
+    }
+    let factor = target / peak;
+    impulse_response
+        .iter()
+        .map(|value| value * factor)
+        .collect()
+}
+
+/// Convert an energetic impulse response into a pressure impulse response.
+/// Each value becomes the square root of its energy, with its sign determined by `sign`.
+/// This keeps the total energy (i.e. the sum of the squared values) intact.
+pub fn convert_energy_to_pressure(impulse_response: &[f64], sign: PressureSign) -> ImpulseResponse {
+    let mut positive = false;
+    impulse_response
+        .iter()
+        .map(|value| {
+            if *value == 0f64 {
+                return 0f64;
+            }
+            positive = match sign {
+                PressureSign::Alternating => !positive,
+                PressureSign::Random => rand::random::<bool>(),
+            };
+            let pressure = value.abs().sqrt();
+            if positive {
+                pressure
+            } else {
+                -pressure
+            }
+        })
+        .collect()
+}
+
+/// Get the energy decay curve (the Schroeder integral) of the given energetic impulse response,
+/// i.e. for each sample, the energy received from that sample on.
+/// Pressure impulse responses need to be squared first, see `convert_energy_to_pressure`.
+pub fn energy_decay_curve(impulse_response: &[f64]) -> Vec<f64> {
+    let mut remaining_energy = 0f64;
+    let mut result: Vec<f64> = impulse_response
+        .iter()
+        .rev()
+        .map(|energy| {
+            remaining_energy += energy;
+            remaining_energy
+        })
+        .collect();
+    result.reverse();
+    result
+}
+
+/// Get the time in seconds the given energetic impulse response takes to decay by 60 dB,
+/// extrapolated from a least-squares line through its energy decay curve (see `energy_decay_curve`)
+/// between `start_db` and `end_db` below its total energy, e.g. -5 and -35 dB for T30.
+/// Returns `None` if the curve doesn't decay by `end_db` or has fewer than two samples in the range.
+pub fn decay_time(
+    impulse_response: &[f64],
+    sample_rate: f64,
+    start_db: f64,
+    end_db: f64,
+) -> Option<f64> {
+    let curve = energy_decay_curve(impulse_response);
+    let total_energy = *curve.first().filter(|energy| **energy > 0f64)?;
+    let levels: Vec<f64> = curve
+        .iter()
+        .map(|energy| 10f64 * (energy / total_energy).log10())
+        .collect();
+    if levels.last().is_none_or(|level| *level >= end_db) {
+        return None;
+    }
+    let points: Vec<(f64, f64)> = levels
+        .iter()
+        .enumerate()
+        .filter(|(_index, level)| (end_db..=start_db).contains(*level))
+        .map(|(index, level)| (index as f64 / sample_rate, *level))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let count = points.len() as f64;
+    let mean_time = points.iter().map(|(time, _level)| time).sum::<f64>() / count;
+    let mean_level = points.iter().map(|(_time, level)| level).sum::<f64>() / count;
+    let (covariance, variance) =
+        points
+            .iter()
+            .fold((0f64, 0f64), |(covariance, variance), (time, level)| {
+                let time = time - mean_time;
+                (
+                    time.mul_add(level - mean_level, covariance),
+                    time.mul_add(time, variance),
+                )
+            });
+    let slope = covariance / variance;
+    (slope < 0f64).then(|| -60f64 / slope)
+}
+
+/// Get the reverberation time T30 in seconds of the given energetic impulse response,
+/// see `decay_time`.
+pub fn t30(impulse_response: &[f64], sample_rate: f64) -> Option<f64> {
+    decay_time(impulse_response, sample_rate, -5f64, -35f64)
+}
+
+/// Get the early decay time in seconds of the given energetic impulse response,
+/// see `decay_time`.
+pub fn early_decay_time(impulse_response: &[f64], sample_rate: f64) -> Option<f64> {
+    decay_time(impulse_response, sample_rate, 0f64, -10f64)
+}
+
+/// Get the reverb envelope of the given impulse response, i.e. for each sample,
+/// the RMS of the values in the window of `window_samples` samples starting at it.
+/// Windows reaching past the end of the impulse response only cover its remaining samples.
+///
+/// # Panics
+///
+/// * If `window_samples` is 0.
+pub fn reverb_envelope(impulse_response: &[f64], window_samples: usize) -> Vec<f64> {
+    assert!(
+        window_samples > 0,
+        "The window needs to cover at least one sample!"
+    );
+    let squares: Vec<f64> = impulse_response.iter().map(|value| value * value).collect();
+    let mut window_sum: f64 = squares.iter().take(window_samples).sum();
+    (0..squares.len())
+        .map(|idx| {
+            let window_len = window_samples.min(squares.len() - idx);
+            let rms = (window_sum.max(0f64) / window_len as f64).sqrt();
+            window_sum -= squares[idx];
+            if let Some(next) = squares.get(idx + window_samples) {
+                window_sum += next;
+            }
+            rms
+        })
+        .collect()
+}
+
+/// Read an impulse response written as text, with each value followed by a semicolon,
+/// e.g. by the `ir` subcommand. Whitespace around the values is ignored.
+///
+/// # Errors
+///
+/// * If the file can't be read.
+/// * `std::io::ErrorKind::InvalidData` if any of the values isn't a number.
+pub fn read_text(path: &Path) -> std::io::Result<ImpulseResponse> {
+    std::fs::read_to_string(path)?
+        .split(';')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value.parse::<f64>().map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("\"{value}\" isn't a number"),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Get the index of the given impulse response's first non-zero sample, i.e. the direct sound's arrival time.
+/// Returns `None` if all samples are zero.
+pub fn direct_sound_arrival(impulse_response: &[f64]) -> Option<usize> {
+    impulse_response.iter().position(|value| *value != 0f64)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::to_impulse_response;
+    use super::{
+        add_statistical_tail, add_to_impulse_response, apply_looped_to_many_samples, apply_to_data,
+        apply_to_many_samples, apply_to_sample, convert_energy_to_pressure, decay_cutoff_time,
+        decay_time, direct_sound_arrival, early_decay_time, energy_decay_curve, max_output_index,
+        merge_impulse_responses, mix_dry_signal, normalise_peak, remove_dc, reverb_envelope, t30,
+        to_ambisonic_impulse_response, to_impulse_response, NormalisationMode, PostProcessing,
+        PressureSign, StatisticalTail,
+    };
+    use nalgebra::Vector3;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn merging_impulse_responses_adds_them_up() {
+        assert_eq!(
+            vec![1.5, 2.5, 3f64],
+            merge_impulse_responses(vec![1f64, 2f64], vec![0.5, 0.5, 3f64])
+        );
+        assert_eq!(
+            vec![1.5, 2.5, 3f64],
+            merge_impulse_responses(vec![0.5, 0.5, 3f64], vec![1f64, 2f64])
+        );
+    }
+
+    #[test]
+    fn decay_cutoff_time_of_halving_events() {
+        // each event has half the energy of the previous one, so the Schroeder integral halves as well
+        let mut input = vec![];
+        for idx in 0..20 {
+            add_to_impulse_response(&mut input, 0.5f64.powi(idx), 10 * idx as u32 + 5);
+        }
+        // a 10 dB decay is reached after 10*log10(2) dB per event, so after 4 events
+        assert_eq!(Some(35), decay_cutoff_time(&input, 10f64));
+        assert_eq!(Some(65), decay_cutoff_time(&input, 20f64));
+        // the events only cover roughly 60 dB
+        assert_eq!(None, decay_cutoff_time(&input, 70f64));
+        assert_eq!(None, decay_cutoff_time(&[], 10f64));
+    }
+
+    /// An impulse train starting at sample 3 with an impulse every 4 samples,
+    /// each with half the energy of the previous one.
+    fn halving_impulse_train() -> Vec<f64> {
+        let mut impulse_response = vec![0f64; 16];
+        for (idx, sample) in (3..16).step_by(4).enumerate() {
+            impulse_response[sample] = 0.5f64.powi(idx as i32);
+        }
+        impulse_response
+    }
+
+    #[test]
+    fn energy_decay_curve_of_impulse_train() {
+        let result = energy_decay_curve(&halving_impulse_train());
+        let mut expected = vec![1.875f64; 4];
+        expected.extend([0.875f64; 4]);
+        expected.extend([0.375f64; 4]);
+        expected.extend([0.125f64; 4]);
+        assert_eq!(expected, result);
+        assert!(energy_decay_curve(&[]).is_empty());
+    }
+
+    #[test]
+    fn reverb_envelope_of_impulse_train() {
+        let impulse_response = halving_impulse_train();
+        // every window of 4 samples holds exactly one impulse
+        let result = reverb_envelope(&impulse_response, 4);
+        assert_eq!(16, result.len());
+        for (idx, expected) in [(0, 0.5f64), (3, 0.5f64), (4, 0.25f64), (12, 0.0625f64)] {
+            assert!((result[idx] - expected).abs() < f64::EPSILON);
+        }
+        // windows at the end only cover the remaining samples
+        assert!((result[13] - 0.125f64 / 3f64.sqrt()).abs() < 1e-12);
+        assert!((result[15] - 0.125f64).abs() < f64::EPSILON);
+        // single-sample windows are the absolute values
+        let single = reverb_envelope(&[0f64, -2f64, 1f64], 1);
+        assert_eq!(vec![0f64, 2f64, 1f64], single);
+    }
+
+    #[test]
+    fn direct_sound_arrival_of_impulse_train() {
+        assert_eq!(Some(3), direct_sound_arrival(&halving_impulse_train()));
+        assert_eq!(Some(0), direct_sound_arrival(&[-1f64, 0f64]));
+        assert_eq!(None, direct_sound_arrival(&[0f64; 5]));
+        assert_eq!(None, direct_sound_arrival(&[]));
+    }
+
+    #[test]
+    fn results_with_direction_to_ambisonic_impulse_response() {
+        let input = vec![
+            // travelling along +X, so arriving from -X
+            (1f64, 2, Vector3::new(2f64, 0f64, 0f64)),
+            // travelling along -Z, so arriving from +Z
+            (0.5f64, 2, Vector3::new(0f64, 0f64, -1f64)),
+            (0.25f64, 4, Vector3::new(0f64, -1f64, 0f64)),
+        ];
+        let result = to_ambisonic_impulse_response(input, 2);
+        assert_eq!(vec![0f64, 0f64, 0.75f64, 0f64, 0.125f64], result.w);
+        assert_eq!(vec![0f64, 0f64, -0.5f64, 0f64, 0f64], result.x);
+        assert_eq!(vec![0f64, 0f64, 0f64, 0f64, 0.125f64], result.y);
+        assert_eq!(vec![0f64, 0f64, 0.25f64, 0f64, 0f64], result.z);
+        assert_eq!(
+            vec![0.75f64, -0.5f64, 0f64, 0.25f64],
+            result.interleaved()[8..12].to_vec()
+        );
+    }
 
     #[test]
     fn empty_result_to_impulse_response() {
         let input: Vec<(f64, u32)> = vec![];
-        let result = to_impulse_response(&input, 10000);
-        assert_eq!(vec![0f64], result)
+        let result = to_impulse_response(input, 10000, NormalisationMode::PerRayCount);
+        assert_eq!(vec![0f64], result);
     }
 
     #[test]
@@ -115,7 +821,10 @@ mod tests {
         let input = vec![(1.0f64, 90)];
         let mut expected = vec![0f64; 91];
         expected[90] = 0.0001f64;
-        assert_eq!(expected, to_impulse_response(&input, 10000))
+        assert_eq!(
+            expected,
+            to_impulse_response(input, 10000, NormalisationMode::PerRayCount)
+        );
     }
 
     #[test]
@@ -123,6 +832,347 @@ mod tests {
         let input = vec![(1.0f64, 90), (0.5f64, 90)];
         let mut expected = vec![0f64; 91];
         expected[90] = 0.00015f64;
-        assert_eq!(expected, to_impulse_response(&input, 10000))
+        assert_eq!(
+            expected,
+            to_impulse_response(input, 10000, NormalisationMode::PerRayCount)
+        );
+    }
+
+    #[test]
+    fn normalisation_modes_to_impulse_response() {
+        let input = [(1.0f64, 2), (0.5f64, 4), (0.5f64, 2)];
+
+        assert_eq!(
+            vec![0f64, 0f64, 0.375f64, 0f64, 0.125f64],
+            to_impulse_response(input, 4, NormalisationMode::PerRayCount)
+        );
+        assert_eq!(
+            vec![0f64, 0f64, 0.75f64, 0f64, 0.25f64],
+            to_impulse_response(
+                input.iter().copied(),
+                4,
+                NormalisationMode::PerReceivedEnergy
+            )
+        );
+        assert_eq!(
+            vec![0f64, 0f64, 1.5f64, 0f64, 0.5f64],
+            to_impulse_response(input, 4, NormalisationMode::None)
+        );
+    }
+
+    #[test]
+    fn unsorted_iterator_to_impulse_response() {
+        let results = (0..4u32).rev().map(|time| (f64::from(time), time * 2));
+        assert_eq!(
+            vec![0f64, 0f64, 1f64, 0f64, 2f64, 0f64, 3f64],
+            to_impulse_response(results, 1, NormalisationMode::None)
+        );
+    }
+
+    #[test]
+    fn empty_result_per_received_energy() {
+        let input: Vec<(f64, u32)> = vec![];
+        assert_eq!(
+            vec![0f64],
+            to_impulse_response(input, 10, NormalisationMode::PerReceivedEnergy)
+        );
+    }
+
+    #[test]
+    fn apply_to_many_samples_has_convolution_length() {
+        let impulse_response = vec![0.5f64, 0.25f64];
+        let samples = vec![1i16; 5];
+        let result = apply_to_many_samples(&impulse_response, &samples, 1f64);
+        assert_eq!(impulse_response.len() + samples.len() - 1, result.len());
+        assert_eq!(
+            Some(impulse_response.len() + samples.len() - 2),
+            result.iter().rposition(|value| *value != 0f64)
+        );
+        assert_eq!(
+            vec![0.5f64, 0.75f64, 0.75f64, 0.75f64, 0.75f64, 0.25f64],
+            result
+        );
+    }
+
+    #[test]
+    fn apply_to_many_samples_empty() {
+        let samples: Vec<i16> = vec![];
+        assert_eq!(
+            Vec::<f64>::new(),
+            apply_to_many_samples(&[1f64], &samples, 1f64)
+        );
+        assert_eq!(Vec::<f64>::new(), apply_to_many_samples(&[], &[1i16], 1f64));
+    }
+
+    #[test]
+    fn apply_looped_to_many_samples_exact_length() {
+        let impulse_response = vec![1f64, 0.5f64, 0f64];
+        let samples = vec![(0usize, 1i16), (4, 2)];
+        let result = apply_looped_to_many_samples(&impulse_response, &samples, 1f64, 4);
+        assert_eq!(vec![1f64, 0.5f64, 0f64, 0f64, 2f64, 1f64], result);
+    }
+
+    #[test]
+    fn apply_looped_to_many_samples_edge_cases() {
+        let impulse_response = vec![1f64, 0.5f64];
+        let no_samples: Vec<(usize, i16)> = vec![];
+        assert_eq!(
+            Vec::<f64>::new(),
+            apply_looped_to_many_samples(&impulse_response, &no_samples, 1f64, 4)
+        );
+        assert_eq!(
+            vec![2f64, 1f64],
+            apply_looped_to_many_samples(&impulse_response, &[(0usize, 2i16)], 1f64, 4)
+        );
+        let result = apply_looped_to_many_samples(&impulse_response, &[(1000usize, 2i16)], 1f64, 4);
+        assert_eq!(1002, result.len());
+        assert!(result[..1000].iter().all(|value| *value == 0f64));
+        assert_eq!(vec![2f64, 1f64], result[1000..].to_vec());
+    }
+
+    #[test]
+    fn decay_times_of_exponential_decay() {
+        // decays by 60 dB every 0.5 seconds at 1000 Hz
+        let sample_rate = 1000f64;
+        let impulse_response: Vec<f64> = (0..2000)
+            .map(|index| 10f64.powf(-6f64 * f64::from(index) / 500f64))
+            .collect();
+        assert!((t30(&impulse_response, sample_rate).unwrap() - 0.5f64).abs() < 1e-3);
+        assert!((early_decay_time(&impulse_response, sample_rate).unwrap() - 0.5f64).abs() < 1e-3);
+        // not decaying far enough
+        assert_eq!(
+            None,
+            decay_time(&impulse_response[..100], sample_rate, -5f64, -35f64)
+        );
+        assert_eq!(None, t30(&[0f64, 0f64], sample_rate));
+        assert_eq!(None, t30(&[], sample_rate));
+    }
+
+    #[test]
+    fn max_output_index_ignores_trailing_zeros() {
+        assert_eq!(Some(11), max_output_index(&[1f64, 0.5f64, 0f64, 0f64], 10));
+        assert_eq!(Some(3), max_output_index(&[0f64, 0f64, 0f64, 1f64], 0));
+        assert_eq!(None, max_output_index(&[0f64, 0f64], 10));
+        assert_eq!(None, max_output_index::<f64>(&[], 0));
+    }
+
+    #[test]
+    fn apply_delta_to_sample_at_index() {
+        let result = apply_to_sample(&[1f64], 1f64, 5, 1f64);
+        let mut expected = vec![0f64; 6];
+        expected[5] = 1f64;
+        // the result starts at the output's beginning, so the sample is at its own index
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn apply_functions_scale_identically() {
+        let impulse_response = vec![1f64, 0.5f64, 0.25f64];
+        let samples = vec![2f64, -1f64, 4f64];
+        let scaling_factor = 0.5f64;
+
+        let many = apply_to_many_samples(&impulse_response, &samples, scaling_factor);
+        let mut summed = vec![0f64; many.len()];
+        for (index, sample) in samples.iter().enumerate() {
+            let single = apply_to_sample(&impulse_response, *sample, index, scaling_factor);
+            assert_eq!(index + impulse_response.len(), single.len());
+            summed
+                .iter_mut()
+                .zip(&single)
+                .for_each(|(val, to_add)| *val += to_add);
+        }
+        assert_eq!(many, summed);
+
+        let per_sample_irs = vec![impulse_response; samples.len()];
+        let data = apply_to_data(&per_sample_irs, &samples, 0, scaling_factor);
+        assert_eq!(many, data);
+    }
+
+    #[test]
+    fn apply_functions_return_convolution_length() {
+        let impulse_response = vec![1f64, 0.5f64, 0.25f64, 0.125f64];
+        let samples = vec![2f64, -1f64, 4f64, 3f64, -2f64];
+        let expected_len = samples.len() + impulse_response.len() - 1;
+        let last_index = samples.len() - 1;
+
+        assert_eq!(
+            expected_len,
+            apply_to_many_samples(&impulse_response, &samples, 1f64).len()
+        );
+        let per_sample_irs = vec![impulse_response.clone(); samples.len()];
+        assert_eq!(
+            expected_len,
+            apply_to_data(&per_sample_irs, &samples, 0, 1f64).len()
+        );
+        assert_eq!(
+            expected_len,
+            apply_to_sample(&impulse_response, samples[last_index], last_index, 1f64).len()
+        );
+        // the impulse response doesn't end in zeros, so none of it is cut off
+        assert_eq!(
+            expected_len,
+            apply_looped_to_many_samples(&impulse_response, &[(last_index, 1f64)], 1f64, 100).len()
+        );
+
+        assert!(apply_to_many_samples::<f64, f64>(&[], &samples, 1f64).is_empty());
+        assert!(apply_to_data::<f64, f64>(&[vec![]], &[1f64], 0, 1f64).is_empty());
+        assert!(apply_to_sample::<f64, f64>(&[], 1f64, last_index, 1f64).is_empty());
+    }
+
+    #[test]
+    fn apply_to_data_matches_summed_single_samples() {
+        // every sample has its own impulse response, as when the scene moves
+        let impulse_responses = vec![
+            vec![1f64, 0.5f64],
+            vec![0f64, 0f64, 0.25f64, 0.125f64],
+            vec![0.75f64],
+        ];
+        let samples = vec![3i16, -2i16, 5i16];
+        let start_index = 4;
+        let scaling_factor = 0.5f64;
+
+        let data = apply_to_data(&impulse_responses, &samples, start_index, scaling_factor);
+        assert_eq!(start_index + samples.len() + 4 - 1, data.len());
+
+        let mut summed = vec![0f64; data.len()];
+        for (index, (sample, impulse_response)) in
+            samples.iter().zip(&impulse_responses).enumerate()
+        {
+            let single = apply_to_sample(
+                impulse_response,
+                *sample,
+                start_index + index,
+                scaling_factor,
+            );
+            summed
+                .iter_mut()
+                .zip(&single)
+                .for_each(|(val, to_add)| *val += to_add);
+        }
+        assert_eq!(summed, data);
+        assert!(data[..start_index].iter().all(|value| *value == 0f64));
+    }
+
+    #[test]
+    fn remove_dc_results_in_zero_mean() {
+        let impulse_response = vec![0f64, 0.5f64, 0.25f64, 0f64, 0.125f64];
+        let result = remove_dc(&impulse_response);
+        assert_eq!(impulse_response.len(), result.len());
+        assert!(result.iter().sum::<f64>().abs() < 1e-12);
+        assert!((result[1] - result[2] - 0.25f64).abs() < 1e-12);
+        assert_eq!(Vec::<f64>::new(), remove_dc(&[]));
+    }
+
+    #[test]
+    fn normalise_peak_hits_target() {
+        let impulse_response = vec![0.1f64, -0.4f64, 0.2f64];
+        let result = normalise_peak(&impulse_response, 0.8f64);
+        let peak = result
+            .iter()
+            .fold(0f64, |peak, value| peak.max(value.abs()));
+        assert!((peak - 0.8f64).abs() < 1e-12);
+        assert!((result[1] + 0.8f64).abs() < 1e-12);
+        assert_eq!(vec![0f64, 0f64], normalise_peak(&[0f64, 0f64], 1f64));
+    }
+
+    #[test]
+    fn convert_energy_to_pressure_preserves_energy() {
+        let impulse_response = vec![0.25f64, 0f64, 0.04f64, 0.01f64, 0f64, 0.09f64];
+        let energy: f64 = impulse_response.iter().sum();
+        for sign in [PressureSign::Alternating, PressureSign::Random] {
+            let result = convert_energy_to_pressure(&impulse_response, sign);
+            let pressure_energy: f64 = result.iter().map(|value| value.powi(2)).sum();
+            assert!((energy - pressure_energy).abs() < 1e-12);
+            assert!(result[1].abs() < f64::EPSILON);
+            assert!(result[4].abs() < f64::EPSILON);
+        }
+        assert_eq!(
+            vec![0.5f64, 0f64, -0.2f64, 0.1f64, 0f64, -0.3f64],
+            convert_energy_to_pressure(&impulse_response, PressureSign::Alternating)
+        );
+    }
+
+    #[test]
+    fn post_processing_chain() {
+        let impulse_response = vec![0.25f64, 0f64, 0.04f64, 0.01f64];
+        assert_eq!(
+            impulse_response,
+            PostProcessing::default().apply(impulse_response.clone())
+        );
+        let result = PostProcessing {
+            pressure: Some(PressureSign::Alternating),
+            normalise_peak: Some(1f64),
+        }
+        .apply(impulse_response);
+        let peak = result
+            .iter()
+            .fold(0f64, |peak, value| peak.max(value.abs()));
+        assert!((peak - 1f64).abs() < 1e-12);
+        assert!(result.iter().sum::<f64>().abs() < 1e-12);
+    }
+
+    #[test]
+    fn mix_dry_signal_blends_input_into_buffer() {
+        let dry = [4i16, -2, 8];
+        let wet = vec![1f64, 2f64, 3f64, 4f64];
+
+        let mut only_dry = wet.clone();
+        mix_dry_signal(&mut only_dry, &dry, 0f64, 0f64);
+        assert_eq!(vec![4f64, -2f64, 8f64, 0f64], only_dry);
+
+        let mut only_wet = wet.clone();
+        mix_dry_signal(&mut only_wet, &dry, 1f64, 0f64);
+        assert_eq!(wet, only_wet);
+
+        // +6dB roughly doubles the amplitude
+        let mut half = wet;
+        mix_dry_signal(&mut half, &dry, 0.5f64, 6f64);
+        let gain = 10f64.powf(0.3f64);
+        for (value, expected) in half.iter().zip([2.5f64, 0f64, 5.5f64, 2f64]) {
+            assert!((value - expected * gain).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn statistical_tail_follows_its_envelope() {
+        let tail = StatisticalTail {
+            t60: 1f64,
+            level_db: -20f64,
+            onset_time: 0.1f64,
+        };
+        let mut impulse_response = vec![0.5f64];
+        add_statistical_tail(
+            &mut impulse_response,
+            &tail,
+            50,
+            1000f64,
+            &mut StdRng::seed_from_u64(1),
+        );
+        // 50 samples until the emission, 100 until the onset, then T60 worth of tail
+        assert_eq!(1150, impulse_response.len());
+        assert!((impulse_response[0] - 0.5f64).abs() < 1e-12);
+        assert!(impulse_response[1..150].iter().all(|value| *value == 0f64));
+        assert!(impulse_response[150..].iter().all(|value| *value >= 0f64));
+
+        // the mean energy over the first and last 100 samples is about 60 dB apart
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        let start_level = 10f64 * mean(&impulse_response[150..250]).log10();
+        let end_level = 10f64 * mean(&impulse_response[1050..]).log10();
+        assert!((start_level - -23f64).abs() < 2f64, "{start_level}");
+        assert!(
+            (start_level - end_level - 54f64).abs() < 3f64,
+            "{end_level}"
+        );
+
+        // tails without a positive T60 don't add anything
+        let mut unchanged = vec![0.5f64];
+        add_statistical_tail(
+            &mut unchanged,
+            &StatisticalTail { t60: 0f64, ..tail },
+            0,
+            1000f64,
+            &mut StdRng::seed_from_u64(1),
+        );
+        assert_eq!(vec![0.5f64], unchanged);
     }
 }
@@ -1,9 +1,15 @@
 use nalgebra::Vector3;
 use num::{Num, NumCast};
 
-use crate::scene::{CoordinateKeyframe, Emitter, Receiver, Scene, Surface, SurfaceKeyframe};
-
-pub trait Interpolation {
+use crate::{
+    error::InterpolationError,
+    scene::{
+        CoordinateKeyframe, Emitter, Receiver, Scene, Surface, SurfaceKeyframe, SurfaceVariant,
+    },
+    time::SampleDuration,
+};
+
+pub trait Interpolation: Sized {
     /// Get a version of this object at the given time.
     /// If the object already has coordinates rather than keyframes, returns a copy of the object.
     /// If the time matches up with a keyframe, use that keyframe's coordinates.
@@ -13,7 +19,24 @@ pub trait Interpolation {
     ///
     /// # Arguments
     /// * `time`: The time to calculate.
-    fn at_time(&self, time: u32) -> Self;
+    ///
+    /// # Errors
+    ///
+    /// * If the object's keyframes are empty or not sorted by time.
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError>;
+
+    /// Get a version of this object at the given time, like `try_at_time`.
+    /// This is meant for objects that are known to be valid,
+    /// e.g. because they are part of a scene that passed `Scene::validate`.
+    ///
+    /// # Panics
+    ///
+    /// * If the object's keyframes are empty or not sorted by time.
+    fn at_time(&self, time: u32) -> Self {
+        self.try_at_time(time).unwrap_or_else(|err| {
+            panic!("Couldn't interpolate an object that should have been validated: {err}")
+        })
+    }
 }
 
 /// Interpolate between the coordinates and return a vector of interpolated coordinates.
@@ -78,29 +101,32 @@ fn interpolate_single_coordinate(coord1: f64, coord2: f64, interp_position: f64)
 ///
 /// # Arguments
 /// * `keyframes`: The keyframes to interpolate between. Must be sorted by time.
-/// * `time`: The time.
+/// * `time`: The time. This may lie between two samples, e.g. as an `f64`.
+///
+/// # Errors
+///
+/// * If there are no keyframes.
+/// * If the keyframes aren't sorted by time, so no pair of keyframes contains `time`.
 ///
 /// # Panics
 ///
-/// * if `interpolate_two_coordinate_keyframes` fails. This shouldn't be able to happen and can be ignored.
-pub fn interpolate_coordinate_keyframes(
+/// * If a keyframe's time can't be cast to T.
+pub fn interpolate_coordinate_keyframes<T: Num + NumCast + PartialOrd + Copy>(
     keyframes: &[CoordinateKeyframe],
-    time: u32,
-) -> Vector3<f64> {
+    time: T,
+) -> Result<Vector3<f64>, InterpolationError> {
+    let Some(last_keyframe) = keyframes.last() else {
+        return Err(InterpolationError::NoKeyframes);
+    };
     // return out early if we're after the last keyframe anyway
-    if time >= keyframes[keyframes.len() - 1].time {
-        return keyframes[keyframes.len() - 1].coords;
-    }
-
-    for pair in keyframes.windows(2) {
-        let result = interpolate_two_coordinate_keyframes(&pair[0], &pair[1], time);
-        if let Some(result) = result {
-            return result;
-        }
+    if time >= num::cast(last_keyframe.time.samples()).unwrap() {
+        return Ok(last_keyframe.coords);
     }
 
-    // unable to happen
-    panic!("Error in interpolation - this should not happen.");
+    keyframes
+        .windows(2)
+        .find_map(|pair| interpolate_two_coordinate_keyframes(&pair[0], &pair[1], time))
+        .ok_or(InterpolationError::UnsortedKeyframes)
 }
 
 /// Calculate the interpolated coordinate between the keyframes at the given time.
@@ -122,8 +148,8 @@ pub fn interpolate_two_coordinate_keyframes<T: Num + NumCast + PartialOrd + Copy
     second: &CoordinateKeyframe,
     time: T,
 ) -> Option<Vector3<f64>> {
-    let first_time: T = num::cast(first.time).unwrap();
-    let second_time: T = num::cast(second.time).unwrap();
+    let first_time: T = num::cast(first.time.samples()).unwrap();
+    let second_time: T = num::cast(second.time.samples()).unwrap();
     if time <= first_time {
         return Some(first.coords);
     }
@@ -150,24 +176,27 @@ pub fn interpolate_two_coordinate_keyframes<T: Num + NumCast + PartialOrd + Copy
 /// # Arguments
 /// * `keyframes`: The keyframes to interpolate between. Must be sorted by time.
 /// * `time`: The time.
+///
+/// # Errors
+///
+/// * If there are no keyframes.
+/// * If the keyframes aren't sorted by time, so no pair of keyframes contains `time`.
 fn interpolate_surface_keyframes<const N: usize>(
     keyframes: &[SurfaceKeyframe<N>],
     time: u32,
-) -> [Vector3<f64>; N] {
+) -> Result<[Vector3<f64>; N], InterpolationError> {
+    let Some(last_keyframe) = keyframes.last() else {
+        return Err(InterpolationError::NoKeyframes);
+    };
     // return out early if we're after the last keyframe, otherwise we'd need to iterate over all the keyframes first
-    if time >= keyframes[keyframes.len() - 1].time {
-        return keyframes[keyframes.len() - 1].coords;
-    }
-
-    for pair in keyframes.windows(2) {
-        let result = interpolate_two_surface_keyframes(&pair[0], &pair[1], time);
-        if let Some(result) = result {
-            return result;
-        }
+    if time >= last_keyframe.time.samples() {
+        return Ok(last_keyframe.coords);
     }
 
-    // unable to happen
-    panic!("Error in interpolation - this should not happen.");
+    keyframes
+        .windows(2)
+        .find_map(|pair| interpolate_two_surface_keyframes(&pair[0], &pair[1], time))
+        .ok_or(InterpolationError::UnsortedKeyframes)
 }
 
 /// Calculate the interpolated coordinate between the keyframes at the given time.
@@ -189,8 +218,8 @@ pub fn interpolate_two_surface_keyframes<const N: usize, T: Num + NumCast + Part
     second: &SurfaceKeyframe<N>,
     time: T,
 ) -> Option<[Vector3<f64>; N]> {
-    let first_time: T = num::cast(first.time).unwrap();
-    let second_time: T = num::cast(second.time).unwrap();
+    let first_time: T = num::cast(first.time.samples()).unwrap();
+    let second_time: T = num::cast(second.time.samples()).unwrap();
     if time <= first_time {
         return Some(first.coords);
     }
@@ -226,70 +255,101 @@ fn calculate_interp_position<T: Num + NumCast + Copy>(
 }
 
 impl Interpolation for Emitter {
-    fn at_time(&self, time: u32) -> Self {
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError> {
         match self {
-            Self::Interpolated(_keyframes, _time, _type) => self.clone(),
-            Self::Keyframes(keyframes, emission_type) => Self::Interpolated(
-                interpolate_coordinate_keyframes(keyframes, time),
+            Self::Interpolated(_keyframes, _time, _type) => Ok(self.clone()),
+            Self::Keyframes(keyframes, emission_type) => Ok(Self::Interpolated(
+                interpolate_coordinate_keyframes(keyframes, time)?,
                 time,
                 *emission_type,
-            ),
+            )),
         }
     }
 }
 
 impl Interpolation for Receiver {
-    fn at_time(&self, time: u32) -> Self {
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError> {
         match self {
-            Self::Interpolated(_keyframes, _radius, _time) => self.clone(),
-            Self::Keyframes(keyframes, radius) => Self::Interpolated(
-                interpolate_coordinate_keyframes(keyframes, time),
-                *radius,
+            Self::Interpolated(..) | Self::Ellipsoid(..) => Ok(self.clone()),
+            Self::Keyframes(keyframes, radius, _loop_duration, occluding) => {
+                Ok(Self::Interpolated(
+                    interpolate_coordinate_keyframes(keyframes, time)?,
+                    *radius,
+                    time,
+                    *occluding,
+                ))
+            }
+            Self::EllipsoidKeyframes(keyframes, semi_axes, orientation) => Ok(Self::Ellipsoid(
+                interpolate_coordinate_keyframes(keyframes, time)?,
+                *semi_axes,
+                *orientation,
                 time,
-            ),
+            )),
         }
     }
 }
 
 impl<const N: usize> Interpolation for Surface<N> {
-    fn at_time(&self, time: u32) -> Self {
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError> {
         match self {
-            Self::Interpolated(_keyframes, _time, _material) => self.clone(),
-            Self::Keyframes(keyframes, material) => Self::Interpolated(
-                interpolate_surface_keyframes(keyframes, time),
-                time,
+            Self::Interpolated(_coords, _material) => Ok(self.clone()),
+            Self::Keyframes(keyframes, material, _loop_duration) => Ok(Self::Interpolated(
+                interpolate_surface_keyframes(keyframes, time)?,
                 *material,
-            ),
+            )),
+        }
+    }
+}
+
+impl Interpolation for SurfaceVariant {
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError> {
+        match self {
+            Self::Triangle(surface) => surface.try_at_time(time).map(Self::Triangle),
+            Self::Quad(surface) => surface.try_at_time(time).map(Self::Quad),
         }
     }
 }
 
 impl Interpolation for Scene {
-    fn at_time(&self, time: u32) -> Self {
-        let time = self.loop_duration.map_or(time, |duration| time % duration);
+    /// Get a snapshot of the whole scene at the given time, with every object interpolated.
+    /// For looping scenes, the time is reduced modulo the loop duration (or the object's own loop duration,
+    /// if it has one), so times past the loop wrap around rather than snapping to the final keyframes.
+    /// The snapshot keeps the scene's loop duration, but not its cached bounds.
+    fn try_at_time(&self, time: u32) -> Result<Self, InterpolationError> {
+        // objects with their own loop duration loop independently from the scene
+        let looped_time =
+            |loop_duration: Option<SampleDuration>| self.looped_time(loop_duration, time);
         let surfaces = self
             .surfaces
             .iter()
-            .map(|surface| surface.at_time(time))
-            .collect();
-        let receiver = self.receiver.at_time(time);
-        let emitter = self.emitter.at_time(time);
-        Self {
+            .map(|surface| surface.try_at_time(looped_time(surface.loop_duration())))
+            .collect::<Result<_, _>>()?;
+        let receiver = self
+            .receiver
+            .try_at_time(looped_time(self.receiver.loop_duration()))?;
+        let emitter = self.emitter.try_at_time(looped_time(None))?;
+        Ok(Self {
             surfaces,
             receiver,
             emitter,
             loop_duration: self.loop_duration,
-        }
+            bounds_cache: None,
+            boundary: self.boundary,
+            emitter_active_ranges: self.emitter_active_ranges.clone(),
+            statistical_tail: self.statistical_tail,
+        })
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod tests {
     use nalgebra::Vector3;
 
     use crate::{
         scene::{CoordinateKeyframe, SurfaceKeyframe},
         test_utils::{self, assert_vector_abs_diff_eq},
+        time::SampleTime,
     };
 
     // TODO tests: at_time() for surface
@@ -303,14 +363,14 @@ mod tests {
     fn interpolate_object_keyframes_before() {
         let keyframes = vec![
             SurfaceKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: [
                     Vector3::new(10f64, 20f64, 30f64),
                     Vector3::new(0f64, 2f64, 16f64),
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(30f64, 20f64, 50f64),
                     Vector3::new(8f64, 10f64, 12f64),
@@ -323,22 +383,22 @@ mod tests {
                 Vector3::new(10f64, 20f64, 30f64),
                 Vector3::new(0f64, 2f64, 16f64),
             ],
-            interpolate_surface_keyframes(&keyframes, time)
-        )
+            interpolate_surface_keyframes(&keyframes, time).unwrap()
+        );
     }
 
     #[test]
     fn interpolate_object_keyframes_during() {
         let keyframes = vec![
             SurfaceKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: [
                     Vector3::new(10f64, 20f64, 30f64),
                     Vector3::new(0f64, 2f64, 16f64),
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(30f64, 20f64, 50f64),
                     Vector3::new(8f64, 10f64, 12f64),
@@ -346,14 +406,14 @@ mod tests {
             },
         ];
         let time = 7;
-        let expected = vec![
+        let expected = [
             Vector3::new(18f64, 20f64, 38f64),
             Vector3::new(3.1999998f64, 5.2f64, 14.4f64),
         ];
-        let result = interpolate_surface_keyframes(&keyframes, time);
+        let result = interpolate_surface_keyframes(&keyframes, time).unwrap();
         assert_eq!(expected.len(), result.len());
         for idx in 0..expected.len() {
-            assert_vector_abs_diff_eq(expected[idx], result[idx])
+            assert_vector_abs_diff_eq(expected[idx], result[idx]);
         }
     }
 
@@ -361,14 +421,14 @@ mod tests {
     fn interpolate_object_keyframes_after() {
         let keyframes = vec![
             SurfaceKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: [
                     Vector3::new(10f64, 20f64, 30f64),
                     Vector3::new(0f64, 2f64, 16f64),
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(30f64, 20f64, 50f64),
                     Vector3::new(8f64, 10f64, 12f64),
@@ -381,65 +441,83 @@ mod tests {
                 Vector3::new(30f64, 20f64, 50f64),
                 Vector3::new(8f64, 10f64, 12f64),
             ],
-            interpolate_surface_keyframes(&keyframes, time)
-        )
+            interpolate_surface_keyframes(&keyframes, time).unwrap()
+        );
     }
 
     #[test]
     fn interpolate_coordinate_keyframes_before() {
         let keyframes = vec![
             CoordinateKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: Vector3::new(10f64, 20f64, 30f64),
             },
             CoordinateKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: Vector3::new(30f64, 20f64, 50f64),
             },
         ];
         let time = 0;
         assert_eq!(
             Vector3::new(10f64, 20f64, 30f64),
-            interpolate_coordinate_keyframes(&keyframes, time)
-        )
+            interpolate_coordinate_keyframes(&keyframes, time).unwrap()
+        );
     }
 
     #[test]
     fn interpolate_coordinate_keyframes_during() {
         let keyframes = vec![
             CoordinateKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: Vector3::new(30f64, 40f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: Vector3::new(30f64, 20f64, 50f64),
             },
         ];
         let time = 6;
         test_utils::assert_vector_abs_diff_eq(
             Vector3::new(30f64, 36f64, 10f64),
-            interpolate_coordinate_keyframes(&keyframes, time),
-        )
+            interpolate_coordinate_keyframes(&keyframes, time).unwrap(),
+        );
     }
 
     #[test]
     fn interpolate_coordinate_keyframes_after() {
         let keyframes = vec![
             CoordinateKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: Vector3::new(30f64, 40f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: Vector3::new(30f64, 20f64, 50f64),
             },
         ];
         let time = 10;
         assert_eq!(
             Vector3::new(30f64, 20f64, 50f64),
-            interpolate_coordinate_keyframes(&keyframes, time)
-        )
+            interpolate_coordinate_keyframes(&keyframes, time).unwrap()
+        );
+    }
+
+    #[test]
+    fn interpolate_coordinate_keyframes_between_samples() {
+        let keyframes = vec![
+            CoordinateKeyframe {
+                time: SampleTime(5),
+                coords: Vector3::new(30f64, 40f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(10),
+                coords: Vector3::new(30f64, 20f64, 50f64),
+            },
+        ];
+        test_utils::assert_vector_abs_diff_eq(
+            Vector3::new(30f64, 38f64, 5f64),
+            interpolate_coordinate_keyframes(&keyframes, 5.5f64).unwrap(),
+        );
     }
 
     #[test]
@@ -451,7 +529,7 @@ mod tests {
         assert_vector_abs_diff_eq(
             expected,
             interpolate_coordinates(&coords1, &coords2, interp_position),
-        )
+        );
     }
 
     #[test]
@@ -463,31 +541,31 @@ mod tests {
         assert_eq!(
             expected,
             interpolate_coordinates(&coords1, &coords2, interp_position)
-        )
+        );
     }
 
     #[test]
     fn interpolate_single_coordinate_w_1() {
-        assert_eq!(2f64, interpolate_single_coordinate(1f64, 3f64, 0.5))
+        assert_eq!(2f64, interpolate_single_coordinate(1f64, 3f64, 0.5));
     }
 
     #[test]
     fn interpolate_single_coordinate_varied_w() {
-        assert_eq!(17f64, interpolate_single_coordinate(20f64, 10f64, 0.7))
+        assert_eq!(17f64, interpolate_single_coordinate(20f64, 10f64, 0.7));
     }
 
     #[test]
     fn calculate_interp_position_0() {
-        assert_eq!(1f64, calculate_interp_position(1000, 50000, 1000))
+        assert_eq!(1f64, calculate_interp_position(1000, 50000, 1000));
     }
 
     #[test]
     fn calculate_interp_position_inbetween() {
-        assert_eq!(0.625f64, calculate_interp_position(10000, 50000, 25000))
+        assert_eq!(0.625f64, calculate_interp_position(10000, 50000, 25000));
     }
 
     #[test]
     fn calculate_interp_position_1() {
-        assert_eq!(0f64, calculate_interp_position(1000, 50000, 50000))
+        assert_eq!(0f64, calculate_interp_position(1000, 50000, 50000));
     }
 }
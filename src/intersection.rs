@@ -1,116 +1,295 @@
-use nalgebra::Vector3;
+#[cfg(test)]
+use std::cell::Cell;
 
-use crate::interpolation::interpolate_two_surface_keyframes;
+use nalgebra::{Matrix3, Rotation3, Unit, Vector3};
+
+use crate::interpolation::{interpolate_two_surface_keyframes, Interpolation};
 use crate::maths;
 use crate::scene::CoordinateKeyframe;
 use crate::{
     ray::Ray,
-    scene::{Receiver, Surface, SurfaceKeyframe},
+    scene::{Receiver, Surface, SurfaceKeyframe, SurfaceVariant},
+    scene_bounds,
+    time::{SampleDuration, SampleTime},
 };
 
-/// Find the first intersection between the given ray and surface.
+/// An intersection with a surface: its time, coordinates and the surface's normal as a unit vector.
+/// The normal is taken from the surface at the intersection time, so moving surfaces don't need to be interpolated again.
+pub type SurfaceIntersection = (f64, Vector3<f64>, Vector3<f64>);
+
+/// A triangle's normal as calculated from its coordinates, see `Surface::normal_unchecked`,
+/// and the same normal as a unit vector.
+pub type TriangleNormal = (Vector3<f64>, Vector3<f64>);
+
+/// Calculate the normal of the triangle with the given coordinates, see `TriangleNormal`.
+pub fn triangle_normal(coords: &[Vector3<f64>; 3]) -> TriangleNormal {
+    let normal = (coords[2] - coords[0]).cross(&(coords[1] - coords[0]));
+    (normal, normal.normalize())
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of keyframe pairs intersection checks were run for,
+    /// and the number of those that weren't rejected by their swept bounds and had their polynomial solved.
+    static KEYFRAME_PAIR_CHECKS: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// An object that rays can intersect with, and that can be sorted into the scene's chunks.
+pub trait Intersectable {
+    /// What an intersection with this object yields, starting with its time and coordinates.
+    type Intersection;
+
+    /// Find the first intersection between the given ray and this object
+    /// between `time_entry` and `time_exit`, both inclusive.
+    /// `loop_duration` is the scene's loop duration. Objects with their own loop duration use it instead.
+    fn intersect(
+        &self,
+        ray: &Ray,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
+    ) -> Option<Self::Intersection>;
+
+    /// Get the minimum and maximum coordinates of the box this object occupies at the given time.
+    /// This is used to determine which chunks the object is in.
+    fn chunk_bounds_at(&self, time: u32) -> (Vector3<f64>, Vector3<f64>);
+}
+
+/// Find the first intersection between the given ray and surface,
+/// returning its time, coordinates and the surface's normal there.
+/// This is a wrapper around `Intersectable::intersect`.
+pub fn intersect_ray_and_surface(
+    ray: &Ray,
+    surface: &SurfaceVariant,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
+    scene_looping_duration: Option<SampleDuration>,
+) -> Option<SurfaceIntersection> {
+    surface.intersect(ray, time_entry, time_exit, scene_looping_duration)
+}
+
+/// Find the first intersection between the given ray and surface, like `intersect_ray_and_surface`.
+/// `pair_bounds` are the swept bounds of the surface's keyframe pairs, see `SceneData::keyframe_bounds`.
+/// Keyframe pairs whose bounds the ray doesn't pass through are skipped without solving their polynomial.
+/// Bounds missing from `pair_bounds` are calculated on the fly.
+/// `triangle_normals` are the normals of a static surface's fan triangles, see `SceneData::static_normals`.
+/// Normals missing from `triangle_normals` are calculated on the fly as well.
+pub fn intersect_ray_and_surface_with_bounds(
+    ray: &Ray,
+    surface: &SurfaceVariant,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
+    scene_looping_duration: Option<SampleDuration>,
+    pair_bounds: &[(Vector3<f64>, Vector3<f64>)],
+    triangle_normals: &[TriangleNormal],
+) -> Option<SurfaceIntersection> {
+    match surface {
+        SurfaceVariant::Triangle(surface) => intersection_check_surface(
+            ray,
+            surface,
+            time_entry.samples(),
+            time_exit.samples(),
+            scene_looping_duration,
+            pair_bounds,
+            triangle_normals,
+        ),
+        SurfaceVariant::Quad(surface) => intersection_check_surface(
+            ray,
+            surface,
+            time_entry.samples(),
+            time_exit.samples(),
+            scene_looping_duration,
+            pair_bounds,
+            triangle_normals,
+        ),
+    }
+}
+
+/// Quads are checked as their two triangles, see `SurfaceVariant`.
+impl Intersectable for SurfaceVariant {
+    type Intersection = SurfaceIntersection;
+
+    fn intersect(
+        &self,
+        ray: &Ray,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
+    ) -> Option<SurfaceIntersection> {
+        match self {
+            Self::Triangle(surface) => surface.intersect(ray, time_entry, time_exit, loop_duration),
+            Self::Quad(surface) => surface.intersect(ray, time_entry, time_exit, loop_duration),
+        }
+    }
+
+    fn chunk_bounds_at(&self, time: u32) -> (Vector3<f64>, Vector3<f64>) {
+        match self {
+            Self::Triangle(surface) => surface.chunk_bounds_at(time),
+            Self::Quad(surface) => surface.chunk_bounds_at(time),
+        }
+    }
+}
+
 /// The intersection logic for interpolated/keyframe surfaces is defined in
 /// `intersection_check_surface_coordinates` and `intersection_check_surface_keyframes`
 /// respectively.
 /// For interpolated surfaces, only one check is required because they don't change. For keyframe
 /// surfaces, a check between every set of keyframes relevant to the entry/exit time is done.
+/// Keyframe surfaces with their own loop duration use it instead of the scene's loop duration.
+/// The swept bounds of keyframe pairs are calculated on the fly,
+/// see `intersect_ray_and_surface_with_bounds` for using precalculated ones.
+impl<const N: usize> Intersectable for Surface<N> {
+    type Intersection = SurfaceIntersection;
+
+    fn intersect(
+        &self,
+        ray: &Ray,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
+    ) -> Option<SurfaceIntersection> {
+        intersection_check_surface(
+            ray,
+            self,
+            time_entry.samples(),
+            time_exit.samples(),
+            loop_duration,
+            &[],
+            &[],
+        )
+    }
+
+    fn chunk_bounds_at(&self, time: u32) -> (Vector3<f64>, Vector3<f64>) {
+        let Self::Interpolated(coords, _material) = self.at_time(time) else {
+            panic!("at_time() somehow returned a non-interpolated surface. This shouldn't happen.")
+        };
+        scene_bounds::maximum_bounds(&coords)
+    }
+}
+
+/// Find the first intersection between the given ray and surface, see `intersect_ray_and_surface_with_bounds`.
 #[allow(clippy::option_if_let_else)]
-pub fn intersect_ray_and_surface(
+fn intersection_check_surface<const N: usize>(
     ray: &Ray,
-    surface: &Surface<3>,
+    surface: &Surface<N>,
     time_entry: u32,
     time_exit: u32,
-    scene_looping_duration: Option<u32>,
-) -> Option<(f64, Vector3<f64>)> {
+    loop_duration: Option<SampleDuration>,
+    pair_bounds: &[(Vector3<f64>, Vector3<f64>)],
+    triangle_normals: &[TriangleNormal],
+) -> Option<SurfaceIntersection> {
     match surface {
-        Surface::Interpolated(coords, _time, _material) => {
-            intersection_check_surface_coordinates(ray, coords, time_entry, time_exit)
+        Surface::Interpolated(coords, _material) => intersection_check_polygon_coordinates(
+            ray,
+            coords,
+            time_entry,
+            time_exit,
+            triangle_normals,
+        ),
+        Surface::Keyframes(keyframes, _material, surface_loop_duration) => {
+            match surface_loop_duration.or(loop_duration) {
+                Some(loop_duration) => intersection_check_surface_looping(
+                    ray,
+                    keyframes,
+                    time_entry,
+                    time_exit,
+                    loop_duration.samples(),
+                    pair_bounds,
+                ),
+                None => intersection_check_surface_non_looping(
+                    ray,
+                    keyframes,
+                    time_entry,
+                    time_exit,
+                    pair_bounds,
+                ),
+            }
         }
-        Surface::Keyframes(keyframes, _material) => match scene_looping_duration {
-            Some(loop_duration) => intersection_check_surface_looping(
-                ray,
-                keyframes,
-                time_entry,
-                time_exit,
-                loop_duration,
-            ),
-            None => intersection_check_surface_non_looping(ray, keyframes, time_entry, time_exit),
-        },
     }
 }
 
-fn intersection_check_surface_non_looping(
+fn intersection_check_surface_non_looping<const N: usize>(
     ray: &Ray,
-    keyframes: &[SurfaceKeyframe<3>],
+    keyframes: &[SurfaceKeyframe<N>],
     time_entry: u32,
     time_exit: u32,
-) -> Option<(f64, Vector3<f64>)> {
-    for pair in keyframes.windows(2) {
-        if pair[1].time < time_entry {
+    pair_bounds: &[(Vector3<f64>, Vector3<f64>)],
+) -> Option<SurfaceIntersection> {
+    for (pair_index, pair) in keyframes.windows(2).enumerate() {
+        if pair[1].time.samples() < time_entry {
             continue;
         }
-        if pair[0].time > time_exit {
+        if pair[0].time.samples() > time_exit {
             return None;
         }
-        if let Some((time, coords)) = intersection_check_surface_keyframes(
+        if let Some(intersection) = intersection_check_bounded_polygon_keyframes(
             ray,
-            &pair[0],
-            &pair[1],
-            std::cmp::max(time_entry, pair[0].time),
-            std::cmp::min(time_exit, pair[1].time),
+            pair,
+            pair_bounds.get(pair_index),
+            std::cmp::max(time_entry, pair[0].time.samples()),
+            std::cmp::min(time_exit, pair[1].time.samples()),
             0,
         ) {
-            return Some((time, coords));
+            return Some(intersection);
         }
     }
     // do final check after last keyframe
     let final_keyframe = &keyframes[keyframes.len() - 1];
-    intersection_check_surface_coordinates(
+    intersection_check_polygon_coordinates(
         ray,
         &final_keyframe.coords,
-        final_keyframe.time,
+        final_keyframe.time.samples(),
         time_exit,
+        &[],
     )
 }
 
-fn intersection_check_surface_looping(
+fn intersection_check_surface_looping<const N: usize>(
     ray: &Ray,
-    keyframes: &[SurfaceKeyframe<3>],
+    keyframes: &[SurfaceKeyframe<N>],
     time_entry: u32,
     time_exit: u32,
     loop_duration: u32,
-) -> Option<(f64, Vector3<f64>)> {
+    pair_bounds: &[(Vector3<f64>, Vector3<f64>)],
+) -> Option<SurfaceIntersection> {
     // round start time to last looping time
     let mut current_time = time_entry - (time_entry % loop_duration);
     while current_time <= time_exit {
-        for pair in keyframes.windows(2) {
-            if current_time + pair[1].time < time_entry {
+        for (pair_index, pair) in keyframes.windows(2).enumerate() {
+            if current_time + pair[1].time.samples() < time_entry {
                 continue;
             }
-            if current_time + pair[0].time > time_exit {
+            if current_time + pair[0].time.samples() > time_exit {
                 return None;
             }
-            if let Some((time, coords)) = intersection_check_surface_keyframes(
+            if let Some(intersection) = intersection_check_bounded_polygon_keyframes(
                 ray,
-                &pair[0],
-                &pair[1],
-                std::cmp::max(time_entry, current_time + pair[0].time),
-                std::cmp::min(time_exit, current_time + pair[1].time),
+                pair,
+                pair_bounds.get(pair_index),
+                std::cmp::max(time_entry, current_time + pair[0].time.samples()),
+                std::cmp::min(time_exit, current_time + pair[1].time.samples()),
                 current_time,
             ) {
-                return Some((time, coords));
+                return Some(intersection);
             }
         }
         // do final check for loop after last keyframe
         let final_keyframe = &keyframes[keyframes.len() - 1];
-        if final_keyframe.time < loop_duration {
-            if let Some((time, coords)) = intersection_check_surface_coordinates(
+        if let Some((hold_entry, hold_exit)) = final_keyframe_window(
+            current_time,
+            final_keyframe.time.samples(),
+            loop_duration,
+            time_entry,
+            time_exit,
+        ) {
+            if let Some(intersection) = intersection_check_polygon_coordinates(
                 ray,
                 &final_keyframe.coords,
-                current_time + final_keyframe.time,
-                current_time + loop_duration,
+                hold_entry,
+                hold_exit,
+                &[],
             ) {
-                return Some((time, coords));
+                return Some(intersection);
             }
         }
         current_time += loop_duration;
@@ -118,6 +297,132 @@ fn intersection_check_surface_looping(
     None
 }
 
+/// Get the window in which a looping object holds its final keyframe during the loop starting at `loop_start`,
+/// i.e. from the final keyframe until the loop ends, limited to `time_entry` and `time_exit`.
+/// The first loop checked usually starts before `time_entry`, so without the limit,
+/// intersections before the ray's entry into the current chunk would be found as well.
+/// Returns `None` if the object doesn't hold its final keyframe within the limits.
+fn final_keyframe_window(
+    loop_start: u32,
+    final_keyframe_time: u32,
+    loop_duration: u32,
+    time_entry: u32,
+    time_exit: u32,
+) -> Option<(u32, u32)> {
+    let hold_entry = std::cmp::max(time_entry, loop_start + final_keyframe_time);
+    let hold_exit = std::cmp::min(time_exit, loop_start + loop_duration);
+    (final_keyframe_time < loop_duration && hold_entry <= hold_exit)
+        .then_some((hold_entry, hold_exit))
+}
+
+/// Get the triangle with the given index of the fan triangulation of a surface with `N` corners.
+/// Triangle `i` consists of the corners `0`, `i + 1` and `i + 2`.
+const fn fan_triangle<const N: usize>(
+    coords: &[Vector3<f64>; N],
+    triangle: usize,
+) -> [Vector3<f64>; 3] {
+    [coords[0], coords[triangle + 1], coords[triangle + 2]]
+}
+
+/// Get the keyframe of the given fan triangle, see `fan_triangle`.
+const fn fan_triangle_keyframe<const N: usize>(
+    keyframe: &SurfaceKeyframe<N>,
+    triangle: usize,
+) -> SurfaceKeyframe<3> {
+    SurfaceKeyframe {
+        time: keyframe.time,
+        coords: fan_triangle(&keyframe.coords, triangle),
+    }
+}
+
+/// Check for an intersection inbetween the given pair of keyframes of a surface with `N` corners,
+/// like `intersection_check_polygon_keyframes`.
+/// If the ray's segment between `time_entry` and `time_exit` doesn't pass through the pair's swept bounds,
+/// the check is skipped. If `bounds` is `None`, they are calculated first.
+fn intersection_check_bounded_polygon_keyframes<const N: usize>(
+    ray: &Ray,
+    pair: &[SurfaceKeyframe<N>],
+    bounds: Option<&(Vector3<f64>, Vector3<f64>)>,
+    time_entry: u32,
+    time_exit: u32,
+    loop_offset: u32,
+) -> Option<SurfaceIntersection> {
+    let bounds = bounds
+        .copied()
+        .unwrap_or_else(|| scene_bounds::swept_keyframe_bounds(&pair[0], &pair[1]));
+    #[cfg(test)]
+    KEYFRAME_PAIR_CHECKS.with(|checks| {
+        let (pairs, solves) = checks.get();
+        checks.set((pairs + 1, solves));
+    });
+    if !scene_bounds::segment_intersects_bounds(
+        &ray.coords_at_time(f64::from(time_entry)),
+        &ray.coords_at_time(f64::from(time_exit)),
+        &bounds,
+    ) {
+        return None;
+    }
+    #[cfg(test)]
+    KEYFRAME_PAIR_CHECKS.with(|checks| {
+        let (pairs, solves) = checks.get();
+        checks.set((pairs, solves + 1));
+    });
+    intersection_check_polygon_keyframes(
+        ray,
+        &pair[0],
+        &pair[1],
+        time_entry,
+        time_exit,
+        loop_offset,
+    )
+}
+
+/// Check for an intersection inbetween the two given keyframes of a surface with `N` corners,
+/// by checking each of its fan triangles and returning the earliest intersection.
+fn intersection_check_polygon_keyframes<const N: usize>(
+    ray: &Ray,
+    keyframe_first: &SurfaceKeyframe<N>,
+    keyframe_second: &SurfaceKeyframe<N>,
+    time_entry: u32,
+    time_exit: u32,
+    loop_offset: u32,
+) -> Option<SurfaceIntersection> {
+    (0..N - 2)
+        .filter_map(|triangle| {
+            intersection_check_surface_keyframes(
+                ray,
+                &fan_triangle_keyframe(keyframe_first, triangle),
+                &fan_triangle_keyframe(keyframe_second, triangle),
+                time_entry,
+                time_exit,
+                loop_offset,
+            )
+        })
+        .min_by(|first, second| first.0.total_cmp(&second.0))
+}
+
+/// Check whether the given surface with `N` corners intersects with the given ray,
+/// by checking each of its fan triangles and returning the earliest intersection.
+fn intersection_check_polygon_coordinates<const N: usize>(
+    ray: &Ray,
+    coords: &[Vector3<f64>; N],
+    time_entry: u32,
+    time_exit: u32,
+    triangle_normals: &[TriangleNormal],
+) -> Option<SurfaceIntersection> {
+    (0..N - 2)
+        .filter_map(|triangle| {
+            intersection_check_surface_coordinates(
+                ray,
+                &fan_triangle(coords, triangle),
+                time_entry,
+                time_exit,
+                triangle_normals.get(triangle),
+            )
+        })
+        .min_by(|first, second| first.0.total_cmp(&second.0))
+}
+
 /// Check for an intersection inbetween the two given keyframes.
 /// This uses the logic explained in the "Intersection Checks" chapter of the thesis,
 /// with its corresponding variable names.
@@ -128,7 +433,7 @@ fn intersection_check_surface_keyframes(
     time_entry: u32,
     time_exit: u32,
     loop_offset: u32,
-) -> Option<(f64, Vector3<f64>)> {
+) -> Option<SurfaceIntersection> {
     let (d3, d2, d1, d0) =
         surface_polynomial_parameters(ray, keyframe_first, keyframe_second, loop_offset);
 
@@ -139,7 +444,7 @@ fn intersection_check_surface_keyframes(
     } else {
         roots::find_roots_cubic(d3, d2, d1, d0)
     };
-    let mut intersection: Option<(f64, Vector3<f64>)> = None;
+    let mut intersection: Option<SurfaceIntersection> = None;
     for intersection_time in intersections.as_ref() {
         if *intersection_time < 0f64
             || (intersection_time.floor() as u32) < time_entry
@@ -149,7 +454,7 @@ fn intersection_check_surface_keyframes(
         }
 
         if match intersection {
-            Some((time, _coords)) => time > *intersection_time,
+            Some((time, _coords, _normal)) => time > *intersection_time,
             None => true,
         } {
             let Some(surface_coords) = interpolate_two_surface_keyframes(
@@ -171,7 +476,7 @@ fn intersection_check_surface_keyframes(
             let ray_coords = ray.coords_at_time(*intersection_time);
 
             if maths::is_point_inside_triangle(&ray_coords, &surface_coords) {
-                intersection = Some((*intersection_time, ray_coords));
+                intersection = Some((*intersection_time, ray_coords, normal.normalize()));
             }
         }
     }
@@ -186,12 +491,12 @@ fn surface_polynomial_parameters(
     keyframe_second: &SurfaceKeyframe<3>,
     loop_offset: u32,
 ) -> (f64, f64, f64, f64) {
-    let second_time = f64::from(keyframe_second.time + loop_offset); // t_k_2
+    let second_time = f64::from(keyframe_second.time.samples() + loop_offset); // t_k_2
     let (g2, g1, g0) =
         surface_cross_product_parameters(keyframe_first, keyframe_second, second_time);
     let ray_time = ray.time; // t_0
     let velocity = ray.velocity * ray.direction.into_inner();
-    let delta_time = f64::from(keyframe_second.time - keyframe_first.time);
+    let delta_time = f64::from(keyframe_second.time.samples() - keyframe_first.time.samples());
     let delta_point_1 = keyframe_second.coords[0] - keyframe_first.coords[0];
     let g2_dot_delta_p1_div_delta_time = g2.dot(&delta_point_1) / delta_time;
     let g1_dot_delta_p1_div_delta_time = g1.dot(&delta_point_1) / delta_time;
@@ -204,15 +509,15 @@ fn surface_polynomial_parameters(
        (
            g2_dot_velocity - g2_dot_delta_p1_div_delta_time, // d_3
            g2.dot(&ray.origin) - ray_time * g2_dot_velocity - g2.dot(&keyframe_second.coords[0])
-               + g2_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time)
+               + g2_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time.samples())
                + g1_dot_velocity
                - g1_dot_delta_p1_div_delta_time, // d_2
            g1.dot(&ray.origin) - ray_time * g1_dot_velocity - g1.dot(&keyframe_second.coords[0])
-               + g1_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time)
+               + g1_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time.samples())
                + g0_dot_velocity
                - g0_dot_delta_p1_div_delta_time, // d_1
            g0.dot(&ray.origin) - ray_time * g0_dot_velocity - g0.dot(&keyframe_second.coords[0])
-               + g0_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time), // d_0
+               + g0_dot_delta_p1_div_delta_time * f64::from(keyframe_second.time.samples()), // d_0
        )
     */
     (
@@ -249,7 +554,7 @@ fn surface_cross_product_parameters(
     keyframe_second: &SurfaceKeyframe<3>,
     second_time: f64,
 ) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
-    let delta_time = f64::from(keyframe_second.time - keyframe_first.time);
+    let delta_time = f64::from(keyframe_second.time.samples() - keyframe_first.time.samples());
     let two_three = surface_sub_cross_product_parameters(
         &keyframe_first.coords[1],
         &keyframe_second.coords[1],
@@ -316,13 +621,17 @@ fn intersection_check_surface_coordinates(
     coords: &[Vector3<f64>; 3],
     time_entry: u32,
     time_exit: u32,
-) -> Option<(f64, Vector3<f64>)> {
-    let normal = (coords[1] - coords[0]).cross(&(coords[2] - coords[0]));
-    let direction_dot_normal = ray.direction.into_inner().dot(&normal);
+    triangle_normal: Option<&TriangleNormal>,
+) -> Option<SurfaceIntersection> {
+    let (normal, unit_normal) =
+        triangle_normal.map_or_else(|| self::triangle_normal(coords), |normal| *normal);
+    // negating the cross product is exact, so this matches calculating it the other way round
+    let flipped_normal = -normal;
+    let direction_dot_normal = ray.direction.into_inner().dot(&flipped_normal);
     if direction_dot_normal == 0f64 {
         return None;
     }
-    let intersection_time = -(ray.origin - coords[0]).dot(&normal)
+    let intersection_time = -(ray.origin - coords[0]).dot(&flipped_normal)
         / (ray.velocity * direction_dot_normal)
         + ray.time;
     if (intersection_time.trunc() as u32) < time_entry
@@ -331,7 +640,6 @@ fn intersection_check_surface_coordinates(
         return None;
     }
 
-    let normal = (coords[2] - coords[0]).cross(&(coords[1] - coords[0]));
     if normal.dot(&ray.direction) > 0f64 {
         return None;
     }
@@ -339,39 +647,196 @@ fn intersection_check_surface_coordinates(
     let ray_coords = ray.coords_at_time(intersection_time);
 
     if maths::is_point_inside_triangle(&ray_coords, coords) {
-        Some((intersection_time, ray_coords))
+        Some((intersection_time, ray_coords, unit_normal))
     } else {
         None
     }
 }
 
 /// Find the first intersection between the given ray and receiver.
+/// This is a wrapper around `Intersectable::intersect`.
+pub fn intersect_ray_and_receiver(
+    ray: &Ray,
+    receiver: &Receiver,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
+    loop_duration: Option<SampleDuration>,
+) -> Option<(f64, Vector3<f64>)> {
+    receiver.intersect(ray, time_entry, time_exit, loop_duration)
+}
+
 /// The intersection logic for interpolated/keyframe receiver is defined in
 /// `intersection_check_receiver_coordinates` and `intersection_check_receiver_keyframes`
-/// respectively.
+/// respectively. Ellipsoids reuse these on the ray transformed into their local coordinates,
+/// see `intersect_ray_and_ellipsoid`.
 /// For interpolated receivers, only one check is required because they don't change. For keyframe
 /// receivers, a check between every set of keyframes relevant to the entry/exit time is done.
+/// Keyframe receivers with their own loop duration use it instead of the scene's loop duration.
+impl Intersectable for Receiver {
+    type Intersection = (f64, Vector3<f64>);
+
+    #[allow(clippy::option_if_let_else)]
+    fn intersect(
+        &self,
+        ray: &Ray,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        loop_duration: Option<SampleDuration>,
+    ) -> Option<Self::Intersection> {
+        let (time_entry, time_exit) = (time_entry.samples(), time_exit.samples());
+        match self {
+            Self::Interpolated(coords, radius, _time, _occluding) => {
+                intersection_check_receiver_coordinates(ray, coords, *radius, time_entry, time_exit)
+            }
+            Self::Keyframes(keyframes, radius, receiver_loop_duration, _occluding) => {
+                match receiver_loop_duration.or(loop_duration) {
+                    Some(loop_time) => intersection_check_receiver_looping(
+                        ray,
+                        keyframes,
+                        time_entry,
+                        time_exit,
+                        *radius,
+                        loop_time.samples(),
+                    ),
+                    None => intersection_check_receiver_non_looping(
+                        ray, keyframes, time_entry, time_exit, *radius,
+                    ),
+                }
+            }
+            Self::Ellipsoid(coords, semi_axes, orientation, _time) => intersect_ray_and_ellipsoid(
+                ray,
+                coords,
+                semi_axes,
+                orientation,
+                SampleTime(time_entry),
+                SampleTime(time_exit),
+            ),
+            Self::EllipsoidKeyframes(keyframes, semi_axes, orientation) => {
+                intersection_check_ellipsoid_keyframes(
+                    ray,
+                    keyframes,
+                    semi_axes,
+                    orientation,
+                    time_entry,
+                    time_exit,
+                    loop_duration,
+                )
+            }
+        }
+    }
+
+    /// The receiver's sphere or ellipsoid is simplified to the box around it.
+    fn chunk_bounds_at(&self, time: u32) -> (Vector3<f64>, Vector3<f64>) {
+        match self.at_time(time) {
+            Self::Interpolated(coords, radius, _time, _occluding) => {
+                (coords.add_scalar(-radius), coords.add_scalar(radius))
+            }
+            Self::Ellipsoid(coords, semi_axes, orientation, _time) => {
+                // each rotated semi-axis extends the box by its absolute components
+                let half_extent = orientation.matrix().abs() * semi_axes;
+                (coords - half_extent, coords + half_extent)
+            }
+            Self::Keyframes(..) | Self::EllipsoidKeyframes(..) => panic!(
+                "at_time() somehow returned a non-interpolated receiver. This shouldn't happen."
+            ),
+        }
+    }
+}
+
+/// Get the linear map from scene coordinates into the local coordinates of an ellipsoid
+/// with the given semi-axes and orientation, scaled by `1/semi_axis` so the ellipsoid becomes a unit sphere.
+fn ellipsoid_local_transform(
+    semi_axes: &Vector3<f64>,
+    orientation: &Rotation3<f64>,
+) -> Matrix3<f64> {
+    Matrix3::from_diagonal(&semi_axes.map(|semi_axis| 1f64 / semi_axis))
+        * orientation.inverse().matrix()
+}
+
+/// Transform the given ray with the given linear map.
+/// The transformed ray is at the transformed coordinates of the ray at every time,
+/// so intersection times with transformed objects are the same as with the original ones.
+fn transform_ray(ray: &Ray, transform: &Matrix3<f64>) -> Ray {
+    let velocity = transform * (ray.velocity * ray.direction.into_inner());
+    Ray {
+        direction: Unit::new_normalize(velocity),
+        origin: transform * ray.origin,
+        velocity: velocity.norm(),
+        ..*ray
+    }
+}
+
+/// Find the first intersection between the given ray and a static ellipsoid between `time_entry` and `time_exit`.
+/// The ray is transformed into the ellipsoid's local coordinates scaled by `1/semi_axis`,
+/// where the ellipsoid is a unit sphere, see `intersection_check_receiver_coordinates`.
+/// The intersection time stays the same, the intersection's coordinates are the original ray's at that time.
+/// Ellipsoids without positive semi-axes can't be hit.
+pub fn intersect_ray_and_ellipsoid(
+    ray: &Ray,
+    center: &Vector3<f64>,
+    semi_axes: &Vector3<f64>,
+    orientation: &Rotation3<f64>,
+    time_entry: SampleTime,
+    time_exit: SampleTime,
+) -> Option<(f64, Vector3<f64>)> {
+    if semi_axes.min() <= 0f64 {
+        return None;
+    }
+    let transform = ellipsoid_local_transform(semi_axes, orientation);
+    let (time, _local_coords) = intersection_check_receiver_coordinates(
+        &transform_ray(ray, &transform),
+        &(transform * center),
+        1f64,
+        time_entry.samples(),
+        time_exit.samples(),
+    )?;
+    Some((time, ray.coords_at_time(time)))
+}
+
+/// Find the first intersection between the given ray and an ellipsoid moving between keyframes,
+/// like `intersect_ray_and_ellipsoid`.
+/// Since the local coordinates are a linear map of the scene's, the transformed center still moves linearly
+/// between the transformed keyframes, so the checks for spherical keyframe receivers apply.
 #[allow(clippy::option_if_let_else)]
-pub fn intersect_ray_and_receiver(
+fn intersection_check_ellipsoid_keyframes(
     ray: &Ray,
-    receiver: &Receiver,
+    keyframes: &[CoordinateKeyframe],
+    semi_axes: &Vector3<f64>,
+    orientation: &Rotation3<f64>,
     time_entry: u32,
     time_exit: u32,
-    loop_duration: Option<u32>,
+    loop_duration: Option<SampleDuration>,
 ) -> Option<(f64, Vector3<f64>)> {
-    match receiver {
-        Receiver::Interpolated(coords, radius, _time) => {
-            intersection_check_receiver_coordinates(ray, coords, *radius, time_entry, time_exit)
-        }
-        Receiver::Keyframes(keyframes, radius) => match loop_duration {
-            Some(loop_time) => intersection_check_receiver_looping(
-                ray, keyframes, time_entry, time_exit, *radius, loop_time,
-            ),
-            None => intersection_check_receiver_non_looping(
-                ray, keyframes, time_entry, time_exit, *radius,
-            ),
-        },
+    if semi_axes.min() <= 0f64 {
+        return None;
     }
+    let transform = ellipsoid_local_transform(semi_axes, orientation);
+    let local_ray = transform_ray(ray, &transform);
+    let local_keyframes: Vec<CoordinateKeyframe> = keyframes
+        .iter()
+        .map(|keyframe| CoordinateKeyframe {
+            time: keyframe.time,
+            coords: transform * keyframe.coords,
+        })
+        .collect();
+    let (time, _local_coords) = match loop_duration {
+        Some(loop_duration) => intersection_check_receiver_looping(
+            &local_ray,
+            &local_keyframes,
+            time_entry,
+            time_exit,
+            1f64,
+            loop_duration.samples(),
+        ),
+        None => intersection_check_receiver_non_looping(
+            &local_ray,
+            &local_keyframes,
+            time_entry,
+            time_exit,
+            1f64,
+        ),
+    }?;
+    Some((time, ray.coords_at_time(time)))
 }
 
 fn intersection_check_receiver_non_looping(
@@ -382,10 +847,10 @@ fn intersection_check_receiver_non_looping(
     radius: f64,
 ) -> Option<(f64, Vector3<f64>)> {
     for pair in keyframes.windows(2) {
-        if pair[1].time < time_entry {
+        if pair[1].time.samples() < time_entry {
             continue;
         }
-        if pair[0].time > time_exit {
+        if pair[0].time.samples() > time_exit {
             return None;
         }
         if let Some((time, coords)) = intersection_check_receiver_keyframes(
@@ -393,8 +858,8 @@ fn intersection_check_receiver_non_looping(
             &pair[0],
             &pair[1],
             radius,
-            std::cmp::max(time_entry, pair[0].time),
-            std::cmp::min(time_exit, pair[1].time),
+            std::cmp::max(time_entry, pair[0].time.samples()),
+            std::cmp::min(time_exit, pair[1].time.samples()),
             0,
         ) {
             return Some((time, coords));
@@ -406,7 +871,7 @@ fn intersection_check_receiver_non_looping(
         ray,
         &final_keyframe.coords,
         radius,
-        final_keyframe.time,
+        final_keyframe.time.samples(),
         time_exit,
     )
 }
@@ -423,10 +888,10 @@ fn intersection_check_receiver_looping(
 
     while current_time <= time_exit {
         for pair in keyframes.windows(2) {
-            if current_time + pair[1].time < time_entry {
+            if current_time + pair[1].time.samples() < time_entry {
                 continue;
             }
-            if current_time + pair[0].time > time_exit {
+            if current_time + pair[0].time.samples() > time_exit {
                 return None;
             }
             if let Some((time, coords)) = intersection_check_receiver_keyframes(
@@ -434,8 +899,8 @@ fn intersection_check_receiver_looping(
                 &pair[0],
                 &pair[1],
                 radius,
-                std::cmp::max(time_entry, current_time + pair[0].time),
-                std::cmp::min(time_exit, current_time + pair[1].time),
+                std::cmp::max(time_entry, current_time + pair[0].time.samples()),
+                std::cmp::min(time_exit, current_time + pair[1].time.samples()),
                 current_time,
             ) {
                 return Some((time, coords));
@@ -443,13 +908,19 @@ fn intersection_check_receiver_looping(
         }
         // do final check after last keyframe
         let final_keyframe = &keyframes[keyframes.len() - 1];
-        if final_keyframe.time < loop_duration {
+        if let Some((hold_entry, hold_exit)) = final_keyframe_window(
+            current_time,
+            final_keyframe.time.samples(),
+            loop_duration,
+            time_entry,
+            time_exit,
+        ) {
             if let Some((time, coords)) = intersection_check_receiver_coordinates(
                 ray,
                 &final_keyframe.coords,
                 radius,
-                current_time + final_keyframe.time,
-                current_time + loop_duration,
+                hold_entry,
+                hold_exit,
             ) {
                 return Some((time, coords));
             }
@@ -474,21 +945,27 @@ fn intersection_check_receiver_keyframes(
     let (d2, d1, d0) =
         receiver_polynomial_parameters(ray, keyframe_first, keyframe_second, radius, loop_offset);
     let intersections = roots::find_roots_quadratic(d2, d1, d0);
+    // the polynomial is negative while the ray is inside the sphere
+    let starts_inside = d2.mul_add(ray.time.powi(2), d1.mul_add(ray.time, d0)) < 0f64;
     let mut intersection: Option<f64> = None;
-    for intersection_time in intersections.as_ref() {
-        if (intersection_time.floor() as u32) < time_entry
+    for intersection_time in intersections
+        .as_ref()
+        .iter()
+        .chain(starts_inside.then_some(&ray.time))
+    {
+        // roots before the ray's launch would be acausal
+        if *intersection_time < ray.time
+            || (intersection_time.floor() as u32) < time_entry
             || intersection_time.ceil() as u32 > time_exit
         {
             continue;
         }
-        if intersection.map_or(true, |time| time > *intersection_time) {
+        if intersection.is_none_or(|time| time > *intersection_time) {
             intersection = Some(*intersection_time);
         }
     }
 
-    let Some(intersection_time) = intersection else {
-        return None;
-    };
+    let intersection_time = intersection?;
 
     let ray_coords = ray.coords_at_time(intersection_time);
 
@@ -506,9 +983,9 @@ fn receiver_polynomial_parameters(
     let p_minus_ck2 = ray.origin - keyframe_second.coords;
     let ray_time = ray.time;
     let velocity = ray.velocity * ray.direction.into_inner();
-    let delta_time = f64::from(keyframe_second.time - keyframe_first.time);
+    let delta_time = f64::from(keyframe_second.time.samples() - keyframe_first.time.samples());
     let delta_time_squared = delta_time.powi(2);
-    let second_time = f64::from(keyframe_second.time + loop_offset);
+    let second_time = f64::from(keyframe_second.time.samples() + loop_offset);
     let delta_center = keyframe_second.coords - keyframe_first.coords;
     let p_minus_ck2_minus_t0_v = p_minus_ck2 - ray_time * velocity;
     let velocity_norm = velocity.norm_squared();
@@ -563,6 +1040,8 @@ fn receiver_polynomial_parameters(
 
 /// Check for an intersection between the receiver (as a static sphere) and
 /// the ray.
+/// Receivers without a positive radius can't be hit.
+/// Rays starting inside the sphere hit it at their current time.
 fn intersection_check_receiver_coordinates(
     ray: &Ray,
     coords: &Vector3<f64>,
@@ -570,9 +1049,13 @@ fn intersection_check_receiver_coordinates(
     time_entry: u32,
     time_exit: u32,
 ) -> Option<(f64, Vector3<f64>)> {
+    if radius <= 0f64 {
+        return None;
+    }
     let origin_to_coords = coords - ray.origin;
     let time_origin_to_angle = origin_to_coords.dot(&(ray.direction.into_inner()));
-    if time_origin_to_angle < 0f64 {
+    let starts_inside = origin_to_coords.norm_squared() < radius.powi(2);
+    if time_origin_to_angle < 0f64 && !starts_inside {
         return None;
     }
     // non-optimised/readable version
@@ -591,8 +1074,11 @@ fn intersection_check_receiver_coordinates(
         .mul_add(radius, -time_coords_to_angle.powi(2))
         .abs()
         .sqrt();
-    let intersection_time =
-        (time_origin_to_angle - time_angle_to_result) / ray.velocity + ray.time;
+    let intersection_time = if starts_inside {
+        ray.time
+    } else {
+        (time_origin_to_angle - time_angle_to_result) / ray.velocity + ray.time
+    };
 
     if (intersection_time.trunc() as u32) < time_entry
         || intersection_time.ceil() as u32 > time_exit
@@ -604,3 +1090,130 @@ fn intersection_check_receiver_coordinates(
 
     Some((intersection_time, ray_coords))
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Unit, Vector3};
+
+    use super::{
+        intersect_ray_and_surface_with_bounds, intersection_check_polygon_keyframes,
+        KEYFRAME_PAIR_CHECKS,
+    };
+    use crate::{
+        ray::{Ray, DEFAULT_PROPAGATION_SPEED},
+        scene::{Surface, SurfaceVariant},
+        scene_builder,
+        time::SampleTime,
+        DEFAULT_SAMPLE_RATE,
+    };
+
+    /// The rays launched from the rotating L scene's emitter, spread across all directions.
+    fn rays_from_emitter() -> Vec<Ray> {
+        let mut rays = vec![];
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    if x == 0 && y == 0 && z == 0 {
+                        continue;
+                    }
+                    rays.push(Ray::new(
+                        Unit::new_normalize(Vector3::new(f64::from(x), f64::from(y), f64::from(z))),
+                        Vector3::new(0f64, 0f64, 0.5f64),
+                        1f64,
+                        0,
+                        DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+                    ));
+                }
+            }
+        }
+        rays
+    }
+
+    #[test]
+    fn precalculated_normals_give_identical_intersections() {
+        let scene = scene_builder::static_cube_scene();
+        let static_normals = scene.static_normals();
+        let mut hits = 0;
+        for ray in rays_from_emitter() {
+            for (surface, triangle_normals) in scene.surfaces.iter().zip(&static_normals) {
+                assert!(!triangle_normals.is_empty());
+                let expected = intersect_ray_and_surface_with_bounds(
+                    &ray,
+                    surface,
+                    SampleTime(0),
+                    SampleTime(2000),
+                    None,
+                    &[],
+                    &[],
+                );
+                let intersection = intersect_ray_and_surface_with_bounds(
+                    &ray,
+                    surface,
+                    SampleTime(0),
+                    SampleTime(2000),
+                    None,
+                    &[],
+                    triangle_normals,
+                );
+                assert_eq!(expected, intersection);
+                hits += usize::from(intersection.is_some());
+            }
+        }
+        // every ray hits at least one of the cube's surfaces, rays towards its edges hit several
+        assert!(hits >= rays_from_emitter().len());
+    }
+
+    #[test]
+    fn swept_bounds_skip_most_polynomial_solves_without_missing_intersections() {
+        let scene = scene_builder::rotating_l_scene(DEFAULT_SAMPLE_RATE as u32);
+        let keyframe_bounds = scene.keyframe_bounds();
+        KEYFRAME_PAIR_CHECKS.with(|checks| checks.set((0, 0)));
+        let mut hits = 0;
+        for ray in rays_from_emitter() {
+            // check in windows like a ray traversing chunks would
+            for time_entry in (0..2000).step_by(20) {
+                let time_exit = time_entry + 20;
+                for (surface, pair_bounds) in scene.surfaces.iter().zip(&keyframe_bounds) {
+                    let intersection = intersect_ray_and_surface_with_bounds(
+                        &ray,
+                        surface,
+                        SampleTime(time_entry),
+                        SampleTime(time_exit),
+                        scene.loop_duration,
+                        pair_bounds,
+                        &[],
+                    );
+                    let SurfaceVariant::Triangle(Surface::Keyframes(keyframes, ..)) = surface
+                    else {
+                        panic!("Rotating L contains a static surface!");
+                    };
+                    let expected = keyframes
+                        .windows(2)
+                        .filter(|pair| {
+                            pair[1].time.samples() >= time_entry
+                                && pair[0].time.samples() <= time_exit
+                        })
+                        .find_map(|pair| {
+                            intersection_check_polygon_keyframes(
+                                &ray,
+                                &pair[0],
+                                &pair[1],
+                                time_entry.max(pair[0].time.samples()),
+                                time_exit.min(pair[1].time.samples()),
+                                0,
+                            )
+                        });
+                    assert_eq!(expected, intersection);
+                    hits += usize::from(intersection.is_some());
+                }
+            }
+        }
+        let (pairs, solves) = KEYFRAME_PAIR_CHECKS.with(std::cell::Cell::get);
+        assert!(hits > 0);
+        assert!(
+            solves * 10 < pairs,
+            "Only {} of {pairs} keyframe pairs were rejected by their bounds",
+            pairs - solves
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt::Display;
+
+/// The energy two impulse responses hold within one decade of time, e.g. from 10ms to 100ms.
+/// The values of the impulse responses are treated as energies, like in `impulse_response::energy_decay_curve`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DecadeEnergies {
+    /// The start of the decade in seconds, inclusive.
+    pub start: f64,
+    /// The end of the decade in seconds, exclusive.
+    pub end: f64,
+    /// The energy of the first impulse response within the decade.
+    pub first: f64,
+    /// The energy of the second impulse response within the decade.
+    pub second: f64,
+}
+
+impl DecadeEnergies {
+    /// Get the ratio of the second to the first impulse response's energy.
+    /// Returns `None` if the first impulse response has no energy within the decade.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.first != 0f64).then(|| self.second / self.first)
+    }
+}
+
+/// The differences between two impulse responses, see `diff_impulse_responses`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImpulseResponseDiff {
+    /// The number of samples compared, i.e. the length of the longer impulse response.
+    pub length: usize,
+    /// The L2 norm of the difference, i.e. the square root of the summed squared differences.
+    pub l2: f64,
+    /// The largest absolute difference of a single sample.
+    pub l_infinity: f64,
+    /// The energies within each decade of time, starting with the first millisecond.
+    pub decades: Vec<DecadeEnergies>,
+    /// The number of samples the second impulse response's peak arrives after the first one's.
+    /// The peak is the first sample with the largest absolute value.
+    /// `None` if either impulse response is all zeros.
+    pub peak_offset: Option<i64>,
+    /// The Pearson correlation coefficient of the two impulse responses.
+    /// `None` if either impulse response is constant.
+    pub correlation: Option<f64>,
+}
+
+/// Compare two impulse responses at the given sample rate.
+/// The shorter impulse response is padded with zeros to the length of the longer one.
+pub fn diff_impulse_responses(
+    first: &[f64],
+    second: &[f64],
+    sample_rate: f64,
+) -> ImpulseResponseDiff {
+    let length = first.len().max(second.len());
+    let padded = |impulse_response: &[f64]| {
+        let mut padded = impulse_response.to_vec();
+        padded.resize(length, 0f64);
+        padded
+    };
+    let (first, second) = (padded(first), padded(second));
+    let differences: Vec<f64> = first
+        .iter()
+        .zip(&second)
+        .map(|(first, second)| (second - first).abs())
+        .collect();
+
+    ImpulseResponseDiff {
+        length,
+        l2: differences
+            .iter()
+            .map(|difference| difference * difference)
+            .sum::<f64>()
+            .sqrt(),
+        l_infinity: differences.iter().copied().fold(0f64, f64::max),
+        decades: decade_energies(&first, &second, sample_rate),
+        peak_offset: peak_index(&first)
+            .zip(peak_index(&second))
+            .map(|(first, second)| second as i64 - first as i64),
+        correlation: correlation(&first, &second),
+    }
+}
+
+/// Sum up the energies of two impulse responses of the same length within each decade of time,
+/// starting with the first millisecond, until the impulse responses' end.
+fn decade_energies(first: &[f64], second: &[f64], sample_rate: f64) -> Vec<DecadeEnergies> {
+    let mut decades = vec![];
+    let (mut start, mut exponent) = (0f64, -3);
+    let mut start_sample = 0;
+    while start_sample < first.len() {
+        let end = 10f64.powi(exponent);
+        let end_sample = ((end * sample_rate).ceil() as usize).clamp(start_sample + 1, first.len());
+        decades.push(DecadeEnergies {
+            start,
+            end,
+            first: first[start_sample..end_sample].iter().sum(),
+            second: second[start_sample..end_sample].iter().sum(),
+        });
+        start_sample = end_sample;
+        start = end;
+        exponent += 1;
+    }
+    decades
+}
+
+/// Get the index of the first sample with the largest absolute value.
+/// Returns `None` if all samples are zero.
+fn peak_index(impulse_response: &[f64]) -> Option<usize> {
+    impulse_response
+        .iter()
+        .enumerate()
+        .filter(|(_index, value)| **value != 0f64)
+        .fold(
+            None,
+            |peak: Option<(usize, f64)>, (index, value)| match peak {
+                Some((_peak_index, peak_value)) if peak_value >= value.abs() => peak,
+                _ => Some((index, value.abs())),
+            },
+        )
+        .map(|(index, _value)| index)
+}
+
+/// Get the Pearson correlation coefficient of two impulse responses of the same length.
+/// Returns `None` if either impulse response is constant.
+fn correlation(first: &[f64], second: &[f64]) -> Option<f64> {
+    let mean = |impulse_response: &[f64]| {
+        impulse_response.iter().sum::<f64>() / impulse_response.len() as f64
+    };
+    let (first_mean, second_mean) = (mean(first), mean(second));
+    let (mut covariance, mut first_variance, mut second_variance) = (0f64, 0f64, 0f64);
+    for (first, second) in first.iter().zip(second) {
+        let (first, second) = (first - first_mean, second - second_mean);
+        covariance = first.mul_add(second, covariance);
+        first_variance = first.mul_add(first, first_variance);
+        second_variance = second.mul_add(second, second_variance);
+    }
+    (first_variance > 0f64 && second_variance > 0f64)
+        .then(|| covariance / (first_variance * second_variance).sqrt())
+}
+
+impl Display for ImpulseResponseDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Compared samples: {}", self.length)?;
+        writeln!(f, "L2 difference: {}", self.l2)?;
+        writeln!(f, "L-infinity difference: {}", self.l_infinity)?;
+        match self.peak_offset {
+            Some(offset) => writeln!(f, "Peak arrival difference: {offset} samples")?,
+            None => writeln!(f, "Peak arrival difference: -")?,
+        }
+        match self.correlation {
+            Some(correlation) => writeln!(f, "Correlation: {correlation}")?,
+            None => writeln!(f, "Correlation: -")?,
+        }
+        writeln!(f, "Energy ratios (second / first):")?;
+        for decade in &self.decades {
+            write!(f, "\t{}s - {}s: ", decade.start, decade.end)?;
+            match decade.ratio() {
+                Some(ratio) => writeln!(f, "{ratio}")?,
+                None => writeln!(f, "- ({} vs. {})", decade.first, decade.second)?,
+            }
+        }
+        Ok(())
+    }
+}
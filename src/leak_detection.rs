@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use nalgebra::Vector3;
+
+/// A ray that went out of bounds in a scene containing surfaces.
+/// In a closed scene, this indicates a gap in the geometry.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LeakedRay {
+    /// The last position the ray was at before leaving the scene,
+    /// i.e. its last bounce position or its launch position.
+    pub position: Vector3<f64>,
+    /// The direction the ray left the scene in.
+    pub direction: Vector3<f64>,
+}
+
+/// Aggregated information on all rays that leaked out of a scene during a simulation.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LeakReport {
+    /// The total number of rays launched.
+    pub number_of_rays: u32,
+    /// All rays that leaked out of the scene.
+    pub leaked_rays: Vec<LeakedRay>,
+}
+
+impl LeakReport {
+    /// Get the number of rays that leaked out of the scene.
+    pub const fn count(&self) -> usize {
+        self.leaked_rays.len()
+    }
+
+    /// Get the percentage (0 to 100) of launched rays that leaked out of the scene.
+    pub fn percentage(&self) -> f64 {
+        if self.number_of_rays == 0 {
+            return 0f64;
+        }
+        self.count() as f64 / f64::from(self.number_of_rays) * 100f64
+    }
+
+    /// Write up to `max_rows` leaked rays to the given writer in CSV format.
+    /// Each row holds the position's and direction's x/y/z values.
+    ///
+    /// # Errors
+    ///
+    /// * If writing to `writer` fails.
+    pub fn write_csv<W: Write>(&self, writer: &mut W, max_rows: usize) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "position_x;position_y;position_z;direction_x;direction_y;direction_z"
+        )?;
+        for leak in self.leaked_rays.iter().take(max_rows) {
+            writeln!(
+                writer,
+                "{};{};{};{};{};{}",
+                leak.position.x,
+                leak.position.y,
+                leak.position.z,
+                leak.direction.x,
+                leak.direction.y,
+                leak.direction.z
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+
+    use super::{LeakReport, LeakedRay};
+
+    fn leaked_ray(x: f64) -> LeakedRay {
+        LeakedRay {
+            position: Vector3::new(x, 0f64, 0f64),
+            direction: Vector3::new(0f64, 0f64, 1f64),
+        }
+    }
+
+    #[test]
+    fn empty_report_percentage() {
+        let report = LeakReport::default();
+        assert_eq!(0, report.count());
+        assert!(report.percentage().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn report_percentage() {
+        let report = LeakReport {
+            number_of_rays: 8,
+            leaked_rays: vec![leaked_ray(1f64), leaked_ray(2f64)],
+        };
+        assert_eq!(2, report.count());
+        assert!((report.percentage() - 25f64).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn write_csv_limits_rows() {
+        let report = LeakReport {
+            number_of_rays: 8,
+            leaked_rays: vec![leaked_ray(1f64), leaked_ray(2f64), leaked_ray(3f64)],
+        };
+        let mut buffer: Vec<u8> = vec![];
+        report.write_csv(&mut buffer, 2).unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            "position_x;position_y;position_z;direction_x;direction_y;direction_z\n1;0;0;0;0;1\n2;0;0;0;0;1\n",
+            csv
+        );
+    }
+}
@@ -1,15 +1,30 @@
-/// The default sample rate of 44.1 `KHz`.
-pub const DEFAULT_SAMPLE_RATE: f64 = 44100f64;
+/// The default sample rate of 44.1 `KHz`, see `Defaults::SAMPLE_RATE`.
+pub const DEFAULT_SAMPLE_RATE: f64 = defaults::Defaults::SAMPLE_RATE;
 
+pub mod analysis;
+pub mod audio_io;
+pub mod bounce;
+pub mod boundary;
 pub mod chunk;
+pub mod cli;
+pub mod defaults;
+pub mod echogram;
+pub mod error;
+pub mod estimate;
+pub mod impulse_response;
 pub mod interpolation;
 pub mod intersection;
+pub mod ir_diff;
+pub mod leak_detection;
 pub mod materials;
 mod maths;
+pub mod parameter_sweep;
 pub mod ray;
 pub mod scene;
 pub mod scene_bounds;
 pub mod scene_builder;
+pub mod simulation_config;
+pub mod sound_field;
+pub mod telemetry;
 mod test_utils;
-pub mod impulse_response;
-pub mod bounce;
\ No newline at end of file
+pub mod time;
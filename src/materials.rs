@@ -2,17 +2,54 @@ pub const ABSORPTION_COEFFICIENT_CONCRETE: f64 = 0.98;
 pub const MATERIAL_CONCRETE_WALL: Material = Material {
     absorption_coefficient: ABSORPTION_COEFFICIENT_CONCRETE,
     diffusion_coefficient: 0.1f64, // no data for this to be found, so just guess :(
+    transmission_coefficient: 0f64,
+};
+pub const MATERIAL_CARPET: Material = Material {
+    absorption_coefficient: 0.7f64,
+    diffusion_coefficient: 0.6f64,
+    transmission_coefficient: 0f64,
+};
+/// A material that barely reflects anything, like acoustic foam.
+pub const MATERIAL_ABSORBER: Material = Material {
+    absorption_coefficient: 0.05f64,
+    diffusion_coefficient: 0.9f64,
+    transmission_coefficient: 0f64,
 };
 
+/// All named material presets, e.g. for selecting materials on the command line.
+pub const MATERIAL_PRESETS: [(&str, Material); 3] = [
+    ("concrete", MATERIAL_CONCRETE_WALL),
+    ("carpet", MATERIAL_CARPET),
+    ("absorber", MATERIAL_ABSORBER),
+];
+
+/// Get the material preset with the given name, if there is one.
+///
+/// ```
+/// use demo::materials::{material_by_name, MATERIAL_CONCRETE_WALL};
+///
+/// assert_eq!(Some(MATERIAL_CONCRETE_WALL), material_by_name("concrete"));
+/// assert_eq!(None, material_by_name("cheese"));
+/// ```
+pub fn material_by_name(name: &str) -> Option<Material> {
+    MATERIAL_PRESETS
+        .iter()
+        .find(|(preset_name, _material)| *preset_name == name)
+        .map(|(_name, material)| *material)
+}
+
 /// Data structure representing a material.
-/// A material has both an absorption coefficient
-/// (denoting how much energy a ray loses when bouncing off of it)
-/// and a diffusion coefficient
+/// A material has an absorption coefficient
+/// (denoting how much energy a ray loses when bouncing off of it),
+/// a diffusion coefficient
 /// (denoting how diffuse vs. specular the reflection is)
+/// and a transmission coefficient
+/// (denoting how much of the sound passes through it, like through windows or curtains)
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Material {
     pub absorption_coefficient: f64,
     pub diffusion_coefficient: f64,
+    pub transmission_coefficient: f64,
 }
 
 impl Material {
@@ -22,4 +59,11 @@ impl Material {
     pub fn is_bounce_diffuse(&self) -> bool {
         self.diffusion_coefficient >= rand::random::<f64>()
     }
+
+    /// Randomly choose whether a ray passes through a surface with this material instead of bouncing off of it.
+    /// A random number between 0 and 1 is rolled and compared to the transmission coefficient.
+    /// If the transmission coefficient is greater than the random number, the ray passes through.
+    pub fn is_transmitted(&self) -> bool {
+        self.transmission_coefficient > rand::random::<f64>()
+    }
 }
@@ -53,7 +53,7 @@ mod tests {
             Vector3::new(1f64, -1f64, 0f64),
             Vector3::new(0f64, 1f64, 0f64),
         ];
-        assert_eq!(true, is_point_inside_triangle(&point, &triangle))
+        assert!(is_point_inside_triangle(&point, &triangle));
     }
 
     #[test]
@@ -64,7 +64,7 @@ mod tests {
             Vector3::new(0f64, 1f64, 0f64),
             Vector3::new(1f64, -1f64, 0f64),
         ];
-        assert_eq!(true, is_point_inside_triangle(&point, &triangle))
+        assert!(is_point_inside_triangle(&point, &triangle));
     }
 
     #[test]
@@ -75,7 +75,7 @@ mod tests {
             Vector3::new(1f64, -1f64, 1f64),
             Vector3::new(0f64, 1f64, 1f64),
         ];
-        assert_eq!(true, is_point_inside_triangle(&point, &triangle))
+        assert!(is_point_inside_triangle(&point, &triangle));
     }
 
     #[test]
@@ -86,7 +86,7 @@ mod tests {
             Vector3::new(3f64, -1f64, 0f64),
             Vector3::new(1f64, 1f64, 0f64),
         ];
-        assert_eq!(false, is_point_inside_triangle(&point, &triangle))
+        assert!(!is_point_inside_triangle(&point, &triangle));
     }
 
     #[test]
@@ -97,7 +97,7 @@ mod tests {
             Vector3::new(1f64, -1f64, 0f64),
             Vector3::new(0f64, 1f64, 0f64),
         ];
-        assert_eq!(false, is_point_inside_triangle(&point, &triangle))
+        assert!(!is_point_inside_triangle(&point, &triangle));
     }
 
     #[test]
@@ -108,6 +108,6 @@ mod tests {
             Vector3::new(3f64, -1f64, 0f64),
             Vector3::new(1f64, 1f64, 0f64),
         ];
-        assert_eq!(true, is_point_inside_triangle(&point, &triangle))
+        assert!(is_point_inside_triangle(&point, &triangle));
     }
 }
@@ -0,0 +1,190 @@
+use std::io::Write;
+use std::ops::Mul;
+
+use generic_array::ArrayLength;
+use rayon::prelude::*;
+use typenum::Unsigned;
+
+use crate::{
+    error::DemoError,
+    impulse_response,
+    scene::{Scene, SceneData},
+    simulation_config::SimulationConfig,
+};
+
+/// Builds the scene to simulate for each value of a swept parameter, see `run_sweep`.
+/// Closures taking the parameter value and returning the scene are templates as well.
+pub trait SceneTemplate: Sync {
+    /// Build the scene for the given parameter value.
+    fn build(&self, param: f64) -> Scene;
+}
+
+impl<F: Fn(f64) -> Scene + Sync> SceneTemplate for F {
+    fn build(&self, param: f64) -> Scene {
+        self(param)
+    }
+}
+
+/// A parameter that can be swept for any scene, see `SweepParameter::apply`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SweepParameter {
+    /// The receiver's bounding radius in meters, see `Receiver::with_radius`.
+    ReceiverRadius,
+    /// The absorption coefficient of every surface's material, i.e. the fraction of energy kept per bounce.
+    AbsorptionCoefficient,
+}
+
+impl SweepParameter {
+    /// All parameters, in the order they're listed in.
+    pub const ALL: [Self; 2] = [Self::ReceiverRadius, Self::AbsorptionCoefficient];
+
+    /// Get the parameter's name as used on the command line.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ReceiverRadius => "receiver_radius",
+            Self::AbsorptionCoefficient => "absorption_coefficient",
+        }
+    }
+
+    /// Get the parameter with the given name, see `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|parameter| parameter.name() == name)
+    }
+
+    /// Get a copy of the given scene with this parameter set to the given value.
+    pub fn apply(self, scene: &Scene, value: f64) -> Scene {
+        let mut scene = scene.clone();
+        match self {
+            Self::ReceiverRadius => {
+                scene.receiver = scene.receiver.with_radius(value);
+                scene.bounds_cache = None;
+            }
+            Self::AbsorptionCoefficient => {
+                for index in 0..scene.surfaces.len() {
+                    let mut material = scene.surfaces[index].data().material;
+                    material.absorption_coefficient = value;
+                    scene.set_material(index, material);
+                }
+            }
+        }
+        scene
+    }
+}
+
+/// Get `count` evenly spaced values from `min` to `max`, both inclusive.
+/// A single value is just `min`.
+pub fn sweep_values(min: f64, max: f64, count: usize) -> Vec<f64> {
+    if count == 1 {
+        return vec![min];
+    }
+    (0..count)
+        .map(|index| (max - min).mul_add(index as f64 / (count - 1) as f64, min))
+        .collect()
+}
+
+/// The acoustic metrics of the impulse response simulated for a single parameter value, see `run_sweep`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SweepResult {
+    /// The parameter value.
+    pub param: f64,
+    /// The sum of the impulse response.
+    pub total_energy: f64,
+    /// The sample the direct sound arrived at, relative to the simulated time.
+    /// `None` if no ray reached the receiver.
+    pub first_arrival: Option<usize>,
+    /// The early decay time in seconds, see `impulse_response::early_decay_time`.
+    pub early_decay_time: Option<f64>,
+    /// The reverberation time in seconds, see `impulse_response::t30`.
+    pub t30: Option<f64>,
+}
+
+/// Build the template's scene for each of the given parameter values, simulate its impulse response at the given time
+/// and get its acoustic metrics, in the same order as the values.
+/// The values are simulated in parallel with `config`'s number of threads (see `SimulationConfig::install`),
+/// while each value's rays are launched serially, so only one impulse response per thread is held at a time.
+///
+/// # Errors
+///
+/// * If one of the built scenes isn't valid, see `Scene::validate`.
+/// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+#[allow(clippy::too_many_arguments)]
+pub fn run_sweep<C>(
+    template: &impl SceneTemplate,
+    params: &[f64],
+    time: u32,
+    number_of_rays: u32,
+    velocity: f64,
+    sample_rate: f64,
+    config: &SimulationConfig,
+) -> Result<Vec<SweepResult>, DemoError>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    // the values are simulated in parallel, so each simulation runs serially instead of building its own thread pool
+    let serial_config = SimulationConfig {
+        threads: Some(1),
+        ..config.clone()
+    };
+    let simulate_param = |param: &f64| -> Result<SweepResult, DemoError> {
+        let scene_data = SceneData::<C>::create_for_scene(template.build(*param))?;
+        let mut impulse_response = scene_data.simulate_at_time(
+            time,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            false,
+            false,
+            &serial_config,
+        )?;
+        impulse_response.drain(..(time as usize).min(impulse_response.len()));
+        Ok(SweepResult {
+            param: *param,
+            total_energy: impulse_response.iter().sum(),
+            first_arrival: impulse_response::direct_sound_arrival(&impulse_response),
+            early_decay_time: impulse_response::early_decay_time(&impulse_response, sample_rate),
+            t30: impulse_response::t30(&impulse_response, sample_rate),
+        })
+    };
+    config.install(|| -> Result<Vec<SweepResult>, DemoError> {
+        if config.is_serial() {
+            params.iter().map(simulate_param).collect()
+        } else {
+            params.par_iter().map(simulate_param).collect()
+        }
+    })?
+}
+
+/// Write the given sweep results to the given writer in CSV format.
+/// Each row holds the value of the parameter with the given name, the total energy, the first arrival sample,
+/// the early decay time and T30 (empty if they couldn't be determined).
+///
+/// # Errors
+///
+/// * If writing to `writer` fails.
+pub fn write_sweep_csv<W: Write>(
+    results: &[SweepResult],
+    parameter_name: &str,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    writeln!(
+        writer,
+        "{parameter_name};total_energy;first_arrival_sample;early_decay_time;t30"
+    )?;
+    for result in results {
+        writeln!(
+            writer,
+            "{};{};{};{};{}",
+            result.param,
+            result.total_energy,
+            optional(result.first_arrival.map(|arrival| arrival.to_string())),
+            optional(result.early_decay_time.map(|time| time.to_string())),
+            optional(result.t30.map(|time| time.to_string()))
+        )?;
+    }
+    Ok(())
+}
@@ -1,37 +1,61 @@
-use std::ops::Mul;
+use std::{
+    fmt::{Debug, Display},
+    ops::Mul,
+};
 
-use approx::abs_diff_eq;
 use generic_array::ArrayLength;
 use nalgebra::{base::Unit, Vector3};
 use num::{Num, NumCast};
 use typenum::Unsigned;
 
 use crate::{
-    bounce::{bounce_off_surface_with_normal, random_direction_in_hemisphere},
-    interpolation::Interpolation,
-    intersection,
-    scene::{SceneData, Surface},
+    bounce::{
+        bounce_off_surface_with_normal, is_degenerate_normal,
+        random_cosine_weighted_direction_in_hemisphere, random_direction_in_hemisphere,
+    },
+    boundary::{BoundaryBehaviour, BoundaryFace},
+    defaults::Defaults,
+    echogram::RayBounceRecord,
+    intersection::{
+        intersect_ray_and_receiver, intersect_ray_and_surface_with_bounds, Intersectable,
+        SurfaceIntersection,
+    },
+    leak_detection::LeakedRay,
+    materials::Material,
+    scene::SceneData,
+    simulation_config::{DiffuseSampling, SimulationConfig, TraversalMode},
+    telemetry::TraversalCounters,
+    time::SampleTime,
     DEFAULT_SAMPLE_RATE,
 };
 
-/// The normal speed of sound in air at 20 °C, in m/s.
-pub const DEFAULT_PROPAGATION_SPEED: f64 = 343.2;
-/// The threshold below which rays get discarded.
-const ENERGY_THRESHOLD: f64 = 0.000001;
+/// The normal speed of sound in air at 20 °C, in m/s, see `Defaults::PROPAGATION_SPEED`.
+pub const DEFAULT_PROPAGATION_SPEED: f64 = Defaults::PROPAGATION_SPEED;
+/// Surface hits less than this many samples after the previous bounce mean the ray barely moved,
+/// which happens when it's stuck in a corner between (nearly) coplanar surfaces.
+const STUCK_BOUNCE_TIME: f64 = 0.1;
+/// The number of consecutive bounces that barely moved the ray after which it's discarded.
+const MAX_STUCK_BOUNCES: u32 = 5;
+/// The distance in meters rays are moved past surfaces they pass through.
+const TRANSMISSION_OFFSET: f64 = 1e-3;
+/// Direction cosines smaller than this (in absolute value) are treated as 0 when traversing chunks,
+/// since the ray would need billions of chunk widths of travel to cross a boundary in that dimension.
+const DIRECTION_COSINE_EPSILON: f64 = 1e-7;
 
 /// The result after checking for an intersection.
 /// * `Found`: found an intersecting surface.
 /// * `NoIntersection`: No intersection, continue propagating this ray.
 /// * `OutOfBounds`: The ray has exited the scene, no need to propagate further.
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum IntersectionCheckResult {
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum IntersectionCheckResult {
     /// An intersection has been found.
     /// Variables represent:
     /// * Whether the intersection is with a receiver as opposed to a surface
     /// * The surface's index (or 0 for receivers)
     /// * The intersection time
     /// * The intersection position's coordinates.
-    Found(bool, usize, f64, Vector3<f64>),
+    /// * The surface's normal at the intersection as a unit vector (or the zero vector for receivers).
+    Found(bool, usize, f64, Vector3<f64>, Vector3<f64>),
     /// No intersection has been found, continue propagating this ray.
     NoIntersection,
     /// The ray has gone out of bounds. No need to bother propagating it further.
@@ -41,11 +65,49 @@ enum IntersectionCheckResult {
 impl IntersectionCheckResult {
     /// Check whether this `IntersectionCheckResult` is of type "Found".
     const fn is_found(&self) -> bool {
-        matches!(self, Self::Found(_is_recv, _index, _time, _coords))
+        matches!(self, Self::Found(_is_recv, _index, _time, _coords, _normal))
+    }
+}
+
+impl Display for IntersectionCheckResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Found(is_receiver, index, time, coords, _normal) => write!(
+                f,
+                "Found(receiver={is_receiver}, surface={index}, t={time:.2}, pos={})",
+                format_coords(coords)
+            ),
+            Self::NoIntersection => write!(f, "NoIntersection"),
+            Self::OutOfBounds => write!(f, "OutOfBounds"),
+        }
+    }
+}
+
+impl Debug for IntersectionCheckResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Copy)]
+/// A traversal's intersection: whether it's with the receiver, the surface index, time, coordinates
+/// and surface normal, see `IntersectionCheckResult::Found`.
+type TraversalHit = (bool, usize, f64, Vector3<f64>, Vector3<f64>);
+
+/// Format the given coordinates as "(x,y,z)".
+fn format_coords(coords: &Vector3<f64>) -> String {
+    format!("({:?},{:?},{:?})", coords.x, coords.y, coords.z)
+}
+
+/// Statistics on a single ray's path through a scene, see `Ray::launch_with_statistics`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RayStatistics {
+    /// The number of times the ray bounced off a surface.
+    pub bounces: u32,
+    /// Whether the ray leaked out of the scene, see `Ray::launch_detecting_leaks`.
+    pub leaked: bool,
+}
+
+#[derive(Clone, PartialEq, Copy)]
 /// A ray to bounce through the scene.
 pub struct Ray {
     /// The direction to shoot the ray in.
@@ -57,6 +119,9 @@ pub struct Ray {
     /// This starts out at 1.0f64 and if it goes near/below 0f64, this ray can
     /// be discarded.
     pub energy: f64,
+    /// The energy the ray was launched with.
+    /// Rays are discarded once their energy drops below a fraction of this.
+    pub initial_energy: f64,
     /// The time at which the ray is launched, in samples. - this
     /// should get incremented with every bounce.
     pub time: f64,
@@ -65,6 +130,29 @@ pub struct Ray {
     pub velocity: f64,
 }
 
+impl Debug for Ray {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ray")
+            .field("origin", &format_args!("{}", format_coords(&self.origin)))
+            .field(
+                "direction",
+                &format_args!("{}", format_coords(&self.direction)),
+            )
+            .field(
+                "energy",
+                &format_args!(
+                    "{:.6} ({:.2}% of {:?})",
+                    self.energy,
+                    self.energy / self.initial_energy * 100f64,
+                    self.initial_energy
+                ),
+            )
+            .field("time", &format_args!("{:.2} samples", self.time))
+            .field("velocity", &format_args!("{:?} m/sample", self.velocity))
+            .finish()
+    }
+}
+
 impl Ray {
     /// Create a new ray with the given parameters.
     /// This function is only relevant for testing purposes and shouldn't be used otherwise.
@@ -79,6 +167,7 @@ impl Ray {
             direction,
             origin,
             energy,
+            initial_energy: energy,
             time: <f64 as From<u32>>::from(time),
             velocity,
         }
@@ -103,42 +192,210 @@ impl Ray {
     ///
     /// * `direction`: The direction to launch the ray in. This will be normalised, so it doesn't have to be normalised before.
     /// * `origin`: The origin coordinates to launch the ray from.
-    /// * `start_time`: The time at which the ray is launched.
+    /// * `start_time`: The time at which the ray is launched. This may lie between two samples.
     /// * `velocity`: The ray's velocity, in meters per second.
     /// * `sample_rate`: The sample rate at which the simulation is run.
-    /// * `scene`: The scene to bounce in.
-    /// * `chunks`: The chunks for the scene.
-    /// * `maximum_bounds`: The scene's outer bounds.
+    /// * `scene_data`: The scene to bounce in, along with its chunks and outer bounds.
+    /// * `config`: The simulation configuration, determining when the ray is discarded.
     pub fn launch<C>(
         direction: Vector3<f64>,
         origin: Vector3<f64>,
-        start_time: u32,
+        start_time: impl Into<f64>,
         velocity: f64,
         sample_rate: f64,
         scene_data: &SceneData<C>,
+        config: &SimulationConfig,
     ) -> Vec<(f64, u32)>
     where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
         <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
     {
-        let mut ray = Self {
+        Self::for_launch(direction, origin, start_time, velocity, sample_rate)
+            .trace(scene_data, config)
+    }
+
+    /// Launch a ray like `launch`, but additionally return the direction the ray travelled in
+    /// when hitting the receiver for each hit.
+    /// The sound arrives at the receiver from the opposite of this direction.
+    pub fn launch_with_directions<C>(
+        direction: Vector3<f64>,
+        origin: Vector3<f64>,
+        start_time: impl Into<f64>,
+        velocity: f64,
+        sample_rate: f64,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+    ) -> Vec<(f64, u32, Vector3<f64>)>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        Self::for_launch(direction, origin, start_time, velocity, sample_rate)
+            .bounce(
+                scene_data,
+                config,
+                false,
+                None,
+                |energy, time, direction| (energy, time, direction),
+            )
+            .0
+    }
+
+    /// Bounce this already set up ray through the given scene, returning
+    /// both the energy and time of each receiver hit.
+    /// Unlike `launch`, this keeps the ray's energy, so rays with an initial energy other than 1 can be traced.
+    pub fn trace<C>(
+        mut self,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+    ) -> Vec<(f64, u32)>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        self.bounce(
+            scene_data,
+            config,
+            false,
+            None,
+            |energy, time, _direction| (energy, time),
+        )
+        .0
+    }
+
+    /// Launch a ray like `launch`, but additionally record each of its surface bounces
+    /// and receiver hits, in the order they happen.
+    pub fn launch_recording_echogram<C>(
+        direction: Vector3<f64>,
+        origin: Vector3<f64>,
+        start_time: impl Into<f64>,
+        velocity: f64,
+        sample_rate: f64,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+    ) -> (Vec<(f64, u32)>, Vec<RayBounceRecord>)
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let mut records = vec![];
+        let (results, _leak, _bounces) =
+            Self::for_launch(direction, origin, start_time, velocity, sample_rate).bounce(
+                scene_data,
+                config,
+                false,
+                Some(&mut records),
+                |energy, time, _direction| (energy, time),
+            );
+        (results, records)
+    }
+
+    /// Launch a ray like `launch`, but additionally report whether the ray leaked
+    /// out of the scene.
+    /// A ray counts as leaked if it goes out of bounds while the scene contains at least one surface.
+    pub fn launch_detecting_leaks<C>(
+        direction: Vector3<f64>,
+        origin: Vector3<f64>,
+        start_time: impl Into<f64>,
+        velocity: f64,
+        sample_rate: f64,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+    ) -> (Vec<(f64, u32)>, Option<LeakedRay>)
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let (results, leak, _bounces) =
+            Self::for_launch(direction, origin, start_time, velocity, sample_rate).bounce(
+                scene_data,
+                config,
+                true,
+                None,
+                |energy, time, _direction| (energy, time),
+            );
+        (results, leak)
+    }
+
+    /// Launch a ray like `launch`, but additionally return statistics on its path,
+    /// i.e. how often it bounced and whether it leaked out of the scene.
+    pub fn launch_with_statistics<C>(
+        direction: Vector3<f64>,
+        origin: Vector3<f64>,
+        start_time: impl Into<f64>,
+        velocity: f64,
+        sample_rate: f64,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+    ) -> (Vec<(f64, u32)>, RayStatistics)
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let (results, leak, bounces) =
+            Self::for_launch(direction, origin, start_time, velocity, sample_rate).bounce(
+                scene_data,
+                config,
+                true,
+                None,
+                |energy, time, _direction| (energy, time),
+            );
+        (
+            results,
+            RayStatistics {
+                bounces,
+                leaked: leak.is_some(),
+            },
+        )
+    }
+
+    /// Create a ray that is ready to be launched with the given parameters.
+    /// The velocity is converted from meters per second to meters per sample.
+    fn for_launch(
+        direction: Vector3<f64>,
+        origin: Vector3<f64>,
+        start_time: impl Into<f64>,
+        velocity: f64,
+        sample_rate: f64,
+    ) -> Self {
+        Self {
             direction: Unit::new_normalize(direction),
             origin,
             velocity: velocity / sample_rate,
-            time: <f64 as From<u32>>::from(start_time),
+            time: start_time.into(),
             ..Default::default()
-        };
-
-        ray.bounce(scene_data)
+        }
     }
 
     /// Bounce this ray through the given scene.
+    /// Each receiver hit is turned into a result using `record`,
+    /// which is passed the ray's energy, the hit's time and the ray's direction.
+    /// If `detect_leaks` is set and the ray goes out of bounds in a scene with surfaces,
+    /// its last position and direction are returned alongside the receiver hits,
+    /// followed by the number of times the ray bounced off a surface.
+    /// If `echogram` is passed, every surface bounce and receiver hit is pushed to it.
+    /// Bounces off the scene's boundary aren't recorded, since they don't belong to a surface.
+    /// The ray is discarded once its energy drops below `config.relative_energy_threshold`
+    /// times its initial energy.
     ///
-    /// KNOWN ISSUE: We lose some rays here (<1% in the extreme case of working with fully diffusing surfaces)
-    /// because of floating point imprecisions, especially when they get into corners.
-    /// This will be ignored for now because it's an edge case that will not lose us a significant amount of rays.
-    fn bounce<C>(&mut self, scene_data: &SceneData<C>) -> Vec<(f64, u32)>
+    /// Rays can get stuck in corners because of floating point imprecisions,
+    /// bouncing between surfaces without moving forward.
+    /// After every bounce that barely moved the ray (see `STUCK_BOUNCE_TIME`), its origin is nudged
+    /// along its new direction. Once more than `MAX_STUCK_BOUNCES` of these happen in a row, the ray is discarded.
+    fn bounce<C, H>(
+        &mut self,
+        scene_data: &SceneData<C>,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+        mut echogram: Option<&mut Vec<RayBounceRecord>>,
+        record: impl Fn(f64, u32, Vector3<f64>) -> H,
+    ) -> (Vec<H>, Option<LeakedRay>, u32)
     where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
@@ -146,63 +403,216 @@ impl Ray {
     {
         let mut allow_receiver = true;
         let mut result = vec![];
-        while self.energy > ENERGY_THRESHOLD {
-            let mut chunk_traversal_data = self.init_chunk_traversal_data(scene_data);
-            match self.traverse(scene_data, &mut chunk_traversal_data, allow_receiver) {
-                None => {
-                    self.energy = -1f64; // cancel the loop, we're out of bounds
-                }
-                Some((is_receiver, index, time, coords)) => {
+        let mut leak = None;
+        let mut stuck_bounces = 0;
+        let mut bounces = 0;
+        let energy_threshold = self.initial_energy * config.relative_energy_threshold;
+        while self.energy > energy_threshold {
+            let traversal_result = match config.traversal_mode {
+                TraversalMode::Chunked => self.init_chunk_traversal_data(scene_data).and_then(
+                    |mut chunk_traversal_data| {
+                        self.traverse(scene_data, &mut chunk_traversal_data, allow_receiver)
+                    },
+                ),
+                TraversalMode::BruteForce => self.intersect_brute_force(scene_data, allow_receiver),
+            };
+            match traversal_result {
+                None => match self.boundary_exit(&scene_data.maximum_bounds) {
+                    Some((face, time)) => match scene_data.scene.boundary.face(face) {
+                        BoundaryBehaviour::Reflect(material) => {
+                            allow_receiver = true;
+                            self.bounce_off_boundary(
+                                &scene_data.maximum_bounds,
+                                face,
+                                time,
+                                material,
+                                config.diffuse_sampling,
+                            );
+                            bounces += 1;
+                        }
+                        BoundaryBehaviour::Absorb => {
+                            self.energy = -1f64; // cancel the loop, the boundary absorbs the ray
+                        }
+                        BoundaryBehaviour::Open => {
+                            self.leave_scene(scene_data, detect_leaks, &mut leak);
+                        }
+                    },
+                    None => self.leave_scene(scene_data, detect_leaks, &mut leak),
+                },
+                Some((is_receiver, index, time, coords, normal)) => {
                     if is_receiver {
-                        // do not change direction because we pass through receivers
-                        result.push((self.energy, time.round() as u32));
+                        if let Some(echogram) = echogram.as_deref_mut() {
+                            echogram.push(RayBounceRecord {
+                                hit_time: time,
+                                surface_index: None,
+                                energy: self.energy,
+                                position: coords,
+                            });
+                        }
+                        result.push(record(
+                            self.energy,
+                            time.round() as u32,
+                            self.direction.into_inner(),
+                        ));
+                        if scene_data.scene.receiver.is_occluding() {
+                            self.energy = -1f64; // cancel the loop, the receiver absorbs the ray
+                        }
+                        // otherwise, do not change direction because we pass through receivers
                         allow_receiver = false;
                     } else {
                         allow_receiver = true;
-                        self.bounce_from_intersection(scene_data, time, coords, index);
+                        let is_stuck = time < self.time + STUCK_BOUNCE_TIME;
+                        if scene_data.scene.surfaces[index]
+                            .data()
+                            .material
+                            .is_transmitted()
+                        {
+                            self.transmit_through_intersection(time, coords);
+                        } else {
+                            if let Some(echogram) = echogram.as_deref_mut() {
+                                echogram.push(RayBounceRecord {
+                                    hit_time: time,
+                                    surface_index: Some(index),
+                                    energy: self.energy,
+                                    position: coords,
+                                });
+                            }
+                            self.bounce_from_intersection(
+                                scene_data,
+                                time,
+                                coords,
+                                normal,
+                                index,
+                                config.diffuse_sampling,
+                            );
+                            bounces += 1;
+                        }
+                        if is_stuck {
+                            stuck_bounces += 1;
+                            if stuck_bounces > MAX_STUCK_BOUNCES {
+                                self.energy = -1f64; // cancel the loop, the ray is stuck
+                            }
+                            let epsilon = 1e-6 / self.velocity;
+                            self.origin += epsilon * self.direction.into_inner();
+                        } else {
+                            stuck_bounces = 0;
+                        }
                     }
                 }
             }
         }
-        result
+        (result, leak, bounces)
     }
 
-    /// Bounce off of an intersection with a surface with the given index.
-    /// The surface material is used to determine how much energy the ray loses
-    /// and whether it's reflected specularly or refracted.
-    /// for refraction, get a random vector within the hemisphere on top of the surface
-    /// and make that the new normal vector.
-    /// for specular reflection, calculate the bouncing angle.
+    /// Stop this ray because it left the scene, recording it as a leak
+    /// if `detect_leaks` is set and the scene has surfaces.
+    fn leave_scene<C>(
+        &mut self,
+        scene_data: &SceneData<C>,
+        detect_leaks: bool,
+        leak: &mut Option<LeakedRay>,
+    ) where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        if detect_leaks && !scene_data.scene.surfaces.is_empty() {
+            *leak = Some(LeakedRay {
+                position: self.origin,
+                direction: self.direction.into_inner(),
+            });
+        }
+        self.energy = -1f64; // cancel the loop, we're out of bounds
+    }
+
+    /// Bounce off the given face of the scene's maximum bounds at the given time,
+    /// like off a surface with the given material lying in the face's plane.
+    /// The new origin is nudged into the bounds, so the next traversal starts inside them.
+    fn bounce_off_boundary(
+        &mut self,
+        bounds: &(Vector3<f64>, Vector3<f64>),
+        face: BoundaryFace,
+        time: f64,
+        material: Material,
+        diffuse_sampling: DiffuseSampling,
+    ) {
+        let normal = face.inward_normal();
+        let (min_coords, max_coords) = bounds;
+        let epsilon = 1e-6;
+        let coords = self.coords_at_time(time) + epsilon * normal;
+        let coords = Vector3::from_fn(|axis, _| {
+            coords[axis].clamp(min_coords[axis] + epsilon, max_coords[axis] - epsilon)
+        });
+        self.reflect(time, coords, &normal, material, diffuse_sampling);
+    }
+
+    /// Pass through a surface at an intersection, keeping the ray's direction and energy.
+    /// Since only a fraction of rays is transmitted (see `Material::is_transmitted`),
+    /// the transmitted energy on average is the material's transmission coefficient.
+    /// The ray is moved past the surface by `TRANSMISSION_OFFSET`, so it doesn't intersect it again.
+    fn transmit_through_intersection(&mut self, time: f64, coords: Vector3<f64>) {
+        self.time = time + TRANSMISSION_OFFSET / self.velocity;
+        self.origin = coords + TRANSMISSION_OFFSET * self.direction.into_inner();
+    }
+
+    /// Bounce off of an intersection with a surface with the given index and normal at the intersection,
+    /// see `reflect`.
+    /// Degenerate surfaces (see `is_degenerate_normal`) have no direction to bounce in, so the ray is discarded instead.
     fn bounce_from_intersection<C>(
         &mut self,
         scene_data: &SceneData<C>,
         time: f64,
         coords: Vector3<f64>,
+        normal: Vector3<f64>,
         index: usize,
+        diffuse_sampling: DiffuseSampling,
     ) where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
         <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
     {
-        let looped_time = scene_data
-            .scene
-            .loop_duration
-            .map_or(time.round() as u32, |duration| {
-                time.round() as u32 % duration
-            });
-        let surface = scene_data.scene.surfaces[index].at_time(looped_time);
-        let Surface::Interpolated(_surface_coords, _time, surface_data) = surface else {
-            panic!("at_time() somehow returned a non-interpolated surface. This shouldn't happen.")
-        };
-        let material = surface_data.material;
-
-        let normal = surface.normal();
+        if is_degenerate_normal(&normal) {
+            self.energy = -1f64; // cancel the loop, the bounce is undefined
+            return;
+        }
+        let material = scene_data.scene.surfaces[index].data().material;
+        self.reflect(time, coords, &normal, material, diffuse_sampling);
+    }
 
+    /// Move this ray to the given coordinates and time and reflect it off a surface with the given normal.
+    /// The material is used to determine how much energy the ray loses
+    /// and whether it's reflected specularly or refracted.
+    /// for refraction, get a random vector within the hemisphere on top of the surface
+    /// (sampled according to `diffuse_sampling`) and make that the new normal vector.
+    /// for specular reflection, calculate the bouncing angle.
+    fn reflect(
+        &mut self,
+        time: f64,
+        coords: Vector3<f64>,
+        normal: &Vector3<f64>,
+        material: Material,
+        diffuse_sampling: DiffuseSampling,
+    ) {
         let new_direction = if material.is_bounce_diffuse() {
-            random_direction_in_hemisphere(&normal)
+            match diffuse_sampling {
+                DiffuseSampling::CosineWeighted => {
+                    random_cosine_weighted_direction_in_hemisphere(normal)
+                }
+                DiffuseSampling::Uniform => random_direction_in_hemisphere(normal),
+            }
+        } else {
+            bounce_off_surface_with_normal(self.direction.into_inner(), normal)
+        };
+        // a zero or NaN direction would never leave its chunk, so fall back to specular reflection
+        let new_direction = if is_degenerate_normal(&new_direction) {
+            bounce_off_surface_with_normal(self.direction.into_inner(), normal)
         } else {
-            bounce_off_surface_with_normal(self.direction.into_inner(), &normal)
+            new_direction
         };
+        if is_degenerate_normal(&new_direction) {
+            self.energy = -1f64; // cancel the loop, the bounce is undefined
+            return;
+        }
 
         self.time = time;
         self.origin = coords;
@@ -216,80 +626,190 @@ impl Ray {
     /// `chunk_traversal_data` holds the information on where the ray
     /// currently is, and is updated in a loop until either a chunk
     /// with an intersection is found or the ray exits the scene.
+    /// Each surface's intersection is calculated at most once, no matter how many chunks it spans,
+    /// see `SurfaceChecks`.
+    /// If the ray hasn't left the scene after `TRAVERSAL_WATCHDOG_FACTOR * C³` steps, e.g. because its
+    /// direction isn't a number, it is treated as out of bounds, see `max_traversal_steps`.
     fn traverse<C>(
         &self,
         scene_data: &SceneData<C>,
         chunk_traversal_data: &mut ChunkTraversalData,
         allow_receiver: bool,
-    ) -> Option<(bool, usize, f64, Vector3<f64>)>
+    ) -> Option<TraversalHit>
     where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
         <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
     {
-        loop {
+        let mut surface_checks = SurfaceChecks::new(
+            self.time_leaving_bounds(&scene_data.maximum_bounds)
+                .unwrap_or(SampleTime::ZERO),
+        );
+        for _step in 0..max_traversal_steps::<C>() {
+            let time_exit = chunk_traversal_data.next_crossing_time();
             if chunk_traversal_data.x.position <= chunk_traversal_data.y.position
                 && chunk_traversal_data.x.position <= chunk_traversal_data.z.position
             {
                 match self.traverse_to_next_chunk(
                     &mut chunk_traversal_data.key,
                     &mut chunk_traversal_data.last_time,
+                    time_exit,
                     &mut chunk_traversal_data.x,
                     scene_data,
                     allow_receiver,
+                    &mut surface_checks,
                 ) {
-                    IntersectionCheckResult::Found(is_receiver, index, time, coords) => {
-                        return Some((is_receiver, index, time, coords))
+                    IntersectionCheckResult::Found(is_receiver, index, time, coords, normal) => {
+                        return Some((is_receiver, index, time, coords, normal))
                     }
                     IntersectionCheckResult::OutOfBounds => return None,
                     IntersectionCheckResult::NoIntersection => (), // continue if no intersection
-                };
+                }
             } else if chunk_traversal_data.y.position <= chunk_traversal_data.x.position
                 && chunk_traversal_data.y.position <= chunk_traversal_data.z.position
             {
                 match self.traverse_to_next_chunk(
                     &mut chunk_traversal_data.key,
                     &mut chunk_traversal_data.last_time,
+                    time_exit,
                     &mut chunk_traversal_data.y,
                     scene_data,
                     allow_receiver,
+                    &mut surface_checks,
                 ) {
-                    IntersectionCheckResult::Found(is_receiver, index, time, coords) => {
-                        return Some((is_receiver, index, time, coords))
+                    IntersectionCheckResult::Found(is_receiver, index, time, coords, normal) => {
+                        return Some((is_receiver, index, time, coords, normal))
                     }
                     IntersectionCheckResult::OutOfBounds => return None,
                     IntersectionCheckResult::NoIntersection => (), // continue if no intersection
-                };
+                }
             } else {
                 match self.traverse_to_next_chunk(
                     &mut chunk_traversal_data.key,
                     &mut chunk_traversal_data.last_time,
+                    time_exit,
                     &mut chunk_traversal_data.z,
                     scene_data,
                     allow_receiver,
+                    &mut surface_checks,
                 ) {
-                    IntersectionCheckResult::Found(is_receiver, index, time, coords) => {
-                        return Some((is_receiver, index, time, coords))
+                    IntersectionCheckResult::Found(is_receiver, index, time, coords, normal) => {
+                        return Some((is_receiver, index, time, coords, normal))
                     }
                     IntersectionCheckResult::OutOfBounds => return None,
                     IntersectionCheckResult::NoIntersection => (), // continue if no intersection
-                };
+                }
             }
         }
+        None
     }
 
-    /// Check for an intersection in the current chunk,
+    /// Find the earliest intersection after the ray's current time without using chunks,
+    /// by checking every surface and the receiver until the ray leaves the scene's maximum bounds.
+    /// Like the receiver in `intersection_check_in_chunk`, the receiver wins ties with surfaces.
+    /// Returns `None` if the ray is or goes out of bounds without intersecting anything.
+    fn intersect_brute_force<C>(
+        &self,
+        scene_data: &SceneData<C>,
+        allow_receiver: bool,
+    ) -> Option<TraversalHit>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let time_exit = self.time_leaving_bounds(&scene_data.maximum_bounds)?;
+        let time_entry = SampleTime(self.time.floor() as u32);
+        let loop_duration = scene_data.scene.loop_duration;
+        TraversalCounters::record_intersection_tests(
+            scene_data.scene.surfaces.len() as u64 + <u64 as From<bool>>::from(allow_receiver),
+        );
+        let receiver = if allow_receiver {
+            intersect_ray_and_receiver(
+                self,
+                &scene_data.scene.receiver,
+                time_entry,
+                time_exit,
+                loop_duration,
+            )
+            .map(|(time, coords)| (true, 0, time, coords, Vector3::zeros()))
+        } else {
+            None
+        };
+        let surfaces =
+            scene_data
+                .scene
+                .surfaces
+                .iter()
+                .enumerate()
+                .filter_map(|(index, surface)| {
+                    intersect_ray_and_surface_with_bounds(
+                        self,
+                        surface,
+                        time_entry,
+                        time_exit,
+                        loop_duration,
+                        scene_data.keyframe_pair_bounds(index),
+                        scene_data.triangle_normals(index),
+                    )
+                    .map(|(time, coords, normal)| (false, index, time, coords, normal))
+                });
+        receiver
+            .into_iter()
+            .chain(surfaces)
+            .filter(|(_is_receiver, _index, time, _coords, _normal)| *time >= self.time)
+            .min_by(|first, second| first.2.total_cmp(&second.2))
+    }
+
+    /// Get the time at which this ray leaves the given bounds, rounded up to the next sample.
+    /// Returns `None` if the ray starts outside of the bounds.
+    fn time_leaving_bounds(&self, bounds: &(Vector3<f64>, Vector3<f64>)) -> Option<SampleTime> {
+        self.boundary_exit(bounds)
+            .map(|(_face, time)| SampleTime(time.ceil() as u32))
+    }
+
+    /// Get the face of the given bounds this ray leaves them through and the exact time it does so.
+    /// Returns `None` if the ray starts outside of the bounds.
+    fn boundary_exit(&self, bounds: &(Vector3<f64>, Vector3<f64>)) -> Option<(BoundaryFace, f64)> {
+        let (min_coords, max_coords) = bounds;
+        if (0..3).any(|axis| {
+            self.origin[axis] < min_coords[axis] || self.origin[axis] > max_coords[axis]
+        }) {
+            return None;
+        }
+        (0..3)
+            .filter(|axis| self.direction[*axis] != 0f64)
+            .map(|axis| {
+                let positive = self.direction[axis] > 0f64;
+                let bound = if positive {
+                    max_coords[axis]
+                } else {
+                    min_coords[axis]
+                };
+                let distance = (bound - self.origin[axis]) / self.direction[axis];
+                (
+                    BoundaryFace::from_axis(axis, positive),
+                    distance / self.velocity + self.time,
+                )
+            })
+            .min_by(|(_first_face, first), (_second_face, second)| first.total_cmp(second))
+    }
+
+    /// Check for an intersection in the current chunk until `time_exit`, the time the ray leaves it,
     /// then traverse to the next chunk.
     /// If an intersection is found in the current chunk, return that.
     /// If the next chunk would be outside the scene bounds, return accordingly.
     /// Otherwise, continue.
+    #[allow(clippy::too_many_arguments)]
     fn traverse_to_next_chunk<C>(
         &self,
         key: &mut i32,
-        last_time: &mut u32,
+        last_time: &mut SampleTime,
+        time_exit: f64,
         dimension: &mut ChunkTraversalDataDimension,
         scene_data: &SceneData<C>,
         allow_receiver: bool,
+        surface_checks: &mut SurfaceChecks,
     ) -> IntersectionCheckResult
     where
         C: Unsigned + Mul<C>,
@@ -299,22 +819,24 @@ impl Ray {
         let intersection = self.intersection_check_in_chunk(
             *key as u32,
             *last_time,
-            dimension.time.ceil() as u32,
+            SampleTime(time_exit.ceil() as u32),
             scene_data,
             allow_receiver,
+            surface_checks,
         );
         if intersection.is_found() {
             return intersection;
         }
 
-        *last_time = dimension.time.trunc() as u32;
+        if dimension.remaining_steps == 0 {
+            return IntersectionCheckResult::OutOfBounds;
+        }
+
+        *last_time = SampleTime(time_exit.trunc() as u32);
         *key += dimension.key_increment;
         dimension.position += dimension.delta_position;
         dimension.time += dimension.delta_time;
-
-        if dimension.position >= dimension.bound {
-            return IntersectionCheckResult::OutOfBounds;
-        }
+        dimension.remaining_steps -= 1;
 
         IntersectionCheckResult::NoIntersection
     }
@@ -324,10 +846,11 @@ impl Ray {
     fn intersection_check_in_chunk<C>(
         &self,
         key: u32,
-        time_entry: u32,
-        time_exit: u32,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
         scene_data: &SceneData<C>,
         allow_receiver: bool,
+        surface_checks: &mut SurfaceChecks,
     ) -> IntersectionCheckResult
     where
         C: Unsigned + Mul<C>,
@@ -337,6 +860,7 @@ impl Ray {
         if !scene_data.chunks.is_chunk_set(key as usize) {
             return IntersectionCheckResult::NoIntersection;
         }
+        TraversalCounters::record_chunk_visit();
         let (receivers, surfaces) = scene_data.chunks.objects_at_key_and_time(
             key,
             time_entry,
@@ -351,7 +875,12 @@ impl Ray {
         };
 
         self.intersection_check_surface_in_chunk(
-            &surfaces, scene_data, time_entry, time_exit, result,
+            &surfaces,
+            scene_data,
+            time_entry,
+            time_exit,
+            result,
+            surface_checks,
         )
     }
 
@@ -361,8 +890,8 @@ impl Ray {
         &self,
         receivers: &[usize],
         scene_data: &SceneData<C>,
-        time_entry: u32,
-        time_exit: u32,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
     ) -> IntersectionCheckResult
     where
         C: Unsigned + Mul<C>,
@@ -372,21 +901,22 @@ impl Ray {
         if receivers.is_empty() {
             return IntersectionCheckResult::NoIntersection;
         }
+        TraversalCounters::record_intersection_tests(1);
         // as of current we only have one receiver - this logic might change in the future
-        if let Some((time, coords)) = intersection::intersect_ray_and_receiver(
+        if let Some((time, coords)) = scene_data.scene.receiver.intersect(
             self,
-            &scene_data.scene.receiver,
             time_entry,
             time_exit,
             scene_data.scene.loop_duration,
         ) {
-            return IntersectionCheckResult::Found(true, 0, time, coords);
+            return IntersectionCheckResult::Found(true, 0, time, coords, Vector3::zeros());
         }
         IntersectionCheckResult::NoIntersection
     }
 
     /// Check if this ray intersects with surfaces inside this chunk.
     /// Surfaces that the ray has last intersected with are skipped.
+    /// Surfaces already checked in a previous chunk reuse that check's result, see `SurfaceChecks`.
     ///
     /// For surfaces the ray does intersect with, if the intersection
     /// is earlier than previously found intersections (including the one from `result`),
@@ -395,9 +925,10 @@ impl Ray {
         &self,
         surfaces: &[usize],
         scene_data: &SceneData<C>,
-        time_entry: u32,
-        time_exit: u32,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
         mut result: IntersectionCheckResult,
+        surface_checks: &mut SurfaceChecks,
     ) -> IntersectionCheckResult
     where
         C: Unsigned + Mul<C>,
@@ -405,24 +936,30 @@ impl Ray {
         <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
     {
         for surface_index in surfaces {
-            let Some((time, coords)) = intersection::intersect_ray_and_surface(
+            let Some((time, coords, normal)) = surface_checks.intersection_in_window(
                 self,
-                &scene_data.scene.surfaces[*surface_index],
+                scene_data,
+                *surface_index,
                 time_entry,
                 time_exit,
-                scene_data.scene.loop_duration,
             ) else {
                 // skip surfaces we don't intersect with
                 continue;
             };
+            if time < TRANSMISSION_OFFSET.mul_add(-0.5 / self.velocity, self.time) {
+                // skip surfaces behind the ray, e.g. one it just passed through.
+                // Intersections found in chunks can be slightly early, so allow some tolerance.
+                continue;
+            }
 
             if match result {
-                IntersectionCheckResult::Found(_is_recv, _index, result_time, _coords) => {
+                IntersectionCheckResult::Found(_is_recv, _index, result_time, _coords, _normal) => {
                     time < result_time
                 }
                 _ => true,
             } {
-                result = IntersectionCheckResult::Found(false, *surface_index, time, coords);
+                result =
+                    IntersectionCheckResult::Found(false, *surface_index, time, coords, normal);
             }
         }
 
@@ -432,20 +969,25 @@ impl Ray {
     /// Initialise the chunk traversal data.
     /// We first calculate the key of the chunk the ray starts in,
     /// then initialise the `ChunkTraversalData` with that and the individual dimensions.
-    fn init_chunk_traversal_data<C>(&self, scene_data: &SceneData<C>) -> ChunkTraversalData
+    /// If the ray starts outside of the chunk grid (e.g. due to floating point drift),
+    /// it is out of bounds and `None` is returned.
+    fn init_chunk_traversal_data<C>(&self, scene_data: &SceneData<C>) -> Option<ChunkTraversalData>
     where
         C: Unsigned + Mul<C>,
         <C as Mul>::Output: Mul<C>,
         <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
     {
+        if !scene_data.chunks.is_in_bounds(&self.origin) {
+            return None;
+        }
         let chunk_indices = scene_data.chunks.coords_to_chunk_index(&self.origin);
         let key = scene_data
             .chunks
             .key_for_index(chunk_indices.0, chunk_indices.1, chunk_indices.2)
             as i32;
-        ChunkTraversalData {
+        Some(ChunkTraversalData {
             key,
-            last_time: self.time.floor() as u32,
+            last_time: SampleTime(self.time.floor() as u32),
             x: init_chunk_traversal_data_dimension(
                 self.direction[0], // we can directly use direction as direction cosine because it's a unit vector
                 C::to_i32() * C::to_i32(),
@@ -455,7 +997,7 @@ impl Ray {
                     <f64 as From<u32>>::from(chunk_indices.0),
                     scene_data.chunks.chunk_starts.x,
                 ),
-                self.time.floor() as u32,
+                self.time,
                 self.velocity,
                 C::to_u32(),
                 chunk_indices.0,
@@ -469,7 +1011,7 @@ impl Ray {
                     <f64 as From<u32>>::from(chunk_indices.1),
                     scene_data.chunks.chunk_starts.y,
                 ),
-                self.time.floor() as u32,
+                self.time,
                 self.velocity,
                 C::to_u32(),
                 chunk_indices.1,
@@ -483,16 +1025,29 @@ impl Ray {
                     <f64 as From<u32>>::from(chunk_indices.2),
                     scene_data.chunks.chunk_starts.z,
                 ),
-                self.time.floor() as u32,
+                self.time,
                 self.velocity,
                 C::to_u32(),
                 chunk_indices.2,
             ),
-        }
+        })
     }
 }
 
+/// The factor of the chunk count after which `Ray::traverse` gives up, see `max_traversal_steps`.
+pub const TRAVERSAL_WATCHDOG_FACTOR: usize = 2;
+
+/// Get the maximum number of chunk steps a single traversal may take, see `Ray::traverse`.
+/// A straight ray crosses at most `3 * C` chunks, so this is only reached if traversal stops making progress.
+fn max_traversal_steps<C: Unsigned>() -> usize {
+    TRAVERSAL_WATCHDOG_FACTOR * C::to_usize().pow(3)
+}
+
 /// Initialise the chunk traversal data for a single dimension.
+/// The number of chunk steps left until the ray exits the scene is counted from
+/// the current chunk index, so leaving the chunk grid doesn't depend on float positions.
+/// Crossing times are counted from the ray's exact `start_time`, which may lie between two samples.
+/// Dimensions with direction cosines below `DIRECTION_COSINE_EPSILON` are treated as not being moved along.
 #[allow(clippy::too_many_arguments)]
 fn init_chunk_traversal_data_dimension(
     direction_cosine: f64,
@@ -500,65 +1055,43 @@ fn init_chunk_traversal_data_dimension(
     origin_position: f64,
     chunk_width: f64,
     chunk_start: f64,
-    start_time: u32,
+    start_time: f64,
     velocity: f64,
     num_chunks: u32,
     chunk_index: u32,
 ) -> ChunkTraversalDataDimension {
-    if abs_diff_eq!(direction_cosine, 0f64) {
+    if direction_cosine.abs() < DIRECTION_COSINE_EPSILON {
+        // the ray never crosses a chunk boundary in this dimension
         ChunkTraversalDataDimension {
             position: f64::MAX,
             delta_position: 0f64,
             key_increment,
-            time: 0f64,
+            time: f64::MAX,
             delta_time: 0f64,
-            bound: 0f64,
+            remaining_steps: 0,
         }
     } else if direction_cosine > 0f64 {
         let delta_position = chunk_width / direction_cosine;
         let delta_time: f64 = delta_position / velocity;
-        let position = (chunk_start + chunk_width - origin_position) / chunk_width * delta_position;
-        let bound = if position > delta_position / 2f64 {
-            // if we're in the second half of the chunk, we'd rather the border be slightly into the OOB chunk
-            // to reduce the risk of rounding errors when we're near the end of the chunk each time
-            (<f64 as From<u32>>::from(num_chunks - chunk_index) + 0.1) * delta_position
-        } else {
-            // if we're in the first half of the chunk, we'd rather the border be slightly into the last chunk
-            // to reduce the risk of rounding errors when we're near the beginning of the chunk each time
-            (<f64 as From<u32>>::from(num_chunks - chunk_index) - 0.1f64) * delta_position
-        };
         ChunkTraversalDataDimension {
-            position,
+            position: (chunk_start + chunk_width - origin_position) / chunk_width * delta_position,
             delta_position,
             key_increment,
             time: ((chunk_start + chunk_width - origin_position) / chunk_width)
-                .mul_add(delta_time, <f64 as From<u32>>::from(start_time)),
+                .mul_add(delta_time, start_time),
             delta_time,
-            bound,
+            remaining_steps: (num_chunks - 1).saturating_sub(chunk_index),
         }
     } else {
         let delta_position = -chunk_width / direction_cosine;
         let delta_time: f64 = delta_position / velocity;
-        let position = (origin_position - chunk_start) / chunk_width * delta_position;
-        let bound = if position > delta_position / 2f64 {
-            // if we're in the second half of the chunk, we'd rather the border be slightly into the OOB chunk
-            // to reduce the risk of rounding errors when we're near the end of the chunk each time
-            (<f64 as From<u32>>::from(chunk_index) + 1.1) * delta_position
-        } else {
-            // if we're in the first half of the chunk, we'd rather the border be slightly into the last chunk
-            // to reduce the risk of rounding errors when we're near the beginning of the chunk each time
-            (<f64 as From<u32>>::from(chunk_index) + 0.9f64) * delta_position
-        };
         ChunkTraversalDataDimension {
-            position,
+            position: (origin_position - chunk_start) / chunk_width * delta_position,
             delta_position,
             key_increment: -key_increment,
-            time: ((origin_position - chunk_start) / chunk_width)
-                .mul_add(delta_time, <f64 as From<u32>>::from(start_time)),
+            time: ((origin_position - chunk_start) / chunk_width).mul_add(delta_time, start_time),
             delta_time,
-            // truncate bound because it doesn't need to be that specific, & float rounding issues in the bound can lead to OOB issues
-            // we'd rather have it be a bit too small (=> nothing changes) than a bit too large (=> we don't return out where we should)
-            bound,
+            remaining_steps: chunk_index,
         }
     }
 }
@@ -569,22 +1102,108 @@ impl Default for Ray {
             direction: Unit::new_normalize(Vector3::new(0f64, 1f64, 0f64)),
             origin: Vector3::new(0f64, 0f64, 0f64),
             energy: 1f64,
+            initial_energy: 1f64,
             time: 0f64,
             velocity: DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
         }
     }
 }
 
+/// The surfaces a ray has checked for intersections during a single traversal, see `Ray::traverse`.
+/// Surfaces are added to every chunk their bounding box touches, so a ray passing along a surface
+/// meets it in several consecutive chunks. Rather than checking it again within each chunk's time window,
+/// the first check covers the time until the ray leaves the scene's bounds and its result is reused.
+/// This also keeps an intersection exactly on the boundary between two windows from being found twice.
+struct SurfaceChecks {
+    /// The time the ray leaves the scene's maximum bounds, rounded up.
+    time_leaving_bounds: SampleTime,
+    /// The indices of the checked surfaces, the time their check started at and its result.
+    checked: Vec<(usize, SampleTime, Option<SurfaceIntersection>)>,
+}
+
+impl SurfaceChecks {
+    const fn new(time_leaving_bounds: SampleTime) -> Self {
+        Self {
+            time_leaving_bounds,
+            checked: vec![],
+        }
+    }
+
+    /// Get the first intersection between the ray and the surface with the given index
+    /// between `time_entry` and `time_exit`, checking the surface only if needed.
+    /// A previous check's result is reused if it started no later than `time_entry`
+    /// and didn't find an intersection before it. Since it found the first intersection,
+    /// either that intersection lies within the window or there is none within it.
+    /// Otherwise, the surface is checked from `time_entry` until the ray leaves the scene's bounds.
+    fn intersection_in_window<C>(
+        &mut self,
+        ray: &Ray,
+        scene_data: &SceneData<C>,
+        surface_index: usize,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+    ) -> Option<SurfaceIntersection>
+    where
+        C: Unsigned + Mul<C>,
+        <C as Mul>::Output: Mul<C>,
+        <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+    {
+        let position = self
+            .checked
+            .iter()
+            .position(|(index, _start, _result)| *index == surface_index);
+        let is_reusable =
+            |(_index, start, result): &(usize, SampleTime, Option<SurfaceIntersection>)| {
+                *start <= time_entry
+                    && result.is_none_or(|(time, _coords, _normal)| {
+                        time >= <f64 as From<u32>>::from(time_entry.samples())
+                    })
+            };
+        let result = match position {
+            Some(position) if is_reusable(&self.checked[position]) => self.checked[position].2,
+            _ => {
+                TraversalCounters::record_intersection_tests(1);
+                let result = intersect_ray_and_surface_with_bounds(
+                    ray,
+                    &scene_data.scene.surfaces[surface_index],
+                    time_entry,
+                    self.time_leaving_bounds.max(time_exit),
+                    scene_data.scene.loop_duration,
+                    scene_data.keyframe_pair_bounds(surface_index),
+                    scene_data.triangle_normals(surface_index),
+                );
+                let check = (surface_index, time_entry, result);
+                match position {
+                    Some(position) => self.checked[position] = check,
+                    None => self.checked.push(check),
+                }
+                result
+            }
+        };
+        result.filter(|(time, _coords, _normal)| {
+            *time <= <f64 as From<u32>>::from(time_exit.samples())
+        })
+    }
+}
+
 /// Data required for chunk traversal as per CW88
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct ChunkTraversalData {
     key: i32,
-    last_time: u32,
+    last_time: SampleTime,
     x: ChunkTraversalDataDimension,
     y: ChunkTraversalDataDimension,
     z: ChunkTraversalDataDimension,
 }
 
+impl ChunkTraversalData {
+    /// Get the time the ray leaves the current chunk, i.e. the earliest time it crosses a chunk boundary in any dimension.
+    /// Dimensions the ray doesn't move in never cross a boundary, so they don't shorten the interval.
+    const fn next_crossing_time(&self) -> f64 {
+        self.x.time.min(self.y.time).min(self.z.time)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct ChunkTraversalDataDimension {
     position: f64,
@@ -593,5 +1212,246 @@ struct ChunkTraversalDataDimension {
     // store time as a float here to avoid rounding errors
     time: f64,
     delta_time: f64,
-    bound: f64,
+    /// The number of chunks the ray can still step through in this dimension
+    /// before leaving the scene, i.e. the distance from the current chunk index to 0 or C-1.
+    remaining_steps: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{Material, MATERIAL_CONCRETE_WALL},
+        ray::IntersectionCheckResult,
+        scene::SceneData,
+        scene_builder::SceneBuilder,
+        simulation_config::DiffuseSampling,
+        telemetry::{TraversalCounters, COUNTING_LOCK},
+    };
+
+    use std::sync::PoisonError;
+
+    use approx::assert_abs_diff_eq;
+    use nalgebra::{Unit, Vector3};
+
+    use super::{
+        max_traversal_steps, ChunkTraversalData, Ray, DEFAULT_PROPAGATION_SPEED,
+        DIRECTION_COSINE_EPSILON,
+    };
+    use crate::DEFAULT_SAMPLE_RATE;
+
+    #[test]
+    fn chunk_crossing_times_start_at_fractional_ray_time() {
+        let scene_data = cube_scene_data();
+        let mut ray = Ray::new(
+            Unit::new_normalize(Vector3::new(-0.7f64, 0.5f64, 0.3f64)),
+            Vector3::new(0.2f64, 0.4f64, -0.1f64),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+        let at_sample = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        ray.time = 0.75f64;
+        let between_samples = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        // flooring the start time would make every crossing up to a sample early,
+        // so the last chunk's window could end right before the ray hits a surface in it
+        for (at_sample, between_samples) in [
+            (at_sample.x, between_samples.x),
+            (at_sample.y, between_samples.y),
+            (at_sample.z, between_samples.z),
+        ] {
+            assert_abs_diff_eq!(
+                at_sample.time + 0.75f64,
+                between_samples.time,
+                epsilon = 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn display_intersection_check_result() {
+        let result = IntersectionCheckResult::Found(
+            true,
+            0,
+            1234.5617,
+            Vector3::new(1f64, 2f64, 3f64),
+            Vector3::zeros(),
+        );
+        assert_eq!(
+            "Found(receiver=true, surface=0, t=1234.56, pos=(1.0,2.0,3.0))",
+            result.to_string()
+        );
+        assert_eq!(format!("{result}"), format!("{result:?}"));
+        assert_eq!(
+            "OutOfBounds",
+            IntersectionCheckResult::OutOfBounds.to_string()
+        );
+    }
+
+    #[test]
+    fn surface_spanning_many_chunks_is_checked_once() {
+        // the wall spans all 10 chunks along x, the ray grazes along it and hits it near its far end
+        let scene = SceneBuilder::new()
+            .with_static_panel(
+                [
+                    (0f64, 0f64, 0f64),
+                    (10f64, 0f64, 0f64),
+                    (10f64, 0f64, 1f64),
+                    (0f64, 0f64, 1f64),
+                ],
+                MATERIAL_CONCRETE_WALL,
+            )
+            .with_emitter_at(0.1f64, 0.05f64, 0.5f64)
+            .with_receiver_at(5f64, 0.9f64, 0.5f64)
+            .build()
+            .unwrap();
+        let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+        let ray = Ray::new(
+            Unit::new_normalize(Vector3::new(1f64, -0.0055f64, 0f64)),
+            Vector3::new(0.1f64, 0.05f64, 0.5f64),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+
+        let _counting = COUNTING_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        // other tests may trace rays at the same time, which only adds to the count,
+        // so the fewest intersection tests over a few traversals are this ray's own
+        let intersection_tests = (0..5)
+            .map(|_| {
+                let mut chunk_traversal_data = ray.init_chunk_traversal_data(&scene_data).unwrap();
+                TraversalCounters::start();
+                ray.traverse(&scene_data, &mut chunk_traversal_data, false);
+                TraversalCounters::finish().intersection_tests
+            })
+            .min();
+        assert_eq!(Some(1), intersection_tests);
+
+        let mut chunk_traversal_data = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        let (is_receiver, index, time, coords, _normal) = ray
+            .traverse(&scene_data, &mut chunk_traversal_data, false)
+            .unwrap();
+
+        let (_, brute_force_index, brute_force_time, ..) =
+            ray.intersect_brute_force(&scene_data, false).unwrap();
+        assert!(!is_receiver);
+        assert_eq!(brute_force_index, index);
+        assert_abs_diff_eq!(brute_force_time, time, epsilon = 1e-9);
+        assert!(coords.x > 9f64, "{coords:?}");
+    }
+
+    fn cube_scene_data() -> SceneData<typenum::U10> {
+        let scene = SceneBuilder::new()
+            .with_static_cube(
+                (-5f64, -5f64, -5f64),
+                (5f64, 5f64, 5f64),
+                MATERIAL_CONCRETE_WALL,
+            )
+            .with_emitter_at(0f64, 0f64, 0f64)
+            .with_receiver_at(2f64, 2f64, 2f64)
+            .build()
+            .unwrap();
+        SceneData::<typenum::U10>::create_for_scene(scene).unwrap()
+    }
+
+    #[test]
+    fn stalled_traversal_is_stopped_by_watchdog() {
+        let scene_data = cube_scene_data();
+        let ray = Ray::new(
+            Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+            Vector3::zeros(),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+        let mut chunk_traversal_data = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        // a traversal that never makes progress, like one with a NaN direction could
+        for dimension in [
+            &mut chunk_traversal_data.x,
+            &mut chunk_traversal_data.y,
+            &mut chunk_traversal_data.z,
+        ] {
+            dimension.delta_position = 0f64;
+            dimension.key_increment = 0;
+            dimension.remaining_steps = u32::MAX;
+        }
+        assert!(ray
+            .traverse(&scene_data, &mut chunk_traversal_data, true)
+            .is_none());
+        // the ray only moves along x, so every step was taken in that dimension
+        assert_eq!(
+            u32::MAX as usize - max_traversal_steps::<typenum::U10>(),
+            chunk_traversal_data.x.remaining_steps as usize
+        );
+    }
+
+    #[test]
+    fn tiny_direction_cosines_are_treated_as_zero() {
+        let scene_data = cube_scene_data();
+        let ray = Ray::new(
+            Unit::new_normalize(Vector3::new(1e-8, 0.9999, 0.0001)),
+            Vector3::new(0.5f64, 0.5f64, 0.5f64),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+        assert!(ray.direction.x.abs() < DIRECTION_COSINE_EPSILON);
+        let mut chunk_traversal_data = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        assert_eq!(0, chunk_traversal_data.x.remaining_steps);
+        assert!(chunk_traversal_data.x.delta_position.abs() < f64::EPSILON);
+        assert!(chunk_traversal_data.z.delta_position.is_finite());
+
+        let remaining_steps = |data: &ChunkTraversalData| {
+            u64::from(data.x.remaining_steps)
+                + u64::from(data.y.remaining_steps)
+                + u64::from(data.z.remaining_steps)
+        };
+        let steps_before = remaining_steps(&chunk_traversal_data);
+        let (is_receiver, _index, _time, coords, _normal) = ray
+            .traverse(&scene_data, &mut chunk_traversal_data, false)
+            .unwrap();
+        let steps = steps_before - remaining_steps(&chunk_traversal_data);
+        // far below C³, the ray only crosses the chunks along y between it and the wall
+        assert!(!is_receiver);
+        assert!(steps <= 5, "{steps}");
+        assert_abs_diff_eq!(5f64, coords.y, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn nan_directions_terminate() {
+        let scene_data = cube_scene_data();
+        let nan = Vector3::new(f64::NAN, f64::NAN, f64::NAN);
+        let ray = Ray::new(
+            Unit::new_unchecked(nan),
+            Vector3::zeros(),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+        let mut chunk_traversal_data = ray.init_chunk_traversal_data(&scene_data).unwrap();
+        assert!(ray
+            .traverse(&scene_data, &mut chunk_traversal_data, true)
+            .is_none());
+
+        // reflecting off a NaN normal can't produce a direction, so the ray is discarded
+        let mut ray = Ray::new(
+            Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+            Vector3::zeros(),
+            1f64,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        );
+        ray.reflect(
+            10f64,
+            Vector3::new(5f64, 0f64, 0f64),
+            &nan,
+            Material {
+                absorption_coefficient: 0.9,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+            DiffuseSampling::CosineWeighted,
+        );
+        assert!(ray.energy < 0f64);
+        assert!(ray.direction.x.is_finite());
+    }
 }
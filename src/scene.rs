@@ -1,30 +1,80 @@
-use std::ops::Mul;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Mul, Range},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
 use generic_array::ArrayLength;
-use itertools::Itertools;
-use nalgebra::Vector3;
+use nalgebra::{Rotation3, Vector3};
 use num::{Bounded, Num, NumCast};
+use rand::random;
 use rayon::prelude::*;
 use typenum::Unsigned;
 use wav::BitDepth;
 
 use crate::{
-    bounce::EmissionType,
+    audio_io,
+    bounce::{self, EmissionType},
+    boundary::SceneBoundary,
     chunk::Chunks,
-    impulse_response::{self, to_impulse_response, ImpulseResponse},
-    interpolation::Interpolation,
+    echogram::RayBounceRecord,
+    error::{DemoError, InterpolationError, SceneBuildError},
+    estimate::{self, SimulationEstimate},
+    impulse_response::{
+        self, to_ambisonic_impulse_response, to_impulse_response, AmbisonicImpulseResponse,
+        ImpulseResponse, IrSample, PostProcessing, StatisticalTail,
+    },
+    interpolation::{self, Interpolation},
+    intersection::{self, TriangleNormal},
+    leak_detection::{LeakReport, LeakedRay},
     materials::Material,
-    ray::Ray,
-    scene_bounds::MaximumBounds,
+    ray::{Ray, RayStatistics},
+    scene_bounds::{self, MaximumBounds},
+    simulation_config::{IrPrecision, SimulationConfig},
+    sound_field::{GridPoint, GridResult, ReceiverGrid},
+    telemetry::Telemetry,
+    time::{SampleDuration, SampleTime},
 };
 
+/// The number of rays launched between two checks of `SimulationConfig::capture_decay_db`.
+const DECAY_CHECK_BATCH_SIZE: u32 = 100;
+
+/// The factor the half-angle of the cone receiver-importance rays are launched in
+/// is larger than the half-angle the receiver subtends, see `SimulationConfig::receiver_importance`.
+/// Every direction hitting the receiver stays well inside the cone, so all of them get
+/// about the same weight and none of them the much larger weight of directions outside it.
+pub const RECEIVER_CONE_WIDENING: f64 = 2f64;
+
 /// Keyframe for a single set of coordinates.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct CoordinateKeyframe {
-    pub time: u32,
+    pub time: SampleTime,
     pub coords: Vector3<f64>,
 }
 
+/// Keyframes are ordered by their time.
+/// Keyframes with the same time are ordered by their coordinates,
+/// so the ordering stays consistent with equality.
+impl Ord for CoordinateKeyframe {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.time
+            .cmp(&other.time)
+            .then_with(|| compare_coords(&self.coords, &other.coords))
+    }
+}
+
+impl PartialOrd for CoordinateKeyframe {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for CoordinateKeyframe {}
+
 /// Sound emitter.
 /// Either has its separate keyframes (sorted by time) or a single interpolated keyframe at a given time.
 #[derive(Clone, PartialEq, Debug)]
@@ -33,22 +83,192 @@ pub enum Emitter {
     Interpolated(Vector3<f64>, u32, EmissionType),
 }
 
+impl Emitter {
+    /// Get the emitter's coordinates at the given time, which may lie between two samples.
+    /// Interpolated emitters always return their coordinates.
+    ///
+    /// # Errors
+    ///
+    /// * If the emitter's keyframes are empty or not sorted by time.
+    pub fn coords_at_time<T: Num + NumCast + PartialOrd + Copy>(
+        &self,
+        time: T,
+    ) -> Result<Vector3<f64>, InterpolationError> {
+        match self {
+            Self::Interpolated(coords, _time, _emission_type) => Ok(*coords),
+            Self::Keyframes(keyframes, _emission_type) => {
+                interpolation::interpolate_coordinate_keyframes(keyframes, time)
+            }
+        }
+    }
+}
+
 /// Sound receiver.
 /// Either has its separate keyframes (sorted by time) or a single interpolated keyframe at a given time.
-/// Always also has a radius.
+/// Spherical receivers have a radius, capturing all incident directions equally.
+/// Keyframe spheres can have their own loop duration, otherwise the scene's loop duration is used.
+/// The last field of spheres marks occluding receivers, which absorb every ray hitting them instead of letting it pass through.
+/// Ellipsoidal receivers have the lengths of their three semi-axes and the rotation from their local axes
+/// to the scene's axes instead, which makes them more sensitive to rays travelling along their longer axes.
+/// They always use the scene's loop duration and never occlude.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Receiver {
-    Keyframes(Vec<CoordinateKeyframe>, f64),
-    Interpolated(Vector3<f64>, f64, u32),
+    Keyframes(Vec<CoordinateKeyframe>, f64, Option<SampleDuration>, bool),
+    Interpolated(Vector3<f64>, f64, u32, bool),
+    EllipsoidKeyframes(Vec<CoordinateKeyframe>, Vector3<f64>, Rotation3<f64>),
+    Ellipsoid(Vector3<f64>, Vector3<f64>, Rotation3<f64>, u32),
+}
+
+impl Receiver {
+    /// Get the receiver's own loop duration, if it has one.
+    pub const fn loop_duration(&self) -> Option<SampleDuration> {
+        match self {
+            Self::Keyframes(_, _radius, loop_duration, _occluding) => *loop_duration,
+            Self::Interpolated(..) | Self::EllipsoidKeyframes(..) | Self::Ellipsoid(..) => None,
+        }
+    }
+
+    /// Whether rays hitting this receiver are terminated instead of passing through it.
+    pub const fn is_occluding(&self) -> bool {
+        match self {
+            Self::Keyframes(_, _, _, occluding) | Self::Interpolated(_, _, _, occluding) => {
+                *occluding
+            }
+            Self::EllipsoidKeyframes(..) | Self::Ellipsoid(..) => false,
+        }
+    }
+
+    /// Get the radius of the smallest sphere around the receiver's center containing the whole receiver,
+    /// i.e. the radius of spheres and the longest semi-axis of ellipsoids.
+    pub fn bounding_radius(&self) -> f64 {
+        match self {
+            Self::Keyframes(_, radius, _, _) | Self::Interpolated(_, radius, _, _) => *radius,
+            Self::EllipsoidKeyframes(_, semi_axes, _) | Self::Ellipsoid(_, semi_axes, _, _) => {
+                semi_axes.max()
+            }
+        }
+    }
+
+    /// Get the center of an interpolated receiver.
+    /// Returns `None` for keyframe receivers, see `Interpolation::at_time`.
+    pub const fn center(&self) -> Option<Vector3<f64>> {
+        match self {
+            Self::Interpolated(coords, ..) | Self::Ellipsoid(coords, ..) => Some(*coords),
+            Self::Keyframes(..) | Self::EllipsoidKeyframes(..) => None,
+        }
+    }
+
+    /// Get a static receiver with this receiver's shape, centered at the given coordinates.
+    pub const fn with_center(&self, coords: Vector3<f64>) -> Self {
+        match self {
+            Self::Keyframes(_, radius, _, occluding)
+            | Self::Interpolated(_, radius, _, occluding) => {
+                Self::Interpolated(coords, *radius, 0, *occluding)
+            }
+            Self::EllipsoidKeyframes(_, semi_axes, orientation)
+            | Self::Ellipsoid(_, semi_axes, orientation, _) => {
+                Self::Ellipsoid(coords, *semi_axes, *orientation, 0)
+            }
+        }
+    }
+
+    /// Get this receiver with the given bounding radius (see `bounding_radius`).
+    /// Ellipsoids are scaled uniformly, so they keep their proportions.
+    pub fn with_radius(&self, radius: f64) -> Self {
+        let mut receiver = self.clone();
+        match &mut receiver {
+            Self::Keyframes(_, receiver_radius, _, _)
+            | Self::Interpolated(_, receiver_radius, _, _) => *receiver_radius = radius,
+            Self::EllipsoidKeyframes(_, semi_axes, _) | Self::Ellipsoid(_, semi_axes, _, _) => {
+                *semi_axes *= radius / semi_axes.max();
+            }
+        }
+        receiver
+    }
 }
 
 /// Keyframe for a set of coordinates for a surface.
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SurfaceKeyframe<const N: usize> {
-    pub time: u32,
+    pub time: SampleTime,
     pub coords: [Vector3<f64>; N],
 }
 
+/// Keyframes are ordered by their time.
+/// Keyframes with the same time are ordered by their coordinates,
+/// so the ordering stays consistent with equality.
+impl<const N: usize> Ord for SurfaceKeyframe<N> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.time.cmp(&other.time).then_with(|| {
+            self.coords
+                .iter()
+                .zip(&other.coords)
+                .map(|(coords, other_coords)| compare_coords(coords, other_coords))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+impl<const N: usize> PartialOrd for SurfaceKeyframe<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Eq for SurfaceKeyframe<N> {}
+
+/// Compare two sets of coordinates lexicographically, using a total order for each component.
+fn compare_coords(coords: &Vector3<f64>, other: &Vector3<f64>) -> std::cmp::Ordering {
+    coords
+        .iter()
+        .zip(other.iter())
+        .map(|(coord, other_coord)| coord.total_cmp(other_coord))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Check that the given keyframe times aren't empty and are sorted.
+fn validate_keyframe_times(
+    mut times: impl Iterator<Item = SampleTime>,
+) -> Result<(), InterpolationError> {
+    let Some(mut previous_time) = times.next() else {
+        return Err(InterpolationError::NoKeyframes);
+    };
+    for time in times {
+        if time < previous_time {
+            return Err(InterpolationError::UnsortedKeyframes);
+        }
+        previous_time = time;
+    }
+    Ok(())
+}
+
+/// Get the loop duration of two merged scenes, see `Scene::merge`.
+///
+/// # Errors
+///
+/// * `SceneBuildError::LoopDurationMismatch` if both loop durations are set and differ.
+pub fn merged_loop_duration(
+    first: Option<SampleDuration>,
+    second: Option<SampleDuration>,
+) -> Result<Option<SampleDuration>, SceneBuildError> {
+    match (first, second) {
+        (Some(first), Some(second)) if first != second => {
+            Err(SceneBuildError::LoopDurationMismatch(first, second))
+        }
+        _ => Ok(first.or(second)),
+    }
+}
+
+/// Sort the given keyframes by their time.
+/// The interpolation code expects all keyframe lists to be sorted,
+/// so lists that may have been assembled out of order should be passed through this.
+pub fn sort_keyframes<K: Ord>(mut keyframes: Vec<K>) -> Vec<K> {
+    keyframes.sort();
+    keyframes
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SurfaceData {
     pub material: Material,
@@ -61,465 +281,2458 @@ impl SurfaceData {
 }
 
 /// Surface in the scene.
-/// Either has its separate keyframes (sorted by time) or a single interpolated keyframe at a given time.
+/// Either has its separate keyframes (sorted by time) or fixed, interpolated coordinates.
+/// Interpolated surfaces don't record when they were interpolated,
+/// since they're valid at every time: static surfaces are never interpolated again,
+/// and snapshot scenes (see `Scene::at_time`) use them for their whole simulation.
 /// Also contains the surface's material.
+/// Keyframe surfaces can have their own loop duration, otherwise the scene's loop duration is used.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Surface<const N: usize> {
-    Keyframes(Vec<SurfaceKeyframe<N>>, SurfaceData),
-    Interpolated([Vector3<f64>; N], u32, SurfaceData),
+    Keyframes(Vec<SurfaceKeyframe<N>>, SurfaceData, Option<SampleDuration>),
+    Interpolated([Vector3<f64>; N], SurfaceData),
 }
 
 impl<const N: usize> Surface<N> {
-    /// Calculate this surface's normal as a unit vector.
+    /// Calculate this surface's normal as a unit vector, see `normal_unchecked`.
+    #[deprecated(
+        note = "use `normal_at_time(0)` for static surfaces, or `normal_unchecked` for interpolated ones"
+    )]
+    pub fn normal(&self) -> Vector3<f64> {
+        self.normal_unchecked()
+    }
+
+    /// Calculate this surface's normal at the given time as a unit vector.
+    /// Keyframe surfaces are interpolated first, so this works for every surface.
+    /// The time isn't looped, see `Scene::looped_time`.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// * When attempting to calculate the normal on a non-interpolated surface.
-    pub fn normal(&self) -> Vector3<f64> {
+    /// * If the surface's keyframes can't be interpolated.
+    pub fn normal_at_time(&self, time: u32) -> Result<Vector3<f64>, InterpolationError> {
+        Ok(self.try_at_time(time)?.normal_unchecked())
+    }
+
+    /// Calculate this surface's normal as a unit vector,
+    /// for surfaces that are already known to be interpolated.
+    /// Calling this on a keyframe surface is a logic error: it fails a debug assertion,
+    /// and in release builds the normal of the first keyframe is returned.
+    pub fn normal_unchecked(&self) -> Vector3<f64> {
+        debug_assert!(
+            matches!(self, Self::Interpolated(..)),
+            "Normals can only be calculated for interpolated surfaces!"
+        );
+        let coords = match self {
+            Self::Interpolated(coords, _material) => coords,
+            Self::Keyframes(keyframes, _material, _loop_duration) => &keyframes[0].coords,
+        };
+        let mut cross = (coords[2] - coords[0]).cross(&(coords[1] - coords[0]));
+        cross.normalize_mut();
+        cross
+    }
+
+    /// Get the surface's own loop duration, if it has one.
+    pub const fn loop_duration(&self) -> Option<SampleDuration> {
         match self {
-            Self::Interpolated(coords, _time, _material) => {
-                let mut cross = (coords[2] - coords[0]).cross(&(coords[1] - coords[0]));
-                cross.normalize_mut();
-                cross
-            }
-            Self::Keyframes(_, _material) => {
-                panic!("Normals can only be calculated for interpolated surfaces!")
+            Self::Keyframes(_, _material, loop_duration) => *loop_duration,
+            Self::Interpolated(..) => None,
+        }
+    }
+
+    /// Get the coordinates describing this surface's shape:
+    /// the interpolated coordinates for static surfaces and the first keyframe's for keyframe surfaces.
+    fn shape_coords(&self) -> &[Vector3<f64>; N] {
+        match self {
+            Self::Interpolated(coords, _data) => coords,
+            Self::Keyframes(keyframes, _data, _loop_duration) => &keyframes[0].coords,
+        }
+    }
+
+    /// Get the areas of the triangles this surface is split into, see `SurfaceVariant`.
+    fn triangle_areas(&self) -> impl Iterator<Item = f64> + '_ {
+        let coords = self.shape_coords();
+        (0..N - 2).map(|triangle| {
+            0.5 * (coords[triangle + 1] - coords[0])
+                .cross(&(coords[triangle + 2] - coords[0]))
+                .norm()
+        })
+    }
+
+    /// Calculate this surface's area, as `0.5 * |AB × AC|` summed over its triangles.
+    /// Keyframe surfaces use their first keyframe, assuming they keep their shape while moving.
+    pub fn area(&self) -> f64 {
+        self.triangle_areas().sum()
+    }
+
+    /// Get a uniformly random point on this surface,
+    /// using the same coordinates as `area`, so keyframe surfaces should be interpolated first.
+    pub fn random_point(&self) -> Vector3<f64> {
+        let coords = self.shape_coords();
+        let mut remaining_area = random::<f64>() * self.area();
+        let triangle = self
+            .triangle_areas()
+            .position(|area| {
+                remaining_area -= area;
+                remaining_area < 0f64
+            })
+            .unwrap_or(N - 3);
+        bounce::random_point_in_triangle(&[coords[0], coords[triangle + 1], coords[triangle + 2]])
+    }
+
+    /// Get the bounds of the volume swept between each pair of consecutive keyframes,
+    /// see `scene_bounds::swept_keyframe_bounds`.
+    /// Interpolated surfaces don't have any keyframe pairs.
+    pub fn keyframe_pair_bounds(&self) -> Vec<(Vector3<f64>, Vector3<f64>)> {
+        match self {
+            Self::Keyframes(keyframes, _data, _loop_duration) => keyframes
+                .windows(2)
+                .map(|pair| scene_bounds::swept_keyframe_bounds(&pair[0], &pair[1]))
+                .collect(),
+            Self::Interpolated(..) => vec![],
+        }
+    }
+
+    /// Get the coordinates of the triangles this surface is split into, see `SurfaceVariant`.
+    /// This uses the same coordinates as `area`, so keyframe surfaces should be interpolated first.
+    pub fn triangles(&self) -> Vec<[Vector3<f64>; 3]> {
+        let coords = self.shape_coords();
+        (0..N - 2)
+            .map(|triangle| [coords[0], coords[triangle + 1], coords[triangle + 2]])
+            .collect()
+    }
+
+    /// Get the normals of this surface's fan triangles, see `intersection::TriangleNormal`.
+    /// Keyframe surfaces move, so only interpolated surfaces have precalculated normals.
+    pub fn triangle_normals(&self) -> Vec<TriangleNormal> {
+        match self {
+            Self::Interpolated(coords, _data) => (0..N - 2)
+                .map(|triangle| {
+                    intersection::triangle_normal(&[
+                        coords[0],
+                        coords[triangle + 1],
+                        coords[triangle + 2],
+                    ])
+                })
+                .collect(),
+            Self::Keyframes(..) => vec![],
+        }
+    }
+
+    /// Get a copy of this surface with all coordinates moved by `translation`
+    /// and all keyframe times shifted by `time_offset`, see `Scene::merge`.
+    /// Keyframe times saturate at `u32::MAX`.
+    pub fn offset(&self, translation: &Vector3<f64>, time_offset: u32) -> Self {
+        match self {
+            Self::Keyframes(keyframes, data, loop_duration) => Self::Keyframes(
+                keyframes
+                    .iter()
+                    .map(|keyframe| SurfaceKeyframe {
+                        time: keyframe.time.saturating_add(SampleDuration(time_offset)),
+                        coords: keyframe.coords.map(|coords| coords + translation),
+                    })
+                    .collect(),
+                *data,
+                *loop_duration,
+            ),
+            Self::Interpolated(coords, data) => {
+                Self::Interpolated(coords.map(|coords| coords + translation), *data)
             }
         }
     }
+
+    /// Get the surface's data, regardless of its variant.
+    pub const fn data(&self) -> &SurfaceData {
+        match self {
+            Self::Keyframes(_, data, _) | Self::Interpolated(_, data) => data,
+        }
+    }
+
+    /// Get a mutable reference to the surface's data, regardless of its variant.
+    pub const fn data_mut(&mut self) -> &mut SurfaceData {
+        match self {
+            Self::Keyframes(_, data, _) | Self::Interpolated(_, data) => data,
+        }
+    }
+}
+
+/// A surface with any of the supported numbers of corners.
+/// Quads need to be planar, and are treated as the two triangles `0, 1, 2` and `0, 2, 3`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SurfaceVariant {
+    Triangle(Surface<3>),
+    Quad(Surface<4>),
+}
+
+impl SurfaceVariant {
+    /// Calculate this surface's normal as a unit vector, see `Surface::normal`.
+    #[deprecated(
+        note = "use `normal_at_time(0)` for static surfaces, or `normal_unchecked` for interpolated ones"
+    )]
+    pub fn normal(&self) -> Vector3<f64> {
+        self.normal_unchecked()
+    }
+
+    /// Calculate this surface's normal at the given time, see `Surface::normal_at_time`.
+    ///
+    /// # Errors
+    ///
+    /// * If the surface's keyframes can't be interpolated.
+    pub fn normal_at_time(&self, time: u32) -> Result<Vector3<f64>, InterpolationError> {
+        match self {
+            Self::Triangle(surface) => surface.normal_at_time(time),
+            Self::Quad(surface) => surface.normal_at_time(time),
+        }
+    }
+
+    /// Calculate this surface's normal as a unit vector, see `Surface::normal_unchecked`.
+    pub fn normal_unchecked(&self) -> Vector3<f64> {
+        match self {
+            Self::Triangle(surface) => surface.normal_unchecked(),
+            Self::Quad(surface) => surface.normal_unchecked(),
+        }
+    }
+
+    /// Get the surface's own loop duration, if it has one.
+    pub const fn loop_duration(&self) -> Option<SampleDuration> {
+        match self {
+            Self::Triangle(surface) => surface.loop_duration(),
+            Self::Quad(surface) => surface.loop_duration(),
+        }
+    }
+
+    /// Get the coordinates of the triangles this surface is split into, see `Surface::triangles`.
+    pub fn triangles(&self) -> Vec<[Vector3<f64>; 3]> {
+        match self {
+            Self::Triangle(surface) => surface.triangles(),
+            Self::Quad(surface) => surface.triangles(),
+        }
+    }
+
+    /// Calculate this surface's area, see `Surface::area`.
+    pub fn area(&self) -> f64 {
+        match self {
+            Self::Triangle(surface) => surface.area(),
+            Self::Quad(surface) => surface.area(),
+        }
+    }
+
+    /// Get a uniformly random point on this surface, see `Surface::random_point`.
+    pub fn random_point(&self) -> Vector3<f64> {
+        match self {
+            Self::Triangle(surface) => surface.random_point(),
+            Self::Quad(surface) => surface.random_point(),
+        }
+    }
+
+    /// Get the bounds swept between each pair of consecutive keyframes, see `Surface::keyframe_pair_bounds`.
+    pub fn keyframe_pair_bounds(&self) -> Vec<(Vector3<f64>, Vector3<f64>)> {
+        match self {
+            Self::Triangle(surface) => surface.keyframe_pair_bounds(),
+            Self::Quad(surface) => surface.keyframe_pair_bounds(),
+        }
+    }
+
+    /// Get the normals of the surface's fan triangles, see `Surface::triangle_normals`.
+    pub fn triangle_normals(&self) -> Vec<TriangleNormal> {
+        match self {
+            Self::Triangle(surface) => surface.triangle_normals(),
+            Self::Quad(surface) => surface.triangle_normals(),
+        }
+    }
+
+    /// Get a moved and time-shifted copy of this surface, see `Surface::offset`.
+    pub fn offset(&self, translation: &Vector3<f64>, time_offset: u32) -> Self {
+        match self {
+            Self::Triangle(surface) => Self::Triangle(surface.offset(translation, time_offset)),
+            Self::Quad(surface) => Self::Quad(surface.offset(translation, time_offset)),
+        }
+    }
+
+    /// Get the surface's data, regardless of its variant.
+    pub const fn data(&self) -> &SurfaceData {
+        match self {
+            Self::Triangle(surface) => surface.data(),
+            Self::Quad(surface) => surface.data(),
+        }
+    }
+
+    /// Get a mutable reference to the surface's data, regardless of its variant.
+    pub const fn data_mut(&mut self) -> &mut SurfaceData {
+        match self {
+            Self::Triangle(surface) => surface.data_mut(),
+            Self::Quad(surface) => surface.data_mut(),
+        }
+    }
+}
+
+impl From<Surface<3>> for SurfaceVariant {
+    fn from(surface: Surface<3>) -> Self {
+        Self::Triangle(surface)
+    }
+}
+
+impl From<Surface<4>> for SurfaceVariant {
+    fn from(surface: Surface<4>) -> Self {
+        Self::Quad(surface)
+    }
 }
 
 /// The full scene.
 /// Scenes always have a single emitter and receiver, but support multiple surfaces.
 #[derive(Clone, PartialEq, Debug)]
 pub struct Scene {
-    pub surfaces: Vec<Surface<3>>, // for now we only work with triangles
+    pub surfaces: Vec<SurfaceVariant>,
     pub receiver: Receiver,
     pub emitter: Emitter,
-    pub loop_duration: Option<u32>,
-}
-
-/// General data about a scene, required to bounce a ray through.
-/// Contains the scene itself, its maximum boundaries and its
-/// chunk representation.
-#[allow(clippy::module_name_repetitions)]
-pub struct SceneData<C>
-where
-    C: Unsigned + Mul<C>,
-    <C as Mul>::Output: Mul<C>,
-    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
-{
-    pub scene: Scene,
-    pub chunks: Chunks<C>,
-    pub maximum_bounds: (nalgebra::Vector3<f64>, nalgebra::Vector3<f64>),
+    pub loop_duration: Option<SampleDuration>,
+    /// The cached result of `maximum_bounds`, see `maximum_bounds_cached`.
+    /// This should usually be initialised as `None`.
+    pub bounds_cache: Option<(Vector3<f64>, Vector3<f64>)>,
+    /// What happens to rays reaching the faces of the scene's maximum bounds.
+    pub boundary: SceneBoundary,
+    /// The sample ranges (start inclusive, end exclusive) during which the emitter emits, see `is_emitter_active`.
+    /// For looping scenes, the ranges are times within the loop. `None` means the emitter always emits.
+    pub emitter_active_ranges: Option<Vec<(u32, u32)>>,
+    /// The statistical late reverb appended to each simulated impulse response, see `impulse_response::add_statistical_tail`.
+    /// `None` only keeps the geometric impulse response.
+    pub statistical_tail: Option<StatisticalTail>,
 }
 
-impl<C> SceneData<C>
-where
-    C: Unsigned + Mul<C>,
-    <C as Mul>::Output: Mul<C>,
-    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
-{
-    /// Calculate the chunks and maximum bounds for a given `Scene`,
-    /// then represent it all in a single `SceneData` object.
-    /// To avoid errors, the maximum bounds are expanded by 0.1 in each direction.
-    pub fn create_for_scene(scene: Scene) -> Self {
-        let chunks = scene.chunks::<C>();
-        let mut maximum_bounds = scene.maximum_bounds();
-        maximum_bounds.0.add_scalar_mut(-0.1);
-        maximum_bounds.1.add_scalar_mut(0.1);
-        Self {
-            scene,
-            chunks,
-            maximum_bounds,
+impl Scene {
+    /// Get this scene's maximum bounds, calculating them only on the first call.
+    /// The result is stored in `bounds_cache`, which is returned on subsequent calls.
+    /// After changing the scene's surfaces, receiver or emitter,
+    /// `invalidate_bounds_cache` needs to be called.
+    /// Chunks and `SceneData` always calculate the bounds themselves and never read the cache.
+    pub fn maximum_bounds_cached(&mut self) -> (Vector3<f64>, Vector3<f64>) {
+        if let Some(bounds) = self.bounds_cache {
+            return bounds;
         }
+        let bounds = self.maximum_bounds();
+        self.bounds_cache = Some(bounds);
+        bounds
     }
 
-    /// Simulate the given number of rays in this `Scene` for each sample in the given input,
-    /// then apply the impulse response.
-    /// see `simulate_for_time_span_internal` for details
-    #[allow(clippy::too_many_arguments)]
-    pub fn simulate_for_time_span(
-        &self,
-        input_data: &BitDepth,
-        number_of_rays: u32,
-        velocity: f64,
-        sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-        single_ir: bool,
-    ) -> (BitDepth, ImpulseResponse) {
-        let mut ir: ImpulseResponse = vec![];
-        let result = match input_data {
-            BitDepth::Eight(data) => BitDepth::Eight(self.simulate_for_time_span_internal(
-                data,
-                number_of_rays,
-                velocity,
-                sample_rate,
-                scaling_factor,
-                do_snapshot_method,
-                single_ir,
-                &mut ir,
-            )),
-            BitDepth::Sixteen(data) => BitDepth::Sixteen(self.simulate_for_time_span_internal(
-                data,
-                number_of_rays,
-                velocity,
-                sample_rate,
-                scaling_factor,
-                do_snapshot_method,
-                single_ir,
-                &mut ir,
-            )),
-            BitDepth::TwentyFour(data) => {
-                BitDepth::TwentyFour(self.simulate_for_time_span_internal(
-                    data,
-                    number_of_rays,
-                    velocity,
-                    sample_rate,
-                    scaling_factor,
-                    do_snapshot_method,
-                    single_ir,
-                    &mut ir,
-                ))
-            }
-            BitDepth::ThirtyTwoFloat(data) => {
-                BitDepth::ThirtyTwoFloat(self.simulate_for_time_span_internal(
-                    data,
-                    number_of_rays,
-                    velocity,
-                    sample_rate,
-                    scaling_factor,
-                    do_snapshot_method,
-                    single_ir,
-                    &mut ir,
-                ))
-            }
-            BitDepth::Empty => BitDepth::Empty,
-        };
-        (result, ir)
+    /// Clear the cached maximum bounds, so they get recalculated on the next
+    /// call to `maximum_bounds_cached`.
+    pub const fn invalidate_bounds_cache(&mut self) {
+        self.bounds_cache = None;
+    }
+
+    /// Get the time within the loop an object with the given own loop duration is at.
+    /// Objects without their own loop duration use the scene's loop duration,
+    /// and the time is returned unchanged if neither is set.
+    pub fn looped_time(&self, own_loop_duration: Option<SampleDuration>, time: u32) -> u32 {
+        own_loop_duration
+            .or(self.loop_duration)
+            .map_or(time, |duration| time % duration.samples())
+    }
+
+    /// Get the scene time the input sample with the given index is simulated at,
+    /// starting at `time_offset`, see `SimulationConfig::time_offset`.
+    /// For looping scenes, the time is wrapped into the loop, so offsets beyond the loop duration
+    /// continue in a later loop iteration. Otherwise, it's returned as is, so objects stay at their
+    /// last keyframe once the time is past it, see `Interpolation::at_time`.
+    pub fn input_sample_time(&self, index: usize, time_offset: u32) -> u32 {
+        self.looped_time(
+            None,
+            self.looped_time(None, time_offset)
+                .saturating_add(index as u32),
+        )
+    }
+
+    /// Check whether the emitter emits at the given time, see `emitter_active_ranges`.
+    pub fn is_emitter_active(&self, time: u32) -> bool {
+        let time = self.looped_time(None, time);
+        self.emitter_active_ranges.as_ref().is_none_or(|ranges| {
+            ranges
+                .iter()
+                .any(|(start, end)| (*start..*end).contains(&time))
+        })
+    }
+
+    /// Append the other scene's surfaces to this scene, moved by `translation`
+    /// and with their keyframe times shifted by `time_offset`.
+    /// This scene's emitter and receiver are kept, the other scene's are discarded.
+    /// If only one of the scenes loops, the merged scene loops with its duration.
+    /// For looping scenes, the shifted keyframes should still lie within the loop.
+    ///
+    /// # Errors
+    ///
+    /// * `SceneBuildError::LoopDurationMismatch` if both scenes loop with different durations.
+    ///   This scene is left unchanged in that case.
+    pub fn merge(
+        &mut self,
+        other: &Self,
+        translation: Vector3<f64>,
+        time_offset: u32,
+    ) -> Result<(), DemoError> {
+        self.loop_duration = merged_loop_duration(self.loop_duration, other.loop_duration)?;
+        self.surfaces.extend(
+            other
+                .surfaces
+                .iter()
+                .map(|surface| surface.offset(&translation, time_offset)),
+        );
+        self.invalidate_bounds_cache();
+        Ok(())
+    }
+
+    /// Get the summed area of all surfaces, see `Surface::area`.
+    pub fn total_surface_area(&self) -> f64 {
+        self.surfaces.iter().map(SurfaceVariant::area).sum()
+    }
+
+    /// Get a uniformly random point on the scene's surfaces at the given time,
+    /// picking each surface proportionally to its area.
+    /// Returns `None` if the scene has no surface area or the picked surface can't be interpolated.
+    pub fn random_surface_point(&self, time: u32) -> Option<Vector3<f64>> {
+        let mut remaining_area = random::<f64>() * self.total_surface_area();
+        if remaining_area <= 0f64 {
+            return None;
+        }
+        let surface = self
+            .surfaces
+            .iter()
+            .find(|surface| {
+                remaining_area -= surface.area();
+                remaining_area < 0f64
+            })
+            .or_else(|| self.surfaces.last())?;
+        let time = self.looped_time(surface.loop_duration(), time);
+        Some(surface.try_at_time(time).ok()?.random_point())
+    }
+
+    /// Get the coordinates of every surface's triangles at the given time, see `SurfaceVariant::triangles`,
+    /// e.g. to export a frame of an animation to a mesh format.
+    /// Like `Interpolation::at_time`, the time is looped for looping scenes and surfaces.
+    /// This only interpolates the surfaces and doesn't build any chunks.
+    ///
+    /// # Panics
+    ///
+    /// * If a surface's keyframes can't be interpolated, see `Scene::validate`.
+    pub fn triangles_at_time(&self, time: u32) -> Vec<[Vector3<f64>; 3]> {
+        self.surfaces
+            .iter()
+            .flat_map(|surface| {
+                surface
+                    .at_time(self.looped_time(surface.loop_duration(), time))
+                    .triangles()
+            })
+            .collect()
+    }
+
+    /// Get the normal of each triangle returned by `triangles_at_time` for the same time,
+    /// as unit vectors, see `Surface::normal_unchecked`.
+    ///
+    /// # Panics
+    ///
+    /// * If a surface's keyframes can't be interpolated, see `Scene::validate`.
+    pub fn normals_at_time(&self, time: u32) -> Vec<Vector3<f64>> {
+        self.surfaces
+            .iter()
+            .flat_map(|surface| {
+                let surface = surface.at_time(self.looped_time(surface.loop_duration(), time));
+                let normal = surface.normal_unchecked();
+                std::iter::repeat_n(normal, surface.triangles().len())
+            })
+            .collect()
+    }
+
+    /// Get the swept bounds of every surface's keyframe pairs, see `SceneData::keyframe_bounds`.
+    pub fn keyframe_bounds(&self) -> Vec<Vec<(Vector3<f64>, Vector3<f64>)>> {
+        self.surfaces
+            .iter()
+            .map(SurfaceVariant::keyframe_pair_bounds)
+            .collect()
+    }
+
+    /// Get the normals of every static surface's fan triangles, see `SceneData::static_normals`.
+    pub fn static_normals(&self) -> Vec<Vec<TriangleNormal>> {
+        self.surfaces
+            .iter()
+            .map(SurfaceVariant::triangle_normals)
+            .collect()
+    }
+
+    /// Check whether all keyframe lists in the scene are sorted by time,
+    /// as the interpolation code expects.
+    /// Keyframe lists can be sorted with `sort_keyframes`.
+    pub fn validate_keyframe_order(&self) -> bool {
+        let receiver_sorted = match &self.receiver {
+            Receiver::Keyframes(keyframes, ..) | Receiver::EllipsoidKeyframes(keyframes, ..) => {
+                keyframes.is_sorted_by_key(|key| key.time)
+            }
+            Receiver::Interpolated(..) | Receiver::Ellipsoid(..) => true,
+        };
+        let emitter_sorted = match &self.emitter {
+            Emitter::Keyframes(keyframes, _) => keyframes.is_sorted_by_key(|key| key.time),
+            Emitter::Interpolated(..) => true,
+        };
+        receiver_sorted
+            && emitter_sorted
+            && self.surfaces.iter().all(|surface| match surface {
+                SurfaceVariant::Triangle(Surface::Keyframes(keyframes, ..)) => {
+                    keyframes.is_sorted_by_key(|key| key.time)
+                }
+                SurfaceVariant::Quad(Surface::Keyframes(keyframes, ..)) => {
+                    keyframes.is_sorted_by_key(|key| key.time)
+                }
+                _ => true,
+            })
+    }
+
+    /// Check whether the emitter's keyframes fit into the scene's loop.
+    /// Like all other objects, the emitter is interpolated at the time within the loop,
+    /// so keyframes after the loop duration would never be reached.
+    /// The last keyframe may lie exactly at the loop duration to describe the movement up to the loop's end,
+    /// or before it, in which case the emitter stays at its position until the loop restarts.
+    /// Emitters without keyframes and non-looping scenes are always aligned.
+    pub fn validate_emitter_loop_alignment(&self) -> bool {
+        match (&self.emitter, self.loop_duration) {
+            (Emitter::Keyframes(keyframes, _), Some(loop_duration)) => keyframes
+                .last()
+                .is_none_or(|keyframe| keyframe.time.samples() <= loop_duration.samples()),
+            _ => true,
+        }
+    }
+
+    /// Check whether this scene can be simulated.
+    /// Every keyframe list needs to contain at least one keyframe and be sorted by time,
+    /// all loop durations need to be positive
+    /// and the emitter's keyframes need to fit into the loop, see `validate_emitter_loop_alignment`.
+    ///
+    /// # Errors
+    ///
+    /// * If any of the above conditions isn't met.
+    pub fn validate(&self) -> Result<(), DemoError> {
+        let mut loop_durations = [self.loop_duration, self.receiver.loop_duration()]
+            .into_iter()
+            .chain(self.surfaces.iter().map(SurfaceVariant::loop_duration));
+        if loop_durations.any(|loop_duration| loop_duration == Some(SampleDuration(0))) {
+            return Err(SceneBuildError::ZeroLoopDuration.into());
+        }
+        match &self.receiver {
+            Receiver::Keyframes(keyframes, ..) | Receiver::EllipsoidKeyframes(keyframes, ..) => {
+                validate_keyframe_times(keyframes.iter().map(|key| key.time))?;
+            }
+            Receiver::Interpolated(..) | Receiver::Ellipsoid(..) => {}
+        }
+        match &self.emitter {
+            Emitter::Keyframes(keyframes, _) => {
+                validate_keyframe_times(keyframes.iter().map(|key| key.time))?;
+                if !self.validate_emitter_loop_alignment() {
+                    return Err(SceneBuildError::EmitterBeyondLoop(
+                        keyframes.last().map_or(SampleTime::ZERO, |key| key.time),
+                        self.loop_duration.unwrap_or_default(),
+                    )
+                    .into());
+                }
+            }
+            Emitter::Interpolated(..) => {}
+        }
+        for surface in &self.surfaces {
+            match surface {
+                SurfaceVariant::Triangle(Surface::Keyframes(keyframes, ..)) => {
+                    validate_keyframe_times(keyframes.iter().map(|key| key.time))?;
+                }
+                SurfaceVariant::Quad(Surface::Keyframes(keyframes, ..)) => {
+                    validate_keyframe_times(keyframes.iter().map(|key| key.time))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the material of the surface with the given index.
+    /// This needs to happen before the scene is turned into a `SceneData` object.
+    ///
+    /// # Panics
+    ///
+    /// * If there is no surface with the given index.
+    pub fn set_material(&mut self, surface_index: usize, material: Material) {
+        self.surfaces[surface_index].data_mut().material = material;
+    }
+
+    /// Replace the material of all surfaces within the given index range.
+    /// This needs to happen before the scene is turned into a `SceneData` object.
+    ///
+    /// # Panics
+    ///
+    /// * If the range exceeds the scene's surfaces.
+    pub fn set_material_range(&mut self, range: Range<usize>, material: Material) {
+        for surface in &mut self.surfaces[range] {
+            surface.data_mut().material = material;
+        }
+    }
+
+    /// Get a copy of this scene without the surface at the given index, see `without_surfaces`.
+    ///
+    /// # Panics
+    ///
+    /// * If there is no surface with the given index.
+    pub fn without_surface(&self, index: usize) -> Self {
+        self.without_surfaces(&[index])
+    }
+
+    /// Get a copy of this scene without the surfaces at the given indices.
+    /// The remaining surfaces keep their order, so their indices shift down past every removed surface.
+    /// Duplicate indices are only removed once.
+    ///
+    /// The bounds cache of the returned scene is cleared, but chunks built for this scene don't match it anymore.
+    /// Use `SceneData::create_for_scene` with the returned scene to rebuild them before simulating.
+    ///
+    /// # Panics
+    ///
+    /// * If there is no surface with one of the given indices.
+    pub fn without_surfaces(&self, indices: &[usize]) -> Self {
+        let mut removed = vec![false; self.surfaces.len()];
+        for index in indices {
+            assert!(
+                *index < self.surfaces.len(),
+                "Can't remove surface {index}, the scene only has {} surfaces!",
+                self.surfaces.len()
+            );
+            removed[*index] = true;
+        }
+        Self {
+            surfaces: self
+                .surfaces
+                .iter()
+                .zip(removed)
+                .filter(|(_surface, removed)| !removed)
+                .map(|(surface, _removed)| surface.clone())
+                .collect(),
+            receiver: self.receiver.clone(),
+            emitter: self.emitter.clone(),
+            loop_duration: self.loop_duration,
+            bounds_cache: None,
+            boundary: self.boundary,
+            emitter_active_ranges: self.emitter_active_ranges.clone(),
+            statistical_tail: self.statistical_tail,
+        }
+    }
+}
+
+/// General data about a scene, required to bounce a ray through.
+/// Contains the scene itself, its maximum boundaries and its
+/// chunk representation.
+#[allow(clippy::module_name_repetitions)]
+pub struct SceneData<C>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    pub scene: Scene,
+    pub chunks: Chunks<C>,
+    pub maximum_bounds: (nalgebra::Vector3<f64>, nalgebra::Vector3<f64>),
+    /// The time it took to build `chunks`, in nanoseconds.
+    pub chunk_build_ns: u64,
+    /// The swept bounds of each surface's keyframe pairs, indexed like the scene's surfaces,
+    /// see `SurfaceVariant::keyframe_pair_bounds`.
+    /// Intersection checks skip keyframe pairs whose bounds the ray doesn't pass through.
+    pub keyframe_bounds: Vec<Vec<(Vector3<f64>, Vector3<f64>)>>,
+    /// The normals of each static surface's fan triangles, indexed like the scene's surfaces,
+    /// see `SurfaceVariant::triangle_normals`.
+    /// Intersection checks use them instead of calculating the normals for every ray.
+    pub static_normals: Vec<Vec<TriangleNormal>>,
+    /// Snapshots of this scene that have already been built, see `cached_snapshot`.
+    pub snapshot_cache: SnapshotCache<C>,
+}
+
+/// The maximum number of snapshots a `SnapshotCache` holds.
+/// Once it is full, further snapshots are built without being cached,
+/// so simulating every sample of a long input doesn't keep all of their chunks around.
+pub const SNAPSHOT_CACHE_CAPACITY: usize = 64;
+
+/// Snapshots of a scene by the time they were taken at, shared between threads.
+/// See `SceneData::cached_snapshot`.
+pub struct SnapshotCache<C>(Mutex<HashMap<u32, Arc<SceneData<C>>>>)
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength;
+
+impl<C> Default for SnapshotCache<C>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+impl<C> SnapshotCache<C>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    /// Get the number of cached snapshots.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Check whether no snapshots are cached.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Lock the cache. Snapshots are only ever inserted whole, so a poisoned lock is still usable.
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, Arc<SceneData<C>>>> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// The result of simulating a scene for an input, see `SceneData::simulate_for_time_span`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimulationOutput {
+    /// The impulse response. If multiple impulse responses were simulated, this is empty.
+    pub ir: ImpulseResponse,
+    /// The auralized audio, with the same bit depth as the input.
+    pub audio: BitDepth,
+    /// Where the simulation spent its time.
+    pub telemetry: Telemetry,
+}
+
+impl<C> SceneData<C>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    /// Calculate the chunks and maximum bounds for a given `Scene`,
+    /// then represent it all in a single `SceneData` object.
+    /// To avoid errors, the maximum bounds are expanded by 0.1 in each direction.
+    /// The time it takes to build the chunks is recorded in `chunk_build_ns`.
+    ///
+    /// # Errors
+    ///
+    /// * If the scene isn't valid, see `Scene::validate`.
+    pub fn create_for_scene(scene: Scene) -> Result<Self, DemoError> {
+        scene.validate()?;
+        let mut maximum_bounds = scene.maximum_bounds();
+        let chunk_build_start = Instant::now();
+        let chunks = scene.chunks::<C>();
+        let chunk_build_ns = Telemetry::elapsed_ns(chunk_build_start);
+        maximum_bounds.0.add_scalar_mut(-0.1);
+        maximum_bounds.1.add_scalar_mut(0.1);
+        let keyframe_bounds = scene.keyframe_bounds();
+        let static_normals = scene.static_normals();
+        Ok(Self {
+            scene,
+            chunks,
+            maximum_bounds,
+            chunk_build_ns,
+            keyframe_bounds,
+            static_normals,
+            snapshot_cache: SnapshotCache::default(),
+        })
+    }
+
+    /// Get the swept bounds of the given surface's keyframe pairs, see `keyframe_bounds`.
+    /// If they haven't been calculated, an empty slice is returned.
+    pub fn keyframe_pair_bounds(&self, surface_index: usize) -> &[(Vector3<f64>, Vector3<f64>)] {
+        self.keyframe_bounds
+            .get(surface_index)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the normals of the given surface's fan triangles, see `static_normals`.
+    /// If they haven't been calculated or the surface isn't static, an empty slice is returned.
+    pub fn triangle_normals(&self, surface_index: usize) -> &[TriangleNormal] {
+        self.static_normals
+            .get(surface_index)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Get a static version of this `SceneData` at the given time, see `Scene::at_time`.
+    /// The snapshot's chunks are recalculated for the interpolated scene,
+    /// while the maximum bounds stay the same.
+    pub fn snapshot(&self, time: u32) -> Self {
+        let scene = self.scene.at_time(time);
+        let chunk_build_start = Instant::now();
+        let chunks = scene.chunks::<C>();
+        let chunk_build_ns = Telemetry::elapsed_ns(chunk_build_start);
+        let keyframe_bounds = scene.keyframe_bounds();
+        let static_normals = scene.static_normals();
+        Self {
+            scene,
+            chunks,
+            maximum_bounds: self.maximum_bounds,
+            chunk_build_ns,
+            keyframe_bounds,
+            static_normals,
+            snapshot_cache: SnapshotCache::default(),
+        }
+    }
+
+    /// Get the snapshot of this `SceneData` at the given time like `snapshot`,
+    /// re-using it if it has been built before, e.g. when simulating and detecting leaks at the same time.
+    /// Snapshots are cached by their exact time, since objects with their own loop duration
+    /// don't necessarily repeat with the scene's loop. Up to `SNAPSHOT_CACHE_CAPACITY` snapshots are cached.
+    pub fn cached_snapshot(&self, time: u32) -> Arc<Self> {
+        if let Some(snapshot) = self.snapshot_cache.lock().get(&time) {
+            return Arc::clone(snapshot);
+        }
+        // build outside of the lock, so other threads can take different snapshots meanwhile
+        let snapshot = Arc::new(self.snapshot(time));
+        let mut cache = self.snapshot_cache.lock();
+        if cache.len() < SNAPSHOT_CACHE_CAPACITY {
+            // another thread may have built the same snapshot meanwhile, keep the first one
+            return Arc::clone(cache.entry(time).or_insert(snapshot));
+        }
+        snapshot
+    }
+
+    /// Simulate the given number of rays in this `Scene` for each sample in the given input,
+    /// then apply the impulse response.
+    /// The resulting audio has the same bit depth as the input. If it exceeds that bit depth's range,
+    /// it is clipped and a warning is printed.
+    /// The output's telemetry is accumulated over all simulated impulse responses, see `Telemetry`.
+    /// see `simulate_for_time_span_internal` for details
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_for_time_span(
+        &self,
+        input_data: &BitDepth,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        single_ir: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+    ) -> Result<SimulationOutput, DemoError> {
+        let (buffer, ir, telemetry) = self.simulate_for_time_span_raw(
+            input_data,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            scaling_factor,
+            do_snapshot_method,
+            single_ir,
+            post_processing,
+            config,
+        )?;
+        let (audio, had_to_clip) = audio_io::to_bit_depth_of(&buffer, input_data);
+        if had_to_clip {
+            println!("WARNING: Part of the resulting audio had to be clipped because it exceeded the file format's range. Please try a bigger scaling factor.");
+        }
+        Ok(SimulationOutput {
+            ir,
+            audio,
+            telemetry,
+        })
+    }
+
+    /// Simulate like `simulate_for_time_span`, but return the resulting samples as `f64`
+    /// before they are cast back to the input's bit depth, so no precision is lost and nothing is clipped.
+    /// The samples are in the input's value range, see `audio_io::to_normalised_f32` for normalising them.
+    /// The simulation runs with `config`'s number of threads, see `SimulationConfig::install`.
+    /// Where the simulation spent its time is returned alongside the impulse response.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_for_time_span_raw(
+        &self,
+        input_data: &BitDepth,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        single_ir: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+    ) -> Result<(Vec<f64>, ImpulseResponse, Telemetry), DemoError> {
+        let (result, ir, telemetry, _) = self.simulate_for_time_span_raw_internal(
+            input_data,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            scaling_factor,
+            do_snapshot_method,
+            single_ir,
+            post_processing,
+            config,
+            false,
+        )?;
+        Ok((result, ir, telemetry))
+    }
+
+    /// Simulate like `simulate_for_time_span_raw`, but also collect every ray launched for
+    /// the first input sample that leaks out of the scene into a `LeakReport`,
+    /// see `simulate_at_time_detecting_leaks`.
+    /// The leaks are collected while simulating, so this doesn't launch any additional rays.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_for_time_span_detecting_leaks(
+        &self,
+        input_data: &BitDepth,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        single_ir: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+    ) -> Result<(Vec<f64>, ImpulseResponse, Telemetry, LeakReport), DemoError> {
+        let (result, ir, telemetry, leaked_rays) = self.simulate_for_time_span_raw_internal(
+            input_data,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            scaling_factor,
+            do_snapshot_method,
+            single_ir,
+            post_processing,
+            config,
+            true,
+        )?;
+        let leak_report = LeakReport {
+            number_of_rays,
+            leaked_rays,
+        };
+        Ok((result, ir, telemetry, leak_report))
+    }
+
+    /// Internal logic for `simulate_for_time_span_raw` and `simulate_for_time_span_detecting_leaks`.
+    /// If `detect_leaks` is set, the rays of the first input sample leaking out of the scene are returned as well.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_time_span_raw_internal(
+        &self,
+        input_data: &BitDepth,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        single_ir: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> Result<(Vec<f64>, ImpulseResponse, Telemetry, Vec<LeakedRay>), DemoError> {
+        let time_start = Instant::now();
+        let mut ir: ImpulseResponse = vec![];
+        let (result, mut telemetry, leaked_rays) = config.install(|| match input_data {
+            BitDepth::Eight(data) => self.simulate_for_time_span_internal(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                single_ir,
+                post_processing,
+                config,
+                &mut ir,
+                detect_leaks,
+            ),
+            BitDepth::Sixteen(data) => self.simulate_for_time_span_internal(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                single_ir,
+                post_processing,
+                config,
+                &mut ir,
+                detect_leaks,
+            ),
+            BitDepth::TwentyFour(data) => self.simulate_for_time_span_internal(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                single_ir,
+                post_processing,
+                config,
+                &mut ir,
+                detect_leaks,
+            ),
+            BitDepth::ThirtyTwoFloat(data) => self.simulate_for_time_span_internal(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                single_ir,
+                post_processing,
+                config,
+                &mut ir,
+                detect_leaks,
+            ),
+            BitDepth::Empty => (vec![], Telemetry::default(), vec![]),
+        })?;
+        telemetry.chunk_build_ns = telemetry.chunk_build_ns.saturating_add(self.chunk_build_ns);
+        telemetry.total_ns = self
+            .chunk_build_ns
+            .saturating_add(Telemetry::elapsed_ns(time_start));
+        Ok((result, ir, telemetry, leaked_rays))
     }
 
     /// Simulate the scene's impulse response for each data point,
     /// then apply it to the relevant data point and collect the full result afterwards.
     /// Processing is done in chunks.
+    /// Regardless of `single_ir`, the result has the standard convolution length of `data.len()`
+    /// plus the length of the longest impulse response minus 1, but is never shorter than `data`.
+    /// Afterwards, the unprocessed input is mixed in according to `config`'s dry/wet mix and input gain,
+    /// before anything is cast back to the input's bit depth.
+    /// If `detect_leaks` is set, the rays of the first input sample leaking out of the scene are returned as well.
     #[allow(clippy::too_many_arguments, clippy::option_if_let_else)]
     fn simulate_for_time_span_internal<T: Num + NumCast + Clone + Copy + Sync + Send + Bounded>(
         &self,
-        data: &[T],
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        single_ir: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        ir: &mut ImpulseResponse,
+        detect_leaks: bool,
+    ) -> (Vec<f64>, Telemetry, Vec<LeakedRay>) {
+        let (mut buffer, max_ir_len, telemetry, leaked_rays) = if single_ir {
+            self.simulate_for_time_span_single_ir(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                ir,
+                detect_leaks,
+            )
+        } else {
+            self.simulate_for_time_span_multiple_irs(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks,
+            )
+        };
+        buffer.resize(
+            (data.len() + max_ir_len).saturating_sub(1).max(data.len()),
+            0f64,
+        );
+        impulse_response::mix_dry_signal(&mut buffer, data, config.dry_wet, config.input_gain_db);
+        (buffer, telemetry, leaked_rays)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_time_span_single_ir<T: Num + NumCast + Bounded + Copy + Clone + Sync + Send>(
+        &self,
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        ir: &mut ImpulseResponse,
+        detect_leaks: bool,
+    ) -> (Vec<f64>, usize, Telemetry, Vec<LeakedRay>) {
+        // the scene doesn't change, so the emitter's schedule only decides which samples it emits
+        let active_data: Vec<T> = if self.scene.emitter_active_ranges.is_some() {
+            data.iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    if self
+                        .scene
+                        .is_emitter_active(self.scene.input_sample_time(idx, config.time_offset))
+                    {
+                        *value
+                    } else {
+                        T::zero()
+                    }
+                })
+                .collect()
+        } else {
+            data.to_vec()
+        };
+        let start_time = self.scene.input_sample_time(0, config.time_offset);
+        let (mut impulse_response, leaked_rays, telemetry) = self.simulate_at_time_internal(
+            start_time,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            do_snapshot_method,
+            true,
+            config,
+            detect_leaks,
+        );
+        // the impulse response is applied relative to each sample, like in `simulate_relative_at_time`
+        impulse_response.drain(..(start_time as usize).min(impulse_response.len()));
+        *ir = post_processing.apply(impulse_response);
+        let buffer = match config.ir_precision {
+            IrPrecision::F64 => {
+                impulse_response::apply_to_many_samples(ir, &active_data, scaling_factor)
+            }
+            IrPrecision::F32 => f32::into_f64_vec(impulse_response::apply_to_many_samples(
+                &f32::from_impulse_response(ir.clone()),
+                &active_data,
+                scaling_factor,
+            )),
+        };
+        (buffer, ir.len(), telemetry, leaked_rays)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_time_span_multiple_irs<
+        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
+    >(
+        &self,
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (Vec<f64>, usize, Telemetry, Vec<LeakedRay>) {
+        match config.ir_precision {
+            IrPrecision::F64 => merge_task_buffers(self.simulate_task_buffers::<T, f64>(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks,
+            )),
+            IrPrecision::F32 => merge_task_buffers(self.simulate_task_buffers::<T, f32>(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks,
+            )),
+        }
+    }
+
+    /// Simulate and apply the impulse responses for all data points in parallel tasks,
+    /// applying them in the sample type `F`, see `SimulationConfig::ir_precision`.
+    /// Returns each task's output buffer, the length of its longest impulse response, its telemetry
+    /// and, if `detect_leaks` is set, the rays of the first input sample leaking out of the scene.
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::option_if_let_else)]
+    fn simulate_task_buffers<
+        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
+        F: IrSample,
+    >(
+        &self,
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> Vec<(Vec<F>, usize, Telemetry, Vec<LeakedRay>)> {
+        match self.scene.loop_duration {
+            // with at most a single loop, every group would only contain a single sample
+            Some(duration) if data.len() > duration.samples() as usize => self
+                .simulate_for_time_span_looping(
+                    data,
+                    number_of_rays,
+                    velocity,
+                    sample_rate,
+                    scaling_factor,
+                    do_snapshot_method,
+                    post_processing,
+                    config,
+                    duration,
+                    detect_leaks,
+                ),
+            _ => self.simulate_for_time_span_non_looping(
+                data,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks,
+            ),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_time_span_non_looping<
+        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
+        F: IrSample,
+    >(
+        &self,
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> Vec<(Vec<F>, usize, Telemetry, Vec<LeakedRay>)> {
+        let samples: Vec<(usize, T)> = data.iter().copied().enumerate().collect();
+        let simulate_chunk = |chunk: &[(usize, T)]| {
+            self.simulate_for_chunk(
+                data.len(),
+                chunk,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks,
+            )
+        };
+        if config.is_serial() {
+            samples
+                .chunks(config.samples_per_task)
+                .map(simulate_chunk)
+                .collect()
+        } else {
+            samples
+                .par_chunks(config.samples_per_task)
+                .map(simulate_chunk)
+                .collect()
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_time_span_looping<
+        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
+        F: IrSample,
+    >(
+        &self,
+        data: &[T],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        loop_duration: SampleDuration,
+        detect_leaks: bool,
+    ) -> Vec<(Vec<F>, usize, Telemetry, Vec<LeakedRay>)> {
+        // a BTreeMap keeps the groups sorted by their time within the loop,
+        // so they're always processed and accumulated in the same order.
+        // Each sample keeps its actual index, which determines its placement in the output,
+        // so groups may contain a different number of loop iterations.
+        let mut groups: BTreeMap<u32, Vec<(usize, T)>> = BTreeMap::new();
+        for (idx, val) in data.iter().enumerate() {
+            groups
+                .entry(self.scene.input_sample_time(idx, config.time_offset))
+                .or_default()
+                .push((idx, *val));
+        }
+        let groups: Vec<_> = groups.iter().collect();
+        let simulate_chunk = |chunk: &[(&u32, &Vec<(usize, T)>)]| {
+            self.simulate_looping_for_chunk(
+                data.len(),
+                chunk,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                scaling_factor,
+                do_snapshot_method,
+                post_processing,
+                config,
+                loop_duration,
+                detect_leaks,
+            )
+        };
+        if config.is_serial() {
+            groups
+                .chunks(config.samples_per_task)
+                .map(simulate_chunk)
+                .collect()
+        } else {
+            groups
+                .par_chunks(config.samples_per_task)
+                .map(simulate_chunk)
+                .collect()
+        }
+    }
+
+    /// Internal logic for `simulate_for_time_span_internal`.
+    /// Returns the chunk's output buffer, the length of its longest impulse response,
+    /// the chunk's accumulated telemetry and the leaking rays of the first input sample, see `simulate_task_buffers`.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_for_chunk<T: Num + NumCast + Clone + Copy + Sync + Send, F: IrSample>(
+        &self,
+        data_len: usize,
+        chunk: &[(usize, T)],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (Vec<F>, usize, Telemetry, Vec<LeakedRay>) {
+        let mut telemetry = Telemetry::default();
+        let mut leaked_rays = vec![];
+        let impulse_responses: Vec<Vec<F>> = chunk
+            .iter()
+            .map(|(idx, _value)| {
+                let (impulse_response, ir_leaked_rays, ir_telemetry) = self
+                    .simulate_relative_at_time(
+                        self.scene.input_sample_time(*idx, config.time_offset),
+                        number_of_rays,
+                        velocity,
+                        sample_rate,
+                        do_snapshot_method,
+                        post_processing,
+                        config,
+                        detect_leaks && *idx == 0,
+                    );
+                telemetry += ir_telemetry;
+                leaked_rays.extend(ir_leaked_rays);
+                F::from_impulse_response(impulse_response)
+            })
+            .collect();
+        let max_ir_len = impulse_responses.iter().map(Vec::len).max().unwrap_or(0);
+        // chunks hold consecutive samples, so the first one's index is the offset of all of them
+        let start_index = chunk.first().map_or(0, |(idx, _value)| *idx);
+        let values: Vec<T> = chunk.iter().map(|(_idx, value)| *value).collect();
+        let mut buffer = impulse_response::apply_to_data(
+            &impulse_responses,
+            &values,
+            start_index,
+            scaling_factor,
+        );
+        if buffer.len() < data_len {
+            buffer.resize(data_len, F::zero());
+        }
+        (buffer, max_ir_len, telemetry, leaked_rays)
+    }
+
+    /// Internal logic for `simulate_for_time_span_internal_looping`.
+    /// Returns the chunk's output buffer, the length of its longest impulse response,
+    /// the chunk's accumulated telemetry and the leaking rays of the first input sample, see `simulate_task_buffers`.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_looping_for_chunk<T: Num + NumCast + Clone + Copy + Sync + Send, F: IrSample>(
+        &self,
+        data_len: usize,
+        chunk: &[(&u32, &Vec<(usize, T)>)],
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        scaling_factor: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        loop_duration: SampleDuration,
+        detect_leaks: bool,
+    ) -> (Vec<F>, usize, Telemetry, Vec<LeakedRay>) {
+        let mut buffer: Vec<F> = vec![F::zero(); data_len];
+        let mut max_ir_len = 0;
+        let mut telemetry = Telemetry::default();
+        let mut leaked_rays = vec![];
+        for (idx, value) in chunk {
+            // samples are added to their groups in order, so the first sample leads its group
+            let is_first_sample = value
+                .first()
+                .is_some_and(|(sample_idx, _)| *sample_idx == 0);
+            let (impulse_response, ir_leaked_rays, ir_telemetry) = self.simulate_relative_at_time(
+                **idx,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                do_snapshot_method,
+                post_processing,
+                config,
+                detect_leaks && is_first_sample,
+            );
+            max_ir_len = max_ir_len.max(impulse_response.len());
+            telemetry += ir_telemetry;
+            leaked_rays.extend(ir_leaked_rays);
+            let buffer_to_add = impulse_response::apply_looped_to_many_samples(
+                &F::from_impulse_response(impulse_response),
+                value,
+                scaling_factor,
+                loop_duration.samples() as usize,
+            );
+            if buffer.len() < buffer_to_add.len() {
+                buffer.resize(buffer_to_add.len(), F::zero());
+            }
+            buffer
+                .iter_mut()
+                .zip(&buffer_to_add)
+                .for_each(|(val, to_add)| *val += *to_add);
+        }
+        (buffer, max_ir_len, telemetry, leaked_rays)
+    }
+
+    /// Simulate the impulse response at the given time like `simulate_at_time`,
+    /// but starting at `time` rather than 0 (i.e. without the leading zeros before the rays launch),
+    /// then apply `post_processing` to it.
+    /// Where the simulation spent its time is returned alongside it.
+    /// If `detect_leaks` is set, the rays leaking out of the scene are returned as well.
+    /// If the emitter doesn't emit at `time`, nothing is simulated and the impulse response is empty.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_relative_at_time(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        post_processing: &PostProcessing,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (ImpulseResponse, Vec<LeakedRay>, Telemetry) {
+        if !self.scene.is_emitter_active(time) {
+            return (vec![], vec![], Telemetry::default());
+        }
+        let (mut impulse_response, leaked_rays, telemetry) = self.simulate_at_time_internal(
+            time,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            do_snapshot_method,
+            false,
+            config,
+            detect_leaks,
+        );
+        impulse_response.drain(..(time as usize).min(impulse_response.len()));
+        (
+            post_processing.apply(impulse_response),
+            leaked_rays,
+            telemetry,
+        )
+    }
+
+    /// Simulate the given number of rays at the given time in this `Scene`,
+    /// then collect all the impulse responses.
+    /// If `do_snapshot_method` is true, a static version of the scene at `time` is taken and simulation is run through that instead.
+    /// The simulation runs with `config`'s number of threads, see `SimulationConfig::install`.
+    /// If the emitter doesn't emit at `time` (see `Scene::is_emitter_active`), the impulse response is empty.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_at_time(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        parallel: bool,
+        config: &SimulationConfig,
+    ) -> Result<Vec<f64>, DemoError> {
+        let (impulse_response, _) = self.simulate_at_time_with_telemetry(
+            time,
+            number_of_rays,
+            velocity,
+            sample_rate,
+            do_snapshot_method,
+            parallel,
+            config,
+        )?;
+        Ok(impulse_response)
+    }
+
+    /// Simulate the given number of rays at the given time like `simulate_at_time`,
+    /// but add each receiver hit to an impulse response buffer right away instead of collecting all hits first.
+    /// Rays are launched in parallel unless `config` is serial, with each parallel task accumulating
+    /// its own buffer. These are merged at the end, so the memory peak grows with the impulse response's length
+    /// rather than the number of hits.
+    /// Capturing the decay (see `SimulationConfig::capture_decay_db`) needs all hits to find the cutoff,
+    /// so with it set, this falls back to `simulate_at_time`.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    pub fn simulate_at_time_streaming(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        config: &SimulationConfig,
+    ) -> Result<ImpulseResponse, DemoError> {
+        if !self.scene.is_emitter_active(time) {
+            return Ok(vec![]);
+        }
+        if config.capture_decay_db.is_some() {
+            return self.simulate_at_time(
+                time,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                do_snapshot_method,
+                true,
+                config,
+            );
+        }
+        config.install(|| {
+            let snapshot_scene_data;
+            let scene_data = if do_snapshot_method {
+                snapshot_scene_data = self.cached_snapshot(time);
+                &snapshot_scene_data
+            } else {
+                self
+            };
+            let accumulate = |(mut buffer, mut received_energy): (ImpulseResponse, f64),
+                              ray_index| {
+                let (results, _leak) = scene_data.launch_ray(
+                    time,
+                    (ray_index, number_of_rays),
+                    velocity,
+                    sample_rate,
+                    config,
+                    false,
+                );
+                for (energy, hit_time) in results {
+                    impulse_response::add_to_impulse_response(&mut buffer, energy, hit_time);
+                    received_energy += energy;
+                }
+                (buffer, received_energy)
+            };
+            // the impulse response always covers at least time 0
+            let empty = || (vec![0f64], 0f64);
+            let (buffer, received_energy) = if config.is_serial() {
+                (0..number_of_rays).fold(empty(), accumulate)
+            } else {
+                (0..number_of_rays)
+                    .into_par_iter()
+                    .fold(empty, accumulate)
+                    .reduce(empty, |(first, first_energy), (second, second_energy)| {
+                        (
+                            impulse_response::merge_impulse_responses(first, second),
+                            first_energy + second_energy,
+                        )
+                    })
+            };
+            let mut impulse_response = impulse_response::normalise_impulse_response(
+                buffer,
+                received_energy,
+                number_of_rays,
+                config.normalisation_mode,
+            );
+            self.add_statistical_tail(&mut impulse_response, time, sample_rate, config);
+            impulse_response
+        })
+    }
+
+    /// Simulate the given number of rays at the given time like `simulate_at_time`,
+    /// but also return where the simulation spent its time.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_at_time_with_telemetry(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        parallel: bool,
+        config: &SimulationConfig,
+    ) -> Result<(ImpulseResponse, Telemetry), DemoError> {
+        if !self.scene.is_emitter_active(time) {
+            return Ok((vec![], Telemetry::default()));
+        }
+        let time_start = Instant::now();
+        let (impulse_response, _, mut telemetry) = config.install(|| {
+            self.simulate_at_time_internal(
+                time,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                do_snapshot_method,
+                parallel,
+                config,
+                false,
+            )
+        })?;
+        telemetry.chunk_build_ns = telemetry.chunk_build_ns.saturating_add(self.chunk_build_ns);
+        telemetry.total_ns = self
+            .chunk_build_ns
+            .saturating_add(Telemetry::elapsed_ns(time_start));
+        Ok((impulse_response, telemetry))
+    }
+
+    /// Simulate the given number of rays at the given time like `simulate_at_time`,
+    /// but also collect every ray that leaks out of the scene into a `LeakReport`.
+    /// When running in parallel, each thread collects its leaks separately and
+    /// the results are merged at the end.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_at_time_detecting_leaks(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        parallel: bool,
+        config: &SimulationConfig,
+    ) -> Result<(ImpulseResponse, LeakReport), DemoError> {
+        if !self.scene.is_emitter_active(time) {
+            return Ok((
+                vec![],
+                LeakReport {
+                    number_of_rays,
+                    leaked_rays: vec![],
+                },
+            ));
+        }
+        let (impulse_response, leaked_rays, _) = config.install(|| {
+            self.simulate_at_time_internal(
+                time,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                do_snapshot_method,
+                parallel,
+                config,
+                true,
+            )
+        })?;
+        Ok((
+            impulse_response,
+            LeakReport {
+                number_of_rays,
+                leaked_rays,
+            },
+        ))
+    }
+
+    /// Simulate the given number of rays at the given time like `simulate_at_time`,
+    /// but if `config.record_echogram` is set, also record every ray's surface bounces and receiver hits,
+    /// see `echogram::echogram_to_csv` for writing them.
+    /// Each ray's records stay in order, which is only guaranteed when the rays are launched serially,
+    /// so nothing is recorded if `parallel` is set and `config` isn't serial.
+    /// While recording, every receiver hit is captured, regardless of `config.capture_decay_db`.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_at_time_with_echogram(
+        &self,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        do_snapshot_method: bool,
+        parallel: bool,
+        config: &SimulationConfig,
+    ) -> Result<(ImpulseResponse, Option<Vec<RayBounceRecord>>), DemoError> {
+        if !config.record_echogram || (parallel && !config.is_serial()) {
+            let impulse_response = self.simulate_at_time(
+                time,
+                number_of_rays,
+                velocity,
+                sample_rate,
+                do_snapshot_method,
+                parallel,
+                config,
+            )?;
+            return Ok((impulse_response, None));
+        }
+        if !self.scene.is_emitter_active(time) {
+            return Ok((vec![], Some(vec![])));
+        }
+        let snapshot_scene_data;
+        let scene_data = if do_snapshot_method {
+            snapshot_scene_data = self.cached_snapshot(time);
+            &snapshot_scene_data
+        } else {
+            self
+        };
+        let mut results = vec![];
+        let mut records = vec![];
+        for ray_index in 0..number_of_rays {
+            let (ray_results, ray_records) = scene_data.launch_ray_recording_echogram(
+                time,
+                (ray_index, number_of_rays),
+                velocity,
+                sample_rate,
+                config,
+            );
+            results.extend(ray_results);
+            records.extend(ray_records);
+        }
+        let mut impulse_response =
+            to_impulse_response(results, number_of_rays, config.normalisation_mode);
+        self.add_statistical_tail(&mut impulse_response, time, sample_rate, config);
+        Ok((impulse_response, Some(records)))
+    }
+
+    /// Simulate the given number of rays at the given time with the receiver placed at each point of the grid,
+    /// e.g. to map the sound field for room mode analysis.
+    /// The receiver keeps its shape and whether it's occluding, but stays static at each point, see `Receiver::with_center`.
+    /// Since the receiver is part of the chunks, they are rebuilt for each point.
+    /// The points are simulated in parallel with `config`'s number of threads (see `SimulationConfig::install`),
+    /// while each point's rays are launched serially.
+    /// If the emitter doesn't emit at `time` (see `Scene::is_emitter_active`), no point receives any energy.
+    ///
+    /// # Errors
+    ///
+    /// * If the scene with the receiver at one of the points isn't valid, see `Scene::validate`.
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sweep_receiver_grid(
+        &self,
+        grid: &ReceiverGrid,
+        time: u32,
+        number_of_rays: u32,
+        velocity: f64,
+        sample_rate: f64,
+        config: &SimulationConfig,
+    ) -> Result<GridResult, DemoError> {
+        let emitter_active = self.scene.is_emitter_active(time);
+        let simulate_point = |(x, y): (f64, f64)| -> Result<GridPoint, DemoError> {
+            let mut scene = self.scene.clone();
+            scene.receiver = scene.receiver.with_center(Vector3::new(x, y, grid.z));
+            scene.bounds_cache = None;
+            let scene_data = Self::create_for_scene(scene)?;
+            let mut impulse_response = if emitter_active {
+                scene_data
+                    .simulate_at_time_internal(
+                        time,
+                        number_of_rays,
+                        velocity,
+                        sample_rate,
+                        false,
+                        false,
+                        config,
+                        false,
+                    )
+                    .0
+            } else {
+                vec![]
+            };
+            impulse_response.drain(..(time as usize).min(impulse_response.len()));
+            Ok(GridPoint {
+                x,
+                y,
+                total_energy: impulse_response.iter().sum(),
+                first_arrival: impulse_response::direct_sound_arrival(&impulse_response),
+            })
+        };
+        let points = grid.points();
+        let points = config.install(|| -> Result<Vec<GridPoint>, DemoError> {
+            if config.is_serial() {
+                points.into_iter().map(simulate_point).collect()
+            } else {
+                points.into_par_iter().map(simulate_point).collect()
+            }
+        })??;
+        Ok(GridResult {
+            grid: *grid,
+            points,
+        })
+    }
+
+    /// Simulate the given number of rays at the given time like `simulate_at_time`,
+    /// but collect a first-order ambisonic impulse response, see `to_ambisonic_impulse_response`.
+    /// `receiver_orientation` rotates the receiver's local axes into the scene's axes,
+    /// so the identity means the receiver's X axis points along the scene's X axis.
+    /// The impulse response always uses `NormalisationMode::PerRayCount` and captures
+    /// every receiver hit, regardless of `config.capture_decay_db`.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_ambisonic_at_time(
+        &self,
+        time: u32,
         number_of_rays: u32,
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-        single_ir: bool,
-        ir: &mut ImpulseResponse,
-    ) -> Vec<T> {
-        let buffer = if single_ir {
-            self.simulate_for_time_span_single_ir(
-                data,
-                number_of_rays,
-                velocity,
-                sample_rate,
-                scaling_factor,
-                do_snapshot_method,
-                ir,
-            )
-        } else {
-            self.simulate_for_time_span_multiple_irs(
-                data,
-                number_of_rays,
+        receiver_orientation: &Rotation3<f64>,
+        parallel: bool,
+        config: &SimulationConfig,
+    ) -> Result<AmbisonicImpulseResponse, DemoError> {
+        if !self.scene.is_emitter_active(time) {
+            return Ok(to_ambisonic_impulse_response(vec![], number_of_rays));
+        }
+        let launch = |ray_index| {
+            self.launch_ray_with_directions(
+                time,
+                (ray_index, number_of_rays),
                 velocity,
                 sample_rate,
-                scaling_factor,
-                do_snapshot_method,
+                receiver_orientation,
+                config,
             )
         };
-        let mut had_to_clip = false;
-        buffer
-            .iter()
-            .map(|val| {
-                // clipping in case we exceed T's range
-                // shouldn't be necessary if scaling_factor does its job
-                num::cast::<f64, T>(*val).unwrap_or_else(|| {
-                    if !had_to_clip {
-                        had_to_clip = true;
-                        println!("WARNING: Part of the resulting audio had to be clipped because it exceeded the file format's range. Please try a bigger scaling factor.");
-                    }
-                    if *val > 0f64 {
-                        T::max_value()
-                    } else {
-                        T::min_value()
-                    }
-                })
-            })
-            .collect()
+        let results: Vec<(f64, u32, Vector3<f64>)> = config.install(|| {
+            if parallel && !config.is_serial() {
+                (0..number_of_rays)
+                    .into_par_iter()
+                    .flat_map_iter(launch)
+                    .collect()
+            } else {
+                (0..number_of_rays).flat_map(launch).collect()
+            }
+        })?;
+        Ok(to_ambisonic_impulse_response(results, number_of_rays))
     }
 
-    fn simulate_for_time_span_single_ir<T: Num + NumCast + Bounded + Copy + Clone + Sync + Send>(
+    /// Estimate the output and cost of simulating `number_of_impulse_responses` impulse responses
+    /// with `number_of_rays` rays each, before running the full simulation.
+    /// A pilot batch of `estimate::PILOT_RAYS` rays is traced at a few times spread across the simulated
+    /// time span (see `estimate::pilot_times`) through the same code path as the full simulation,
+    /// including snapshots, looping and `config`'s threads, and its timing is extrapolated.
+    ///
+    /// # Errors
+    ///
+    /// * If `config`'s thread pool can't be built, see `SimulationConfig::install`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn estimate(
         &self,
-        data: &[T],
         number_of_rays: u32,
+        number_of_impulse_responses: u32,
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
         do_snapshot_method: bool,
-        ir: &mut ImpulseResponse,
-    ) -> Vec<f64> {
-        *ir = self.simulate_at_time(
-            0,
-            number_of_rays,
-            velocity,
-            sample_rate,
-            do_snapshot_method,
-            true,
-        );
-        impulse_response::apply_to_many_samples(ir, data, scaling_factor)
+        config: &SimulationConfig,
+    ) -> Result<SimulationEstimate, DemoError> {
+        let times = estimate::pilot_times(number_of_impulse_responses, self.scene.loop_duration);
+        let rays_per_time = estimate::PILOT_RAYS / times.len() as u32;
+        let time_start = Instant::now();
+        let results = config.install(|| {
+            let mut results = vec![];
+            for time in &times {
+                let mut scene_data = self;
+                let interp_scene_data;
+                if do_snapshot_method {
+                    interp_scene_data = self.cached_snapshot(*time);
+                    scene_data = &interp_scene_data;
+                }
+                let launch = |ray_index| {
+                    let (hits, statistics) = scene_data.launch_ray_with_statistics(
+                        *time,
+                        (ray_index, rays_per_time),
+                        velocity,
+                        sample_rate,
+                        config,
+                    );
+                    (*time, hits, statistics)
+                };
+                if config.is_serial() {
+                    results.extend((0..rays_per_time).map(launch));
+                } else {
+                    results.par_extend((0..rays_per_time).into_par_iter().map(launch));
+                }
+            }
+            results
+        })?;
+        Ok(SimulationEstimate::from_pilot_results(
+            &results,
+            time_start.elapsed(),
+            <u64 as From<u32>>::from(number_of_rays)
+                * <u64 as From<u32>>::from(number_of_impulse_responses),
+        ))
     }
 
-    #[allow(clippy::too_many_arguments)]
-    #[allow(clippy::option_if_let_else)]
-    fn simulate_for_time_span_multiple_irs<
-        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
-    >(
+    /// Add the scene's statistical tail (if it has one) to the impulse response simulated at the given time,
+    /// see `Scene::statistical_tail`. The noise is drawn from `config.tail_rng`.
+    fn add_statistical_tail(
         &self,
-        data: &[T],
-        number_of_rays: u32,
-        velocity: f64,
+        impulse_response: &mut ImpulseResponse,
+        time: u32,
         sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-    ) -> Vec<f64> {
-        let buffers: Vec<Vec<f64>> = match self.scene.loop_duration {
-            Some(duration) => self.simulate_for_time_span_looping(
-                data,
-                number_of_rays,
-                velocity,
-                sample_rate,
-                scaling_factor,
-                do_snapshot_method,
-                duration,
-            ),
-            None => self.simulate_for_time_span_non_looping(
-                data,
-                number_of_rays,
-                velocity,
+        config: &SimulationConfig,
+    ) {
+        if let Some(tail) = &self.scene.statistical_tail {
+            impulse_response::add_statistical_tail(
+                impulse_response,
+                tail,
+                time as usize,
                 sample_rate,
-                scaling_factor,
-                do_snapshot_method,
-            ),
-        };
-        let max_len = buffers.iter().max_by_key(|vec| vec.len()).unwrap().len();
-        let mut buffer = vec![0f64; max_len];
-        for buffer_to_add in &buffers {
-            buffer
-                .iter_mut()
-                .zip(buffer_to_add)
-                .for_each(|(val, to_add)| *val += *to_add);
+                &mut config.tail_rng(time),
+            );
         }
-        buffer
     }
 
+    /// Internal logic for `simulate_at_time` and `simulate_at_time_detecting_leaks`.
+    /// Rays are only launched in parallel if `parallel` is set and `config` doesn't ask for a serial run.
+    /// If `config.capture_decay_db` is set, rays are launched in batches, and once the accumulated results
+    /// have decayed far enough, receiver hits after that point are no longer accepted.
+    /// The returned telemetry covers building the snapshot's chunks, launching the rays and
+    /// constructing the impulse response, but leaves `total_ns` to the caller.
     #[allow(clippy::too_many_arguments)]
-    fn simulate_for_time_span_non_looping<
-        T: Num + NumCast + Bounded + Copy + Clone + Sync + Send,
-    >(
+    fn simulate_at_time_internal(
         &self,
-        data: &[T],
+        time: u32,
         number_of_rays: u32,
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
         do_snapshot_method: bool,
-    ) -> Vec<Vec<f64>> {
-        data.iter()
-            .enumerate()
-            .map(|(idx, val)| (idx, *val))
-            .collect::<Vec<(usize, T)>>()
-            .par_chunks(100)
-            // .chunks(100)
-            .map(|chunk| {
-                let result = self.simulate_for_chunk(
-                    data.len(),
-                    chunk,
+        parallel: bool,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (ImpulseResponse, Vec<LeakedRay>, Telemetry) {
+        let mut telemetry = Telemetry::default();
+        let mut scene_data = self;
+        let interp_scene_data;
+        if do_snapshot_method {
+            // cached snapshots don't need to be built again
+            let snapshot_start = Instant::now();
+            interp_scene_data = self.cached_snapshot(time);
+            telemetry.chunk_build_ns = Telemetry::elapsed_ns(snapshot_start);
+            scene_data = &interp_scene_data;
+        }
+
+        let ray_launch_start = Instant::now();
+        let (rt_results, leaked_rays) = config.capture_decay_db.map_or_else(
+            || {
+                scene_data.launch_rays(
+                    0..number_of_rays,
                     number_of_rays,
+                    time,
                     velocity,
                     sample_rate,
-                    scaling_factor,
-                    do_snapshot_method,
-                );
-                result
-            })
-            .collect()
+                    parallel,
+                    config,
+                    detect_leaks,
+                )
+            },
+            |decay_db| {
+                scene_data.launch_rays_capturing_decay(
+                    decay_db,
+                    number_of_rays,
+                    time,
+                    velocity,
+                    sample_rate,
+                    parallel,
+                    config,
+                    detect_leaks,
+                )
+            },
+        );
+        telemetry.ray_launch_ns = Telemetry::elapsed_ns(ray_launch_start);
+        let ir_construction_start = Instant::now();
+        let mut impulse_response =
+            to_impulse_response(rt_results, number_of_rays, config.normalisation_mode);
+        self.add_statistical_tail(&mut impulse_response, time, sample_rate, config);
+        telemetry.ir_construction_ns = Telemetry::elapsed_ns(ir_construction_start);
+        (impulse_response, leaked_rays, telemetry)
     }
 
+    /// Launch the rays with the given indices out of `number_of_rays` rays into this `Scene` at the given time,
+    /// and return all their results.
+    /// If `detect_leaks` is set, all rays leaking out of the scene are returned as well.
     #[allow(clippy::too_many_arguments)]
-    fn simulate_for_time_span_looping<T: Num + NumCast + Bounded + Copy + Clone + Sync + Send>(
+    fn launch_rays(
         &self,
-        data: &[T],
+        ray_indices: Range<u32>,
         number_of_rays: u32,
+        time: u32,
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-        loop_duration: u32,
-    ) -> Vec<Vec<f64>> {
-        data.iter()
-            .enumerate()
-            .map(|(idx, val)| (idx as u32 % loop_duration, (idx, *val)))
-            // slight hack bc group_by only cares for consecutive elements with the same key
-            .into_group_map()
-            .iter()
-            .collect::<Vec<_>>()
-            .par_chunks(100)
-            // .chunks(100)
-            .map(|chunk| {
-                let result = self.simulate_looping_for_chunk(
-                    data.len(),
-                    chunk,
-                    number_of_rays,
+        parallel: bool,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (Vec<(f64, u32)>, Vec<LeakedRay>) {
+        if parallel && !config.is_serial() {
+            ray_indices
+                .into_par_iter()
+                .fold(
+                    || (vec![], vec![]),
+                    |(mut results, mut leaks), ray_index| {
+                        let (ray_results, leak) = self.launch_ray(
+                            time,
+                            (ray_index, number_of_rays),
+                            velocity,
+                            sample_rate,
+                            config,
+                            detect_leaks,
+                        );
+                        results.extend(ray_results);
+                        leaks.extend(leak);
+                        (results, leaks)
+                    },
+                )
+                .reduce(
+                    || (vec![], vec![]),
+                    |(mut results, mut leaks), (other_results, other_leaks)| {
+                        results.extend(other_results);
+                        leaks.extend(other_leaks);
+                        (results, leaks)
+                    },
+                )
+        } else {
+            let mut results: Vec<(f64, u32)> = vec![];
+            let mut leaks: Vec<LeakedRay> = vec![];
+            for ray_index in ray_indices {
+                let (ray_results, leak) = self.launch_ray(
+                    time,
+                    (ray_index, number_of_rays),
                     velocity,
                     sample_rate,
-                    scaling_factor,
-                    do_snapshot_method,
-                    loop_duration,
+                    config,
+                    detect_leaks,
                 );
-                result
-            })
-            .collect()
+                results.extend(ray_results);
+                leaks.extend(leak);
+            }
+            (results, leaks)
+        }
     }
 
-    /// Internal logic for `simulate_for_time_span_internal`
+    /// Launch `number_of_rays` rays like `launch_rays`, but in batches of `DECAY_CHECK_BATCH_SIZE`.
+    /// The received energy is accumulated in a histogram over time, and after each batch,
+    /// it is checked for having decayed by `decay_db` dB, see `impulse_response::decay_cutoff_time`.
+    /// Later batches can move the cutoff time, so it's re-evaluated until two batches in a row agree on it.
+    /// From then on, the cutoff time is kept and later batches' hits after it are discarded right away.
+    /// In the end, all receiver hits after the last cutoff time are discarded.
     #[allow(clippy::too_many_arguments)]
-    fn simulate_for_chunk<T: Num + NumCast + Clone + Copy + Sync + Send>(
+    fn launch_rays_capturing_decay(
         &self,
-        data_len: usize,
-        chunk: &[(usize, T)],
+        decay_db: f64,
         number_of_rays: u32,
+        time: u32,
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-    ) -> Vec<f64> {
-        let mut buffer: Vec<f64> = vec![0f64; data_len];
-        for (idx, value) in chunk {
-            let impulse_response = self.simulate_at_time(
-                *idx as u32,
+        parallel: bool,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (Vec<(f64, u32)>, Vec<LeakedRay>) {
+        let mut results: Vec<(f64, u32)> = vec![];
+        let mut leaks: Vec<LeakedRay> = vec![];
+        let mut energy_histogram: ImpulseResponse = vec![];
+        let mut cutoff_time: Option<u32> = None;
+        let mut is_cutoff_stable = false;
+        let mut launched_rays = 0;
+        while launched_rays < number_of_rays {
+            let batch_size = DECAY_CHECK_BATCH_SIZE.min(number_of_rays - launched_rays);
+            let (batch_results, batch_leaks) = self.launch_rays(
+                launched_rays..launched_rays + batch_size,
                 number_of_rays,
+                time,
                 velocity,
                 sample_rate,
-                do_snapshot_method,
-                false,
+                parallel,
+                config,
+                detect_leaks,
             );
-            let buffer_to_add =
-                impulse_response::apply_to_sample(&impulse_response, *value, *idx, scaling_factor);
-            if buffer.len() < buffer_to_add.len() {
-                buffer.resize(buffer_to_add.len(), 0f64);
+            launched_rays += batch_size;
+            leaks.extend(batch_leaks);
+            if is_cutoff_stable {
+                results.extend(
+                    batch_results
+                        .into_iter()
+                        .filter(|result| cutoff_time.is_none_or(|cutoff| result.1 <= cutoff)),
+                );
+                continue;
             }
-            buffer
-                .iter_mut()
-                .zip(&buffer_to_add)
-                .for_each(|(val, to_add)| *val += *to_add);
+            for (energy, time) in &batch_results {
+                impulse_response::add_to_impulse_response(&mut energy_histogram, *energy, *time);
+            }
+            results.extend(batch_results);
+            let batch_cutoff_time =
+                impulse_response::decay_cutoff_time(&energy_histogram, decay_db);
+            is_cutoff_stable = batch_cutoff_time.is_some() && batch_cutoff_time == cutoff_time;
+            cutoff_time = batch_cutoff_time;
         }
-        buffer
+        if let Some(cutoff) = cutoff_time {
+            results.retain(|result| result.1 <= cutoff);
+        }
+        (results, leaks)
     }
 
-    /// Internal logic for `simulate_for_time_span_internal_looping`
-    #[allow(clippy::too_many_arguments)]
-    fn simulate_looping_for_chunk<T: Num + NumCast + Clone + Copy + Sync + Send>(
+    /// Launch a single ray into this `Scene`, and return its result.
+    /// The direction it is launched in depends on the emitter's emission type and `ray`,
+    /// the ray's index and the number of rays launched at once, see `launch_origin_and_direction`.
+    /// If `detect_leaks` is set, the ray is also returned if it leaks out of the scene.
+    fn launch_ray(
         &self,
-        data_len: usize,
-        chunk: &[(&u32, &Vec<(usize, T)>)],
-        number_of_rays: u32,
+        time: u32,
+        ray: (u32, u32),
         velocity: f64,
         sample_rate: f64,
-        scaling_factor: f64,
-        do_snapshot_method: bool,
-        loop_duration: u32,
-    ) -> Vec<f64> {
-        let mut buffer: Vec<f64> = vec![0f64; data_len];
-        for (idx, value) in chunk {
-            let impulse_response = self.simulate_at_time(
-                **idx,
-                number_of_rays,
+        config: &SimulationConfig,
+        detect_leaks: bool,
+    ) -> (Vec<(f64, u32)>, Option<LeakedRay>) {
+        let Some((launch_time, emitter_coords, direction, weight)) =
+            self.launch_origin_and_direction(time, ray, velocity / sample_rate, config)
+        else {
+            // this should not be able to happen
+            return (vec![], None);
+        };
+        let (mut results, leak) = if detect_leaks {
+            Ray::launch_detecting_leaks(
+                direction,
+                emitter_coords,
+                launch_time,
                 velocity,
                 sample_rate,
-                do_snapshot_method,
-                false,
-            );
-            let buffer_to_add = impulse_response::apply_looped_to_many_samples(
-                &impulse_response,
-                value,
-                scaling_factor,
-                loop_duration as usize,
-            );
-            if buffer.len() < buffer_to_add.len() {
-                buffer.resize(buffer_to_add.len(), 0f64);
-            }
-            buffer
-                .iter_mut()
-                .zip(&buffer_to_add)
-                .for_each(|(val, to_add)| *val += *to_add);
+                self,
+                config,
+            )
+        } else {
+            (
+                Ray::launch(
+                    direction,
+                    emitter_coords,
+                    launch_time,
+                    velocity,
+                    sample_rate,
+                    self,
+                    config,
+                ),
+                None,
+            )
+        };
+        for (energy, _time) in &mut results {
+            *energy *= weight;
         }
-        buffer
+        (results, leak)
     }
 
-    /// Simulate the given number of rays at the given time in this `Scene`,
-    /// then collect all the impulse responses.
-    /// If `do_snapshot_method` is true, a static version of the scene at `time` is taken and simulation is run through that instead.
-    pub fn simulate_at_time(
+    /// Launch a single ray like `launch_ray`, but also return its surface bounces and receiver hits,
+    /// see `Ray::launch_recording_echogram`.
+    fn launch_ray_recording_echogram(
         &self,
         time: u32,
-        number_of_rays: u32,
+        ray: (u32, u32),
         velocity: f64,
         sample_rate: f64,
-        do_snapshot_method: bool,
-        parallel: bool,
-    ) -> Vec<f64> {
-        let mut scene_data = self;
-        let interp_scene_data;
-        if do_snapshot_method {
-            let interp_scene = self.scene.at_time(time);
-            let chunks = interp_scene.chunks::<C>();
-            interp_scene_data = Self {
-                scene: interp_scene,
-                chunks,
-                maximum_bounds: self.maximum_bounds,
-            };
-            scene_data = &interp_scene_data;
+        config: &SimulationConfig,
+    ) -> (Vec<(f64, u32)>, Vec<RayBounceRecord>) {
+        let Some((launch_time, emitter_coords, direction, weight)) =
+            self.launch_origin_and_direction(time, ray, velocity / sample_rate, config)
+        else {
+            // this should not be able to happen
+            return (vec![], vec![]);
+        };
+        let (mut results, mut records) = Ray::launch_recording_echogram(
+            direction,
+            emitter_coords,
+            launch_time,
+            velocity,
+            sample_rate,
+            self,
+            config,
+        );
+        for (energy, _time) in &mut results {
+            *energy *= weight;
+        }
+        for record in &mut records {
+            record.energy *= weight;
         }
+        (results, records)
+    }
 
-        let rt_results: Vec<(f64, u32)> = if parallel {
-            (0..number_of_rays)
-                .into_par_iter()
-                .flat_map(|_| scene_data.launch_ray(time, velocity, sample_rate))
-                .collect()
+    /// Get the time to launch a ray at, the emitter's position at that time,
+    /// a direction to launch the ray in from there and the weight to multiply the ray's received energies with.
+    /// The launch time is `time`, unless `config.launch_jitter` is set,
+    /// in which case it's randomly offset by up to a sample, see `SimulationConfig::launch_jitter`.
+    /// The direction doesn't need to be a unit vector, the ray's launch function normalises it.
+    /// With area-weighted emission, randomly emitted rays are aimed at a random point on the scene's surfaces,
+    /// see `SimulationConfig::use_area_weighted_emission`.
+    /// Otherwise, randomly emitted rays may be aimed at the receiver, see `SimulationConfig::receiver_importance`.
+    /// `ray` holds the ray's index and the number of rays launched at once, see `EmissionType::get_direction`.
+    /// `velocity` is the rays' velocity in meters per sample, see `Scene::receiver_cone`.
+    /// Returns `None` if interpolating the emitter somehow doesn't return coordinates.
+    fn launch_origin_and_direction(
+        &self,
+        time: u32,
+        (ray_index, number_of_rays): (u32, u32),
+        velocity: f64,
+        config: &SimulationConfig,
+    ) -> Option<(f64, Vector3<f64>, Vector3<f64>, f64)> {
+        // the emitter's keyframes only cover a single loop, just like all other objects,
+        // see `Scene::validate_emitter_loop_alignment`
+        let looped_time = self.scene.looped_time(None, time);
+        let Emitter::Interpolated(emitter_coords, _, emission_type) =
+            self.scene.emitter.at_time(looped_time)
+        else {
+            return None;
+        };
+        let (launch_time, emitter_coords) = if config.launch_jitter {
+            let offset = random::<f64>();
+            (
+                <f64 as From<u32>>::from(time) + offset,
+                self.scene
+                    .emitter
+                    .coords_at_time(<f64 as From<u32>>::from(looped_time) + offset)
+                    .ok()?,
+            )
         } else {
-            (0..number_of_rays)
-                .flat_map(|_| scene_data.launch_ray(time, velocity, sample_rate))
-                .collect()
+            (<f64 as From<u32>>::from(time), emitter_coords)
+        };
+        let direction = match emission_type {
+            EmissionType::Random if config.use_area_weighted_emission => self
+                .scene
+                .random_surface_point(time)
+                .map(|point| point - emitter_coords)
+                .filter(|direction| direction.norm_squared() > 0f64)
+                .unwrap_or_else(|| emission_type.get_direction(ray_index, number_of_rays)),
+            EmissionType::Random if config.receiver_importance > 0f64 => {
+                if let Some((to_receiver, half_angle)) =
+                    self.receiver_cone(time, &emitter_coords, velocity)
+                {
+                    let direction = if random::<f64>() < config.receiver_importance {
+                        bounce::random_direction_in_cone(&to_receiver, half_angle)
+                    } else {
+                        emission_type.get_direction(ray_index, number_of_rays)
+                    };
+                    let weight = bounce::importance_weight(
+                        &direction,
+                        &to_receiver,
+                        half_angle,
+                        config.receiver_importance,
+                    );
+                    return Some((launch_time, emitter_coords, direction, weight));
+                }
+                emission_type.get_direction(ray_index, number_of_rays)
+            }
+            _ => emission_type.get_direction(ray_index, number_of_rays),
         };
-        to_impulse_response(&rt_results, number_of_rays)
+        Some((launch_time, emitter_coords, direction, 1f64))
     }
 
-    /// Launch a single ray into this `Scene`, and return its result.
-    /// The direction it is launched in is a random position in the unit cube,
-    /// which gets normalised in the ray's launch function.
-    fn launch_ray(&self, time: u32, velocity: f64, sample_rate: f64) -> Vec<(f64, u32)> {
-        let Emitter::Interpolated(emitter_coords, _, emission_type) =
-            self.scene.emitter.at_time(time)
+    /// Get the direction from the given emitter coordinates to the receiver
+    /// and the half-angle in radians of the cone to launch receiver-importance rays in.
+    /// The receiver is taken at the time a ray launched at `time` with the given velocity
+    /// in meters per sample would reach it, estimated from its distance at `time`.
+    /// The half-angle is the one the receiver subtends from the emitter, widened by `RECEIVER_CONE_WIDENING`.
+    /// Ellipsoidal receivers are treated as the sphere around them, see `Receiver::bounding_radius`.
+    /// Returns `None` if the emitter is inside the receiver.
+    fn receiver_cone(
+        &self,
+        time: u32,
+        emitter_coords: &Vector3<f64>,
+        velocity: f64,
+    ) -> Option<(Vector3<f64>, f64)> {
+        let receiver = &self.scene.receiver;
+        let receiver_center_at = |time: u32| {
+            receiver
+                .at_time(self.scene.looped_time(receiver.loop_duration(), time))
+                .center()
+        };
+        let launch_distance = (receiver_center_at(time)? - emitter_coords).norm();
+        let arrival_time = time.saturating_add((launch_distance / velocity) as u32);
+        let to_receiver = receiver_center_at(arrival_time)? - emitter_coords;
+        let radius = receiver.bounding_radius();
+        let distance = to_receiver.norm();
+        (distance > radius).then(|| {
+            let half_angle = (radius / distance).asin() * RECEIVER_CONE_WIDENING;
+            (to_receiver, half_angle.min(std::f64::consts::PI))
+        })
+    }
+
+    /// Launch a single ray like `launch_ray`, but return statistics on its path,
+    /// see `Ray::launch_with_statistics`.
+    fn launch_ray_with_statistics(
+        &self,
+        time: u32,
+        ray: (u32, u32),
+        velocity: f64,
+        sample_rate: f64,
+        config: &SimulationConfig,
+    ) -> (Vec<(f64, u32)>, RayStatistics) {
+        let Some((launch_time, emitter_coords, direction, weight)) =
+            self.launch_origin_and_direction(time, ray, velocity / sample_rate, config)
+        else {
+            // this should not be able to happen
+            return (vec![], RayStatistics::default());
+        };
+        let (mut results, statistics) = Ray::launch_with_statistics(
+            direction,
+            emitter_coords,
+            launch_time,
+            velocity,
+            sample_rate,
+            self,
+            config,
+        );
+        for (energy, _time) in &mut results {
+            *energy *= weight;
+        }
+        (results, statistics)
+    }
+
+    /// Launch a single ray like `launch_ray`, but return the direction of each receiver hit
+    /// relative to the given receiver orientation, see `Ray::launch_with_directions`.
+    fn launch_ray_with_directions(
+        &self,
+        time: u32,
+        ray: (u32, u32),
+        velocity: f64,
+        sample_rate: f64,
+        receiver_orientation: &Rotation3<f64>,
+        config: &SimulationConfig,
+    ) -> Vec<(f64, u32, Vector3<f64>)> {
+        let Some((launch_time, emitter_coords, direction, weight)) =
+            self.launch_origin_and_direction(time, ray, velocity / sample_rate, config)
         else {
             // this should not be able to happen
             return vec![];
         };
-        Ray::launch(
-            // doesn't need to be a unit vector, Ray::launch() normalises this
-            emission_type.get_direction(),
+        let mut results = Ray::launch_with_directions(
+            direction,
             emitter_coords,
-            time,
+            launch_time,
             velocity,
             sample_rate,
             self,
-        )
+            config,
+        );
+        for (energy, _time, direction) in &mut results {
+            *energy *= weight;
+            *direction = receiver_orientation.inverse_transform_vector(direction);
+        }
+        results
+    }
+}
+
+/// The maximum number of scenes `batch_simulate` creates `SceneData` for at once.
+/// Larger batches are simulated one scene at a time to bound memory usage.
+const MAX_PARALLEL_BATCH_SCENES: usize = 8;
+
+/// Sum up the output buffers of parallel tasks, see `SceneData::simulate_task_buffers`.
+/// Returns the summed buffer as `f64`, the length of the longest impulse response, the summed telemetry
+/// and all tasks' leaking rays.
+fn merge_task_buffers<F: IrSample>(
+    buffers: Vec<(Vec<F>, usize, Telemetry, Vec<LeakedRay>)>,
+) -> (Vec<f64>, usize, Telemetry, Vec<LeakedRay>) {
+    let max_len = buffers
+        .iter()
+        .map(|(vec, _, _, _)| vec.len())
+        .max()
+        .unwrap_or(0);
+    let max_ir_len = buffers
+        .iter()
+        .map(|(_, ir_len, _, _)| *ir_len)
+        .max()
+        .unwrap_or(0);
+    let mut buffer = vec![0f64; max_len];
+    let mut telemetry = Telemetry::default();
+    let mut leaked_rays = vec![];
+    for (buffer_to_add, _, chunk_telemetry, chunk_leaked_rays) in buffers {
+        buffer
+            .iter_mut()
+            .zip(buffer_to_add)
+            .for_each(|(val, to_add)| *val += to_add.widen());
+        telemetry += chunk_telemetry;
+        leaked_rays.extend(chunk_leaked_rays);
+    }
+    (buffer, max_ir_len, telemetry, leaked_rays)
+}
+
+/// Called with the number of completed scenes and the total number of scenes
+/// whenever `batch_simulate` finishes simulating a scene.
+pub type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Simulate each of the given scenes with its own `SimulationConfig` for the given input,
+/// like `SceneData::simulate_for_time_span`.
+/// All other parameters are shared between the scenes.
+/// The results are returned in the same order as `configs`, without their telemetry.
+/// A scene that fails to validate or simulate doesn't stop the others, its error is returned in its place.
+///
+/// Up to `MAX_PARALLEL_BATCH_SCENES` scenes are simulated in parallel.
+/// For bigger batches, each scene's `SceneData` is created and dropped one at a time to bound memory usage,
+/// with each simulation still running in parallel internally.
+#[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
+pub fn batch_simulate<C>(
+    configs: &[(Scene, SimulationConfig)],
+    input_data: &BitDepth,
+    number_of_rays: u32,
+    velocity: f64,
+    sample_rate: f64,
+    scaling_factor: f64,
+    do_snapshot_method: bool,
+    single_ir: bool,
+    post_processing: &PostProcessing,
+    progress_callback: Option<ProgressCallback>,
+) -> Vec<Result<(BitDepth, ImpulseResponse), DemoError>>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    let completed = AtomicUsize::new(0);
+    let simulate = |(scene, config): &(Scene, SimulationConfig)| {
+        let result = SceneData::<C>::create_for_scene(scene.clone()).and_then(|scene_data| {
+            scene_data
+                .simulate_for_time_span(
+                    input_data,
+                    number_of_rays,
+                    velocity,
+                    sample_rate,
+                    scaling_factor,
+                    do_snapshot_method,
+                    single_ir,
+                    post_processing,
+                    config,
+                )
+                .map(|output| (output.audio, output.ir))
+        });
+        let completed = completed.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(callback) = &progress_callback {
+            callback(completed, configs.len());
+        }
+        result
+    };
+    if configs.len() > MAX_PARALLEL_BATCH_SCENES {
+        configs.iter().map(simulate).collect()
+    } else {
+        configs.par_iter().map(simulate).collect()
     }
 }
@@ -1,6 +1,13 @@
+use std::ops::Mul;
+
+use generic_array::ArrayLength;
 use nalgebra::Vector3;
+use typenum::Unsigned;
 
-use crate::scene::{Emitter, Receiver, Scene, Surface, SurfaceKeyframe};
+use crate::{
+    chunk::Chunks,
+    scene::{Emitter, Receiver, Scene, Surface, SurfaceKeyframe, SurfaceVariant},
+};
 
 pub trait MaximumBounds {
     /// Get the maximum bounds of the element(s) described by this object.
@@ -44,47 +51,88 @@ fn update_maximum_bounds(
     }
 }
 
+/// update the `min_coords` and `max_coords` with all coordinates of the given surface,
+/// including every keyframe.
+fn update_surface_bounds<const N: usize>(
+    surface: &Surface<N>,
+    min_coords: &mut Vector3<f64>,
+    max_coords: &mut Vector3<f64>,
+) {
+    match surface {
+        Surface::Interpolated(coordinates, _material) => {
+            for coord in coordinates {
+                update_maximum_bounds(coord, min_coords, max_coords, None);
+            }
+        }
+        Surface::Keyframes(keyframes, _material, _loop_duration) => {
+            for keyframe in keyframes {
+                for coord in &keyframe.coords {
+                    update_maximum_bounds(coord, min_coords, max_coords, None);
+                }
+            }
+        }
+    }
+}
+
 impl<const N: usize> MaximumBounds for SurfaceKeyframe<N> {
     fn maximum_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
         maximum_bounds(&self.coords)
     }
 }
 
+/// Includes every keyframe for keyframe surfaces.
+impl<const N: usize> MaximumBounds for Surface<N> {
+    fn maximum_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
+        let mut min_coords: Vector3<f64> = Vector3::new(f64::MAX, f64::MAX, f64::MAX);
+        let mut max_coords: Vector3<f64> = Vector3::new(f64::MIN, f64::MIN, f64::MIN);
+        update_surface_bounds(self, &mut min_coords, &mut max_coords);
+        (min_coords, max_coords)
+    }
+}
+
+impl MaximumBounds for SurfaceVariant {
+    fn maximum_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
+        match self {
+            Self::Triangle(surface) => surface.maximum_bounds(),
+            Self::Quad(surface) => surface.maximum_bounds(),
+        }
+    }
+}
+
 impl MaximumBounds for Scene {
     fn maximum_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
         let mut min_coords: Vector3<f64> = Vector3::new(f64::MAX, f64::MAX, f64::MAX);
         let mut max_coords: Vector3<f64> = Vector3::new(f64::MIN, f64::MIN, f64::MIN);
         for surface in &self.surfaces {
             match surface {
-                Surface::Interpolated(coordinates, _time, _material) => {
-                    for coord in coordinates {
-                        update_maximum_bounds(coord, &mut min_coords, &mut max_coords, None);
-                    }
+                SurfaceVariant::Triangle(surface) => {
+                    update_surface_bounds(surface, &mut min_coords, &mut max_coords);
                 }
-                Surface::Keyframes(keyframes, _material) => {
-                    for keyframe in keyframes {
-                        for coord in &keyframe.coords {
-                            update_maximum_bounds(coord, &mut min_coords, &mut max_coords, None);
-                        }
-                    }
+                SurfaceVariant::Quad(surface) => {
+                    update_surface_bounds(surface, &mut min_coords, &mut max_coords);
                 }
-            };
+            }
         }
         match &self.receiver {
-            Receiver::Interpolated(coordinates, radius, _time) => {
-                update_maximum_bounds(coordinates, &mut min_coords, &mut max_coords, Some(*radius));
+            Receiver::Interpolated(coordinates, ..) | Receiver::Ellipsoid(coordinates, ..) => {
+                update_maximum_bounds(
+                    coordinates,
+                    &mut min_coords,
+                    &mut max_coords,
+                    Some(self.receiver.bounding_radius()),
+                );
             }
-            Receiver::Keyframes(keyframes, radius) => {
+            Receiver::Keyframes(keyframes, ..) | Receiver::EllipsoidKeyframes(keyframes, ..) => {
                 for keyframe in keyframes {
                     update_maximum_bounds(
                         &keyframe.coords,
                         &mut min_coords,
                         &mut max_coords,
-                        Some(*radius),
+                        Some(self.receiver.bounding_radius()),
                     );
                 }
             }
-        };
+        }
         match &self.emitter {
             Emitter::Interpolated(coordinates, _time, _emission_type) => {
                 update_maximum_bounds(coordinates, &mut min_coords, &mut max_coords, Some(0.1f64));
@@ -99,12 +147,87 @@ impl MaximumBounds for Scene {
                     );
                 }
             }
-        };
+        }
 
         (min_coords, max_coords)
     }
 }
 
+/// The bounds of the whole chunk grid, from the first chunk's lower bound
+/// to the last chunk's upper bound.
+impl<C> MaximumBounds for Chunks<C>
+where
+    C: Unsigned + Mul<C>,
+    <C as Mul>::Output: Mul<C>,
+    <<C as Mul>::Output as Mul<C>>::Output: ArrayLength,
+{
+    fn maximum_bounds(&self) -> (Vector3<f64>, Vector3<f64>) {
+        let number_of_chunks = f64::from(C::to_u32());
+        (
+            self.chunk_starts,
+            self.chunk_starts
+                + Vector3::new(
+                    self.size_x * number_of_chunks,
+                    self.size_y * number_of_chunks,
+                    self.size_z * number_of_chunks,
+                ),
+        )
+    }
+}
+
+/// The margin added around swept keyframe bounds, so rays grazing a surface's edge aren't rejected
+/// because of rounding errors, see `swept_keyframe_bounds`.
+const SWEPT_BOUNDS_MARGIN: f64 = 1e-6;
+
+/// Get the bounds of the volume a surface sweeps through between the two given keyframes.
+/// The corners move linearly inbetween keyframes, so the box around both keyframes' corners contains the whole sweep.
+pub fn swept_keyframe_bounds<const N: usize>(
+    keyframe_first: &SurfaceKeyframe<N>,
+    keyframe_second: &SurfaceKeyframe<N>,
+) -> (Vector3<f64>, Vector3<f64>) {
+    let mut min_coords: Vector3<f64> = Vector3::new(f64::MAX, f64::MAX, f64::MAX);
+    let mut max_coords: Vector3<f64> = Vector3::new(f64::MIN, f64::MIN, f64::MIN);
+    for coord in keyframe_first.coords.iter().chain(&keyframe_second.coords) {
+        update_maximum_bounds(
+            coord,
+            &mut min_coords,
+            &mut max_coords,
+            Some(SWEPT_BOUNDS_MARGIN),
+        );
+    }
+
+    (min_coords, max_coords)
+}
+
+/// Check whether the line segment from `start` to `end` passes through the given bounds,
+/// using the slab test. Touching the bounds counts as passing through them.
+pub fn segment_intersects_bounds(
+    start: &Vector3<f64>,
+    end: &Vector3<f64>,
+    bounds: &(Vector3<f64>, Vector3<f64>),
+) -> bool {
+    let (min_coords, max_coords) = bounds;
+    let direction = end - start;
+    let mut factor_entry = 0f64;
+    let mut factor_exit = 1f64;
+    for axis in 0..3 {
+        if direction[axis] == 0f64 {
+            if start[axis] < min_coords[axis] || start[axis] > max_coords[axis] {
+                return false;
+            }
+            continue;
+        }
+        let factor_min = (min_coords[axis] - start[axis]) / direction[axis];
+        let factor_max = (max_coords[axis] - start[axis]) / direction[axis];
+        factor_entry = factor_entry.max(factor_min.min(factor_max));
+        factor_exit = factor_exit.min(factor_min.max(factor_max));
+        if factor_entry > factor_exit {
+            return false;
+        }
+    }
+    true
+}
+
 /// Get the maximum bounds of the object described by the given coordinates.
 pub fn maximum_bounds(coordinates: &[Vector3<f64>]) -> (Vector3<f64>, Vector3<f64>) {
     let mut min_coords: Vector3<f64> = Vector3::new(f64::MAX, f64::MAX, f64::MAX);
@@ -120,33 +243,42 @@ pub fn maximum_bounds(coordinates: &[Vector3<f64>]) -> (Vector3<f64>, Vector3<f6
 mod tests {
     use nalgebra::Vector3;
 
-    use super::MaximumBounds;
+    use super::{segment_intersects_bounds, swept_keyframe_bounds, MaximumBounds};
     use crate::{
         bounce::EmissionType,
+        boundary::SceneBoundary,
         materials::MATERIAL_CONCRETE_WALL,
         scene::{
             CoordinateKeyframe, Emitter, Receiver, Scene, Surface, SurfaceData, SurfaceKeyframe,
+            SurfaceVariant,
         },
+        time::SampleTime,
     };
 
     fn empty_scene() -> Scene {
         Scene {
             receiver: Receiver::Keyframes(
                 vec![CoordinateKeyframe {
-                    time: 0,
+                    time: SampleTime(0),
                     coords: Vector3::new(0f64, 0f64, 0f64),
                 }],
                 0.1f64,
+                None,
+                false,
             ),
             surfaces: vec![],
             emitter: Emitter::Keyframes(
                 vec![CoordinateKeyframe {
-                    time: 0,
+                    time: SampleTime(0),
                     coords: Vector3::new(0f64, 0f64, 0f64),
                 }],
                 EmissionType::Random,
             ),
             loop_duration: None,
+            bounds_cache: None,
+            boundary: SceneBoundary::default(),
+            emitter_active_ranges: None,
+            statistical_tail: None,
         }
     }
 
@@ -162,37 +294,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn maximum_bounds_cached_stores_result() {
+        let mut scene = empty_scene();
+        let bounds = scene.maximum_bounds_cached();
+        assert_eq!(scene.maximum_bounds(), bounds);
+        assert_eq!(Some(bounds), scene.bounds_cache);
+
+        // moving the receiver doesn't change the cached bounds until the cache is invalidated
+        scene.receiver = Receiver::Keyframes(
+            vec![CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(5f64, 0f64, 0f64),
+            }],
+            0.1f64,
+            None,
+            false,
+        );
+        assert_eq!(bounds, scene.maximum_bounds_cached());
+        scene.invalidate_bounds_cache();
+        assert_eq!(None, scene.bounds_cache);
+        assert_eq!(scene.maximum_bounds(), scene.maximum_bounds_cached());
+        assert_ne!(bounds, scene.maximum_bounds_cached());
+    }
+
     #[test]
     fn maximum_bounds_moving_receiver_and_moving_emitter() {
         let scene = Scene {
             receiver: Receiver::Keyframes(
                 vec![
                     CoordinateKeyframe {
-                        time: 0,
+                        time: SampleTime(0),
                         coords: Vector3::new(0f64, 0f64, 0f64),
                     },
                     CoordinateKeyframe {
-                        time: 3,
+                        time: SampleTime(3),
                         coords: Vector3::new(20f64, 10f64, 34f64),
                     },
                 ],
                 0.1f64,
+                None,
+                false,
             ),
             surfaces: vec![],
             emitter: Emitter::Keyframes(
                 vec![
                     CoordinateKeyframe {
-                        time: 0,
+                        time: SampleTime(0),
                         coords: Vector3::new(0f64, 0f64, 0f64),
                     },
                     CoordinateKeyframe {
-                        time: 3,
+                        time: SampleTime(3),
                         coords: Vector3::new(-10f64, -20f64, -50f64),
                     },
                 ],
                 EmissionType::Random,
             ),
             loop_duration: None,
+            bounds_cache: None,
+            boundary: SceneBoundary::default(),
+            emitter_active_ranges: None,
+            statistical_tail: None,
         };
 
         assert_eq!(
@@ -210,21 +372,23 @@ mod tests {
             receiver: Receiver::Keyframes(
                 vec![
                     CoordinateKeyframe {
-                        time: 0,
+                        time: SampleTime(0),
                         coords: Vector3::new(0f64, 0f64, 0f64),
                     },
                     CoordinateKeyframe {
-                        time: 3,
+                        time: SampleTime(3),
                         coords: Vector3::new(20f64, 10f64, 34f64),
                     },
                 ],
                 0.1,
+                None,
+                false,
             ),
             surfaces: vec![
                 Surface::Keyframes(
                     vec![
                         SurfaceKeyframe {
-                            time: 5,
+                            time: SampleTime(5),
                             coords: [
                                 Vector3::new(-10f64, -20f64, -30f64),
                                 Vector3::new(0f64, 2f64, 16f64),
@@ -232,7 +396,7 @@ mod tests {
                             ],
                         },
                         SurfaceKeyframe {
-                            time: 10,
+                            time: SampleTime(10),
                             coords: [
                                 Vector3::new(3f64, 2f64, 5f64),
                                 Vector3::new(8f64, 10f64, 12f64),
@@ -241,11 +405,12 @@ mod tests {
                         },
                     ],
                     SurfaceData::new(MATERIAL_CONCRETE_WALL),
+                    None,
                 ),
                 Surface::Keyframes(
                     vec![
                         SurfaceKeyframe {
-                            time: 5,
+                            time: SampleTime(5),
                             coords: [
                                 Vector3::new(0f64, 0f64, 0f64),
                                 Vector3::new(0f64, 2f64, 16f64),
@@ -253,7 +418,7 @@ mod tests {
                             ],
                         },
                         SurfaceKeyframe {
-                            time: 10,
+                            time: SampleTime(10),
                             coords: [
                                 Vector3::new(3f64, 2f64, 5f64),
                                 Vector3::new(8f64, 10f64, 12f64),
@@ -261,7 +426,7 @@ mod tests {
                             ],
                         },
                         SurfaceKeyframe {
-                            time: 15,
+                            time: SampleTime(15),
                             coords: [
                                 Vector3::new(0f64, 0f64, 0f64),
                                 Vector3::new(0f64, 2f64, 16f64),
@@ -270,22 +435,30 @@ mod tests {
                         },
                     ],
                     SurfaceData::new(MATERIAL_CONCRETE_WALL),
+                    None,
                 ),
-            ],
+            ]
+            .into_iter()
+            .map(SurfaceVariant::Triangle)
+            .collect(),
             emitter: Emitter::Keyframes(
                 vec![
                     CoordinateKeyframe {
-                        time: 0,
+                        time: SampleTime(0),
                         coords: Vector3::new(0f64, 0f64, 0f64),
                     },
                     CoordinateKeyframe {
-                        time: 3,
+                        time: SampleTime(3),
                         coords: Vector3::new(-10f64, -20f64, -50f64),
                     },
                 ],
                 EmissionType::Random,
             ),
             loop_duration: None,
+            bounds_cache: None,
+            boundary: SceneBoundary::default(),
+            emitter_active_ranges: None,
+            statistical_tail: None,
         };
 
         assert_eq!(
@@ -296,4 +469,52 @@ mod tests {
             scene.maximum_bounds()
         );
     }
+
+    #[test]
+    fn swept_keyframe_bounds_contain_both_keyframes() {
+        let first = SurfaceKeyframe {
+            time: SampleTime(0),
+            coords: [
+                Vector3::new(0f64, 0f64, 0f64),
+                Vector3::new(1f64, 0f64, 0f64),
+                Vector3::new(0f64, 1f64, 0f64),
+            ],
+        };
+        let second = SurfaceKeyframe {
+            time: SampleTime(10),
+            coords: [
+                Vector3::new(-2f64, 0f64, 3f64),
+                Vector3::new(1f64, 0f64, 3f64),
+                Vector3::new(0f64, 4f64, 3f64),
+            ],
+        };
+        let (min_coords, max_coords) = swept_keyframe_bounds(&first, &second);
+        assert!((min_coords - Vector3::new(-2f64, 0f64, 0f64)).amax() < 1e-5);
+        assert!((max_coords - Vector3::new(1f64, 4f64, 3f64)).amax() < 1e-5);
+    }
+
+    #[test]
+    fn segment_intersects_bounds_slab_test() {
+        let bounds = (
+            Vector3::new(0f64, 0f64, 0f64),
+            Vector3::new(1f64, 1f64, 1f64),
+        );
+        let check = |start: (f64, f64, f64), end: (f64, f64, f64)| {
+            segment_intersects_bounds(
+                &Vector3::new(start.0, start.1, start.2),
+                &Vector3::new(end.0, end.1, end.2),
+                &bounds,
+            )
+        };
+        // passing through, starting inside and ending inside
+        assert!(check((-1f64, 0.5f64, 0.5f64), (2f64, 0.5f64, 0.5f64)));
+        assert!(check((0.5f64, 0.5f64, 0.5f64), (5f64, 5f64, 5f64)));
+        assert!(check((-1f64, -1f64, -1f64), (0.5f64, 0.5f64, 0.5f64)));
+        // touching a corner
+        assert!(check((-1f64, -1f64, -1f64), (0f64, 0f64, 0f64)));
+        // stopping short, passing beside and parallel outside
+        assert!(!check((-2f64, 0.5f64, 0.5f64), (-0.5f64, 0.5f64, 0.5f64)));
+        assert!(!check((-1f64, 0f64, 0.5f64), (0.5f64, 2f64, 0.5f64)));
+        assert!(!check((-1f64, 2f64, 0.5f64), (2f64, 2f64, 0.5f64)));
+    }
 }
@@ -1,11 +1,24 @@
+use std::fmt::Display;
+
 use nalgebra::{Point3, Rotation3, Translation3, Unit, Vector3};
 
 use crate::{
-    bounce::EmissionType,
+    bounce::{is_degenerate_normal, EmissionType},
+    boundary::{BoundaryBehaviour, BoundaryFace, SceneBoundary},
+    error::{DemoError, SceneBuildError},
+    impulse_response::StatisticalTail,
     materials::{Material, MATERIAL_CONCRETE_WALL},
-    scene::{CoordinateKeyframe, Emitter, Receiver, Scene, Surface, SurfaceData, SurfaceKeyframe},
+    scene::{
+        merged_loop_duration, sort_keyframes, CoordinateKeyframe, Emitter, Receiver, Scene,
+        Surface, SurfaceData, SurfaceKeyframe, SurfaceVariant,
+    },
+    scene_bounds::MaximumBounds,
+    time::{SampleDuration, SampleTime},
 };
 
+/// The number of keyframes per rotation the predefined rotating scenes use, see `rotate`.
+pub const DEFAULT_ROTATION_KEYFRAMES: u32 = 100;
+
 /// Create a static cube primitive described by the given coordinates and material.
 pub fn static_cube(
     bottom_left: Vector3<f64>,
@@ -14,27 +27,50 @@ pub fn static_cube(
 ) -> Vec<Surface<3>> {
     cube_polygons(bottom_left, top_right)
         .iter()
-        .map(|coords| Surface::Interpolated(*coords, 0, SurfaceData::new(material)))
+        .map(|coords| Surface::Interpolated(*coords, SurfaceData::new(material)))
+        .collect()
+}
+
+/// Create a static cube primitive described by the given coordinates and material,
+/// rotated by `rotation` around the cube's centre.
+pub fn static_cube_rotated(
+    bottom_left: Vector3<f64>,
+    top_right: Vector3<f64>,
+    rotation: Rotation3<f64>,
+    material: Material,
+) -> Vec<Surface<3>> {
+    let center = (bottom_left + top_right) / 2f64;
+    cube_polygons(bottom_left, top_right)
+        .iter()
+        .map(|coords| {
+            let rotated = coords.map(|coord| center + rotation * (coord - center));
+            Surface::Interpolated(rotated, SurfaceData::new(material))
+        })
         .collect()
 }
 
 /// Create a rotating cube primitive described by the given coordinates and material.
+/// The cube rotates around the given axis going through `rotation_origin`,
+/// described by the given number of keyframes per rotation, see `rotate`.
 pub fn rotating_cube(
     bottom_left: Vector3<f64>,
     top_right: Vector3<f64>,
     rotation_origin: Vector3<f64>,
-    rotation_duration: u32,
+    rotation_axis: Vector3<f64>,
+    rotation_duration: SampleDuration,
+    keyframes: u32,
     material: Material,
 ) -> Vec<Surface<3>> {
-    let keyframes = rotate(
+    rotate(
         &cube_polygons(bottom_left, top_right),
         rotation_origin,
+        rotation_axis,
         rotation_duration,
-    );
-    keyframes
-        .iter()
-        .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material)))
-        .collect()
+        keyframes,
+    )
+    .iter()
+    .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material), None))
+    .collect()
 }
 
 /// Create a static L primitive described by the given coordinates and material.
@@ -49,11 +85,13 @@ pub fn static_l(
 ) -> Vec<Surface<3>> {
     l_polygons(bottom_left, length_1, length_2, width_1, width_2, height)
         .iter()
-        .map(|coords| Surface::Interpolated(*coords, 0, SurfaceData::new(material)))
+        .map(|coords| Surface::Interpolated(*coords, SurfaceData::new(material)))
         .collect()
 }
 
 /// Create a rotating L primitive described by the given coordinates and material.
+/// The L rotates around the given axis going through `rotation_origin`,
+/// described by the given number of keyframes per rotation, see `rotate`.
 #[allow(clippy::too_many_arguments)]
 pub fn rotating_l(
     bottom_left: Vector3<f64>,
@@ -63,17 +101,117 @@ pub fn rotating_l(
     width_2: f64,
     height: f64,
     rotation_origin: Vector3<f64>,
-    rotation_duration: u32,
+    rotation_axis: Vector3<f64>,
+    rotation_duration: SampleDuration,
+    keyframes: u32,
     material: Material,
 ) -> Vec<Surface<3>> {
     let polygons = l_polygons(bottom_left, length_1, length_2, width_1, width_2, height);
-    let keyframes = rotate(&polygons, rotation_origin, rotation_duration);
-    keyframes
+    rotate(
+        &polygons,
+        rotation_origin,
+        rotation_axis,
+        rotation_duration,
+        keyframes,
+    )
+    .iter()
+    .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material), None))
+    .collect()
+}
+
+/// Create a static sphere primitive described by the given coordinates and material.
+/// The sphere is approximated by an icosahedron whose triangles are each split into four
+/// `subdivisions` times, resulting in `20 * 4^subdivisions` triangles.
+pub fn static_sphere(
+    center: Vector3<f64>,
+    radius: f64,
+    subdivisions: u32,
+    material: Material,
+) -> Vec<Surface<3>> {
+    sphere_polygons(center, radius, subdivisions)
+        .iter()
+        .map(|coords| Surface::Interpolated(*coords, SurfaceData::new(material)))
+        .collect()
+}
+
+/// Create a rotating sphere primitive described by the given coordinates and material.
+/// The sphere rotates around the given axis going through `rotation_origin`,
+/// described by the given number of keyframes per rotation, see `rotate`.
+#[allow(clippy::too_many_arguments)]
+pub fn rotating_sphere(
+    center: Vector3<f64>,
+    radius: f64,
+    subdivisions: u32,
+    rotation_origin: Vector3<f64>,
+    rotation_axis: Vector3<f64>,
+    rotation_duration: SampleDuration,
+    keyframes: u32,
+    material: Material,
+) -> Vec<Surface<3>> {
+    let polygons = sphere_polygons(center, radius, subdivisions);
+    rotate(
+        &polygons,
+        rotation_origin,
+        rotation_axis,
+        rotation_duration,
+        keyframes,
+    )
+    .iter()
+    .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material), None))
+    .collect()
+}
+
+/// Create a static cylinder primitive described by the given coordinates and material.
+/// The cylinder stands upright on `base_center` and is approximated by a tube of
+/// `segments` flat sides closed by two caps, resulting in `4 * segments` triangles.
+pub fn static_cylinder(
+    base_center: Vector3<f64>,
+    radius: f64,
+    height: f64,
+    segments: u32,
+    material: Material,
+) -> Vec<Surface<3>> {
+    cylinder_polygons(base_center, radius, height, segments)
         .iter()
-        .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material)))
+        .map(|coords| Surface::Interpolated(*coords, SurfaceData::new(material)))
         .collect()
 }
 
+/// Create a rotating cylinder primitive described by the given coordinates and material.
+/// The cylinder rotates around the given axis going through `rotation_origin`,
+/// described by the given number of keyframes per rotation, see `rotate`.
+#[allow(clippy::too_many_arguments)]
+pub fn rotating_cylinder(
+    base_center: Vector3<f64>,
+    radius: f64,
+    height: f64,
+    segments: u32,
+    rotation_origin: Vector3<f64>,
+    rotation_axis: Vector3<f64>,
+    rotation_duration: SampleDuration,
+    keyframes: u32,
+    material: Material,
+) -> Vec<Surface<3>> {
+    let polygons = cylinder_polygons(base_center, radius, height, segments);
+    rotate(
+        &polygons,
+        rotation_origin,
+        rotation_axis,
+        rotation_duration,
+        keyframes,
+    )
+    .iter()
+    .map(|keys| Surface::Keyframes(keys.clone(), SurfaceData::new(material), None))
+    .collect()
+}
+
+/// Create a static, flat panel described by the given corners and material.
+/// The corners need to lie in one plane and be given in order around the panel.
+/// Like the other primitives' triangles, the panel faces the side its corners appear clockwise from.
+pub const fn static_panel(corners: [Vector3<f64>; 4], material: Material) -> Surface<4> {
+    Surface::Interpolated(corners, SurfaceData::new(material))
+}
+
 #[allow(clippy::too_many_lines)]
 fn cube_polygons(bottom_left: Vector3<f64>, top_right: Vector3<f64>) -> [[Vector3<f64>; 3]; 12] {
     [
@@ -230,18 +368,124 @@ fn l_polygons(
     ]
 }
 
+// polygons for an icosphere
+// Like the cube's, the triangles are wound counter-clockwise when seen from the outside,
+// so their surface normals face the inside of the sphere.
+fn sphere_polygons(center: Vector3<f64>, radius: f64, subdivisions: u32) -> Vec<[Vector3<f64>; 3]> {
+    let golden_ratio = f64::midpoint(1f64, 5f64.sqrt());
+    let vertices = [
+        Vector3::new(-1f64, golden_ratio, 0f64),
+        Vector3::new(1f64, golden_ratio, 0f64),
+        Vector3::new(-1f64, -golden_ratio, 0f64),
+        Vector3::new(1f64, -golden_ratio, 0f64),
+        Vector3::new(0f64, -1f64, golden_ratio),
+        Vector3::new(0f64, 1f64, golden_ratio),
+        Vector3::new(0f64, -1f64, -golden_ratio),
+        Vector3::new(0f64, 1f64, -golden_ratio),
+        Vector3::new(golden_ratio, 0f64, -1f64),
+        Vector3::new(golden_ratio, 0f64, 1f64),
+        Vector3::new(-golden_ratio, 0f64, -1f64),
+        Vector3::new(-golden_ratio, 0f64, 1f64),
+    ]
+    .map(|vertex| vertex.normalize());
+    let faces = [
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    let mut triangles: Vec<[Vector3<f64>; 3]> = faces
+        .iter()
+        .map(|face| face.map(|idx| vertices[idx]))
+        .collect();
+    for _ in 0..subdivisions {
+        triangles = triangles
+            .iter()
+            .flat_map(|[a, b, c]| {
+                let ab = ((a + b) / 2f64).normalize();
+                let bc = ((b + c) / 2f64).normalize();
+                let ca = ((c + a) / 2f64).normalize();
+                [[*a, ab, ca], [*b, bc, ab], [*c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    triangles
+        .iter()
+        .map(|triangle| triangle.map(|vertex| center + vertex * radius))
+        .collect()
+}
+
+// polygons for an upright cylinder
+// Like the cube's, the triangles are wound counter-clockwise when seen from the outside,
+// so their surface normals face the inside of the cylinder.
+fn cylinder_polygons(
+    base_center: Vector3<f64>,
+    radius: f64,
+    height: f64,
+    segments: u32,
+) -> Vec<[Vector3<f64>; 3]> {
+    let to_top = Vector3::new(0f64, 0f64, height);
+    let top_center = base_center + to_top;
+    let ring: Vec<Vector3<f64>> = (0..segments)
+        .map(|segment| {
+            let angle = 2f64 * std::f64::consts::PI * f64::from(segment) / f64::from(segments);
+            base_center + Vector3::new(radius * angle.cos(), radius * angle.sin(), 0f64)
+        })
+        .collect();
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .flat_map(|(bottom, next_bottom)| {
+            let top = bottom + to_top;
+            let next_top = next_bottom + to_top;
+            [
+                // side
+                [*bottom, *next_bottom, next_top],
+                [*bottom, next_top, top],
+                // caps
+                [base_center, *next_bottom, *bottom],
+                [top_center, top, next_top],
+            ]
+        })
+        .collect()
+}
+
+/// Rotate the given triangles once around `rotation_axis` going through `rotation_origin`
+/// over `rotation_duration` samples, described by `keyframes` evenly spaced keyframes
+/// plus a final one completing the rotation.
+/// Keyframe `num` is at `num * rotation_duration / keyframes` samples (rounded down),
+/// so the final keyframe is at exactly `rotation_duration`,
+/// and the gaps between neighbouring keyframes differ by at most a sample.
+/// To keep the keyframes' times distinct, `keyframes` is clamped to between 1 and `rotation_duration`,
+/// so e.g. 0 keyframes are treated as 1 and 200 keyframes over 100 samples as 100.
+/// With a duration of 0, both resulting keyframes are at time 0.
+/// `rotation_axis` needs to have a direction, otherwise the coordinates are NaN,
+/// which `SceneBuilder::try_build` rejects with `SceneBuildError::DegenerateRotationAxis`.
 fn rotate(
     coordinates: &[[Vector3<f64>; 3]],
     rotation_origin: Vector3<f64>,
-    rotation_duration: u32,
+    rotation_axis: Vector3<f64>,
+    rotation_duration: SampleDuration,
+    keyframes: u32,
 ) -> Vec<Vec<SurfaceKeyframe<3>>> {
-    let (number_of_keyframes, time_factor) = if rotation_duration < 1000 {
-        (rotation_duration, 1)
-    } else {
-        (rotation_duration / 100, 100)
-    };
+    let number_of_keyframes = keyframes.clamp(1, rotation_duration.samples().max(1));
     let from_origin = Translation3::from(rotation_origin);
-    let z_axis = Unit::new_unchecked(Vector3::new(0f64, 0f64, 1f64));
+    let rotation_axis = Unit::new_normalize(rotation_axis);
     coordinates
         .iter()
         .map(|coords| {
@@ -251,7 +495,7 @@ fn rotate(
                 .map(|num| {
                     let rot_amount = f64::from(num) / f64::from(number_of_keyframes);
                     let rot = Rotation3::from_axis_angle(
-                        &z_axis,
+                        &rotation_axis,
                         2f64 * std::f64::consts::PI * rot_amount,
                     );
                     let result_coords: Vec<Vector3<f64>> = point_coords
@@ -266,7 +510,11 @@ fn rotate(
                         .collect();
                     SurfaceKeyframe {
                         coords: (&result_coords[0..3]).try_into().unwrap(),
-                        time: time_factor * num,
+                        time: SampleTime(
+                            (u64::from(num) * u64::from(rotation_duration.samples())
+                                / u64::from(number_of_keyframes))
+                                as u32,
+                        ),
                     }
                 })
                 .collect()
@@ -277,7 +525,16 @@ fn rotate(
 /// Representations of object primitives `SceneBuilder` can create.
 enum Object {
     StaticCube(Vector3<f64>, Vector3<f64>, Material),
-    RotatingCube(Vector3<f64>, Vector3<f64>, Vector3<f64>, u32, Material),
+    StaticCubeRotated(Vector3<f64>, Vector3<f64>, Rotation3<f64>, Material),
+    RotatingCube(
+        Vector3<f64>,
+        Vector3<f64>,
+        Vector3<f64>,
+        Vector3<f64>,
+        SampleDuration,
+        u32,
+        Material,
+    ),
     StaticL(Vector3<f64>, f64, f64, f64, f64, f64, Material),
     RotatingL(
         Vector3<f64>,
@@ -287,82 +544,250 @@ enum Object {
         f64,
         f64,
         Vector3<f64>,
+        Vector3<f64>,
+        SampleDuration,
+        u32,
+        Material,
+    ),
+    StaticSphere(Vector3<f64>, f64, u32, Material),
+    RotatingSphere(
+        Vector3<f64>,
+        f64,
+        u32,
+        Vector3<f64>,
+        Vector3<f64>,
+        SampleDuration,
+        u32,
+        Material,
+    ),
+    StaticCylinder(Vector3<f64>, f64, f64, u32, Material),
+    RotatingCylinder(
+        Vector3<f64>,
+        f64,
+        f64,
+        u32,
+        Vector3<f64>,
+        Vector3<f64>,
+        SampleDuration,
         u32,
         Material,
     ),
+    StaticPanel([Vector3<f64>; 4], Material),
+    Scene(Box<Scene>, Vector3<f64>),
 }
 
 impl Object {
-    fn build(&self) -> Vec<Surface<3>> {
+    /// Get the axis this object rotates around, if it's rotating.
+    const fn rotation_axis(&self) -> Option<&Vector3<f64>> {
         match self {
+            Self::RotatingCube(_, _, _, rotation_axis, ..)
+            | Self::RotatingL(_, _, _, _, _, _, _, rotation_axis, ..)
+            | Self::RotatingSphere(_, _, _, _, rotation_axis, ..)
+            | Self::RotatingCylinder(_, _, _, _, _, rotation_axis, ..) => Some(rotation_axis),
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn build(&self) -> Vec<SurfaceVariant> {
+        let triangles = match self {
             Self::StaticCube(bottom_left, top_right, material) => {
                 static_cube(*bottom_left, *top_right, *material)
             }
+            Self::StaticCubeRotated(bottom_left, top_right, rotation, material) => {
+                static_cube_rotated(*bottom_left, *top_right, *rotation, *material)
+            }
             Self::RotatingCube(
                 bottom_left,
                 top_right,
                 rotation_origin,
+                rotation_axis,
                 rotation_duration,
+                keyframes,
                 material,
             ) => rotating_cube(
                 *bottom_left,
                 *top_right,
                 *rotation_origin,
+                *rotation_axis,
                 *rotation_duration,
+                *keyframes,
                 *material,
             ),
-            Self::StaticL(
+            Self::StaticL(bottom_left, length_1, length_2, width_1, width_2, height, material) => {
+                static_l(
+                    *bottom_left,
+                    *length_1,
+                    *length_2,
+                    *width_1,
+                    *width_2,
+                    *height,
+                    *material,
+                )
+            }
+            Self::RotatingL(
                 bottom_left,
                 length_1,
                 length_2,
                 width_1,
                 width_2,
                 height,
+                rotation_origin,
+                rotation_axis,
+                rotation_duration,
+                keyframes,
                 material,
-            ) => static_l(
+            ) => rotating_l(
                 *bottom_left,
                 *length_1,
                 *length_2,
                 *width_1,
                 *width_2,
                 *height,
+                *rotation_origin,
+                *rotation_axis,
+                *rotation_duration,
+                *keyframes,
                 *material,
             ),
-            Self::RotatingL(
-                bottom_left,
-                length_1,
-                length_2,
-                width_1,
-                width_2,
+            Self::StaticSphere(center, radius, subdivisions, material) => {
+                static_sphere(*center, *radius, *subdivisions, *material)
+            }
+            Self::RotatingSphere(
+                center,
+                radius,
+                subdivisions,
+                rotation_origin,
+                rotation_axis,
+                rotation_duration,
+                keyframes,
+                material,
+            ) => rotating_sphere(
+                *center,
+                *radius,
+                *subdivisions,
+                *rotation_origin,
+                *rotation_axis,
+                *rotation_duration,
+                *keyframes,
+                *material,
+            ),
+            Self::StaticCylinder(base_center, radius, height, segments, material) => {
+                static_cylinder(*base_center, *radius, *height, *segments, *material)
+            }
+            Self::RotatingCylinder(
+                base_center,
+                radius,
                 height,
+                segments,
                 rotation_origin,
+                rotation_axis,
                 rotation_duration,
+                keyframes,
                 material,
-            ) => rotating_l(
-                *bottom_left,
-                *length_1,
-                *length_2,
-                *width_1,
-                *width_2,
+            ) => rotating_cylinder(
+                *base_center,
+                *radius,
                 *height,
+                *segments,
                 *rotation_origin,
+                *rotation_axis,
                 *rotation_duration,
+                *keyframes,
                 *material,
             ),
+            Self::StaticPanel(corners, material) => {
+                return vec![SurfaceVariant::Quad(static_panel(*corners, *material))];
+            }
+            Self::Scene(scene, translation) => {
+                return scene
+                    .surfaces
+                    .iter()
+                    .map(|surface| surface.offset(translation, 0))
+                    .collect();
+            }
+        };
+        triangles
+            .into_iter()
+            .map(SurfaceVariant::Triangle)
+            .collect()
+    }
+}
+
+/// Create coordinate keyframes by evaluating `coords_at` at `steps` evenly spaced times from `start` to `end`, both inclusive.
+/// The times are rounded towards `start` to whole samples, so keyframes falling onto the same sample are dropped.
+/// With fewer than 2 steps, only a single keyframe at `start` is created.
+pub fn keyframes_from_fn(
+    coords_at: impl Fn(u32) -> Vector3<f64>,
+    start: u32,
+    end: u32,
+    steps: u32,
+) -> Vec<CoordinateKeyframe> {
+    let mut times: Vec<u32> = if steps < 2 {
+        vec![start]
+    } else {
+        let offset = i64::from(end) - i64::from(start);
+        (0..steps)
+            .map(|step| (i64::from(start) + offset * i64::from(step) / i64::from(steps - 1)) as u32)
+            .collect()
+    };
+    times.dedup();
+    times
+        .into_iter()
+        .map(|time| CoordinateKeyframe {
+            time: SampleTime(time),
+            coords: coords_at(time),
+        })
+        .collect()
+}
+
+/// Something that makes a built scene questionable, without making it invalid,
+/// see `SceneBuilder::try_build`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildWarning {
+    /// The scene doesn't have any surfaces, so rays never bounce.
+    NoSurfaces,
+    /// The surface with the given index has no area at time 0, so it has no normal to bounce rays off of.
+    DegenerateSurface(usize),
+}
+
+impl Display for BuildWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSurfaces => write!(f, "the scene doesn't have any surfaces"),
+            Self::DegenerateSurface(index) => {
+                write!(
+                    f,
+                    "surface {index} has no area, rays hitting it are discarded"
+                )
+            }
         }
     }
 }
 
+/// A built scene along with the warnings raised while building it, see `SceneBuilder::try_build`.
+#[derive(Clone, Debug)]
+pub struct BuildResult {
+    pub scene: Scene,
+    pub warnings: Vec<BuildWarning>,
+}
+
 /// A builder to easily create scenes with.
+#[must_use = "call .build() to construct the Scene"]
 pub struct SceneBuilder {
     objects: Vec<Object>,
     receiver_coords: Option<Vector3<f64>>,
     receiver_keyframes: Option<Vec<CoordinateKeyframe>>,
     receiver_radius: f64,
+    occluding_receiver: bool,
     emitter_coords: Option<Vector3<f64>>,
     emitter_keyframes: Option<Vec<CoordinateKeyframe>>,
     emission_type: EmissionType,
-    loop_duration: Option<u32>,
+    loop_duration: Option<SampleDuration>,
+    strict_validation: bool,
+    boundary: SceneBoundary,
+    emitter_active_ranges: Option<Vec<(u32, u32)>>,
+    statistical_tail: Option<StatisticalTail>,
 }
 
 impl SceneBuilder {
@@ -390,21 +815,44 @@ impl SceneBuilder {
         self
     }
 
+    /// Add a static cube to the scene, rotated by `rotation` around its centre.
+    pub fn with_static_cube_rotated(
+        mut self,
+        bottom_left: (f64, f64, f64),
+        top_right: (f64, f64, f64),
+        rotation: Rotation3<f64>,
+        material: Material,
+    ) -> Self {
+        self.objects.push(Object::StaticCubeRotated(
+            Vector3::new(bottom_left.0, bottom_left.1, bottom_left.2),
+            Vector3::new(top_right.0, top_right.1, top_right.2),
+            rotation,
+            material,
+        ));
+        self
+    }
+
     /// Add a rotating cube to the scene.
+    /// It rotates around `axis`, going through `rotation_origin`,
+    /// described by `keyframes` keyframes per rotation, see `rotate`.
     #[allow(clippy::too_many_arguments)]
     pub fn with_rotating_cube(
         mut self,
         bottom_left: (f64, f64, f64),
         top_right: (f64, f64, f64),
         rotation_origin: (f64, f64, f64),
-        rotation_time: u32,
+        axis: (f64, f64, f64),
+        rotation_time: SampleDuration,
+        keyframes: u32,
         material: Material,
     ) -> Self {
         self.objects.push(Object::RotatingCube(
             Vector3::new(bottom_left.0, bottom_left.1, bottom_left.2),
             Vector3::new(top_right.0, top_right.1, top_right.2),
             Vector3::new(rotation_origin.0, rotation_origin.1, rotation_origin.2),
+            Vector3::new(axis.0, axis.1, axis.2),
             rotation_time,
+            keyframes,
             material,
         ));
         self
@@ -434,7 +882,9 @@ impl SceneBuilder {
         self
     }
 
-    /// Add a L to the scene.
+    /// Add a rotating L to the scene.
+    /// It rotates around `axis`, going through `rotation_origin`,
+    /// described by `keyframes` keyframes per rotation, see `rotate`.
     #[allow(clippy::too_many_arguments)]
     pub fn with_rotating_l(
         mut self,
@@ -445,7 +895,9 @@ impl SceneBuilder {
         width_2: f64,
         height: f64,
         rotation_origin: (f64, f64, f64),
-        rotation_time: u32,
+        axis: (f64, f64, f64),
+        rotation_time: SampleDuration,
+        keyframes: u32,
         material: Material,
     ) -> Self {
         self.objects.push(Object::RotatingL(
@@ -456,12 +908,131 @@ impl SceneBuilder {
             width_2,
             height,
             Vector3::new(rotation_origin.0, rotation_origin.1, rotation_origin.2),
+            Vector3::new(axis.0, axis.1, axis.2),
+            rotation_time,
+            keyframes,
+            material,
+        ));
+        self
+    }
+
+    /// Add a static sphere to the scene.
+    /// Each of its `20 * 4^subdivisions` triangles faces the inside of the sphere.
+    pub fn with_static_sphere(
+        mut self,
+        center: (f64, f64, f64),
+        radius: f64,
+        subdivisions: u32,
+        material: Material,
+    ) -> Self {
+        self.objects.push(Object::StaticSphere(
+            Vector3::new(center.0, center.1, center.2),
+            radius,
+            subdivisions,
+            material,
+        ));
+        self
+    }
+
+    /// Add a rotating sphere to the scene.
+    /// It rotates around `axis`, going through `rotation_origin`,
+    /// described by `keyframes` keyframes per rotation, see `rotate`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_rotating_sphere(
+        mut self,
+        center: (f64, f64, f64),
+        radius: f64,
+        subdivisions: u32,
+        rotation_origin: (f64, f64, f64),
+        axis: (f64, f64, f64),
+        rotation_time: SampleDuration,
+        keyframes: u32,
+        material: Material,
+    ) -> Self {
+        self.objects.push(Object::RotatingSphere(
+            Vector3::new(center.0, center.1, center.2),
+            radius,
+            subdivisions,
+            Vector3::new(rotation_origin.0, rotation_origin.1, rotation_origin.2),
+            Vector3::new(axis.0, axis.1, axis.2),
+            rotation_time,
+            keyframes,
+            material,
+        ));
+        self
+    }
+
+    /// Add a static, upright cylinder to the scene.
+    /// Each of its `4 * segments` triangles faces the inside of the cylinder.
+    pub fn with_static_cylinder(
+        mut self,
+        base_center: (f64, f64, f64),
+        radius: f64,
+        height: f64,
+        segments: u32,
+        material: Material,
+    ) -> Self {
+        self.objects.push(Object::StaticCylinder(
+            Vector3::new(base_center.0, base_center.1, base_center.2),
+            radius,
+            height,
+            segments,
+            material,
+        ));
+        self
+    }
+
+    /// Add a rotating cylinder to the scene.
+    /// It rotates around `axis`, going through `rotation_origin`,
+    /// described by `keyframes` keyframes per rotation, see `rotate`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_rotating_cylinder(
+        mut self,
+        base_center: (f64, f64, f64),
+        radius: f64,
+        height: f64,
+        segments: u32,
+        rotation_origin: (f64, f64, f64),
+        axis: (f64, f64, f64),
+        rotation_time: SampleDuration,
+        keyframes: u32,
+        material: Material,
+    ) -> Self {
+        self.objects.push(Object::RotatingCylinder(
+            Vector3::new(base_center.0, base_center.1, base_center.2),
+            radius,
+            height,
+            segments,
+            Vector3::new(rotation_origin.0, rotation_origin.1, rotation_origin.2),
+            Vector3::new(axis.0, axis.1, axis.2),
             rotation_time,
+            keyframes,
             material,
         ));
         self
     }
 
+    /// Add a static, flat panel to the scene, see `static_panel`.
+    pub fn with_static_panel(mut self, corners: [(f64, f64, f64); 4], material: Material) -> Self {
+        self.objects.push(Object::StaticPanel(
+            corners.map(|corner| Vector3::new(corner.0, corner.1, corner.2)),
+            material,
+        ));
+        self
+    }
+
+    /// Add all surfaces of another scene to the scene, moved by `translation`.
+    /// The other scene's emitter and receiver are discarded.
+    /// If the other scene loops, the built scene loops with the same duration,
+    /// see `Scene::merge`.
+    pub fn with_scene(mut self, scene: Scene, translation: (f64, f64, f64)) -> Self {
+        self.objects.push(Object::Scene(
+            Box::new(scene),
+            Vector3::new(translation.0, translation.1, translation.2),
+        ));
+        self
+    }
+
     /// Set the coordinates for the receiver.
     /// If coordinates or coordinate keyframes have previously been set,
     /// they are discarded in favour of the new coordinates.
@@ -471,21 +1042,54 @@ impl SceneBuilder {
         self
     }
 
+    /// Set the coordinates for the receiver like `with_receiver_at`, given as a vector.
+    pub fn with_receiver_at_vec(self, pos: Vector3<f64>) -> Self {
+        self.with_receiver_at(pos.x, pos.y, pos.z)
+    }
+
     /// Set the coordinate keyframes for the receiver.
     /// If coordinates or coordinate keyframes have previously been set,
     /// they are discarded in favour of the new coordinate keyframes.
+    /// The keyframes don't need to be in order, they are sorted by time.
     pub fn with_receiver_keyframes(mut self, coords: Vec<CoordinateKeyframe>) -> Self {
-        self.receiver_keyframes = Some(coords);
+        self.receiver_keyframes = Some(sort_keyframes(coords));
         self.receiver_coords = None;
         self
     }
 
+    /// Set the coordinate keyframes for the receiver by evaluating `coords_at` along a parametric path,
+    /// see `keyframes_from_fn`.
+    /// If coordinates or coordinate keyframes have previously been set,
+    /// they are discarded in favour of the new coordinate keyframes.
+    pub fn with_receiver_from_fn(
+        self,
+        coords_at: impl Fn(u32) -> Vector3<f64>,
+        start: u32,
+        end: u32,
+        steps: u32,
+    ) -> Self {
+        self.with_receiver_keyframes(keyframes_from_fn(coords_at, start, end, steps))
+    }
+
     /// Set the radius for the receiver.
-    pub const fn with_receiver_radius(mut self, radius: f64) -> Self {
+    /// A radius that isn't positive results in a receiver no ray can hit, so a warning is printed.
+    pub fn with_receiver_radius(mut self, radius: f64) -> Self {
+        if radius <= 0f64 {
+            eprintln!(
+                "WARNING: Receiver radius {radius} isn't positive, no rays will hit the receiver."
+            );
+        }
         self.receiver_radius = radius;
         self
     }
 
+    /// Make the receiver occluding, so rays hitting it are absorbed instead of passing through it.
+    /// This models physical receivers like a human head or a measurement rig, which block sound.
+    pub const fn with_occluding_receiver(mut self) -> Self {
+        self.occluding_receiver = true;
+        self
+    }
+
     /// Set the coordinates for the emitter.
     /// If coordinates or coordinate keyframes have previously been set,
     /// they are discarded in favour of the new coordinates.
@@ -495,15 +1099,35 @@ impl SceneBuilder {
         self
     }
 
+    /// Set the coordinates for the emitter like `with_emitter_at`, given as a vector.
+    pub fn with_emitter_at_vec(self, pos: Vector3<f64>) -> Self {
+        self.with_emitter_at(pos.x, pos.y, pos.z)
+    }
+
     /// Set the coordinate keyframes for the emitter.
     /// If coordinates or coordinate keyframes have previously been set,
     /// they are discarded in favour of the new coordinate keyframes.
+    /// The keyframes don't need to be in order, they are sorted by time.
     pub fn with_emitter_keyframes(mut self, coords: Vec<CoordinateKeyframe>) -> Self {
-        self.emitter_keyframes = Some(coords);
+        self.emitter_keyframes = Some(sort_keyframes(coords));
         self.emitter_coords = None;
         self
     }
 
+    /// Set the coordinate keyframes for the emitter by evaluating `coords_at` along a parametric path,
+    /// see `keyframes_from_fn`.
+    /// If coordinates or coordinate keyframes have previously been set,
+    /// they are discarded in favour of the new coordinate keyframes.
+    pub fn with_emitter_from_fn(
+        self,
+        coords_at: impl Fn(u32) -> Vector3<f64>,
+        start: u32,
+        end: u32,
+        steps: u32,
+    ) -> Self {
+        self.with_emitter_keyframes(keyframes_from_fn(coords_at, start, end, steps))
+    }
+
     /// Set the emission type to be randomised, i.e. rays are initially launched in all directions.
     pub const fn with_random_emission(mut self) -> Self {
         self.emission_type = EmissionType::Random;
@@ -516,6 +1140,50 @@ impl SceneBuilder {
         self
     }
 
+    /// Set the emission type to launch rays within a cone around a specific direction,
+    /// with the cone's half-angle given in radians, see `EmissionType::DirectedCone`.
+    pub fn with_directed_cone_emission(mut self, x: f64, y: f64, z: f64, half_angle: f64) -> Self {
+        self.emission_type = EmissionType::DirectedCone {
+            direction: Vector3::new(x, y, z).normalize(),
+            half_angle,
+        };
+        self
+    }
+
+    /// Spread the emitted rays evenly across the sphere, with each ray's direction randomly offset
+    /// by up to half a lattice cell times `jitter` (0 to 1), see `EmissionType::Stratified`.
+    pub const fn with_stratified_emission(mut self, jitter: f64) -> Self {
+        self.emission_type = EmissionType::Stratified { jitter };
+        self
+    }
+
+    /// Reject scenes with surfaces that don't touch any other surface when building,
+    /// see `check_connectivity`.
+    pub const fn with_strict_validation(mut self) -> Self {
+        self.strict_validation = true;
+        self
+    }
+
+    /// Set what happens to rays reaching the given face of the scene's maximum bounds.
+    /// All faces are open by default, e.g. reflecting the lower Z face models an infinite ground plane.
+    pub const fn with_boundary(mut self, face: BoundaryFace, behaviour: BoundaryBehaviour) -> Self {
+        self.boundary.set_face(face, behaviour);
+        self
+    }
+
+    /// Only let the emitter emit during the given sample ranges (start inclusive, end exclusive),
+    /// see `Scene::emitter_active_ranges`. For looping scenes, the ranges are times within the loop.
+    pub fn with_emitter_active_ranges(mut self, ranges: Vec<(u32, u32)>) -> Self {
+        self.emitter_active_ranges = Some(ranges);
+        self
+    }
+
+    /// Append the given statistical late reverb to each simulated impulse response, see `Scene::statistical_tail`.
+    pub const fn with_statistical_tail(mut self, tail: StatisticalTail) -> Self {
+        self.statistical_tail = Some(tail);
+        self
+    }
+
     /// Set the scene to not loop.
     pub const fn non_looping(mut self) -> Self {
         self.loop_duration = None;
@@ -523,29 +1191,84 @@ impl SceneBuilder {
     }
 
     /// Set the scene to loop with the specified duration.
-    pub const fn looping(mut self, duration: u32) -> Self {
+    pub const fn looping(mut self, duration: SampleDuration) -> Self {
         self.loop_duration = Some(duration);
         self
     }
 
+    /// Get the indices of all surfaces whose bounding box doesn't overlap with any other surface's bounding box.
+    /// This is a crude check for surfaces floating in free space, which are usually a modelling error.
+    /// The indices refer to the surfaces of the built scene.
+    pub fn check_connectivity(&self) -> Vec<usize> {
+        disconnected_surfaces(&self.surfaces())
+    }
+
+    /// Build all objects' surfaces.
+    fn surfaces(&self) -> Vec<SurfaceVariant> {
+        self.objects.iter().flat_map(Object::build).collect()
+    }
+
+    /// Check whether the built scene would have any surfaces.
+    /// Empty scenes are valid, but only useful for testing.
+    pub fn has_surfaces(&self) -> bool {
+        !self.surfaces().is_empty()
+    }
+
     /// Build the `Scene` described by the data passed into this `SceneBuilder`.
+    /// Warnings are discarded, see `try_build`.
     ///
-    /// # Panics
-    /// * If somehow neither coordinate keyframes nor coordinates for a receiver/emitter are set. This shouldn't be able to happen.
+    /// # Errors
+    /// * If the scene can't be built, see `try_build`.
+    pub fn build(&self) -> Result<Scene, DemoError> {
+        self.try_build().map(|result| result.scene)
+    }
+
+    /// Build the `Scene` described by the data passed into this `SceneBuilder`,
+    /// along with warnings about it, e.g. `BuildWarning::NoSurfaces` if it doesn't have any surfaces
+    /// or `BuildWarning::DegenerateSurface` for surfaces without a normal.
+    ///
+    /// # Errors
+    /// * `SceneBuildError::MissingReceiver`/`MissingEmitter` if neither coordinate keyframes nor coordinates for the receiver/emitter are set.
+    /// * `SceneBuildError::LoopDurationMismatch` if an added scene loops with a different duration
+    ///   than the scene being built or another added scene.
+    /// * `SceneBuildError::DegenerateRotationAxis` if a rotating object's axis is (nearly) zero.
+    /// * `SceneBuildError::DisconnectedSurface` with the first disconnected surface's index
+    ///   if strict validation is enabled and any surfaces are disconnected, see `check_connectivity`.
+    /// * If the resulting scene isn't valid, see `Scene::validate`.
     #[allow(clippy::option_if_let_else)]
-    pub fn build(&self) -> Scene {
-        let objects: Vec<Vec<Surface<3>>> = self.objects.iter().map(Object::build).collect();
-        let mut surfaces: Vec<Surface<3>> = Vec::with_capacity(objects.len() * 6);
-        for object in &objects {
-            surfaces.extend_from_slice(object);
+    pub fn try_build(&self) -> Result<BuildResult, DemoError> {
+        let mut loop_duration = self.loop_duration;
+        for object in &self.objects {
+            if let Object::Scene(scene, _translation) = object {
+                loop_duration = merged_loop_duration(loop_duration, scene.loop_duration)?;
+            }
+        }
+        if self
+            .objects
+            .iter()
+            .filter_map(Object::rotation_axis)
+            .any(is_degenerate_normal)
+        {
+            return Err(SceneBuildError::DegenerateRotationAxis.into());
+        }
+        let surfaces = self.surfaces();
+        if self.strict_validation {
+            if let Some(index) = disconnected_surfaces(&surfaces).first() {
+                return Err(SceneBuildError::DisconnectedSurface(*index).into());
+            }
         }
 
         let receiver = if let Some(coords) = self.receiver_coords {
-            Receiver::Interpolated(coords, self.receiver_radius, 0)
+            Receiver::Interpolated(coords, self.receiver_radius, 0, self.occluding_receiver)
         } else if let Some(keyframes) = &self.receiver_keyframes {
-            Receiver::Keyframes(keyframes.clone(), self.receiver_radius)
+            Receiver::Keyframes(
+                keyframes.clone(),
+                self.receiver_radius,
+                None,
+                self.occluding_receiver,
+            )
         } else {
-            panic!("Somehow, neither receiver_keyframes nor receiver_coords was set. This shouldn't happen.")
+            return Err(SceneBuildError::MissingReceiver.into());
         };
 
         let emitter = if let Some(coords) = self.emitter_coords {
@@ -553,18 +1276,59 @@ impl SceneBuilder {
         } else if let Some(keyframes) = &self.emitter_keyframes {
             Emitter::Keyframes(keyframes.clone(), self.emission_type)
         } else {
-            panic!("Somehow, neither emitter_keyframes nor emitter_coords was set. This shouldn't happen.")
+            return Err(SceneBuildError::MissingEmitter.into());
         };
 
-        Scene {
+        let scene = Scene {
             surfaces,
             receiver,
             emitter,
-            loop_duration: self.loop_duration,
-        }
+            loop_duration,
+            bounds_cache: None,
+            boundary: self.boundary,
+            emitter_active_ranges: self.emitter_active_ranges.clone(),
+            statistical_tail: self.statistical_tail,
+        };
+        scene.validate()?;
+        let mut warnings = if scene.surfaces.is_empty() {
+            vec![BuildWarning::NoSurfaces]
+        } else {
+            vec![]
+        };
+        warnings.extend(
+            scene
+                .surfaces
+                .iter()
+                .enumerate()
+                .filter(|(_index, surface)| {
+                    surface
+                        .normal_at_time(0)
+                        .is_ok_and(|normal| is_degenerate_normal(&normal))
+                })
+                .map(|(index, _surface)| BuildWarning::DegenerateSurface(index)),
+        );
+        Ok(BuildResult { scene, warnings })
     }
 }
 
+/// Get the indices of all given surfaces whose bounding box doesn't overlap with any other surface's bounding box,
+/// see `SceneBuilder::check_connectivity`. Touching bounding boxes count as overlapping.
+fn disconnected_surfaces(surfaces: &[SurfaceVariant]) -> Vec<usize> {
+    let bounds: Vec<(Vector3<f64>, Vector3<f64>)> =
+        surfaces.iter().map(MaximumBounds::maximum_bounds).collect();
+    let overlaps = |(min_a, max_a): &(Vector3<f64>, Vector3<f64>),
+                    (min_b, max_b): &(Vector3<f64>, Vector3<f64>)| {
+        (0..3).all(|axis| min_a[axis] <= max_b[axis] && min_b[axis] <= max_a[axis])
+    };
+    (0..bounds.len())
+        .filter(|index| {
+            !bounds.iter().enumerate().any(|(other, other_bounds)| {
+                other != *index && overlaps(&bounds[*index], other_bounds)
+            })
+        })
+        .collect()
+}
+
 impl Default for SceneBuilder {
     fn default() -> Self {
         Self {
@@ -572,16 +1336,24 @@ impl Default for SceneBuilder {
             receiver_coords: Some(Vector3::new(0f64, 0f64, 0f64)),
             receiver_keyframes: None,
             receiver_radius: 0.1f64,
+            occluding_receiver: false,
             emitter_coords: Some(Vector3::new(0f64, 0f64, 0f64)),
             emitter_keyframes: None,
             emission_type: EmissionType::Random,
             loop_duration: None,
+            strict_validation: false,
+            boundary: SceneBoundary::default(),
+            emitter_active_ranges: None,
+            statistical_tail: None,
         }
     }
 }
 
 /// A scene inside a static cube.
 /// The cube is 4x4x3 meters in size.
+///
+/// # Panics
+/// * Never, the scene is valid by construction.
 pub fn static_cube_scene() -> Scene {
     SceneBuilder::new()
         .with_static_cube(
@@ -591,26 +1363,36 @@ pub fn static_cube_scene() -> Scene {
         )
         .with_emitter_at(0f64, 0f64, 1.2f64)
         .build()
+        .expect("Predefined scenes should be valid")
 }
 
 /// A scene inside a rotating cube.
 /// The cube is 4x4x3 meters in size.
+///
+/// # Panics
+/// * If `sample_rate` is 0.
 pub fn rotating_cube_scene(sample_rate: u32) -> Scene {
     SceneBuilder::new()
         .with_rotating_cube(
             (-2f64, -2f64, -1.5f64),
             (2f64, 2f64, 1.5f64),
             (0f64, 0f64, 0f64),
-            sample_rate,
+            (0f64, 0f64, 1f64),
+            SampleDuration(sample_rate),
+            DEFAULT_ROTATION_KEYFRAMES,
             MATERIAL_CONCRETE_WALL,
         )
         .with_emitter_at(0f64, 0f64, 1.2f64)
-        .looping(sample_rate)
+        .looping(SampleDuration(sample_rate))
         .build()
+        .expect("Predefined scenes should be valid")
 }
 
 /// A scene inside a rotating cube.
 /// The cube is 4x4x3 meters in size.
+///
+/// # Panics
+/// * If `sample_rate` is 0.
 pub fn rotating_l_scene(sample_rate: u32) -> Scene {
     SceneBuilder::new()
         .with_rotating_l(
@@ -621,55 +1403,138 @@ pub fn rotating_l_scene(sample_rate: u32) -> Scene {
             2f64,
             2f64,
             (0f64, 0f64, 0f64),
-            sample_rate * 3,
+            (0f64, 0f64, 1f64),
+            SampleDuration(sample_rate * 3),
+            DEFAULT_ROTATION_KEYFRAMES,
             MATERIAL_CONCRETE_WALL,
         )
         .with_emitter_at(0f64, 0f64, 0.5f64)
-        .looping(sample_rate * 3)
+        .looping(SampleDuration(sample_rate * 3))
         .build()
+        .expect("Predefined scenes should be valid")
 }
 
 /// A scene without surfaces,
 /// where the receiver is exactly 1 second of travelling at the speed of sound away from the emitter.
+///
+/// # Panics
+/// * Never, the scene is valid by construction.
 pub fn static_receiver_scene() -> Scene {
     SceneBuilder::new()
         .with_directed_emission(1f64, 0f64, 0f64)
         .with_receiver_at(343.3f64, 0f64, 0f64)
         .build()
+        .expect("Predefined scenes should be valid")
 }
 
 /// A scene without surfaces, where the receiver starts 1 second of speed of sound travel away
 /// and approaches the emitter at 1/9th the speed of sound.
+///
+/// # Panics
+/// * Never, the scene is valid by construction.
 pub fn approaching_receiver_scene(sample_rate: u32) -> Scene {
     SceneBuilder::new()
         .with_directed_emission(1f64, 0f64, 0f64)
         .with_receiver_keyframes(vec![
             CoordinateKeyframe {
                 coords: Vector3::new(343.3f64, 0f64, 0f64),
-                time: 0,
+                time: SampleTime(0),
             },
             CoordinateKeyframe {
                 coords: Vector3::new(0f64, 0f64, 0f64),
-                time: sample_rate * 9,
+                time: SampleTime(sample_rate * 9),
             },
         ])
         .build()
+        .expect("Predefined scenes should be valid")
 }
 
 /// A scene without surfaces, where the receiver starts 4 seconds of speed of sound travel away
 /// and approaches the emitter at 1/9th the speed of sound.
+///
+/// # Panics
+/// * Never, the scene is valid by construction.
 pub fn long_approaching_receiver_scene(sample_rate: u32) -> Scene {
     SceneBuilder::new()
         .with_directed_emission(1f64, 0f64, 0f64)
         .with_receiver_keyframes(vec![
             CoordinateKeyframe {
                 coords: Vector3::new(343.3f64 * 4f64, 0f64, 0f64),
-                time: 0,
+                time: SampleTime(0),
             },
             CoordinateKeyframe {
                 coords: Vector3::new(0f64, 0f64, 0f64),
-                time: sample_rate * 9 * 4,
+                time: SampleTime(sample_rate * 9 * 4),
             },
         ])
         .build()
+        .expect("Predefined scenes should be valid")
+}
+
+/// A predefined scene, see `available_scenes`.
+#[derive(Clone, Copy, Debug)]
+pub struct SceneDescriptor {
+    /// The scene's human-readable name.
+    pub name: &'static str,
+    /// The scene's short identifier, e.g. for selecting it on the command line.
+    pub slug: &'static str,
+    /// A one-sentence description of the scene's geometry and movement.
+    pub description: &'static str,
+    /// Build the scene for the given sample rate. Static scenes ignore the sample rate.
+    pub build: fn(u32) -> Scene,
+}
+
+/// Get all predefined scenes. A scene's index in the list is its scene index.
+pub fn available_scenes() -> Vec<SceneDescriptor> {
+    vec![
+        SceneDescriptor {
+            name: "Static Cube",
+            slug: "static-cube",
+            description: "A static 4x4x3 m concrete cube with the emitter above the receiver in its center.",
+            build: |_sample_rate| static_cube_scene(),
+        },
+        SceneDescriptor {
+            name: "Static Receiver",
+            slug: "static-receiver",
+            description: "No surfaces, the receiver is 1 second of sound travel away from the directed emitter.",
+            build: |_sample_rate| static_receiver_scene(),
+        },
+        SceneDescriptor {
+            name: "Approaching Receiver 1s",
+            slug: "approaching-receiver",
+            description: "No surfaces, the receiver starts 1 second of sound travel away and approaches the emitter at 1/9th the speed of sound.",
+            build: approaching_receiver_scene,
+        },
+        SceneDescriptor {
+            name: "Approaching Receiver 4s",
+            slug: "long-approaching-receiver",
+            description: "No surfaces, the receiver starts 4 seconds of sound travel away and approaches the emitter at 1/9th the speed of sound.",
+            build: long_approaching_receiver_scene,
+        },
+        SceneDescriptor {
+            name: "Rotating Cube 1s",
+            slug: "rotating-cube",
+            description: "A 4x4x3 m concrete cube rotating around the vertical axis once per second, looping.",
+            build: rotating_cube_scene,
+        },
+        SceneDescriptor {
+            name: "Rotating L 1s",
+            slug: "rotating-l",
+            description: "A large L-shaped concrete room rotating around the vertical axis once every 3 seconds, looping.",
+            build: rotating_l_scene,
+        },
+    ]
+}
+
+/// Find the predefined scene with the given slug or scene index, see `available_scenes`.
+/// Returns the scene's index and descriptor.
+pub fn find_scene(slug_or_index: &str) -> Option<(u32, SceneDescriptor)> {
+    let scenes = available_scenes();
+    let index = slug_or_index.parse::<usize>().ok().or_else(|| {
+        scenes
+            .iter()
+            .position(|descriptor| descriptor.slug == slug_or_index)
+    })?;
+    let descriptor = *scenes.get(index)?;
+    Some((u32::try_from(index).ok()?, descriptor))
 }
@@ -0,0 +1,191 @@
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::ThreadPoolBuilder;
+
+use crate::{
+    defaults::Defaults,
+    error::{DemoError, SimulationError},
+    impulse_response::NormalisationMode,
+};
+
+/// The default fraction of a ray's initial energy below which it gets discarded,
+/// see `Defaults::RELATIVE_ENERGY_THRESHOLD`.
+pub const DEFAULT_RELATIVE_ENERGY_THRESHOLD: f64 = Defaults::RELATIVE_ENERGY_THRESHOLD;
+/// The default number of input samples processed per parallel task.
+pub const DEFAULT_SAMPLES_PER_TASK: usize = 100;
+
+/// How rays find their next intersection.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TraversalMode {
+    /// Traverse the scene chunk by chunk, only checking the objects in chunks the ray passes through.
+    #[default]
+    Chunked,
+    /// Check every surface and the receiver directly for every bounce, without using chunks.
+    /// This is a lot slower and meant as a reference for cross-checking the chunk traversal.
+    BruteForce,
+}
+
+/// How the directions of diffuse bounces are sampled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DiffuseSampling {
+    /// Directions are weighted by the cosine of their angle to the surface normal,
+    /// which matches ideal diffuse (Lambertian) reflection.
+    #[default]
+    CosineWeighted,
+    /// Directions are uniformly distributed in the hemisphere above the surface.
+    /// This over-represents grazing directions and is only meant for comparison.
+    Uniform,
+}
+
+/// The sample type impulse responses are applied to the input in, see `impulse_response::IrSample`.
+/// Impulse responses are always accumulated as `f64`, and all geometry is calculated as `f64` as well.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum IrPrecision {
+    #[default]
+    F64,
+    /// Convert each impulse response to `f32` once it's done and apply it in `f32`,
+    /// which halves the memory (bandwidth) of impulse responses and the parallel tasks' output buffers.
+    /// `f32` has a precision of about 7 significant digits, so the output deviates from `F64`
+    /// by roughly 1e-7 relative to its peak, well below the quantisation of 16-bit audio (3e-5)
+    /// but about the size of 24-bit audio's (1.2e-7).
+    F32,
+}
+
+impl IrPrecision {
+    /// Get the size of a single impulse response sample as it's applied, in bytes.
+    pub const fn bytes_per_sample(self) -> usize {
+        match self {
+            Self::F64 => std::mem::size_of::<f64>(),
+            Self::F32 => std::mem::size_of::<f32>(),
+        }
+    }
+}
+
+/// Configuration for how a simulation is run.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimulationConfig {
+    /// Rays get discarded once their energy drops below this fraction of their initial energy.
+    pub relative_energy_threshold: f64,
+    /// How the received energies are normalised when creating impulse responses.
+    pub normalisation_mode: NormalisationMode,
+    /// The number of threads to run the simulation on.
+    /// `None` (or `Some(0)`) uses rayon's global thread pool, which uses all cores.
+    /// `Some(1)` runs the simulation fully serially, without any rayon overhead.
+    pub threads: Option<usize>,
+    /// The number of input samples each parallel task simulates impulse responses for.
+    /// Smaller values spread short inputs across more threads. This needs to be positive.
+    pub samples_per_task: usize,
+    /// If set, stop accepting receiver hits once the Schroeder integral of the accumulated
+    /// impulse response has dropped this many dB below the direct sound.
+    /// This is checked between batches of rays until the cutoff stops changing, see `impulse_response::decay_cutoff_time`.
+    /// If unset, all hits until the rays' energy threshold are accepted.
+    pub capture_decay_db: Option<f64>,
+    /// How rays find their next intersection.
+    pub traversal_mode: TraversalMode,
+    /// How the directions of diffuse bounces are sampled.
+    pub diffuse_sampling: DiffuseSampling,
+    /// Whether randomly emitted rays are aimed at uniformly random points on the scene's surfaces,
+    /// so each surface receives rays proportionally to its area, see `Scene::random_surface_point`.
+    /// This makes large walls contribute proportionally more to the late reverb.
+    /// The rays' energies aren't reweighted, so this changes the result rather than just its noise.
+    /// Directed emission isn't affected.
+    pub use_area_weighted_emission: bool,
+    /// The fraction (0 to 1) of the convolved (wet) signal in the output,
+    /// with the rest being the unprocessed (dry) input, see `impulse_response::mix_dry_signal`.
+    /// 1 only outputs the convolved signal.
+    pub dry_wet: f64,
+    /// The gain in dB applied to the input, for both the dry and the wet signal.
+    pub input_gain_db: f64,
+    /// The fraction (0 to below 1) of randomly emitted rays launched within a cone
+    /// around the receiver as seen from the emitter, where it is when their direct sound arrives.
+    /// The cone is somewhat wider than the receiver, see `scene::RECEIVER_CONE_WIDENING`.
+    /// Their energies are weighted down so the result stays unbiased and just gets less noisy
+    /// for distant receivers, see `bounce::importance_weight`. 0 disables this.
+    pub receiver_importance: f64,
+    /// Whether to record every surface bounce and receiver hit of every ray,
+    /// see `SceneData::simulate_at_time_with_echogram`. This only works for serial simulations.
+    pub record_echogram: bool,
+    /// The sample type impulse responses are applied to the input in.
+    pub ir_precision: IrPrecision,
+    /// Whether each ray is launched at a random offset from 0 to below 1 samples after its sample's time,
+    /// from the emitter's position interpolated at that offset.
+    /// Otherwise, all rays of a sample see the emitter at the same position,
+    /// which causes combing in the output when the emitter moves quickly.
+    /// The rays' arrival times are still rounded to whole samples once they hit the receiver.
+    pub launch_jitter: bool,
+    /// The seed of the noise making up statistical tails, see `Scene::statistical_tail`.
+    /// Each impulse response's tail is seeded with this plus the time it's simulated at, so they differ
+    /// from each other but are reproducible. `None` uses a random seed. The rays themselves aren't seeded.
+    pub seed: Option<u64>,
+    /// The scene time the first input sample is simulated at, in samples.
+    /// Input sample `i` is simulated at `time_offset + i`, so a window in the middle of a long scene
+    /// can be simulated without everything before it, see `Scene::input_sample_time`.
+    /// This only affects `SceneData::simulate_for_time_span` and the like,
+    /// simulations at a single time are simply passed the time they're simulated at.
+    pub time_offset: u32,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            relative_energy_threshold: DEFAULT_RELATIVE_ENERGY_THRESHOLD,
+            normalisation_mode: NormalisationMode::PerRayCount,
+            threads: None,
+            samples_per_task: DEFAULT_SAMPLES_PER_TASK,
+            capture_decay_db: None,
+            traversal_mode: TraversalMode::Chunked,
+            diffuse_sampling: DiffuseSampling::CosineWeighted,
+            use_area_weighted_emission: false,
+            dry_wet: 1f64,
+            input_gain_db: 0f64,
+            receiver_importance: 0f64,
+            record_echogram: false,
+            ir_precision: IrPrecision::F64,
+            launch_jitter: false,
+            seed: None,
+            time_offset: 0,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Get the random number generator for the statistical tail of the impulse response simulated
+    /// at the given time, see `seed`.
+    pub fn tail_rng(&self, time: u32) -> StdRng {
+        self.seed.map_or_else(StdRng::from_entropy, |seed| {
+            StdRng::seed_from_u64(seed.wrapping_add(<u64 as From<u32>>::from(time)))
+        })
+    }
+
+    /// Get the default config for the given conditions.
+    /// The config doesn't hold the sample rate and propagation speed,
+    /// pass `defaults.sample_rate` and `defaults.propagation_speed` to the simulation functions along with it.
+    pub fn from_defaults(defaults: &Defaults) -> Self {
+        Self {
+            relative_energy_threshold: defaults.relative_energy_threshold,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the simulation should run fully serially.
+    pub const fn is_serial(&self) -> bool {
+        matches!(self.threads, Some(1))
+    }
+
+    /// Run the given operation with this config's number of threads.
+    /// For more than one thread, a dedicated thread pool is built and `op` is run inside it,
+    /// so all parallel work within `op` is limited to that pool.
+    ///
+    /// # Errors
+    ///
+    /// * `SimulationError::ThreadPool` if the thread pool can't be built.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> Result<R, DemoError> {
+        match self.threads {
+            Some(threads) if threads > 1 => Ok(ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|err| SimulationError::ThreadPool(err.to_string()))?
+                .install(op)),
+            _ => Ok(op()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+/// A horizontal grid of receiver positions, see `SceneData::sweep_receiver_grid`.
+/// Both ranges are inclusive, the grid points are spaced `step` meters apart starting at each range's minimum.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ReceiverGrid {
+    /// The height of the grid.
+    pub z: f64,
+    /// The minimum and maximum x coordinate.
+    pub x_range: (f64, f64),
+    /// The minimum and maximum y coordinate.
+    pub y_range: (f64, f64),
+    /// The distance between neighbouring grid points. Needs to be positive, otherwise the grid has no points.
+    pub step: f64,
+}
+
+impl ReceiverGrid {
+    /// Get the x coordinates of the grid's columns, in ascending order.
+    pub fn xs(&self) -> Vec<f64> {
+        self.coordinates(self.x_range)
+    }
+
+    /// Get the y coordinates of the grid's rows, in ascending order.
+    pub fn ys(&self) -> Vec<f64> {
+        self.coordinates(self.y_range)
+    }
+
+    /// Get the (x, y) coordinates of all grid points, row by row.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let xs = self.xs();
+        self.ys()
+            .into_iter()
+            .flat_map(|y| xs.iter().map(move |x| (*x, y)))
+            .collect()
+    }
+
+    /// Get the coordinates from the range's minimum up to its maximum, `step` apart.
+    /// A small tolerance keeps the maximum despite rounding errors.
+    fn coordinates(&self, (min, max): (f64, f64)) -> Vec<f64> {
+        if self.step <= 0f64 || max < min {
+            return vec![];
+        }
+        let count = ((max - min) / self.step + 1e-9).floor() as usize + 1;
+        (0..count)
+            .map(|index| (index as f64).mul_add(self.step, min))
+            .collect()
+    }
+}
+
+/// What the receiver picked up at a single grid point.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GridPoint {
+    pub x: f64,
+    pub y: f64,
+    /// The sum of the point's impulse response.
+    pub total_energy: f64,
+    /// The sample the direct sound arrived at, relative to the simulated time.
+    /// `None` if no ray reached the point.
+    pub first_arrival: Option<usize>,
+}
+
+/// The result of sweeping a receiver across a grid, see `SceneData::sweep_receiver_grid`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GridResult {
+    pub grid: ReceiverGrid,
+    /// The grid's points, row by row like `ReceiverGrid::points`.
+    pub points: Vec<GridPoint>,
+}
+
+/// The dynamic range of the heat maps written by `GridResult::write_pgm`, in dB.
+pub const HEAT_MAP_RANGE_DB: f64 = 60f64;
+
+impl GridResult {
+    /// Write the grid points to the given writer in CSV format.
+    /// Each row holds the point's x and y coordinates, total energy and first arrival sample
+    /// (empty if no ray reached the point).
+    ///
+    /// # Errors
+    ///
+    /// * If writing to `writer` fails.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "x;y;total_energy;first_arrival_sample")?;
+        for point in &self.points {
+            writeln!(
+                writer,
+                "{};{};{};{}",
+                point.x,
+                point.y,
+                point.total_energy,
+                point
+                    .first_arrival
+                    .map_or_else(String::new, |arrival| arrival.to_string())
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the total energies as a plain (ASCII) PGM heat map to the given writer,
+    /// with one pixel per grid point, x increasing to the right and y increasing upwards.
+    /// The loudest point is white, points `HEAT_MAP_RANGE_DB` below it or without energy are black.
+    ///
+    /// # Errors
+    ///
+    /// * If writing to `writer` fails.
+    pub fn write_pgm<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let width = self.grid.xs().len();
+        let height = self.points.len().checked_div(width).unwrap_or(0);
+        let max_energy = self
+            .points
+            .iter()
+            .map(|point| point.total_energy)
+            .fold(0f64, f64::max);
+        writeln!(writer, "P2\n{width} {height}\n255")?;
+        for row in self.points.chunks(width.max(1)).rev() {
+            let values: Vec<String> = row
+                .iter()
+                .map(|point| heat_map_value(point.total_energy, max_energy).to_string())
+                .collect();
+            writeln!(writer, "{}", values.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the grey value (0 to 255) of a point with the given energy, see `GridResult::write_pgm`.
+fn heat_map_value(energy: f64, max_energy: f64) -> u8 {
+    if energy <= 0f64 || max_energy <= 0f64 {
+        return 0;
+    }
+    let level_db = 10f64 * (energy / max_energy).log10();
+    (((level_db + HEAT_MAP_RANGE_DB) / HEAT_MAP_RANGE_DB).clamp(0f64, 1f64) * 255f64).round() as u8
+}
@@ -0,0 +1,182 @@
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Instant,
+};
+
+/// Whether traversal work is counted, see `TraversalCounters::start`.
+static COUNT_TRAVERSAL: AtomicBool = AtomicBool::new(false);
+/// The number of chunks visited by rays since counting started.
+static CHUNK_VISITS: AtomicU64 = AtomicU64::new(0);
+/// The number of intersection tests run by rays since counting started.
+static INTERSECTION_TESTS: AtomicU64 = AtomicU64::new(0);
+/// Held by tests that count traversal work, so they don't reset or stop each other's counts.
+#[cfg(test)]
+pub(crate) static COUNTING_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// A breakdown of where a simulation spent its time, in nanoseconds.
+/// The parts are summed up over all impulse responses, so when these are simulated in parallel,
+/// they can add up to more than `total_ns`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Telemetry {
+    /// The time spent building the scene's chunks, including those of snapshots.
+    pub chunk_build_ns: u64,
+    /// The time spent launching rays and bouncing them through the scene.
+    pub ray_launch_ns: u64,
+    /// The time spent turning the rays' receiver hits into impulse responses.
+    pub ir_construction_ns: u64,
+    /// The total wall-clock time, including building the scene's chunks.
+    pub total_ns: u64,
+}
+
+impl Telemetry {
+    /// Get the nanoseconds elapsed since `start`, saturating at `u64::MAX`.
+    pub fn elapsed_ns(start: Instant) -> u64 {
+        u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+    }
+}
+
+impl Add for Telemetry {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            chunk_build_ns: self.chunk_build_ns.saturating_add(other.chunk_build_ns),
+            ray_launch_ns: self.ray_launch_ns.saturating_add(other.ray_launch_ns),
+            ir_construction_ns: self
+                .ir_construction_ns
+                .saturating_add(other.ir_construction_ns),
+            total_ns: self.total_ns.saturating_add(other.total_ns),
+        }
+    }
+}
+
+impl AddAssign for Telemetry {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// Format a duration in nanoseconds as milliseconds.
+fn format_ms(nanoseconds: u64) -> String {
+    format!("{:.3}ms", nanoseconds as f64 / 1e6)
+}
+
+impl Display for Telemetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "\tChunk construction: {}",
+            format_ms(self.chunk_build_ns)
+        )?;
+        writeln!(f, "\tRay launches: {}", format_ms(self.ray_launch_ns))?;
+        writeln!(
+            f,
+            "\tImpulse response construction: {}",
+            format_ms(self.ir_construction_ns)
+        )?;
+        write!(f, "\tTotal: {}", format_ms(self.total_ns))
+    }
+}
+
+/// How much work tracing rays took, counted across all threads while enabled.
+/// Counting is off by default, so it doesn't cost anything beyond checking a flag.
+/// Since the counters are global, runs counted at the same time are added up.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TraversalCounters {
+    /// The number of set chunks rays traversed, see `Chunks::is_chunk_set`.
+    pub chunk_visits: u64,
+    /// The number of intersection tests between rays and surfaces or the receiver.
+    pub intersection_tests: u64,
+}
+
+impl TraversalCounters {
+    /// Reset the counters and start counting.
+    pub fn start() {
+        CHUNK_VISITS.store(0, Ordering::Relaxed);
+        INTERSECTION_TESTS.store(0, Ordering::Relaxed);
+        COUNT_TRAVERSAL.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop counting and get the counts since `start` was called.
+    pub fn finish() -> Self {
+        COUNT_TRAVERSAL.store(false, Ordering::Relaxed);
+        Self {
+            chunk_visits: CHUNK_VISITS.load(Ordering::Relaxed),
+            intersection_tests: INTERSECTION_TESTS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Count a visit to a set chunk, if counting.
+    pub(crate) fn record_chunk_visit() {
+        if COUNT_TRAVERSAL.load(Ordering::Relaxed) {
+            CHUNK_VISITS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count the given number of intersection tests, if counting.
+    pub(crate) fn record_intersection_tests(tests: u64) {
+        if COUNT_TRAVERSAL.load(Ordering::Relaxed) {
+            INTERSECTION_TESTS.fetch_add(tests, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Display for TraversalCounters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\tChunk visits: {}", self.chunk_visits)?;
+        write!(f, "\tIntersection tests: {}", self.intersection_tests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::PoisonError;
+
+    use super::{Telemetry, TraversalCounters, COUNTING_LOCK};
+
+    #[test]
+    fn traversal_counters_count_while_started() {
+        let _counting = COUNTING_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        TraversalCounters::start();
+        TraversalCounters::record_chunk_visit();
+        TraversalCounters::record_intersection_tests(3);
+        // other tests may trace rays at the same time, so only lower bounds are known
+        let counters = TraversalCounters::finish();
+        assert!(counters.chunk_visits >= 1);
+        assert!(counters.intersection_tests >= 3);
+        assert_eq!(
+            "\tChunk visits: 2\n\tIntersection tests: 5",
+            TraversalCounters {
+                chunk_visits: 2,
+                intersection_tests: 5
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn telemetry_accumulates_and_formats() {
+        let mut telemetry = Telemetry {
+            chunk_build_ns: 1_000_000,
+            ray_launch_ns: 2_500_000,
+            ir_construction_ns: 500,
+            total_ns: 4_000_000,
+        };
+        telemetry += Telemetry {
+            chunk_build_ns: 1_000_000,
+            ..Telemetry::default()
+        };
+        assert_eq!(
+            "\tChunk construction: 2.000ms\n\tRay launches: 2.500ms\n\tImpulse response construction: 0.001ms\n\tTotal: 4.000ms",
+            telemetry.to_string()
+        );
+        telemetry += Telemetry {
+            ray_launch_ns: u64::MAX,
+            ..Telemetry::default()
+        };
+        assert_eq!(u64::MAX, telemetry.ray_launch_ns);
+        assert_eq!(500, telemetry.ir_construction_ns);
+    }
+}
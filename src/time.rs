@@ -0,0 +1,232 @@
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Sub},
+};
+
+/// A point in time, in samples since the start of the simulation (or of the loop, for keyframes of looping scenes).
+/// Keeping this separate from plain numbers makes the compiler catch times accidentally given in seconds,
+/// see `from_seconds` for converting them.
+/// Adding and subtracting times and durations panics on overflow, even in release builds,
+/// see `saturating_add` and `saturating_duration_since` for clamping instead.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct SampleTime(pub u32);
+
+/// A length of time in samples, e.g. the difference between two `SampleTime`s.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct SampleDuration(pub u32);
+
+/// Convert the given number of seconds to samples at the given sample rate, rounding to the nearest sample.
+/// Negative times are clamped to 0 and times beyond `u32::MAX` samples to `u32::MAX`.
+fn seconds_to_samples(seconds: f64, sample_rate: f64) -> u32 {
+    (seconds * sample_rate).round() as u32
+}
+
+impl SampleTime {
+    /// The start of the simulation.
+    pub const ZERO: Self = Self(0);
+
+    /// Get the sample at the given number of seconds, see `seconds_to_samples`.
+    pub fn from_seconds(seconds: f64, sample_rate: f64) -> Self {
+        Self(seconds_to_samples(seconds, sample_rate))
+    }
+
+    /// Get this time in seconds at the given sample rate.
+    pub fn to_seconds(self, sample_rate: f64) -> f64 {
+        <f64 as From<u32>>::from(self.0) / sample_rate
+    }
+
+    /// Get this time as a plain number of samples.
+    pub const fn samples(self) -> u32 {
+        self.0
+    }
+
+    /// Get the time the given duration after this one, staying at `u32::MAX` samples instead of overflowing.
+    pub const fn saturating_add(self, duration: SampleDuration) -> Self {
+        Self(self.0.saturating_add(duration.0))
+    }
+
+    /// Get the duration from the given earlier time to this one, or 0 if it's later than this one.
+    pub const fn saturating_duration_since(self, earlier: Self) -> SampleDuration {
+        SampleDuration(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl SampleDuration {
+    /// Get the number of samples in the given number of seconds, see `seconds_to_samples`.
+    pub fn from_seconds(seconds: f64, sample_rate: f64) -> Self {
+        Self(seconds_to_samples(seconds, sample_rate))
+    }
+
+    /// Get this duration in seconds at the given sample rate.
+    pub fn to_seconds(self, sample_rate: f64) -> f64 {
+        <f64 as From<u32>>::from(self.0) / sample_rate
+    }
+
+    /// Get this duration as a plain number of samples.
+    pub const fn samples(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for SampleTime {
+    fn from(samples: u32) -> Self {
+        Self(samples)
+    }
+}
+
+impl From<SampleTime> for u32 {
+    fn from(time: SampleTime) -> Self {
+        time.0
+    }
+}
+
+impl From<u32> for SampleDuration {
+    fn from(samples: u32) -> Self {
+        Self(samples)
+    }
+}
+
+impl From<SampleDuration> for u32 {
+    fn from(duration: SampleDuration) -> Self {
+        duration.0
+    }
+}
+
+impl Add<SampleDuration> for SampleTime {
+    type Output = Self;
+
+    fn add(self, duration: SampleDuration) -> Self {
+        Self(
+            self.0
+                .checked_add(duration.0)
+                .expect("sample time overflowed u32::MAX samples"),
+        )
+    }
+}
+
+impl AddAssign<SampleDuration> for SampleTime {
+    fn add_assign(&mut self, duration: SampleDuration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub for SampleTime {
+    type Output = SampleDuration;
+
+    fn sub(self, earlier: Self) -> SampleDuration {
+        SampleDuration(
+            self.0
+                .checked_sub(earlier.0)
+                .expect("subtracted a later sample time from an earlier one"),
+        )
+    }
+}
+
+impl Sub<SampleDuration> for SampleTime {
+    type Output = Self;
+
+    fn sub(self, duration: SampleDuration) -> Self {
+        Self(
+            self.0
+                .checked_sub(duration.0)
+                .expect("sample time went below sample 0"),
+        )
+    }
+}
+
+impl Add for SampleDuration {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(
+            self.0
+                .checked_add(other.0)
+                .expect("sample duration overflowed u32::MAX samples"),
+        )
+    }
+}
+
+impl Sub for SampleDuration {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(
+            self.0
+                .checked_sub(other.0)
+                .expect("subtracted a longer sample duration from a shorter one"),
+        )
+    }
+}
+
+impl Display for SampleTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sample {}", self.0)
+    }
+}
+
+impl Display for SampleDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} samples", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SampleDuration, SampleTime};
+
+    #[test]
+    fn seconds_are_converted_to_samples() {
+        assert_eq!(SampleTime(66150), SampleTime::from_seconds(1.5, 44100f64));
+        assert_eq!(
+            SampleDuration(48),
+            SampleDuration::from_seconds(0.001, 48000f64)
+        );
+        // rounded to the nearest sample, with negative times clamped to the start
+        assert_eq!(
+            SampleTime(1),
+            SampleTime::from_seconds(0.6 / 44100f64, 44100f64)
+        );
+        assert_eq!(SampleTime::ZERO, SampleTime::from_seconds(-1f64, 44100f64));
+        assert!((SampleTime(66150).to_seconds(44100f64) - 1.5).abs() < 1e-12);
+        assert!((SampleDuration(48).to_seconds(48000f64) - 0.001).abs() < 1e-12);
+    }
+
+    #[test]
+    fn times_and_durations_combine() {
+        let start = SampleTime(100);
+        let end = start + SampleDuration(50);
+        assert_eq!(SampleTime(150), end);
+        assert_eq!(SampleDuration(50), end - start);
+        assert_eq!(start, end - SampleDuration(50));
+        assert_eq!(SampleDuration(0), start.saturating_duration_since(end));
+        assert_eq!(
+            SampleTime(u32::MAX),
+            end.saturating_add(SampleDuration(u32::MAX))
+        );
+        assert_eq!(SampleDuration(75), SampleDuration(50) + SampleDuration(25));
+
+        let mut time = SampleTime::from(10);
+        time += SampleDuration::from(5);
+        assert_eq!(15, u32::from(time));
+        assert_eq!("sample 15", time.to_string());
+        assert_eq!("5 samples", SampleDuration(5).to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "sample time overflowed")]
+    fn adding_past_the_last_sample_panics() {
+        let _ = SampleTime(u32::MAX) + SampleDuration(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "subtracted a later sample time")]
+    fn subtracting_a_later_time_panics() {
+        let _ = SampleTime(10) - SampleTime(11);
+    }
+
+    #[test]
+    #[should_panic(expected = "below sample 0")]
+    fn subtracting_past_the_start_panics() {
+        let _ = SampleTime(10) - SampleDuration(11);
+    }
+}
@@ -0,0 +1,66 @@
+use approx::assert_abs_diff_eq;
+use demo::{
+    analysis::{receiver_kinematics, write_kinematics_csv},
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene_builder,
+    time::SampleDuration,
+};
+
+#[test]
+fn approaching_receiver_has_constant_velocity() {
+    let sample_rate = 44100;
+    let duration = sample_rate * 9;
+    let kinematics = receiver_kinematics(
+        &scene_builder::approaching_receiver_scene(sample_rate),
+        f64::from(sample_rate),
+        DEFAULT_PROPAGATION_SPEED,
+        duration,
+    )
+    .unwrap();
+
+    assert_eq!(duration as usize, kinematics.len());
+    let speed = 343.3f64 / 9f64;
+    // the emitter is standing still, while the receiver approaches it head-on
+    let doppler_factor = (DEFAULT_PROPAGATION_SPEED + speed) / DEFAULT_PROPAGATION_SPEED;
+    for row in kinematics.iter().step_by(997) {
+        assert_abs_diff_eq!(speed, row.velocity.norm(), epsilon = 1e-6);
+        assert_abs_diff_eq!(-speed, row.velocity.x, epsilon = 1e-6);
+        assert_abs_diff_eq!(doppler_factor, row.doppler_factor, epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            343.3f64 - speed * f64::from(row.time) / f64::from(sample_rate),
+            row.position.x,
+            epsilon = 1e-6
+        );
+    }
+}
+
+#[test]
+fn looping_receiver_kinematics_wrap_around() {
+    let mut scene = scene_builder::approaching_receiver_scene(100);
+    scene.loop_duration = Some(SampleDuration(450));
+    let kinematics = receiver_kinematics(&scene, 100f64, DEFAULT_PROPAGATION_SPEED, 1000).unwrap();
+
+    assert_eq!(kinematics[10].position, kinematics[460].position);
+    assert_eq!(kinematics[10].velocity, kinematics[910].velocity);
+    // the receiver jumps back to its starting position at the end of the loop
+    assert!(kinematics[449].velocity.x > 0f64);
+}
+
+#[test]
+fn kinematics_csv_has_a_row_per_sample() {
+    let kinematics = receiver_kinematics(
+        &scene_builder::approaching_receiver_scene(100),
+        100f64,
+        DEFAULT_PROPAGATION_SPEED,
+        3,
+    )
+    .unwrap();
+    let mut csv = vec![];
+    write_kinematics_csv(&kinematics, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(4, lines.len());
+    assert!(lines[0].starts_with("time;position_x"));
+    assert!(lines[1].starts_with("0;343.3;0;0;"));
+}
@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+
+use demo::audio_io::{
+    describe_input, open_audio, read_input, sample_count, sample_rate_note, unit_impulse_input,
+    write_f64_raw, AudioIoError, InputError, OutputFormat,
+};
+use wav::BitDepth;
+
+/// CRC-8 as used for FLAC frame headers (polynomial 0x07).
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x07
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-16 as used for FLAC frame footers (polynomial 0x8005).
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x8005
+            };
+        }
+    }
+    crc
+}
+
+/// Encode the given samples as a mono, 16-bit FLAC file using only verbatim subframes.
+fn encode_flac(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 4096;
+    let mut result = b"fLaC".to_vec();
+    // STREAMINFO, marked as the last metadata block
+    result.extend_from_slice(&[0x80, 0, 0, 34]);
+    result.extend_from_slice(&(BLOCK_SIZE as u16).to_be_bytes());
+    result.extend_from_slice(&(BLOCK_SIZE as u16).to_be_bytes());
+    result.extend_from_slice(&[0; 6]); // unknown min/max frame size
+
+    // sample rate, 1 channel (stored as 0), 16 bits per sample (stored as 15), sample count
+    let packed: u64 = (u64::from(sample_rate) << 44) | (15u64 << 36) | samples.len() as u64;
+    result.extend_from_slice(&packed.to_be_bytes());
+    result.extend_from_slice(&[0; 16]); // MD5 signature unknown
+
+    for (frame_number, block) in samples.chunks(BLOCK_SIZE).enumerate() {
+        let mut frame = vec![
+            0xFF,
+            0xF8,
+            0x70, // block size stored at end of header, sample rate from STREAMINFO
+            0x08, // mono, 16 bits per sample
+            frame_number as u8,
+        ];
+        frame.extend_from_slice(&((block.len() - 1) as u16).to_be_bytes());
+        frame.push(crc8(&frame));
+        frame.push(0x02); // verbatim subframe, no wasted bits
+        for sample in block {
+            frame.extend_from_slice(&sample.to_be_bytes());
+        }
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_be_bytes());
+        result.extend_from_slice(&frame);
+    }
+    result
+}
+
+fn temp_file_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("demo_audio_io_{}_{name}", std::process::id()))
+}
+
+#[test]
+fn open_synthetic_flac() {
+    let samples: Vec<i16> = (0..10000)
+        .map(|idx| ((f64::from(idx) * 0.05).sin() * 10000f64) as i16)
+        .collect();
+    let path = temp_file_path("sine.flac");
+    std::fs::write(&path, encode_flac(&samples, 22050)).unwrap();
+
+    let result = open_audio(&path);
+    std::fs::remove_file(&path).unwrap();
+    let input = result.unwrap();
+
+    assert_eq!(22050, input.sample_rate);
+    assert_eq!(1, input.channel_count);
+    let BitDepth::Sixteen(data) = input.data else {
+        panic!("FLAC data with 16 bits per sample wasn't returned as i16!")
+    };
+    assert_eq!(samples.len(), data.len());
+    assert_eq!(samples, data);
+}
+
+#[test]
+fn open_wav_keeps_sample_rate() {
+    let input = open_audio(Path::new("testfiles/sine_440Hz_1second.wav")).unwrap();
+    assert_eq!(44100, input.sample_rate);
+    assert_eq!(input.sample_rate, input.wav_header().sampling_rate);
+}
+
+#[test]
+fn open_unsupported_format() {
+    let result = open_audio(Path::new("testfiles/speech.mp3"));
+    assert!(
+        matches!(result, Err(AudioIoError::UnsupportedFormat(extension)) if extension == "mp3")
+    );
+}
+
+#[test]
+fn write_f64_raw_with_sidecar() {
+    let path = temp_file_path("result.raw");
+    let samples = vec![0f64, 16384f64, -32768f64];
+    write_f64_raw(&path, &samples, &BitDepth::Sixteen(vec![]), 22050, 1).unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    let mut sidecar_path = path.clone().into_os_string();
+    sidecar_path.push(".json");
+    let sidecar = std::fs::read_to_string(&sidecar_path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&sidecar_path).unwrap();
+
+    let values: Vec<f64> = data
+        .chunks_exact(8)
+        .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    assert_eq!(vec![0f64, 0.5f64, -1f64], values);
+    assert_eq!(
+        "{\"sample_rate\": 22050, \"length\": 3, \"channels\": 1, \"format\": \"f64le\"}\n",
+        sidecar
+    );
+}
+
+#[test]
+fn output_format_names() {
+    assert_eq!(Some(OutputFormat::Same), OutputFormat::from_name("same"));
+    assert_eq!(Some(OutputFormat::Float32), OutputFormat::from_name("f32"));
+    assert_eq!(
+        Some(OutputFormat::Float64Raw),
+        OutputFormat::from_name("f64-raw")
+    );
+    assert_eq!(None, OutputFormat::from_name("f64"));
+}
+
+#[test]
+fn read_empty_wav_input() {
+    let result = read_input(Path::new("testfiles/empty.wav"));
+    let Err(err) = result else {
+        panic!("Empty input file was read successfully!")
+    };
+    assert!(matches!(err, InputError::Empty));
+    assert_eq!(
+        "the input file doesn't contain any samples",
+        err.to_string()
+    );
+}
+
+#[test]
+fn read_8khz_wav_input() {
+    let (header, data) = read_input(Path::new("testfiles/sine_440Hz_8kHz.wav")).unwrap();
+
+    assert_eq!(8000, header.sampling_rate);
+    assert_eq!(2000, sample_count(&data));
+    assert_eq!(
+        "Input: 8000 Hz, 1 channel(s), 0.250s (2000 samples)",
+        describe_input(&header, &data)
+    );
+    let note = sample_rate_note(header.sampling_rate).unwrap();
+    assert!(note.contains("8000 Hz differs from the default of 44100 Hz"));
+    assert_eq!(None, sample_rate_note(44100));
+}
+
+#[test]
+fn unit_impulse_replaces_missing_input() {
+    let (header, data) = unit_impulse_input(44100);
+    assert_eq!(44100, header.sampling_rate);
+    assert_eq!(1, header.channel_count);
+    assert_eq!(BitDepth::ThirtyTwoFloat(vec![1f32]), data);
+}
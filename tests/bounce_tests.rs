@@ -0,0 +1,296 @@
+use demo::bounce::{
+    cone_solid_angle, importance_weight, is_degenerate_normal,
+    random_cosine_weighted_direction_in_hemisphere, random_direction, random_direction_density,
+    random_direction_in_cone, random_direction_in_hemisphere, random_point_in_triangle,
+    spherical_fibonacci_direction, EmissionType, MIN_DIFFUSE_COSINE,
+};
+use demo::scene::RECEIVER_CONE_WIDENING;
+use nalgebra::Vector3;
+
+/// The chi-square statistic of sorting the polar angles of the given directions around the given normal
+/// into `bins` bins that each hold the same fraction of directions if their polar angles θ are distributed
+/// according to cos(θ)sin(θ) (up to the minimum cosine).
+fn cosine_weighted_chi_square(
+    normal: &Vector3<f64>,
+    directions: &[Vector3<f64>],
+    bins: usize,
+) -> f64 {
+    let normal = normal.normalize();
+    let mut counts = vec![0u32; bins];
+    for direction in directions {
+        let cos_theta = direction.dot(&normal);
+        // the cumulative distribution of cos(θ)sin(θ) is proportional to sin²(θ)
+        let cumulative = cos_theta.mul_add(-cos_theta, 1f64)
+            / MIN_DIFFUSE_COSINE.mul_add(-MIN_DIFFUSE_COSINE, 1f64);
+        counts[((cumulative * bins as f64) as usize).min(bins - 1)] += 1;
+    }
+    let expected = directions.len() as f64 / bins as f64;
+    counts
+        .iter()
+        .map(|count| (f64::from(*count) - expected).powi(2) / expected)
+        .sum()
+}
+
+#[test]
+fn cosine_weighted_polar_angles_follow_lambert() {
+    let normal = Vector3::new(0.3f64, -1f64, 0.5f64);
+    let directions: Vec<Vector3<f64>> = (0..10000)
+        .map(|_| random_cosine_weighted_direction_in_hemisphere(&normal))
+        .collect();
+    for direction in &directions {
+        assert!((direction.norm() - 1f64).abs() < 1e-9);
+        assert!(direction.dot(&normal.normalize()) >= MIN_DIFFUSE_COSINE - 1e-9);
+    }
+    // the critical value for 9 degrees of freedom at p = 0.0001
+    let chi_square = cosine_weighted_chi_square(&normal, &directions, 10);
+    assert!(chi_square < 33.72, "{chi_square}");
+}
+
+#[test]
+fn uniform_polar_angles_do_not_follow_lambert() {
+    let normal = Vector3::new(0.3f64, -1f64, 0.5f64);
+    let directions: Vec<Vector3<f64>> = (0..10000)
+        .map(|_| random_direction_in_hemisphere(&normal))
+        .collect();
+    for direction in &directions {
+        assert!((direction.norm() - 1f64).abs() < 1e-9);
+        assert!(direction.dot(&normal.normalize()) >= MIN_DIFFUSE_COSINE - 1e-9);
+    }
+    let chi_square = cosine_weighted_chi_square(&normal, &directions, 10);
+    assert!(chi_square > 33.72, "{chi_square}");
+}
+
+#[test]
+fn degenerate_normals_fall_back_to_whole_sphere() {
+    for normal in [Vector3::zeros(), Vector3::new(f64::NAN, 0f64, 1f64)] {
+        assert!(is_degenerate_normal(&normal));
+        for _ in 0..100 {
+            for direction in [
+                random_direction_in_hemisphere(&normal),
+                random_cosine_weighted_direction_in_hemisphere(&normal),
+            ] {
+                assert!((direction.norm() - 1f64).abs() < 1e-9);
+            }
+        }
+    }
+    assert!(!is_degenerate_normal(&Vector3::new(0f64, 1e-3, 0f64)));
+}
+
+#[test]
+fn cone_with_zero_half_angle_returns_direction() {
+    let direction = Vector3::new(1f64, 2f64, -1f64);
+    let emission_type = EmissionType::DirectedCone {
+        direction,
+        half_angle: 0f64,
+    };
+    for _ in 0..10 {
+        assert_eq!(direction, emission_type.get_direction(0, 1));
+    }
+}
+
+#[test]
+fn cone_directions_are_uniformly_distributed_within_cone() {
+    let direction = Vector3::new(1f64, 2f64, -1f64);
+    let axis = direction.normalize();
+    let half_angle = 0.3f64;
+    let samples = 10000u32;
+
+    let cosines: Vec<f64> = (0..samples)
+        .map(|_| random_direction_in_cone(&direction, half_angle))
+        .map(|sample| {
+            assert!((sample.norm() - 1f64).abs() < 1e-9);
+            sample.dot(&axis)
+        })
+        .collect();
+
+    assert!(cosines
+        .iter()
+        .all(|cosine| cosine.clamp(-1f64, 1f64).acos() <= half_angle + 1e-9));
+    // uniformly distributed directions cover equal areas of the spherical cap equally often,
+    // so half of them lie closer to the axis than the cosine halfway between 1 and the edge's cosine
+    let halfway_cosine = f64::midpoint(1f64, half_angle.cos());
+    let inner_fraction = cosines
+        .iter()
+        .filter(|cosine| **cosine >= halfway_cosine)
+        .count() as f64
+        / f64::from(samples);
+    assert!((0.45..0.55).contains(&inner_fraction), "{inner_fraction}");
+}
+
+#[test]
+fn wide_cone_covers_opposite_direction() {
+    let direction = Vector3::new(0f64, 0f64, 1f64);
+    let has_backwards_direction = (0..1000)
+        .map(|_| random_direction_in_cone(&direction, std::f64::consts::PI))
+        .any(|sample| sample.z < -0.5f64);
+    assert!(has_backwards_direction);
+}
+
+#[test]
+fn random_points_are_spread_evenly_inside_triangle() {
+    // a right triangle with its right angle at the origin, inside the unit square
+    let triangle = [
+        Vector3::new(0f64, 0f64, 2f64),
+        Vector3::new(1f64, 0f64, 2f64),
+        Vector3::new(0f64, 1f64, 2f64),
+    ];
+    let points: Vec<Vector3<f64>> = (0..20000)
+        .map(|_| random_point_in_triangle(&triangle))
+        .collect();
+    assert!(points
+        .iter()
+        .all(|point| point.x >= 0f64 && point.y >= 0f64 && point.x + point.y <= 1f64 + 1e-12));
+    assert!(points.iter().all(|point| (point.z - 2f64).abs() < 1e-12));
+    // the part with x + y < 0.5 covers a quarter of the triangle's area
+    let near_corner = points
+        .iter()
+        .filter(|point| point.x + point.y < 0.5f64)
+        .count();
+    let fraction = near_corner as f64 / points.len() as f64;
+    assert!((fraction - 0.25f64).abs() < 0.02, "fraction {fraction}");
+}
+
+#[test]
+fn random_direction_density_matches_sampled_directions() {
+    // the density integrates to 1 over the sphere
+    let uniform_directions = (0..100_000)
+        .map(|_| random_direction_in_cone(&Vector3::new(0f64, 0f64, 1f64), std::f64::consts::PI));
+    let mean_density = uniform_directions
+        .map(|direction| random_direction_density(&direction))
+        .sum::<f64>()
+        / 100_000f64;
+    let integral = mean_density * 4f64 * std::f64::consts::PI;
+    assert!((integral - 1f64).abs() < 0.02, "integral {integral}");
+
+    // random directions within a narrow cone along an axis,
+    // compared to the density averaged over the cone since it rises towards the cone's edge
+    let half_angle = 0.2f64;
+    let axis = Vector3::new(1f64, 0f64, 0f64);
+    let inside = (0..200_000)
+        .filter(|_| random_direction().normalize().dot(&axis) >= half_angle.cos())
+        .count();
+    let fraction = inside as f64 / 200_000f64;
+    let mean_cone_density = (0..100_000)
+        .map(|_| random_direction_density(&random_direction_in_cone(&axis, half_angle)))
+        .sum::<f64>()
+        / 100_000f64;
+    let expected = mean_cone_density * cone_solid_angle(half_angle);
+    assert!(
+        (fraction - expected).abs() < 0.12 * expected,
+        "{fraction} vs. {expected}"
+    );
+}
+
+#[test]
+fn importance_weight_inside_and_outside_cone() {
+    let cone_direction = Vector3::new(0f64, 0f64, 1f64);
+    let half_angle = 0.1f64;
+    let outside = importance_weight(
+        &Vector3::new(1f64, 0f64, 0f64),
+        &cone_direction,
+        half_angle,
+        0.2,
+    );
+    assert!((outside - 1.25f64).abs() < 1e-12);
+    let inside = importance_weight(&cone_direction, &cone_direction, half_angle, 0.2);
+    let density = random_direction_density(&cone_direction);
+    let expected = density / 0.8f64.mul_add(density, 0.2f64 / cone_solid_angle(half_angle));
+    assert!((inside - expected).abs() < 1e-12);
+    assert!(inside < 1f64);
+}
+
+#[test]
+fn importance_sampling_a_widened_cone_is_unbiased_with_bounded_weights() {
+    let axis = Vector3::new(0f64, 0f64, 1f64);
+    let receiver_half_angle = 0.05f64;
+    let cone_half_angle = receiver_half_angle * RECEIVER_CONE_WIDENING;
+    let importance = 0.5f64;
+    let count = 100_000u32;
+    let weighted_hits: Vec<f64> = (0..count)
+        .map(|_| {
+            let direction = if rand::random::<f64>() < importance {
+                random_direction_in_cone(&axis, cone_half_angle)
+            } else {
+                random_direction()
+            };
+            let hits_receiver = direction.normalize().dot(&axis) >= receiver_half_angle.cos();
+            if hits_receiver {
+                importance_weight(&direction, &axis, cone_half_angle, importance)
+            } else {
+                0f64
+            }
+        })
+        .collect();
+    let hit_weights: Vec<f64> = weighted_hits
+        .iter()
+        .copied()
+        .filter(|w| *w > 0f64)
+        .collect();
+    let max_weight = hit_weights.iter().copied().fold(f64::MIN, f64::max);
+    let min_weight = hit_weights.iter().copied().fold(f64::MAX, f64::min);
+    // no weight of a receiver hit is far larger than the others
+    assert!(
+        max_weight / min_weight < 1.05f64,
+        "{min_weight} to {max_weight}"
+    );
+
+    let mean = weighted_hits.iter().sum::<f64>() / f64::from(count);
+    let variance = weighted_hits
+        .iter()
+        .map(|w| (w - mean).powi(2))
+        .sum::<f64>()
+        / f64::from(count - 1);
+    let standard_error = (variance / f64::from(count)).sqrt();
+    // the density 1 / (24 cos³θ) of `random_direction` integrated over the cap around the z axis
+    let expected = std::f64::consts::PI / 24f64 * receiver_half_angle.tan().powi(2);
+    assert!(
+        (mean - expected).abs() < 5f64 * standard_error,
+        "{mean} vs {expected} ± {standard_error}"
+    );
+    // far fewer rays than that are needed without importance sampling for the same accuracy
+    assert!(standard_error < 0.02f64 * expected, "{standard_error}");
+}
+
+#[test]
+fn stratified_directions_without_jitter_are_deterministic_and_evenly_spaced() {
+    let count = 500u32;
+    let emission_type = EmissionType::Stratified { jitter: 0f64 };
+    let directions: Vec<Vector3<f64>> = (0..count)
+        .map(|index| emission_type.get_direction(index, count))
+        .collect();
+    let repeated: Vec<Vector3<f64>> = (0..count)
+        .map(|index| spherical_fibonacci_direction(index, count, 0f64))
+        .collect();
+    assert_eq!(directions, repeated);
+
+    // each direction covers 4π / count steradians, so neighbours are about this far apart
+    let cell_width = (4f64 * std::f64::consts::PI / f64::from(count)).sqrt();
+    for (index, direction) in directions.iter().enumerate() {
+        assert!((direction.norm() - 1f64).abs() < 1e-9);
+        let nearest_angle = directions
+            .iter()
+            .enumerate()
+            .filter(|(other_index, _other)| *other_index != index)
+            .map(|(_other_index, other)| direction.angle(other))
+            .fold(f64::MAX, f64::min);
+        assert!(
+            nearest_angle > 0.6 * cell_width && nearest_angle < 1.4 * cell_width,
+            "{index}: {nearest_angle} vs. {cell_width}"
+        );
+    }
+    // the directions balance out like uniformly distributed ones
+    let mean: Vector3<f64> = directions.iter().sum::<Vector3<f64>>() / f64::from(count);
+    assert!(mean.norm() < 1e-2, "{mean:?}");
+}
+
+#[test]
+fn jittered_stratified_directions_stay_near_their_lattice_points() {
+    let count = 200u32;
+    let cell_width = (4f64 * std::f64::consts::PI / f64::from(count)).sqrt();
+    for index in 0..count {
+        let lattice_point = spherical_fibonacci_direction(index, count, 0f64);
+        let jittered = spherical_fibonacci_direction(index, count, 1f64);
+        assert!((jittered.norm() - 1f64).abs() < 1e-9);
+        assert!(lattice_point.angle(&jittered) < cell_width);
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use demo::chunk::{Chunks, SceneChunk, TimedChunkEntry};
+use demo::time::{SampleDuration, SampleTime};
+use generic_array::GenericArray;
+use nalgebra::Vector3;
+use proptest::prelude::*;
+
+const MAX_LOOP_DURATION: u32 = 200;
+const MAX_LOOP_ITERATION: u32 = 50;
+
+/// Generate a chunk entry for one of a few objects, with all timestamps lying within a loop of the given duration.
+fn entry_strategy(loop_duration: u32) -> impl Strategy<Value = TimedChunkEntry> {
+    let index = 0..8usize;
+    prop_oneof![
+        index.clone().prop_map(TimedChunkEntry::Static),
+        (index.clone(), 0..loop_duration)
+            .prop_map(|(index, entry)| TimedChunkEntry::Final(index, SampleTime(entry))),
+        (index, 0..loop_duration, 0..loop_duration).prop_map(|(index, first, second)| {
+            TimedChunkEntry::Dynamic(
+                index,
+                SampleTime(first.min(second)),
+                SampleTime(first.max(second)),
+            )
+        }),
+    ]
+}
+
+/// Generate a loop duration and a chunk's receiver and surface entries fitting into it.
+fn chunk_strategy() -> impl Strategy<Value = (u32, SceneChunk)> {
+    (1..MAX_LOOP_DURATION).prop_flat_map(|loop_duration| {
+        (
+            Just(loop_duration),
+            (
+                prop::collection::vec(entry_strategy(loop_duration), 0..4),
+                prop::collection::vec(entry_strategy(loop_duration), 0..12),
+            )
+                .prop_map(|(receivers, surfaces)| SceneChunk {
+                    surfaces,
+                    receivers,
+                }),
+        )
+    })
+}
+
+/// Wrap the given chunk into chunks holding only it, at key 0.
+fn single_chunk(chunk: SceneChunk) -> Chunks<typenum::U2> {
+    Chunks {
+        set_chunks: GenericArray::default(),
+        chunks: HashMap::from([(0, chunk)]),
+        size_x: 1f64,
+        size_y: 1f64,
+        size_z: 1f64,
+        chunk_starts: Vector3::zeros(),
+    }
+}
+
+/// Get the objects in the chunk at key 0 during the given time span, sorted to compare them regardless of order.
+fn sorted_objects(
+    chunks: &Chunks<typenum::U2>,
+    time_entry: u32,
+    time_exit: u32,
+    loop_duration: Option<SampleDuration>,
+) -> (Vec<usize>, Vec<usize>) {
+    let (mut receivers, mut surfaces) = chunks.objects_at_key_and_time(
+        0,
+        SampleTime(time_entry),
+        SampleTime(time_exit),
+        loop_duration,
+    );
+    receivers.sort_unstable();
+    surfaces.sort_unstable();
+    (receivers, surfaces)
+}
+
+/// Get the sorted indices of all objects with an entry in the given entries.
+fn all_objects(entries: &[TimedChunkEntry]) -> Vec<usize> {
+    let mut indices: Vec<usize> = entries
+        .iter()
+        .map(|entry| match entry {
+            TimedChunkEntry::Dynamic(index, ..)
+            | TimedChunkEntry::Periodic(index, ..)
+            | TimedChunkEntry::Static(index)
+            | TimedChunkEntry::Final(index, ..) => *index,
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+proptest! {
+    #[test]
+    fn span_within_one_loop_matches_non_looping(
+        (loop_duration, chunk) in chunk_strategy(),
+        iteration in 0..MAX_LOOP_ITERATION,
+        first in 0..MAX_LOOP_DURATION,
+        second in 0..MAX_LOOP_DURATION,
+    ) {
+        let (first, second) = (first % loop_duration, second % loop_duration);
+        let (start, end) = (first.min(second), first.max(second));
+        let chunks = single_chunk(chunk);
+        let offset = iteration * loop_duration;
+
+        prop_assert_eq!(
+            sorted_objects(&chunks, start, end, None),
+            sorted_objects(&chunks, offset + start, offset + end, Some(SampleDuration(loop_duration)))
+        );
+    }
+
+    #[test]
+    fn span_covering_two_loops_contains_all_objects(
+        (loop_duration, chunk) in chunk_strategy(),
+        time_entry in 0..MAX_LOOP_ITERATION * MAX_LOOP_DURATION,
+        extra_time in 0..MAX_LOOP_DURATION,
+    ) {
+        let expected = (all_objects(&chunk.receivers), all_objects(&chunk.surfaces));
+        let chunks = single_chunk(chunk);
+        let time_exit = time_entry + 2 * loop_duration + extra_time;
+
+        prop_assert_eq!(
+            expected,
+            sorted_objects(&chunks, time_entry, time_exit, Some(SampleDuration(loop_duration)))
+        );
+    }
+
+    #[test]
+    fn single_time_matches_time_within_loop(
+        (loop_duration, chunk) in chunk_strategy(),
+        time in 0..MAX_LOOP_ITERATION * MAX_LOOP_DURATION,
+    ) {
+        let chunks = single_chunk(chunk);
+        let looped_time = time % loop_duration;
+
+        prop_assert_eq!(
+            sorted_objects(&chunks, looped_time, looped_time, None),
+            sorted_objects(&chunks, time, time, Some(SampleDuration(loop_duration)))
+        );
+    }
+}
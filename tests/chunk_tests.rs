@@ -4,26 +4,40 @@ use generic_array::GenericArray;
 
 use demo::{
     bounce::EmissionType,
+    boundary::SceneBoundary,
     chunk::{Chunks, SceneChunk, TimedChunkEntry},
-    materials::MATERIAL_CONCRETE_WALL,
-    scene::{CoordinateKeyframe, Emitter, Receiver, Scene, Surface, SurfaceData, SurfaceKeyframe},
+    materials::{Material, MATERIAL_CONCRETE_WALL},
+    ray::{Ray, DEFAULT_PROPAGATION_SPEED},
+    scene::{
+        CoordinateKeyframe, Emitter, Receiver, Scene, SceneData, Surface, SurfaceData,
+        SurfaceKeyframe, SurfaceVariant,
+    },
+    scene_bounds::MaximumBounds,
     scene_builder,
+    simulation_config::{SimulationConfig, TraversalMode},
+    time::{SampleDuration, SampleTime},
+    DEFAULT_SAMPLE_RATE,
 };
 use itertools::Itertools;
 use nalgebra::Vector3;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 fn empty_scene() -> Scene {
     Scene {
-        receiver: Receiver::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0.1, 0),
+        receiver: Receiver::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0.1, 0, false),
         surfaces: vec![],
         emitter: Emitter::Keyframes(
             vec![CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             }],
             EmissionType::Random,
         ),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     }
 }
 
@@ -32,27 +46,36 @@ fn static_scene_moving_receiver() -> Scene {
         receiver: Receiver::Keyframes(
             vec![
                 CoordinateKeyframe {
-                    time: 10,
+                    time: SampleTime(10),
                     coords: Vector3::new(-1f64, -1f64, -1f64),
                 },
                 CoordinateKeyframe {
-                    time: 20,
+                    time: SampleTime(20),
                     coords: Vector3::new(1f64, -1f64, 0f64),
                 },
                 CoordinateKeyframe {
-                    time: 40,
+                    time: SampleTime(40),
                     coords: Vector3::new(1f64, 1f64, 1f64),
                 },
             ],
             0.1,
+            None,
+            false,
         ),
         surfaces: scene_builder::static_cube(
             Vector3::new(-10f64, -10f64, -10f64),
             Vector3::new(10f64, 10f64, 10f64),
             MATERIAL_CONCRETE_WALL,
-        ),
+        )
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     }
 }
 
@@ -65,7 +88,7 @@ fn moving_scene_and_receiver() -> Scene {
     surfaces.push(Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: [
                     Vector3::new(2f64, 2f64, 2f64),
                     Vector3::new(2f64, 2f64, 3f64),
@@ -73,7 +96,7 @@ fn moving_scene_and_receiver() -> Scene {
                 ],
             },
             SurfaceKeyframe {
-                time: 500,
+                time: SampleTime(500),
                 coords: [
                     Vector3::new(6f64, 6f64, 6f64),
                     Vector3::new(6f64, 6f64, 7f64),
@@ -82,28 +105,35 @@ fn moving_scene_and_receiver() -> Scene {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
+        None,
     ));
     Scene {
         receiver: Receiver::Keyframes(
             vec![
                 CoordinateKeyframe {
-                    time: 10,
+                    time: SampleTime(10),
                     coords: Vector3::new(-1f64, -1f64, -1f64),
                 },
                 CoordinateKeyframe {
-                    time: 20,
+                    time: SampleTime(20),
                     coords: Vector3::new(1f64, -1f64, 0f64),
                 },
                 CoordinateKeyframe {
-                    time: 40,
+                    time: SampleTime(40),
                     coords: Vector3::new(1f64, 1f64, 1f64),
                 },
             ],
             0.1,
+            None,
+            false,
         ),
-        surfaces,
+        surfaces: surfaces.into_iter().map(SurfaceVariant::Triangle).collect(),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     }
 }
 
@@ -116,7 +146,7 @@ fn looping_moving_scene_and_receiver() -> Scene {
     surfaces.push(Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: [
                     Vector3::new(2f64, 2f64, 2f64),
                     Vector3::new(2f64, 2f64, 3f64),
@@ -124,7 +154,7 @@ fn looping_moving_scene_and_receiver() -> Scene {
                 ],
             },
             SurfaceKeyframe {
-                time: 480,
+                time: SampleTime(480),
                 coords: [
                     Vector3::new(6f64, 6f64, 6f64),
                     Vector3::new(6f64, 6f64, 7f64),
@@ -133,32 +163,39 @@ fn looping_moving_scene_and_receiver() -> Scene {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
+        None,
     ));
     Scene {
         receiver: Receiver::Keyframes(
             vec![
                 CoordinateKeyframe {
-                    time: 10,
+                    time: SampleTime(10),
                     coords: Vector3::new(-1f64, -1f64, -1f64),
                 },
                 CoordinateKeyframe {
-                    time: 20,
+                    time: SampleTime(20),
                     coords: Vector3::new(1f64, -1f64, 0f64),
                 },
                 CoordinateKeyframe {
-                    time: 40,
+                    time: SampleTime(40),
                     coords: Vector3::new(1f64, 1f64, 1f64),
                 },
                 CoordinateKeyframe {
-                    time: 480,
+                    time: SampleTime(480),
                     coords: Vector3::new(1f64, 1f64, 1f64),
                 },
             ],
             0.1,
+            None,
+            false,
         ),
-        surfaces,
+        surfaces: surfaces.into_iter().map(SurfaceVariant::Triangle).collect(),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
-        loop_duration: Some(480),
+        loop_duration: Some(SampleDuration(480)),
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     }
 }
 
@@ -273,9 +310,9 @@ fn chunks_static_scene_moving_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 0, 10),
-                TimedChunkEntry::Dynamic(0, 10, 14),
-                TimedChunkEntry::Dynamic(0, 15, 15),
+                TimedChunkEntry::Dynamic(0, SampleTime(0), SampleTime(10)),
+                TimedChunkEntry::Dynamic(0, SampleTime(10), SampleTime(14)),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
             ],
         },
     );
@@ -284,10 +321,10 @@ fn chunks_static_scene_moving_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 15, 15),
-                TimedChunkEntry::Dynamic(0, 16, 18),
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
+                TimedChunkEntry::Dynamic(0, SampleTime(16), SampleTime(18)),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
             ],
         },
     );
@@ -296,10 +333,10 @@ fn chunks_static_scene_moving_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
-                TimedChunkEntry::Dynamic(0, 22, 28),
-                TimedChunkEntry::Dynamic(0, 29, 30),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
+                TimedChunkEntry::Dynamic(0, SampleTime(22), SampleTime(28)),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
             ],
         },
     );
@@ -308,9 +345,9 @@ fn chunks_static_scene_moving_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 29, 30),
-                TimedChunkEntry::Dynamic(0, 31, 39),
-                TimedChunkEntry::Final(0, 40),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
+                TimedChunkEntry::Dynamic(0, SampleTime(31), SampleTime(39)),
+                TimedChunkEntry::Final(0, SampleTime(40)),
             ],
         },
     );
@@ -352,6 +389,62 @@ fn chunks_static_scene_moving_receiver() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn chunk_statistics_empty_scene() {
+    let statistics = empty_scene().chunks::<typenum::U10>().statistics();
+    // the receiver covers 6x6x6 chunks
+    assert_eq!(1000, statistics.total_chunks);
+    assert_eq!(216, statistics.set_chunks);
+    assert_eq!(216, statistics.total_entries);
+    assert_eq!((1, 1), (statistics.min_entries, statistics.max_entries));
+    assert!((statistics.mean_entries - 1f64).abs() < f64::EPSILON);
+    assert_eq!(vec![0, 216], statistics.histogram);
+}
+
+#[test]
+fn chunk_statistics_static_scene_moving_receiver() {
+    let statistics = static_scene_moving_receiver()
+        .chunks::<typenum::U10>()
+        .statistics();
+    // the cube's 488 outer chunks hold two triangles per face they lie on,
+    // the receiver passes through 4 inner chunks with 3, 4, 4 and 3 entries
+    assert_eq!(1000, statistics.total_chunks);
+    assert_eq!(488 + 4, statistics.set_chunks);
+    let total_entries = 384 * 2 + 96 * 4 + 8 * 6 + 3 + 4 + 4 + 3;
+    assert_eq!(total_entries, statistics.total_entries);
+    assert_eq!((2, 6), (statistics.min_entries, statistics.max_entries));
+    assert!((statistics.mean_entries - total_entries as f64 / 492f64).abs() < 1e-12);
+    assert_eq!(vec![0, 0, 384, 2, 98, 0, 8], statistics.histogram);
+    assert_eq!(
+        "Set chunks: 492 of 1000\nChunk entries: 1214\nEntries per set chunk: 2 min, 2.467 mean, 6 max\n Entries |   Chunks\n       2 |      384\n       3 |        2\n       4 |       98\n       6 |        8",
+        statistics.to_string()
+    );
+}
+
+#[test]
+fn parallel_chunks_match_sequential_chunks() {
+    let scene = moving_scene_and_receiver();
+    let sequential = scene.chunks::<typenum::U10>();
+    let parallel = scene.parallel_chunks::<typenum::U10>();
+    assert_eq!(
+        (sequential.size_x, sequential.size_y, sequential.size_z),
+        (parallel.size_x, parallel.size_y, parallel.size_z)
+    );
+    assert_eq!(sequential.chunk_starts, parallel.chunk_starts);
+    assert_eq!(sequential.set_chunks, parallel.set_chunks);
+    assert_eq!(sequential.chunks.len(), parallel.chunks.len());
+    assert_chunks_equal(&sequential.chunks, &parallel);
+
+    let sphere_scene = scene_builder::SceneBuilder::new()
+        .with_static_sphere((0f64, 0f64, 0f64), 5f64, 2, MATERIAL_CONCRETE_WALL)
+        .build()
+        .unwrap();
+    assert_eq!(
+        sphere_scene.chunks::<typenum::U10>(),
+        sphere_scene.parallel_chunks::<typenum::U10>()
+    );
+}
+
 #[allow(clippy::too_many_lines)]
 #[allow(clippy::all)]
 #[test]
@@ -402,9 +495,9 @@ fn chunks_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 0, 10),
-                TimedChunkEntry::Dynamic(0, 10, 14),
-                TimedChunkEntry::Dynamic(0, 15, 15),
+                TimedChunkEntry::Dynamic(0, SampleTime(0), SampleTime(10)),
+                TimedChunkEntry::Dynamic(0, SampleTime(10), SampleTime(14)),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
             ],
         },
     );
@@ -413,10 +506,10 @@ fn chunks_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 15, 15),
-                TimedChunkEntry::Dynamic(0, 16, 18),
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
+                TimedChunkEntry::Dynamic(0, SampleTime(16), SampleTime(18)),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
             ],
         },
     );
@@ -425,10 +518,10 @@ fn chunks_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
-                TimedChunkEntry::Dynamic(0, 22, 28),
-                TimedChunkEntry::Dynamic(0, 29, 30),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
+                TimedChunkEntry::Dynamic(0, SampleTime(22), SampleTime(28)),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
             ],
         },
     );
@@ -437,9 +530,9 @@ fn chunks_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 29, 30),
-                TimedChunkEntry::Dynamic(0, 31, 39),
-                TimedChunkEntry::Final(0, 40),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
+                TimedChunkEntry::Dynamic(0, SampleTime(31), SampleTime(39)),
+                TimedChunkEntry::Final(0, SampleTime(40)),
             ],
         },
     );
@@ -477,21 +570,21 @@ fn chunks_moving_scene_and_receiver() {
     }
     for y in 5..=6 {
         for z in 5..=6 {
-            expected.add_surface_at(5, y, z, 12, Some((0, Some(20))));
-            expected.add_surface_at(5, y, z, 12, Some((20, Some(22))));
+            expected.add_surface_at(5, y, z, 12, Some((SampleTime(0), Some(SampleTime(20)))));
+            expected.add_surface_at(5, y, z, 12, Some((SampleTime(20), Some(SampleTime(22)))));
         }
     }
-    expected.add_surface_at(6, 6, 6, 12, Some((23, Some(144))));
+    expected.add_surface_at(6, 6, 6, 12, Some((SampleTime(23), Some(SampleTime(144)))));
     for y in 6..=7 {
         for z in 6..=7 {
-            expected.add_surface_at(6, y, z, 12, Some((145, Some(264))));
+            expected.add_surface_at(6, y, z, 12, Some((SampleTime(145), Some(SampleTime(264)))));
         }
     }
-    expected.add_surface_at(7, 7, 7, 12, Some((265, Some(387))));
+    expected.add_surface_at(7, 7, 7, 12, Some((SampleTime(265), Some(SampleTime(387)))));
     for y in 7..=8 {
         for z in 7..=8 {
-            expected.add_surface_at(7, y, z, 12, Some((388, Some(499))));
-            expected.add_surface_at(7, y, z, 12, Some((500, None)));
+            expected.add_surface_at(7, y, z, 12, Some((SampleTime(388), Some(SampleTime(499)))));
+            expected.add_surface_at(7, y, z, 12, Some((SampleTime(500), None)));
         }
     }
 
@@ -550,9 +643,9 @@ fn chunks_looping_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 0, 10),
-                TimedChunkEntry::Dynamic(0, 10, 14),
-                TimedChunkEntry::Dynamic(0, 15, 15),
+                TimedChunkEntry::Dynamic(0, SampleTime(0), SampleTime(10)),
+                TimedChunkEntry::Dynamic(0, SampleTime(10), SampleTime(14)),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
             ],
         },
     );
@@ -561,10 +654,10 @@ fn chunks_looping_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 15, 15),
-                TimedChunkEntry::Dynamic(0, 16, 18),
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
+                TimedChunkEntry::Dynamic(0, SampleTime(15), SampleTime(15)),
+                TimedChunkEntry::Dynamic(0, SampleTime(16), SampleTime(18)),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
             ],
         },
     );
@@ -573,10 +666,10 @@ fn chunks_looping_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 19, 19),
-                TimedChunkEntry::Dynamic(0, 20, 21),
-                TimedChunkEntry::Dynamic(0, 22, 28),
-                TimedChunkEntry::Dynamic(0, 29, 30),
+                TimedChunkEntry::Dynamic(0, SampleTime(19), SampleTime(19)),
+                TimedChunkEntry::Dynamic(0, SampleTime(20), SampleTime(21)),
+                TimedChunkEntry::Dynamic(0, SampleTime(22), SampleTime(28)),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
             ],
         },
     );
@@ -585,10 +678,10 @@ fn chunks_looping_moving_scene_and_receiver() {
         SceneChunk {
             surfaces: vec![],
             receivers: vec![
-                TimedChunkEntry::Dynamic(0, 29, 30),
-                TimedChunkEntry::Dynamic(0, 31, 39),
-                TimedChunkEntry::Dynamic(0, 40, 479),
-                TimedChunkEntry::Dynamic(0, 480, 480),
+                TimedChunkEntry::Dynamic(0, SampleTime(29), SampleTime(30)),
+                TimedChunkEntry::Dynamic(0, SampleTime(31), SampleTime(39)),
+                TimedChunkEntry::Dynamic(0, SampleTime(40), SampleTime(479)),
+                TimedChunkEntry::Dynamic(0, SampleTime(480), SampleTime(480)),
             ],
         },
     );
@@ -626,20 +719,20 @@ fn chunks_looping_moving_scene_and_receiver() {
     }
     for y in 5..=6 {
         for z in 5..=6 {
-            expected.add_surface_at(5, y, z, 12, Some((0, Some(2))));
+            expected.add_surface_at(5, y, z, 12, Some((SampleTime(0), Some(SampleTime(2)))));
         }
     }
-    expected.add_surface_at(6, 6, 6, 12, Some((3, Some(124))));
+    expected.add_surface_at(6, 6, 6, 12, Some((SampleTime(3), Some(SampleTime(124)))));
     for y in 6..=7 {
         for z in 6..=7 {
-            expected.add_surface_at(6, y, z, 12, Some((125, Some(244))));
+            expected.add_surface_at(6, y, z, 12, Some((SampleTime(125), Some(SampleTime(244)))));
         }
     }
-    expected.add_surface_at(7, 7, 7, 12, Some((245, Some(367))));
+    expected.add_surface_at(7, 7, 7, 12, Some((SampleTime(245), Some(SampleTime(367)))));
     for y in 7..=8 {
         for z in 7..=8 {
-            expected.add_surface_at(7, y, z, 12, Some((368, Some(479))));
-            expected.add_surface_at(7, y, z, 12, Some((480, Some(480))));
+            expected.add_surface_at(7, y, z, 12, Some((SampleTime(368), Some(SampleTime(479)))));
+            expected.add_surface_at(7, y, z, 12, Some((SampleTime(480), Some(SampleTime(480)))));
         }
     }
 
@@ -655,20 +748,25 @@ fn objects_at_loop_start_and_end_match_loop_overarching_times() {
 
     for key in 0..1110 {
         let (mut expected_receivers, mut expected_surfaces) =
-            chunks.objects_at_key_and_time(key, 460, 480, None);
+            chunks.objects_at_key_and_time(key, SampleTime(460), SampleTime(480), None);
         let (expected_receivers_beginning, expected_surfaces_beginning) =
-            chunks.objects_at_key_and_time(key, 0, 40, None);
+            chunks.objects_at_key_and_time(key, SampleTime(0), SampleTime(40), None);
         expected_receivers.extend_from_slice(&expected_receivers_beginning);
         expected_surfaces.extend_from_slice(&expected_surfaces_beginning);
-        expected_receivers = expected_receivers.iter().unique().map(|val| *val).collect();
-        expected_surfaces = expected_surfaces.iter().unique().map(|val| *val).collect();
+        expected_receivers = expected_receivers.iter().unique().copied().collect();
+        expected_surfaces = expected_surfaces.iter().unique().copied().collect();
 
-        let result = chunks.objects_at_key_and_time(key, 460, 520, Some(480));
+        let result = chunks.objects_at_key_and_time(
+            key,
+            SampleTime(460),
+            SampleTime(520),
+            Some(SampleDuration(480)),
+        );
         assert_eq!(
             (expected_receivers, expected_surfaces),
             result,
             "Failed for key {key}"
-        )
+        );
     }
 }
 
@@ -677,10 +775,15 @@ fn objects_at_loop_covering_span_is_all_objects() {
     let scene = looping_moving_scene_and_receiver();
     let chunks = scene.chunks::<typenum::U11>();
     for key in 0..1110 {
-        let expected = chunks.objects_at_key_and_time(key, 0, 1000, None);
+        let expected = chunks.objects_at_key_and_time(key, SampleTime(0), SampleTime(1000), None);
 
-        let result = chunks.objects_at_key_and_time(key, 300, 781, Some(480));
-        assert_eq!(expected, result, "Failed for key {key}")
+        let result = chunks.objects_at_key_and_time(
+            key,
+            SampleTime(300),
+            SampleTime(781),
+            Some(SampleDuration(480)),
+        );
+        assert_eq!(expected, result, "Failed for key {key}");
     }
 }
 
@@ -707,18 +810,422 @@ fn objects_at_looping_times_match() {
                 assert_eq!(
                     chunks.objects_at_key_and_time(
                         *index,
-                        1 + 480 * a_time,
-                        100 + 480 * a_time,
-                        Some(480)
+                        SampleTime(1 + 480 * a_time),
+                        SampleTime(100 + 480 * a_time),
+                        Some(SampleDuration(480))
                     ),
                     chunks.objects_at_key_and_time(
                         *index,
-                        1 + 480 * b_time,
-                        100 + 480 * b_time,
-                        Some(480)
+                        SampleTime(1 + 480 * b_time),
+                        SampleTime(100 + 480 * b_time),
+                        Some(SampleDuration(480))
                     )
                 );
             }
         }
     }
 }
+
+#[test]
+fn scene_fits_inside_chunk_world_bounds() {
+    let mut scene = scene_builder::rotating_l_scene(100);
+    let (scene_min, scene_max) = scene.maximum_bounds_cached();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let (chunk_min, chunk_max) = scene_data.chunks.world_bounds();
+
+    assert_eq!((chunk_min, chunk_max), scene_data.chunks.maximum_bounds());
+    for axis in 0..3 {
+        assert!(chunk_min[axis] <= scene_min[axis]);
+        assert!(chunk_max[axis] >= scene_max[axis]);
+    }
+}
+
+#[test]
+fn stale_bounds_cache_does_not_change_scene_data_bounds() {
+    let mut scene = scene_builder::static_cube_scene();
+    let bounds = scene.maximum_bounds();
+    scene.bounds_cache = Some((Vector3::zeros(), Vector3::zeros()));
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    assert_eq!(
+        (bounds.0.add_scalar(-0.1), bounds.1.add_scalar(0.1)),
+        scene_data.maximum_bounds
+    );
+    assert_eq!(scene_data.maximum_bounds.0, scene_data.chunks.chunk_starts);
+}
+
+#[test]
+fn large_receiver_at_scene_corner_stays_inside_chunk_grid() {
+    let scene = scene_builder::SceneBuilder::new()
+        .with_static_cube(
+            (0f64, 0f64, 0f64),
+            (1f64, 1f64, 1f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(0.1, 0.1, 0.1)
+        .with_receiver_radius(0.5)
+        .with_emitter_at(0.5, 0.5, 0.5)
+        .build()
+        .unwrap();
+    let chunks = scene.chunks::<typenum::U10>();
+    // the receiver's corner chunk holds it
+    assert!(chunks.is_chunk_set(0));
+    assert_eq!(
+        vec![TimedChunkEntry::Static(0)],
+        chunks.chunks[&0].receivers
+    );
+    // coordinates beyond the grid are clamped to its outermost chunks
+    let (min, max) = chunks.world_bounds();
+    assert_eq!(
+        (0, 0, 0),
+        chunks.coords_to_chunk_index(&min.add_scalar(-0.5))
+    );
+    assert_eq!(
+        (9, 9, 9),
+        chunks.coords_to_chunk_index(&max.add_scalar(0.5))
+    );
+}
+
+/// Launch a ray with the given traversal mode and return its receiver hits.
+fn launch_with_traversal_mode(
+    scene_data: &SceneData<typenum::U10>,
+    direction: Vector3<f64>,
+    origin: Vector3<f64>,
+    start_time: u32,
+    traversal_mode: TraversalMode,
+) -> Vec<(f64, u32)> {
+    Ray::launch(
+        direction,
+        origin,
+        start_time,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        scene_data,
+        &SimulationConfig {
+            traversal_mode,
+            ..SimulationConfig::default()
+        },
+    )
+}
+
+/// Get the moving scene with specular surfaces and a bigger receiver, so chunked and brute force traversal can be compared.
+fn specular_moving_scene_data() -> SceneData<typenum::U10> {
+    let mut scene = moving_scene_and_receiver();
+    // specular surfaces make every ray's path deterministic
+    let surface_count = scene.surfaces.len();
+    scene.set_material_range(
+        0..surface_count,
+        Material {
+            absorption_coefficient: 0.8,
+            diffusion_coefficient: 0f64,
+            transmission_coefficient: 0f64,
+        },
+    );
+    // a bigger receiver gets hit by more of the rays
+    let Receiver::Keyframes(keyframes, _radius, loop_duration, occluding) = scene.receiver else {
+        panic!("The fixture's receiver should have keyframes!")
+    };
+    scene.receiver = Receiver::Keyframes(keyframes, 2f64, loop_duration, occluding);
+    SceneData::create_for_scene(scene).unwrap()
+}
+
+/// Launch a ray with both traversal modes, assert that they find the same hits and return the number of hits.
+fn assert_traversal_modes_agree(
+    scene_data: &SceneData<typenum::U10>,
+    direction: Vector3<f64>,
+    origin: Vector3<f64>,
+    start_time: u32,
+) -> usize {
+    let chunked = launch_with_traversal_mode(
+        scene_data,
+        direction,
+        origin,
+        start_time,
+        TraversalMode::Chunked,
+    );
+    let brute_force = launch_with_traversal_mode(
+        scene_data,
+        direction,
+        origin,
+        start_time,
+        TraversalMode::BruteForce,
+    );
+    assert_eq!(
+            chunked.len(),
+            brute_force.len(),
+            "hits differ for direction {direction:?}, origin {origin:?}, start time {start_time}:\n{chunked:?}\n{brute_force:?}"
+        );
+    for ((chunked_energy, chunked_time), (brute_force_energy, brute_force_time)) in
+        chunked.iter().zip(&brute_force)
+    {
+        assert!((chunked_energy - brute_force_energy).abs() < 1e-9);
+        assert!(chunked_time.abs_diff(*brute_force_time) <= 1);
+    }
+    chunked.len()
+}
+
+#[test]
+fn chunked_and_brute_force_traversal_agree() {
+    let scene_data = specular_moving_scene_data();
+    let mut rng = StdRng::seed_from_u64(1332);
+    let mut total_hits = 0;
+    for _ in 0..300 {
+        let direction = Vector3::new(
+            rng.gen_range(-1f64..1f64),
+            rng.gen_range(-1f64..1f64),
+            rng.gen_range(-1f64..1f64),
+        );
+        let origin = Vector3::new(
+            rng.gen_range(-9f64..9f64),
+            rng.gen_range(-9f64..9f64),
+            rng.gen_range(-9f64..9f64),
+        );
+        let start_time = rng.gen_range(0..600);
+        total_hits += assert_traversal_modes_agree(&scene_data, direction, origin, start_time);
+    }
+    assert!(total_hits > 0);
+}
+
+#[test]
+fn axis_aligned_chunked_and_brute_force_traversal_agree() {
+    let scene_data = specular_moving_scene_data();
+    let chunk_size = Vector3::new(
+        scene_data.chunks.size_x,
+        scene_data.chunks.size_y,
+        scene_data.chunks.size_z,
+    );
+    let mut rng = StdRng::seed_from_u64(1347);
+    let mut total_hits = 0;
+    for axis in 0..3 {
+        for sign in [1f64, -1f64] {
+            let mut direction = Vector3::zeros();
+            direction[axis] = sign;
+            for _ in 0..50 {
+                let mut origin = Vector3::new(
+                    rng.gen_range(-9f64..9f64),
+                    rng.gen_range(-9f64..9f64),
+                    rng.gen_range(-9f64..9f64),
+                );
+                // put the origin exactly onto a chunk boundary in the dimensions the ray doesn't move in
+                for other_axis in (0..3).filter(|other_axis| *other_axis != axis) {
+                    let chunk_start = scene_data.chunks.chunk_starts[other_axis];
+                    let chunk_width = chunk_size[other_axis];
+                    origin[other_axis] = ((origin[other_axis] - chunk_start) / chunk_width)
+                        .round()
+                        .mul_add(chunk_width, chunk_start);
+                }
+                let start_time = rng.gen_range(0..600);
+                total_hits +=
+                    assert_traversal_modes_agree(&scene_data, direction, origin, start_time);
+            }
+        }
+    }
+    assert!(total_hits > 0);
+}
+
+/// Get the objects in the chunk with the given key by querying every single sample of the given time span.
+fn objects_at_key_per_sample(
+    chunks: &Chunks<typenum::U11>,
+    key: u32,
+    time_entry: u32,
+    time_exit: u32,
+    loop_duration: Option<SampleDuration>,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut receivers = vec![];
+    let mut surfaces = vec![];
+    for time in time_entry..=time_exit {
+        let (time_receivers, time_surfaces) =
+            chunks.objects_at_key_and_time(key, SampleTime(time), SampleTime(time), loop_duration);
+        receivers.extend(time_receivers);
+        surfaces.extend(time_surfaces);
+    }
+    (
+        receivers.into_iter().sorted().dedup().collect(),
+        surfaces.into_iter().sorted().dedup().collect(),
+    )
+}
+
+#[test]
+fn objects_at_looping_time_spans_match_per_sample_objects() {
+    let scene = looping_moving_scene_and_receiver();
+    let chunks = scene.chunks::<typenum::U11>();
+    let time_spans = [
+        // within a single loop
+        (5, 20),
+        (100, 479),
+        // into the next loop, exiting later in the loop than entering
+        (100, 630),
+        (400, 930),
+        // into the next loop, exiting at the same time in the loop as entering
+        (0, 480),
+        (480, 960),
+        (250, 730),
+        // into the next loop, exiting one sample earlier in the loop than entering
+        (100, 579),
+        // into the next loop, exiting earlier in the loop than entering
+        (470, 490),
+        (300, 500),
+        (479, 480),
+        // spanning multiple loops
+        (300, 1450),
+    ];
+    for key in 0..1331 {
+        if !chunks.is_chunk_set(key as usize) {
+            continue;
+        }
+        for (time_entry, time_exit) in time_spans {
+            let (receivers, surfaces) = chunks.objects_at_key_and_time(
+                key,
+                SampleTime(time_entry),
+                SampleTime(time_exit),
+                Some(SampleDuration(480)),
+            );
+            assert_eq!(
+                objects_at_key_per_sample(
+                    &chunks,
+                    key,
+                    time_entry,
+                    time_exit,
+                    Some(SampleDuration(480))
+                ),
+                (
+                    receivers.into_iter().sorted().collect(),
+                    surfaces.into_iter().sorted().collect()
+                ),
+                "Failed for key {key} between {time_entry} and {time_exit}"
+            );
+        }
+    }
+}
+
+/// The looping scene from `looping_moving_scene_and_receiver`, with its moving surface
+/// looping on its own every 100 samples instead: it moves within the first 50 samples
+/// and stays at its last position for the remaining 50.
+fn own_loop_surface_scene() -> Scene {
+    let mut scene = looping_moving_scene_and_receiver();
+    let surface = scene.surfaces.pop().unwrap();
+    let SurfaceVariant::Triangle(Surface::Keyframes(mut keyframes, surface_data, None)) = surface
+    else {
+        panic!("expected a keyframe triangle");
+    };
+    keyframes[1].time = SampleTime(50);
+    scene
+        .surfaces
+        .push(SurfaceVariant::Triangle(Surface::Keyframes(
+            keyframes,
+            surface_data,
+            Some(SampleDuration(100)),
+        )));
+    scene
+}
+
+#[test]
+fn own_loop_surface_is_only_in_chunks_within_its_time_window() {
+    let scene = own_loop_surface_scene();
+    let surface_index = scene.surfaces.len() - 1;
+    let chunks = scene.chunks::<typenum::U10>();
+    let start_key = chunks.key_for_coordinates(&Vector3::new(2f64, 2.1, 2.1));
+    let end_key = chunks.key_for_coordinates(&Vector3::new(6f64, 6.1, 6.1));
+    let surfaces_at = |key, time| {
+        chunks
+            .objects_at_key_and_time(
+                key,
+                SampleTime(time),
+                SampleTime(time),
+                Some(SampleDuration(480)),
+            )
+            .1
+    };
+    // within the surface's first loop, the second one and one crossing the scene's loop
+    for loop_start in [0, 100, 400, 500] {
+        assert!(surfaces_at(start_key, loop_start).contains(&surface_index));
+        assert!(!surfaces_at(start_key, loop_start + 70).contains(&surface_index));
+        assert!(!surfaces_at(end_key, loop_start).contains(&surface_index));
+        assert!(surfaces_at(end_key, loop_start + 70).contains(&surface_index));
+    }
+    assert!(chunks.chunks.values().any(|chunk| chunk
+        .surfaces
+        .iter()
+        .any(|entry| matches!(entry, TimedChunkEntry::Periodic(index, .., SampleDuration(100)) if *index == surface_index))));
+}
+
+#[test]
+fn own_loop_time_spans_match_per_sample_objects() {
+    let chunks = own_loop_surface_scene().chunks::<typenum::U11>();
+    let time_spans = [
+        (5, 20),
+        (40, 120),
+        (90, 110),
+        (60, 250),
+        (470, 490),
+        (300, 500),
+    ];
+    for key in 0..1331 {
+        if !chunks.is_chunk_set(key as usize) {
+            continue;
+        }
+        for (time_entry, time_exit) in time_spans {
+            let (receivers, surfaces) = chunks.objects_at_key_and_time(
+                key,
+                SampleTime(time_entry),
+                SampleTime(time_exit),
+                Some(SampleDuration(480)),
+            );
+            assert_eq!(
+                objects_at_key_per_sample(
+                    &chunks,
+                    key,
+                    time_entry,
+                    time_exit,
+                    Some(SampleDuration(480))
+                ),
+                (
+                    receivers.into_iter().sorted().collect(),
+                    surfaces.into_iter().sorted().collect()
+                ),
+                "Failed for key {key} between {time_entry} and {time_exit}"
+            );
+        }
+    }
+}
+
+/// Assert that no chunk contains the same entry twice,
+/// or timed entries for an object that also has a static entry in the same chunk.
+fn assert_no_redundant_entries(chunks: &Chunks<typenum::U10>) {
+    for chunk in chunks.chunks.values() {
+        for entries in [&chunk.surfaces, &chunk.receivers] {
+            assert!(entries.iter().all_unique(), "{entries:?}");
+            for entry in entries {
+                if let TimedChunkEntry::Dynamic(index, ..)
+                | TimedChunkEntry::Final(index, _)
+                | TimedChunkEntry::Periodic(index, ..) = entry
+                {
+                    assert!(!entries.contains(&TimedChunkEntry::Static(*index)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn chunks_contain_each_surface_once() {
+    let chunks = scene_builder::static_cube_scene().chunks::<typenum::U10>();
+    assert_no_redundant_entries(&chunks);
+    for chunk in chunks.chunks.values() {
+        // every static surface only has a single static entry
+        assert!(chunk
+            .surfaces
+            .iter()
+            .all(|entry| matches!(entry, TimedChunkEntry::Static(_))));
+    }
+
+    for scene in [
+        static_scene_moving_receiver(),
+        moving_scene_and_receiver(),
+        looping_moving_scene_and_receiver(),
+        own_loop_surface_scene(),
+        scene_builder::rotating_cube_scene(100),
+    ] {
+        assert_no_redundant_entries(&scene.chunks::<typenum::U10>());
+    }
+}
@@ -0,0 +1,337 @@
+use std::path::PathBuf;
+
+use approx::assert_abs_diff_eq;
+use demo::{
+    audio_io::OutputFormat,
+    cli::{self, export, ir, ir_diff, list_materials, list_scenes, simulate, CliError},
+    impulse_response,
+    materials::MATERIAL_CARPET,
+    parameter_sweep::SweepParameter,
+    simulation_config::IrPrecision,
+};
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+fn temp_file_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("demo_cli_{}_{name}", std::process::id()))
+}
+
+#[test]
+fn list_scenes_and_materials() {
+    assert!(list_scenes::run(&[]).is_ok());
+    assert_eq!(6, list_scenes::scene_list().len());
+    assert!(list_scenes::scene_list()[0].starts_with("\t0 - static-cube (Static Cube): "));
+
+    assert!(list_materials::run(&[]).is_ok());
+    assert!(list_materials::material_list()[0].starts_with("\tconcrete - "));
+    assert!(matches!(
+        list_materials::run(&args(&["--verbose"])),
+        Err(CliError::UnknownArgument(arg)) if arg == "--verbose"
+    ));
+}
+
+#[test]
+fn export_static_cube_as_obj() {
+    let path = temp_file_path("cube.obj");
+    let outfile = format!("--outfile={}", path.display());
+    export::run(&args(&["--scene=0", &outfile])).unwrap();
+
+    let obj = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        36,
+        obj.lines().filter(|line| line.starts_with("v ")).count()
+    );
+    assert_eq!(
+        12,
+        obj.lines().filter(|line| line.starts_with("f ")).count()
+    );
+    assert!(obj.contains("\nf 1 2 3\n"));
+    assert!(obj.contains("# emitter 0 0 1.2\n"));
+}
+
+#[test]
+fn ir_writes_impulse_response() {
+    let path = temp_file_path("ir.csv");
+    let irfile = format!("--irfile={}", path.display());
+    ir::run(&args(&["--scene=1", "--rays=3", "--threads=1", &irfile])).unwrap();
+
+    let impulse_response = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let values: Vec<f64> = impulse_response
+        .split_terminator(';')
+        .map(|value| value.parse().unwrap())
+        .collect();
+    // every ray travels one second towards the receiver
+    assert!(values.len() > 44000);
+    assert!(values.iter().any(|value| *value > 0f64));
+}
+
+#[test]
+fn ir_writes_ambisonic_impulse_response() {
+    let path = temp_file_path("ir_ambisonic.csv");
+    let ambisonic_path = temp_file_path("ir_ambisonic.wav");
+    let irfile = format!("--irfile={}", path.display());
+    let ambisonic = format!("--ambisonic={}", ambisonic_path.display());
+    ir::run(&args(&[
+        "--scene=1",
+        "--rays=3",
+        "--threads=1",
+        &irfile,
+        &ambisonic,
+    ]))
+    .unwrap();
+
+    let mut ambisonic_file = std::fs::File::open(&ambisonic_path).unwrap();
+    let (header, data) = wav::read(&mut ambisonic_file).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&ambisonic_path).unwrap();
+    assert_eq!(4, header.channel_count);
+    let wav::BitDepth::ThirtyTwoFloat(data) = data else {
+        panic!("The ambisonic impulse response should be written as 32-bit floats!")
+    };
+    assert_eq!(0, data.len() % 4);
+    // the receiver lies along +X of the emitter, so the sound arrives from -X
+    assert!(data
+        .chunks(4)
+        .any(|frame| frame[0] > 0f32 && frame[1] < 0f32));
+}
+
+#[test]
+fn ir_sweeps_receiver_grid() {
+    let grid = ir::parse_receiver_grid("step=0.5,z=1.2,x=-1..1,y=0..0.5").unwrap();
+    assert_eq!((-1f64, 1f64), grid.x_range);
+    assert_eq!(10, grid.points().len());
+    for invalid in [
+        "z=1.2,x=-1..1,y=0..1",
+        "z=1.2,x=1..-1,y=0..1,step=1",
+        "z=1.2,x=-1..1,y=0..1,step=0",
+        "z=high,x=-1..1,y=0..1,step=1",
+    ] {
+        assert!(matches!(
+            ir::parse_receiver_grid(invalid),
+            Err(CliError::InvalidArgument(_))
+        ));
+    }
+
+    let path = temp_file_path("grid.csv");
+    let pgm_path = temp_file_path("grid.pgm");
+    let gridfile = format!("--gridfile={}", path.display());
+    let grid_pgm = format!("--grid-pgm={}", pgm_path.display());
+    ir::run(&args(&[
+        "--scene=0",
+        "--rays=3",
+        "--threads=1",
+        "--energy-threshold=0.5",
+        "--sweep-grid=z=0,x=0..1,y=0..0,step=1",
+        &gridfile,
+        &grid_pgm,
+    ]))
+    .unwrap();
+
+    let grid = std::fs::read_to_string(&path).unwrap();
+    let pgm = std::fs::read_to_string(&pgm_path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&pgm_path).unwrap();
+    let lines: Vec<&str> = grid.lines().collect();
+    assert_eq!("x;y;total_energy;first_arrival_sample", lines[0]);
+    assert!(lines[2].starts_with("1;0;"));
+    assert_eq!(3, lines.len());
+    assert!(pgm.starts_with("P2\n2 1\n255\n"));
+}
+
+#[test]
+fn ir_sweeps_parameter() {
+    let (parameter, values) = ir::parse_parameter_sweep("receiver_radius:0.05..0.5:10").unwrap();
+    assert_eq!(SweepParameter::ReceiverRadius, parameter);
+    assert_eq!(10, values.len());
+    assert_abs_diff_eq!(0.05f64, values[0]);
+    assert_abs_diff_eq!(0.5f64, values[9], epsilon = 1e-12);
+    for invalid in [
+        "receiver_radius:0.05..0.5",
+        "receiver_radius:0.05..0.5:0",
+        "receiver_size:0.05..0.5:10",
+        "receiver_radius:0.05-0.5:10",
+        "receiver_radius:0.05..0.5:10:1",
+    ] {
+        assert!(matches!(
+            ir::parse_parameter_sweep(invalid),
+            Err(CliError::InvalidArgument(_))
+        ));
+    }
+
+    let path = temp_file_path("sweep.csv");
+    let sweepfile = format!("--sweepfile={}", path.display());
+    ir::run(&args(&[
+        "--scene=static-cube",
+        "--rays=3",
+        "--threads=1",
+        "--energy-threshold=0.5",
+        "--sweep=absorption_coefficient:0.5..0.9:2",
+        &sweepfile,
+    ]))
+    .unwrap();
+
+    let sweep = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<&str> = sweep.lines().collect();
+    assert_eq!(
+        "absorption_coefficient;total_energy;first_arrival_sample;early_decay_time;t30",
+        lines[0]
+    );
+    assert!(lines[1].starts_with("0.5;"));
+    assert!(lines[2].starts_with("0.9;"));
+    assert_eq!(3, lines.len());
+}
+
+#[test]
+fn ir_estimate_does_not_write_impulse_response() {
+    let path = temp_file_path("estimate_ir.csv");
+    let irfile = format!("--irfile={}", path.display());
+    ir::run(&args(&["--scene=1", "--rays=3", "--estimate", &irfile])).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn simulate_single_ir_without_input() {
+    let path = temp_file_path("result.wav");
+    let outfile = format!("--outfile={}", path.display());
+    let arguments = args(&[
+        "--scene=1",
+        "--rays=3",
+        "--single-ir",
+        "--out-format=f32",
+        &outfile,
+    ]);
+    // the flat flags still run the simulation
+    cli::run(&arguments).unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    std::fs::remove_file(&path).unwrap();
+
+    let mut subcommand_arguments = args(&["simulate"]);
+    subcommand_arguments.extend(arguments);
+    cli::run(&subcommand_arguments).unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() > 0);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn parse_simulate_options() {
+    let options = simulate::SimulateOptions::parse(&args(&[
+        "--fname=input.wav",
+        "--scene=rotating-cube",
+        "--rays=20",
+        "--out-format=f64-raw",
+        "--material-override=2:carpet",
+        "--ir-normalise",
+        "--detect-leaks",
+        "--verbose",
+        "--area-weighted-emission",
+        "--dry-wet=0.3",
+        "--input-gain=-6",
+        "--receiver-importance=0.2",
+        "--speed-of-sound=1482",
+        "--energy-threshold=0.001",
+        "--ir-precision=f32",
+        "--launch-jitter",
+        "--chunk-stats",
+        "--start-time-samples=80000",
+    ]))
+    .unwrap();
+    assert_eq!(Some("input.wav".to_string()), options.input_fname);
+    assert_eq!(Some(4), options.scene.scene_index);
+    assert_eq!(vec![(2, MATERIAL_CARPET)], options.scene.material_overrides);
+    assert_eq!(20, options.simulation.number_of_rays);
+    assert_eq!(
+        Some(1f64),
+        options.simulation.post_processing.normalise_peak
+    );
+    assert_eq!(OutputFormat::Float64Raw, options.output_format);
+    assert!(options.detect_leaks);
+    assert!(options.simulation.verbose);
+    assert!(options.simulation.chunk_stats);
+    assert!(options.simulation.config.use_area_weighted_emission);
+    assert!((options.simulation.config.dry_wet - 0.3f64).abs() < f64::EPSILON);
+    assert!((options.simulation.config.input_gain_db + 6f64).abs() < f64::EPSILON);
+    assert!((options.simulation.config.receiver_importance - 0.2f64).abs() < f64::EPSILON);
+    assert!((options.simulation.speed_of_sound - 1482f64).abs() < f64::EPSILON);
+    assert!((options.simulation.config.relative_energy_threshold - 0.001f64).abs() < f64::EPSILON);
+    assert_eq!(IrPrecision::F32, options.simulation.config.ir_precision);
+    assert!(options.simulation.config.launch_jitter);
+    assert_eq!(80000, options.simulation.config.time_offset);
+    assert!(matches!(
+        simulate::SimulateOptions::parse(&args(&["--ir-precision=f16"])),
+        Err(CliError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        simulate::SimulateOptions::parse(&args(&["--speed-of-sound=0"])),
+        Err(CliError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        simulate::SimulateOptions::parse(&args(&["--energy-threshold=-1"])),
+        Err(CliError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        simulate::SimulateOptions::parse(&args(&["--receiver-importance=1"])),
+        Err(CliError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        simulate::SimulateOptions::parse(&args(&["--dry-wet=1.5"])),
+        Err(CliError::InvalidArgument(_))
+    ));
+    assert_eq!("result.wav", options.out_fname);
+}
+
+#[test]
+fn invalid_arguments_are_reported() {
+    let err = cli::run(&args(&["compare-methods"])).unwrap_err();
+    assert!(matches!(&err, CliError::UnknownSubcommand(name) if name == "compare-methods"));
+    assert_eq!(1, err.exit_code());
+
+    let err = ir::run(&args(&["--rays=3"])).unwrap_err();
+    assert!(matches!(err, CliError::MissingArgument(_)));
+
+    let err = export::run(&args(&["--scene=9"])).unwrap_err();
+    assert!(matches!(err, CliError::InvalidArgument(_)));
+    let err = export::run(&args(&["--scene=rotating-triangle"])).unwrap_err();
+    assert!(matches!(err, CliError::InvalidArgument(_)));
+
+    let err = simulate::run(&args(&["--scene=0", "--rays=many"])).unwrap_err();
+    assert_eq!("\"--rays\" needs to be passed a number!", err.to_string());
+
+    let err = simulate::run(&args(&["--scene=0", "--fname=testfiles/empty.wav"])).unwrap_err();
+    assert!(matches!(err, CliError::EmptyInput));
+    assert_eq!(4, err.exit_code());
+}
+
+#[test]
+fn ir_diff_compares_text_impulse_responses() {
+    let first_path = temp_file_path("ir_diff_first.csv");
+    let second_path = temp_file_path("ir_diff_second.csv");
+    let first = vec![0f64, 1f64, 0.5f64, 0.25f64];
+    let second = vec![0f64, 1f64, 0.5f64, 0.2f64, 0.1f64];
+    ir::write_impulse_response(first_path.to_str().unwrap(), &first).unwrap();
+    ir::write_impulse_response(second_path.to_str().unwrap(), &second).unwrap();
+    assert_eq!(first, impulse_response::read_text(&first_path).unwrap());
+
+    let fnames = [first_path.to_str().unwrap(), second_path.to_str().unwrap()];
+    assert!(ir_diff::run(&args(&[fnames[0], fnames[0]])).is_ok());
+    assert!(ir_diff::run(&args(&[fnames[0], fnames[1], "--tolerance=0.2"])).is_ok());
+    let err = ir_diff::run(&args(&[fnames[0], fnames[1], "--tolerance=0.05"])).unwrap_err();
+    assert!(matches!(err, CliError::ToleranceExceeded { .. }));
+    assert_eq!(5, err.exit_code());
+    assert!(matches!(
+        ir_diff::run(&args(&[fnames[0]])),
+        Err(CliError::MissingArgument(_))
+    ));
+
+    std::fs::write(&second_path, "0;1;zero;").unwrap();
+    let err = ir_diff::run(&args(&[fnames[0], fnames[1]])).unwrap_err();
+    assert!(matches!(err, CliError::Read { .. }));
+    assert_eq!(4, err.exit_code());
+
+    std::fs::remove_file(&first_path).unwrap();
+    std::fs::remove_file(&second_path).unwrap();
+}
@@ -0,0 +1,48 @@
+use approx::assert_abs_diff_eq;
+use demo::{
+    defaults::Defaults,
+    ray::DEFAULT_PROPAGATION_SPEED,
+    simulation_config::{SimulationConfig, DEFAULT_RELATIVE_ENERGY_THRESHOLD},
+    DEFAULT_SAMPLE_RATE,
+};
+
+#[test]
+fn standard_defaults_match_crate_constants() {
+    let defaults = Defaults::default();
+    assert_abs_diff_eq!(DEFAULT_SAMPLE_RATE, defaults.sample_rate);
+    assert_abs_diff_eq!(DEFAULT_PROPAGATION_SPEED, defaults.propagation_speed);
+    assert_abs_diff_eq!(
+        DEFAULT_RELATIVE_ENERGY_THRESHOLD,
+        defaults.relative_energy_threshold
+    );
+    assert_eq!(defaults, Defaults::at_temperature(Defaults::TEMPERATURE));
+    assert_eq!(
+        SimulationConfig::default(),
+        SimulationConfig::from_defaults(&defaults)
+    );
+    assert_abs_diff_eq!(343.2f64 / 44100f64, defaults.velocity());
+}
+
+#[test]
+fn propagation_speed_follows_temperature() {
+    // reference values for dry air
+    assert_abs_diff_eq!(
+        331.3f64,
+        Defaults::at_temperature(0f64).propagation_speed,
+        epsilon = 0.5
+    );
+    assert_abs_diff_eq!(
+        349.0f64,
+        Defaults::at_temperature(30f64).propagation_speed,
+        epsilon = 0.5
+    );
+    let cold = Defaults::at_temperature(-10f64);
+    assert_abs_diff_eq!(Defaults::SAMPLE_RATE, cold.sample_rate);
+    assert!(cold.velocity() < Defaults::default().velocity());
+
+    let config = SimulationConfig::from_defaults(&Defaults {
+        relative_energy_threshold: 1e-3,
+        ..cold
+    });
+    assert_abs_diff_eq!(1e-3, config.relative_energy_threshold);
+}
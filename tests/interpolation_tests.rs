@@ -1,8 +1,14 @@
-use approx::abs_diff_eq;
+use approx::{abs_diff_eq, assert_abs_diff_eq};
 use demo::bounce::EmissionType;
+use demo::error::{DemoError, InterpolationError};
 use demo::interpolation::Interpolation;
 use demo::materials::MATERIAL_CONCRETE_WALL;
-use demo::scene::{CoordinateKeyframe, Emitter, Receiver, Surface, SurfaceData, SurfaceKeyframe};
+use demo::scene::{
+    sort_keyframes, CoordinateKeyframe, Emitter, Receiver, Scene, SceneData, Surface, SurfaceData,
+    SurfaceKeyframe, SurfaceVariant,
+};
+use demo::scene_builder::{self, SceneBuilder};
+use demo::time::{SampleDuration, SampleTime};
 use nalgebra::Vector3;
 
 fn vector_abs_diff_eq(a: Vector3<f64>, b: Vector3<f64>) -> bool {
@@ -26,14 +32,14 @@ fn interpolate_surface() {
     let object = Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: [
                     Vector3::new(10f64, 20f64, 30f64),
                     Vector3::new(0f64, 2f64, 16f64),
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(30f64, 20f64, 50f64),
                     Vector3::new(8f64, 10f64, 12f64),
@@ -41,9 +47,10 @@ fn interpolate_surface() {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
+        None,
     );
     let result = object.at_time(7);
-    let Surface::Interpolated(result_coords, time, surface_data) = result else {
+    let Surface::Interpolated(result_coords, surface_data) = result else {
         panic!("Surface wasn't interpolated!")
     };
     let material = surface_data.material;
@@ -54,7 +61,6 @@ fn interpolate_surface() {
     for idx in 0..expected_coords.len() {
         assert_vector_abs_diff_eq(expected_coords[idx], result_coords[idx]);
     }
-    assert_eq!(7, time);
     assert_eq!(MATERIAL_CONCRETE_WALL, material);
 }
 
@@ -63,35 +69,85 @@ fn interpolate_receiver() {
     let receiver = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: Vector3::new(30f64, 40f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: Vector3::new(30f64, 20f64, 50f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
     let result = receiver.at_time(6);
-    let Receiver::Interpolated(result_coords, radius, time) = result else {
+    let Receiver::Interpolated(result_coords, radius, time, _occluding) = result else {
         panic!("Receiver wasn't interpolated!")
     };
     assert_vector_abs_diff_eq(Vector3::new(30f64, 36f64, 10f64), result_coords);
-    assert_eq!(0.1f64, radius);
+    assert_abs_diff_eq!(0.1f64, radius);
     assert_eq!(6, time);
 }
 
+#[test]
+fn interpolate_receiver_with_reversed_keyframes() {
+    let keyframes: Vec<CoordinateKeyframe> = (0..4)
+        .rev()
+        .map(|idx| CoordinateKeyframe {
+            time: SampleTime(idx * 10),
+            coords: Vector3::new(f64::from(idx), f64::from(idx * idx), 0f64),
+        })
+        .collect();
+    let scene = SceneBuilder::new()
+        .with_receiver_keyframes(keyframes.clone())
+        .with_emitter_keyframes(keyframes.clone())
+        .build()
+        .unwrap();
+    assert!(scene.validate_keyframe_order());
+
+    let unsorted_scene = Scene {
+        receiver: Receiver::Keyframes(keyframes.clone(), 0.1f64, None, false),
+        ..scene.clone()
+    };
+    assert!(!unsorted_scene.validate_keyframe_order());
+
+    let Receiver::Keyframes(sorted_keyframes, ..) = &scene.receiver else {
+        panic!("Receiver has no keyframes!")
+    };
+    assert_eq!(&sort_keyframes(keyframes), sorted_keyframes);
+    // halfway between the keyframes at 20 and 30
+    let Receiver::Interpolated(result_coords, _radius, _time, _occluding) =
+        scene.receiver.at_time(25)
+    else {
+        panic!("Receiver wasn't interpolated!")
+    };
+    assert_vector_abs_diff_eq(Vector3::new(2.5f64, 6.5f64, 0f64), result_coords);
+}
+
+#[test]
+fn sort_surface_keyframes_by_time() {
+    let keyframe = |time: u32| SurfaceKeyframe {
+        time: SampleTime(time),
+        coords: [Vector3::new(f64::from(time), 0f64, 0f64); 3],
+    };
+    assert_eq!(
+        vec![keyframe(0), keyframe(3), keyframe(7)],
+        sort_keyframes(vec![keyframe(7), keyframe(0), keyframe(3)])
+    );
+    assert!(keyframe(2) < keyframe(5));
+}
+
 #[test]
 fn interpolate_emitter() {
     let emitter = Emitter::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 5,
+                time: SampleTime(5),
                 coords: Vector3::new(30f64, 40f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: Vector3::new(30f64, 20f64, 50f64),
             },
         ],
@@ -105,3 +161,81 @@ fn interpolate_emitter() {
     assert_eq!(6, time);
     assert_eq!(EmissionType::Random, emission_type);
 }
+
+#[test]
+fn interpolate_looping_scene_wraps_around() {
+    const LOOP_DURATION: u32 = 1000;
+    let scene = scene_builder::rotating_cube_scene(LOOP_DURATION);
+    assert_eq!(Some(SampleDuration(LOOP_DURATION)), scene.loop_duration);
+
+    for time in [0, 37, 250, 999] {
+        let in_first_loop = scene.at_time(time);
+        for loop_count in 1..3 {
+            assert_eq!(
+                in_first_loop,
+                scene.at_time(loop_count * LOOP_DURATION + time)
+            );
+        }
+    }
+    // the cube does move within the loop
+    assert_ne!(scene.at_time(0).surfaces, scene.at_time(250).surfaces);
+}
+
+#[test]
+fn snapshot_looping_scene_data() {
+    const LOOP_DURATION: u32 = 1000;
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(
+        scene_builder::rotating_cube_scene(LOOP_DURATION),
+    )
+    .unwrap();
+    let sorted_chunk_keys = |scene_data: &SceneData<typenum::U10>| {
+        let mut keys: Vec<u32> = scene_data.chunks.chunks.keys().copied().collect();
+        keys.sort_unstable();
+        keys
+    };
+
+    let snapshot = scene_data.snapshot(250);
+    let later_snapshot = scene_data.snapshot(LOOP_DURATION + 250);
+
+    assert_eq!(scene_data.scene.at_time(250), snapshot.scene);
+    assert_eq!(snapshot.scene, later_snapshot.scene);
+    assert_eq!(scene_data.maximum_bounds, snapshot.maximum_bounds);
+    assert_eq!(
+        sorted_chunk_keys(&snapshot),
+        sorted_chunk_keys(&later_snapshot)
+    );
+    assert!(snapshot
+        .scene
+        .surfaces
+        .iter()
+        .all(|surface| matches!(surface, SurfaceVariant::Triangle(Surface::Interpolated(..)))));
+}
+
+#[test]
+fn invalid_keyframes_are_rejected() {
+    let keyframes = vec![
+        CoordinateKeyframe {
+            time: SampleTime(10),
+            coords: Vector3::new(1f64, 0f64, 0f64),
+        },
+        CoordinateKeyframe {
+            time: SampleTime(0),
+            coords: Vector3::new(0f64, 0f64, 0f64),
+        },
+    ];
+    assert_eq!(
+        Err(InterpolationError::NoKeyframes),
+        Receiver::Keyframes(vec![], 0.1f64, None, false).try_at_time(5)
+    );
+
+    let scene = Scene {
+        receiver: Receiver::Keyframes(keyframes, 0.1f64, None, false),
+        ..scene_builder::static_receiver_scene()
+    };
+    assert!(matches!(
+        SceneData::<typenum::U10>::create_for_scene(scene),
+        Err(DemoError::Interpolation(
+            InterpolationError::UnsortedKeyframes
+        ))
+    ));
+}
@@ -1,10 +1,17 @@
 use approx::{abs_diff_eq, assert_abs_diff_eq};
-use demo::intersection::{intersect_ray_and_receiver, intersect_ray_and_surface};
+use demo::interpolation::interpolate_two_surface_keyframes;
+use demo::intersection::{
+    intersect_ray_and_ellipsoid, intersect_ray_and_receiver, intersect_ray_and_surface,
+    Intersectable, SurfaceIntersection,
+};
 use demo::materials::MATERIAL_CONCRETE_WALL;
 use demo::ray::{Ray, DEFAULT_PROPAGATION_SPEED};
-use demo::scene::{CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe};
+use demo::scene::{
+    CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe, SurfaceVariant,
+};
+use demo::time::{SampleDuration, SampleTime};
 use demo::DEFAULT_SAMPLE_RATE;
-use nalgebra::{Unit, Vector3};
+use nalgebra::{Rotation3, Unit, Vector3};
 
 fn assert_intersection_equals(
     expected: Option<(f64, Vector3<f64>)>,
@@ -21,53 +28,69 @@ fn assert_intersection_equals(
                 "Result is None where it should have been Some. Expected: {expected:?}"
             );
             let result = result.unwrap();
-            if !abs_diff_eq!(time, result.0, epsilon = 0.01) {
-                assert!(false, "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}")
-            };
+            assert!(
+                abs_diff_eq!(time, result.0, epsilon = 0.01),
+                "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}"
+            );
             for idx in 0..3 {
-                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01)
+                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01);
             }
         }
     }
 }
 
-fn static_receiver() -> Receiver {
-    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0)
+/// Like `assert_intersection_equals` for surface intersections, additionally checking that the normal is a unit vector.
+fn assert_surface_intersection_equals(
+    expected: Option<(f64, Vector3<f64>)>,
+    result: Option<SurfaceIntersection>,
+) {
+    if let Some((_time, _coords, normal)) = result {
+        assert_abs_diff_eq!(1f64, normal.norm(), epsilon = 1e-9);
+    }
+    assert_intersection_equals(
+        expected,
+        result.map(|(time, coords, _normal)| (time, coords)),
+    );
+}
+
+const fn static_receiver() -> Receiver {
+    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0, false)
 }
 
 fn moving_receiver() -> Receiver {
     Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(0f64, 20f64, 1f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(20f64, 0f64, 1f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     )
 }
 
-fn static_surface() -> Surface<3> {
-    Surface::Interpolated(
+const fn static_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Interpolated(
         [
             Vector3::new(10f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 10f64),
         ],
-        0,
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+    ))
 }
 
-fn moving_surface() -> Surface<3> {
-    Surface::Keyframes(
+fn moving_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: [
                     Vector3::new(0f64, 3f64, 0f64),
                     Vector3::new(-10f64, 3f64, 0f64),
@@ -75,7 +98,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(10f64, 3f64, 0f64),
                     Vector3::new(0f64, 3f64, 0f64),
@@ -83,7 +106,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: [
                     Vector3::new(10f64, 5f64, 0f64),
                     Vector3::new(0f64, 5f64, 0f64),
@@ -92,7 +115,8 @@ fn moving_surface() -> Surface<3> {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+        None,
+    ))
 }
 
 #[test]
@@ -109,7 +133,54 @@ fn clearly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((11.125f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+}
+
+#[test]
+fn zero_radius_static_receiver_is_never_hit() {
+    let receiver = Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0f64, 0, false);
+
+    let directly_aimed_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(5f64, 10f64, -1f64)),
+        Vector3::new(5f64, 0f64, 2f64),
+        1f64,
+        0,
+        1f64,
+    );
+    let grazing_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 1f64, 0f64)),
+        Vector3::new(10f64, 0f64, 1f64),
+        1f64,
+        0,
+        1f64,
+    );
+
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &directly_aimed_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &grazing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -127,13 +198,19 @@ fn clearly_hit_static_receiver_velocity_not_1() {
 
     assert_intersection_equals(
         Some((27.25f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
 #[test]
 fn clearly_hit_distant_static_receiver() {
-    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0);
+    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0, false);
 
     let hitting_ray: Ray = Ray::new(
         Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
@@ -148,7 +225,13 @@ fn clearly_hit_distant_static_receiver() {
             (1373.1 * DEFAULT_SAMPLE_RATE / DEFAULT_PROPAGATION_SPEED),
             Vector3::new(1373.1, 0f64, 0f64),
         )),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 300000, None),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(300000),
+            None,
+        ),
     );
 }
 
@@ -166,7 +249,7 @@ fn miss_static_receiver_because_time() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 1, 10, None),
+        intersect_ray_and_receiver(&hitting_ray, &receiver, SampleTime(1), SampleTime(10), None),
     );
 }
 
@@ -184,7 +267,13 @@ fn narrowly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((10.05f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -202,7 +291,13 @@ fn narrowly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -220,8 +315,14 @@ fn clearly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 0, 100, None),
-    )
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
 }
 
 #[test]
@@ -229,15 +330,17 @@ fn hit_receiver_moving_towards_ray() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(-10f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -250,7 +353,13 @@ fn hit_receiver_moving_towards_ray() {
 
     assert_intersection_equals(
         Some((9.933f64, Vector3::new(-4.93, 0.0, 0.0))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver_moving_towards_ray, 0, 100, None),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver_moving_towards_ray,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -259,15 +368,17 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(343.3f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 44100 * 9,
+                time: SampleTime(44100 * 9),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -280,7 +391,13 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
 
     assert_intersection_equals(
         Some((39689.74f64, Vector3::new(308.87, 0.0, 0.0))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver_moving_towards_ray, 0, 100000, None),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver_moving_towards_ray,
+            SampleTime(0),
+            SampleTime(100000),
+            None,
+        ),
     );
 }
 
@@ -298,7 +415,13 @@ fn narrowly_hit_moving_receiver() {
 
     assert_intersection_equals(
         Some((10f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 #[test]
@@ -315,7 +438,13 @@ fn narrowly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -333,7 +462,13 @@ fn clearly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -351,7 +486,13 @@ fn miss_moving_receiver_because_timing() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&too_late_ray, &receiver, 2, 100, None),
+        intersect_ray_and_receiver(
+            &too_late_ray,
+            &receiver,
+            SampleTime(2),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -368,7 +509,13 @@ fn hit_moving_receiver_after_movement_finished() {
 
     assert_intersection_equals(
         Some((34.042f64, Vector3::new(19.93f64, -0.07f64, 1f64))),
-        intersect_ray_and_receiver(&late_hitting_ray, &receiver, 0, 100, None),
+        intersect_ray_and_receiver(
+            &late_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -384,11 +531,86 @@ fn clearly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((7f64, Vector3::new(5f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(0), SampleTime(100), None),
     );
 }
+
+/// The corners of a quad in the given y plane, extending `static_surface` to a full square.
+/// Its second triangle covers the area where x + z > 10.
+const fn quad_corners(y: f64) -> [Vector3<f64>; 4] {
+    [
+        Vector3::new(10f64, y, 0f64),
+        Vector3::new(0f64, y, 0f64),
+        Vector3::new(0f64, y, 10f64),
+        Vector3::new(10f64, y, 10f64),
+    ]
+}
+
+#[test]
+fn hit_static_quad_in_second_triangle() {
+    let quad = SurfaceVariant::Quad(Surface::Interpolated(
+        quad_corners(3f64),
+        SurfaceData::new(MATERIAL_CONCRETE_WALL),
+    ));
+
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 10f64, 0f64)),
+        Vector3::new(8f64, -4f64, 6f64),
+        1f64,
+        0,
+        1f64,
+    );
+
+    assert_surface_intersection_equals(
+        Some((7f64, Vector3::new(8f64, 3f64, 6f64))),
+        intersect_ray_and_surface(&hitting_ray, &quad, SampleTime(0), SampleTime(100), None),
+    );
+    // the triangle alone doesn't cover that part of the quad
+    assert_surface_intersection_equals(
+        None,
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &static_surface(),
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+}
+
+#[test]
+fn hit_moving_quad_in_second_triangle() {
+    let quad = SurfaceVariant::Quad(Surface::Keyframes(
+        vec![
+            SurfaceKeyframe {
+                time: SampleTime(0),
+                coords: quad_corners(3f64),
+            },
+            SurfaceKeyframe {
+                time: SampleTime(20),
+                coords: quad_corners(13f64),
+            },
+        ],
+        SurfaceData::new(MATERIAL_CONCRETE_WALL),
+        None,
+    ));
+
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 10f64, 0f64)),
+        Vector3::new(8f64, -4f64, 6f64),
+        1f64,
+        0,
+        1f64,
+    );
+
+    assert_surface_intersection_equals(
+        Some((14f64, Vector3::new(8f64, 10f64, 6f64))),
+        intersect_ray_and_surface(&hitting_ray, &quad, SampleTime(0), SampleTime(100), None),
+    );
+}
+
 #[test]
 fn miss_static_surface_because_time() {
     let surface = static_surface();
@@ -401,9 +623,9 @@ fn miss_static_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 1, 5, None),
+        intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(1), SampleTime(5), None),
     );
 }
 
@@ -419,9 +641,15 @@ fn narrowly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((3f64, Vector3::new(0f64, 3f64, 0f64))),
-        intersect_ray_and_surface(&narrowly_hitting_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(
+            &narrowly_hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -437,9 +665,15 @@ fn narrowly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -455,10 +689,10 @@ fn clearly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&missing_ray, &surface, 0, 100, None),
-    )
+        intersect_ray_and_surface(&missing_ray, &surface, SampleTime(0), SampleTime(100), None),
+    );
 }
 
 #[test]
@@ -473,10 +707,73 @@ fn clearly_hit_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((10f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(0), SampleTime(100), None),
+    );
+}
+
+#[test]
+fn surface_normal_faces_ray() {
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 10f64, 0f64)),
+        Vector3::new(1f64, -7f64, 2f64),
+        1f64,
+        0,
+        1f64,
+    );
+    for surface in [static_surface(), moving_surface()] {
+        let (_time, _coords, normal) =
+            intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(0), SampleTime(100), None)
+                .unwrap();
+        assert_abs_diff_eq!(Vector3::new(0f64, -1f64, 0f64), normal, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn tilting_surface_normal_is_taken_at_intersection_time() {
+    let first = SurfaceKeyframe {
+        time: SampleTime(0),
+        coords: [
+            Vector3::new(10f64, 3f64, 0f64),
+            Vector3::new(0f64, 3f64, 0f64),
+            Vector3::new(0f64, 3f64, 10f64),
+        ],
+    };
+    // the first corner rises, tilting the surface around the z axis
+    let second = SurfaceKeyframe {
+        time: SampleTime(20),
+        coords: [
+            Vector3::new(10f64, 13f64, 0f64),
+            Vector3::new(0f64, 3f64, 0f64),
+            Vector3::new(0f64, 3f64, 10f64),
+        ],
+    };
+    let surface = SurfaceVariant::Triangle(Surface::Keyframes(
+        vec![first, second],
+        SurfaceData::new(MATERIAL_CONCRETE_WALL),
+        None,
+    ));
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 10f64, 0f64)),
+        Vector3::new(1f64, -7f64, 2f64),
+        1f64,
+        0,
+        1f64,
     );
+
+    let (time, _coords, normal) =
+        intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(0), SampleTime(100), None)
+            .unwrap();
+    // the surface is at a height of 3 + 0.05t where the ray passes through it
+    assert_abs_diff_eq!(10f64 / 0.95f64, time, epsilon = 1e-6);
+    let coords = interpolate_two_surface_keyframes(&first, &second, time).unwrap();
+    let expected_normal = (coords[2] - coords[0])
+        .cross(&(coords[1] - coords[0]))
+        .normalize();
+    assert_abs_diff_eq!(expected_normal, normal, epsilon = 1e-9);
+    // the surface has already tilted away from its initial normal
+    assert!(normal.x > 0.1f64);
 }
 
 #[test]
@@ -491,9 +788,9 @@ fn miss_moving_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 1, 5, None),
+        intersect_ray_and_surface(&hitting_ray, &surface, SampleTime(1), SampleTime(5), None),
     );
 }
 
@@ -509,9 +806,15 @@ fn hit_moving_surface_with_ray_starting_late() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((10f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray_with_later_start, &surface, 0, 100, None),
+        intersect_ray_and_surface(
+            &hitting_ray_with_later_start,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -527,9 +830,15 @@ fn narrowly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -545,9 +854,15 @@ fn clearly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&clearly_missing_ray, &surface, 0, 100, None),
+        intersect_ray_and_surface(
+            &clearly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
     );
 }
 
@@ -573,3 +888,284 @@ assert_intersection_equal(
     intersect_ray_and_surface(&missing_ray, &surface, 0, 100),
 )
 */
+
+#[test]
+fn ray_starting_inside_static_receiver() {
+    let receiver = static_receiver();
+
+    for direction in [
+        Vector3::new(1f64, 0f64, 0f64),
+        Vector3::new(-1f64, 0f64, 0f64),
+    ] {
+        let inside_ray: Ray = Ray::new(
+            Unit::new_normalize(direction),
+            Vector3::new(10.05f64, 10f64, 1f64),
+            1f64,
+            5,
+            1f64,
+        );
+
+        assert_intersection_equals(
+            Some((5f64, Vector3::new(10.05f64, 10f64, 1f64))),
+            intersect_ray_and_receiver(
+                &inside_ray,
+                &receiver,
+                SampleTime(5),
+                SampleTime(100),
+                None,
+            ),
+        );
+    }
+}
+
+#[test]
+fn ray_starting_inside_moving_receiver() {
+    let receiver = Receiver::Keyframes(
+        vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(0f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(10),
+                coords: Vector3::new(10f64, 0f64, 0f64),
+            },
+        ],
+        1f64,
+        None,
+        false,
+    );
+
+    let inside_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 1f64, 0f64)),
+        Vector3::new(5.5f64, 0f64, 0f64),
+        1f64,
+        5,
+        1f64,
+    );
+
+    assert_intersection_equals(
+        Some((5f64, Vector3::new(5.5f64, 0f64, 0f64))),
+        intersect_ray_and_receiver(&inside_ray, &receiver, SampleTime(5), SampleTime(100), None),
+    );
+}
+
+#[test]
+fn chunk_bounds_at_moving_objects() {
+    let (min_coords, max_coords) = moving_surface().chunk_bounds_at(5);
+    assert_eq!(Vector3::new(-5f64, 3f64, 0f64), min_coords);
+    assert_eq!(Vector3::new(5f64, 3f64, 10f64), max_coords);
+
+    let (min_coords, max_coords) = moving_receiver().chunk_bounds_at(10);
+    assert_abs_diff_eq!(Vector3::new(9.9f64, 9.9f64, 0.9f64), min_coords);
+    assert_abs_diff_eq!(Vector3::new(10.1f64, 10.1f64, 1.1f64), max_coords);
+}
+
+/// A static wall at the given x coordinate, extending infinitely along y and z.
+struct Wall(f64);
+
+impl Intersectable for Wall {
+    type Intersection = (f64, Vector3<f64>);
+
+    fn intersect(
+        &self,
+        ray: &Ray,
+        time_entry: SampleTime,
+        time_exit: SampleTime,
+        _loop_duration: Option<SampleDuration>,
+    ) -> Option<(f64, Vector3<f64>)> {
+        let time = (self.0 - ray.origin.x) / (ray.velocity * ray.direction.x) + ray.time;
+        (time >= f64::from(time_entry.samples()) && time <= f64::from(time_exit.samples()))
+            .then(|| (time, ray.coords_at_time(time)))
+    }
+
+    fn chunk_bounds_at(&self, _time: u32) -> (Vector3<f64>, Vector3<f64>) {
+        (
+            Vector3::new(self.0, f64::MIN, f64::MIN),
+            Vector3::new(self.0, f64::MAX, f64::MAX),
+        )
+    }
+}
+
+/// Find the earliest intersection between the ray and any of the given objects.
+fn first_intersection(
+    ray: &Ray,
+    objects: &[&dyn Intersectable<Intersection = (f64, Vector3<f64>)>],
+    time_entry: u32,
+    time_exit: u32,
+) -> Option<(f64, Vector3<f64>)> {
+    objects
+        .iter()
+        .filter_map(|object| {
+            object.intersect(ray, SampleTime(time_entry), SampleTime(time_exit), None)
+        })
+        .min_by(|first, second| first.0.total_cmp(&second.0))
+}
+
+#[test]
+fn custom_intersectable_next_to_scene_objects() {
+    let ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+        Vector3::new(0f64, 10f64, 1f64),
+        1f64,
+        0,
+        1f64,
+    );
+    let receiver = static_receiver();
+
+    assert_intersection_equals(
+        Some((5f64, Vector3::new(5f64, 10f64, 1f64))),
+        first_intersection(&ray, &[&receiver, &Wall(5f64)], 0, 100),
+    );
+    assert_intersection_equals(
+        Some((9.9f64, Vector3::new(9.9f64, 10f64, 1f64))),
+        first_intersection(&ray, &[&receiver, &Wall(15f64)], 0, 100),
+    );
+}
+
+/// An ellipsoid at the origin, elongated along its local x axis.
+const fn elongated_ellipsoid(orientation: Rotation3<f64>) -> Receiver {
+    Receiver::Ellipsoid(
+        Vector3::new(0f64, 0f64, 0f64),
+        Vector3::new(1f64, 0.1f64, 0.1f64),
+        orientation,
+        0,
+    )
+}
+
+fn ray_from(origin: Vector3<f64>, direction: Vector3<f64>) -> Ray {
+    Ray::new(Unit::new_normalize(direction), origin, 1f64, 0, 1f64)
+}
+
+#[test]
+fn ellipsoid_elongated_along_x_captures_rays_depending_on_their_direction() {
+    let receiver = elongated_ellipsoid(Rotation3::identity());
+    // rays travelling along the long axis hit its tip, off-center ones miss the thin cross-section
+    assert_intersection_equals(
+        Some((4f64, Vector3::new(-1f64, 0f64, 0f64))),
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(-5f64, 0f64, 0f64), Vector3::x()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(-5f64, 0.5f64, 0f64), Vector3::x()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+    // rays travelling along y hit its wide side, even half way towards the tip
+    assert_intersection_equals(
+        Some((4.9f64, Vector3::new(0f64, -0.1f64, 0f64))),
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(0f64, -5f64, 0f64), Vector3::y()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+    let half_way_depth = 0.1f64 * 0.75f64.sqrt();
+    assert_intersection_equals(
+        Some((
+            5f64 - half_way_depth,
+            Vector3::new(0.5f64, -half_way_depth, 0f64),
+        )),
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(0.5f64, -5f64, 0f64), Vector3::y()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+}
+
+#[test]
+fn rotated_ellipsoid_is_elongated_along_rotated_axis() {
+    // rotating by 90 degrees around z turns the local x axis into the scene's y axis
+    let orientation = Rotation3::from_axis_angle(&Vector3::z_axis(), std::f64::consts::FRAC_PI_2);
+    let receiver = elongated_ellipsoid(orientation);
+    assert_intersection_equals(
+        Some((4f64, Vector3::new(0f64, -1f64, 0f64))),
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(0f64, -5f64, 0f64), Vector3::y()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(0.5f64, -5f64, 0f64), Vector3::y()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            None,
+        ),
+    );
+
+    let (min_coords, max_coords) = receiver.chunk_bounds_at(0);
+    for (expected, result) in [
+        (Vector3::new(-0.1f64, -1f64, -0.1f64), min_coords),
+        (Vector3::new(0.1f64, 1f64, 0.1f64), max_coords),
+    ] {
+        for idx in 0..3 {
+            assert_abs_diff_eq!(expected[idx], result[idx], epsilon = 1e-9);
+        }
+    }
+}
+
+#[test]
+fn ellipsoid_without_positive_semi_axes_is_never_hit() {
+    let ray = ray_from(Vector3::new(-5f64, 0f64, 0f64), Vector3::x());
+    assert!(intersect_ray_and_ellipsoid(
+        &ray,
+        &Vector3::new(0f64, 0f64, 0f64),
+        &Vector3::new(1f64, 0f64, 1f64),
+        &Rotation3::identity(),
+        SampleTime(0),
+        SampleTime(100),
+    )
+    .is_none());
+}
+
+#[test]
+fn moving_ellipsoid_intersection() {
+    let receiver = Receiver::EllipsoidKeyframes(
+        vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(0f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(10),
+                coords: Vector3::new(10f64, 0f64, 0f64),
+            },
+        ],
+        Vector3::new(1f64, 0.1f64, 0.1f64),
+        Rotation3::identity(),
+    );
+    // the ray crosses the ellipsoid's path just as its center passes by,
+    // so it enters where ((5 - t) / 1)^2 + ((t - 5) / 0.1)^2 = 1
+    let depth = 1f64 / 101f64.sqrt();
+    assert_intersection_equals(
+        Some((5f64 - depth, Vector3::new(5f64, -depth, 0f64))),
+        intersect_ray_and_receiver(
+            &ray_from(Vector3::new(5f64, -5f64, 0f64), Vector3::y()),
+            &receiver,
+            SampleTime(0),
+            SampleTime(20),
+            None,
+        ),
+    );
+}
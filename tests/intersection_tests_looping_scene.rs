@@ -1,8 +1,13 @@
 use approx::{abs_diff_eq, assert_abs_diff_eq};
-use demo::intersection::{intersect_ray_and_receiver, intersect_ray_and_surface};
+use demo::intersection::{
+    intersect_ray_and_receiver, intersect_ray_and_surface, SurfaceIntersection,
+};
 use demo::materials::MATERIAL_CONCRETE_WALL;
 use demo::ray::{Ray, DEFAULT_PROPAGATION_SPEED};
-use demo::scene::{CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe};
+use demo::scene::{
+    CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe, SurfaceVariant,
+};
+use demo::time::{SampleDuration, SampleTime};
 use demo::DEFAULT_SAMPLE_RATE;
 use nalgebra::{Unit, Vector3};
 
@@ -21,53 +26,69 @@ fn assert_intersection_equals(
                 "Result is None where it should have been Some. Expected: {expected:?}"
             );
             let result = result.unwrap();
-            if !abs_diff_eq!(time, result.0, epsilon = 0.01) {
-                assert!(false, "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}")
-            };
+            assert!(
+                abs_diff_eq!(time, result.0, epsilon = 0.01),
+                "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}"
+            );
             for idx in 0..3 {
-                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01)
+                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01);
             }
         }
     }
 }
 
-fn static_receiver() -> Receiver {
-    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0)
+/// Like `assert_intersection_equals` for surface intersections, additionally checking that the normal is a unit vector.
+fn assert_surface_intersection_equals(
+    expected: Option<(f64, Vector3<f64>)>,
+    result: Option<SurfaceIntersection>,
+) {
+    if let Some((_time, _coords, normal)) = result {
+        assert_abs_diff_eq!(1f64, normal.norm(), epsilon = 1e-9);
+    }
+    assert_intersection_equals(
+        expected,
+        result.map(|(time, coords, _normal)| (time, coords)),
+    );
+}
+
+const fn static_receiver() -> Receiver {
+    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0, false)
 }
 
 fn moving_receiver() -> Receiver {
     Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(0f64, 20f64, 1f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(20f64, 0f64, 1f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     )
 }
 
-fn static_surface() -> Surface<3> {
-    Surface::Interpolated(
+const fn static_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Interpolated(
         [
             Vector3::new(10f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 10f64),
         ],
-        0,
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+    ))
 }
 
-fn moving_surface() -> Surface<3> {
-    Surface::Keyframes(
+fn moving_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: [
                     Vector3::new(0f64, 3f64, 0f64),
                     Vector3::new(-10f64, 3f64, 0f64),
@@ -75,7 +96,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(10f64, 3f64, 0f64),
                     Vector3::new(0f64, 3f64, 0f64),
@@ -83,7 +104,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: [
                     Vector3::new(10f64, 5f64, 0f64),
                     Vector3::new(0f64, 5f64, 0f64),
@@ -92,7 +113,8 @@ fn moving_surface() -> Surface<3> {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+        None,
+    ))
 }
 
 #[test]
@@ -109,7 +131,13 @@ fn clearly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((11.125f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 100, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -127,13 +155,19 @@ fn clearly_hit_static_receiver_velocity_not_1() {
 
     assert_intersection_equals(
         Some((27.25f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 100, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
 #[test]
 fn clearly_hit_distant_static_receiver() {
-    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0);
+    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0, false);
 
     let hitting_ray: Ray = Ray::new(
         Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
@@ -148,7 +182,13 @@ fn clearly_hit_distant_static_receiver() {
             (1373.1 * DEFAULT_SAMPLE_RATE / DEFAULT_PROPAGATION_SPEED),
             Vector3::new(1373.1, 0f64, 0f64),
         )),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 300000, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(300000),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -166,7 +206,13 @@ fn miss_static_receiver_because_time() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 1, 10, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(1),
+            SampleTime(10),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -184,7 +230,13 @@ fn narrowly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((10.05f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 0, 100, Some(300)),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(300)),
+        ),
     );
 }
 
@@ -202,7 +254,13 @@ fn narrowly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 0, 100, Some(500)),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(500)),
+        ),
     );
 }
 
@@ -220,8 +278,14 @@ fn clearly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 0, 100, Some(400)),
-    )
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(400)),
+        ),
+    );
 }
 
 #[test]
@@ -229,15 +293,17 @@ fn hit_receiver_moving_towards_ray() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(-10f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -250,7 +316,13 @@ fn hit_receiver_moving_towards_ray() {
 
     assert_intersection_equals(
         Some((9.933f64, Vector3::new(-4.93, 0.0, 0.0))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver_moving_towards_ray, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver_moving_towards_ray,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -259,15 +331,17 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(343.3f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 44100 * 9,
+                time: SampleTime(44100 * 9),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -283,9 +357,9 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
         intersect_ray_and_receiver(
             &hitting_ray,
             &receiver_moving_towards_ray,
-            0,
-            100000,
-            Some(44100 * 9),
+            SampleTime(0),
+            SampleTime(100000),
+            Some(SampleDuration(44100 * 9)),
         ),
     );
 }
@@ -304,7 +378,13 @@ fn narrowly_hit_moving_receiver() {
 
     assert_intersection_equals(
         Some((10f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 #[test]
@@ -321,7 +401,13 @@ fn narrowly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -339,7 +425,13 @@ fn clearly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -357,7 +449,13 @@ fn miss_moving_receiver_because_timing() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&too_late_ray, &receiver, 2, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &too_late_ray,
+            &receiver,
+            SampleTime(2),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -374,7 +472,13 @@ fn hit_moving_receiver_after_movement_finished() {
 
     assert_intersection_equals(
         Some((34.042f64, Vector3::new(19.93f64, -0.07f64, 1f64))),
-        intersect_ray_and_receiver(&late_hitting_ray, &receiver, 0, 100, Some(40)),
+        intersect_ray_and_receiver(
+            &late_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(40)),
+        ),
     );
 }
 
@@ -390,9 +494,15 @@ fn clearly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((7f64, Vector3::new(5f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 100, Some(100)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(100)),
+        ),
     );
 }
 #[test]
@@ -407,9 +517,15 @@ fn miss_static_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 1, 5, Some(120)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(1),
+            SampleTime(5),
+            Some(SampleDuration(120)),
+        ),
     );
 }
 
@@ -425,9 +541,15 @@ fn narrowly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((3f64, Vector3::new(0f64, 3f64, 0f64))),
-        intersect_ray_and_surface(&narrowly_hitting_ray, &surface, 0, 100, Some(400)),
+        intersect_ray_and_surface(
+            &narrowly_hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -443,9 +565,15 @@ fn narrowly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 0, 100, Some(500)),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(500)),
+        ),
     );
 }
 
@@ -461,10 +589,16 @@ fn clearly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&missing_ray, &surface, 0, 100, Some(700)),
-    )
+        intersect_ray_and_surface(
+            &missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(700)),
+        ),
+    );
 }
 
 #[test]
@@ -479,9 +613,15 @@ fn clearly_hit_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((10f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -497,9 +637,15 @@ fn miss_moving_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 1, 5, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(1),
+            SampleTime(5),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -515,9 +661,15 @@ fn hit_moving_surface_with_ray_starting_late() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((10f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray_with_later_start, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray_with_later_start,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -533,9 +685,15 @@ fn narrowly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -551,9 +709,15 @@ fn clearly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&clearly_missing_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &clearly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -579,3 +743,38 @@ assert_intersection_equal(
     intersect_ray_and_surface(&missing_ray, &surface, 0, 100),
 )
 */
+
+#[test]
+fn hit_moving_surface_with_own_loop_duration() {
+    let SurfaceVariant::Triangle(Surface::Keyframes(keyframes, material, _)) = moving_surface()
+    else {
+        unreachable!()
+    };
+    // loops faster than the scene, so at time 30 it's at the same position as at time 10
+    let surface = SurfaceVariant::Triangle(Surface::Keyframes(
+        keyframes,
+        material,
+        Some(SampleDuration(20)),
+    ));
+
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 10f64, 0f64)),
+        Vector3::new(1f64, -7f64, 2f64),
+        1f64,
+        20,
+        1f64,
+    );
+
+    for scene_loop_duration in [None, Some(SampleDuration(700))] {
+        assert_surface_intersection_equals(
+            Some((30f64, Vector3::new(1f64, 3f64, 2f64))),
+            intersect_ray_and_surface(
+                &hitting_ray,
+                &surface,
+                SampleTime(20),
+                SampleTime(100),
+                scene_loop_duration,
+            ),
+        );
+    }
+}
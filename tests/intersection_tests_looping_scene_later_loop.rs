@@ -1,8 +1,13 @@
 use approx::{abs_diff_eq, assert_abs_diff_eq};
-use demo::intersection::{intersect_ray_and_receiver, intersect_ray_and_surface};
+use demo::intersection::{
+    intersect_ray_and_receiver, intersect_ray_and_surface, SurfaceIntersection,
+};
 use demo::materials::MATERIAL_CONCRETE_WALL;
 use demo::ray::{Ray, DEFAULT_PROPAGATION_SPEED};
-use demo::scene::{CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe};
+use demo::scene::{
+    CoordinateKeyframe, Receiver, Surface, SurfaceData, SurfaceKeyframe, SurfaceVariant,
+};
+use demo::time::{SampleDuration, SampleTime};
 use demo::DEFAULT_SAMPLE_RATE;
 use nalgebra::{Unit, Vector3};
 
@@ -21,53 +26,69 @@ fn assert_intersection_equals(
                 "Result is None where it should have been Some. Expected: {expected:?}"
             );
             let result = result.unwrap();
-            if !abs_diff_eq!(time, result.0, epsilon = 0.01) {
-                assert!(false, "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}")
-            };
+            assert!(
+                abs_diff_eq!(time, result.0, epsilon = 0.01),
+                "Times between expected and result don't match! Expected: {expected:?}, Result: {result:?}"
+            );
             for idx in 0..3 {
-                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01)
+                assert_abs_diff_eq!(coords[idx], result.1[idx], epsilon = 0.01);
             }
         }
     }
 }
 
-fn static_receiver() -> Receiver {
-    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0)
+/// Like `assert_intersection_equals` for surface intersections, additionally checking that the normal is a unit vector.
+fn assert_surface_intersection_equals(
+    expected: Option<(f64, Vector3<f64>)>,
+    result: Option<SurfaceIntersection>,
+) {
+    if let Some((_time, _coords, normal)) = result {
+        assert_abs_diff_eq!(1f64, normal.norm(), epsilon = 1e-9);
+    }
+    assert_intersection_equals(
+        expected,
+        result.map(|(time, coords, _normal)| (time, coords)),
+    );
+}
+
+const fn static_receiver() -> Receiver {
+    Receiver::Interpolated(Vector3::new(10f64, 10f64, 1f64), 0.1f64, 0, false)
 }
 
 fn moving_receiver() -> Receiver {
     Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(0f64, 20f64, 1f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(20f64, 0f64, 1f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     )
 }
 
-fn static_surface() -> Surface<3> {
-    Surface::Interpolated(
+const fn static_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Interpolated(
         [
             Vector3::new(10f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 0f64),
             Vector3::new(0f64, 3f64, 10f64),
         ],
-        0,
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+    ))
 }
 
-fn moving_surface() -> Surface<3> {
-    Surface::Keyframes(
+fn moving_surface() -> SurfaceVariant {
+    SurfaceVariant::Triangle(Surface::Keyframes(
         vec![
             SurfaceKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: [
                     Vector3::new(0f64, 3f64, 0f64),
                     Vector3::new(-10f64, 3f64, 0f64),
@@ -75,7 +96,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 10,
+                time: SampleTime(10),
                 coords: [
                     Vector3::new(10f64, 3f64, 0f64),
                     Vector3::new(0f64, 3f64, 0f64),
@@ -83,7 +104,7 @@ fn moving_surface() -> Surface<3> {
                 ],
             },
             SurfaceKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: [
                     Vector3::new(10f64, 5f64, 0f64),
                     Vector3::new(0f64, 5f64, 0f64),
@@ -92,7 +113,8 @@ fn moving_surface() -> Surface<3> {
             },
         ],
         SurfaceData::new(MATERIAL_CONCRETE_WALL),
-    )
+        None,
+    ))
 }
 
 #[test]
@@ -109,7 +131,13 @@ fn clearly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((811.125f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 900, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(900),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -127,13 +155,19 @@ fn clearly_hit_static_receiver_velocity_not_1() {
 
     assert_intersection_equals(
         Some((927.25f64, Vector3::new(9.95549, 9.910981, 1.0089018f64))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 1000, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(1000),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
 #[test]
 fn clearly_hit_distant_static_receiver() {
-    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0);
+    let receiver = Receiver::Interpolated(Vector3::new(1373.2, 0f64, 0f64), 0.1, 0, false);
 
     let hitting_ray: Ray = Ray::new(
         Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
@@ -148,7 +182,13 @@ fn clearly_hit_distant_static_receiver() {
             500f64 + (1373.1 * DEFAULT_SAMPLE_RATE / DEFAULT_PROPAGATION_SPEED),
             Vector3::new(1373.1, 0f64, 0f64),
         )),
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 0, 300000, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(300000),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -166,7 +206,13 @@ fn miss_static_receiver_because_time() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&hitting_ray, &receiver, 1, 610, Some(400)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(1),
+            SampleTime(610),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -184,7 +230,13 @@ fn narrowly_hit_static_receiver() {
 
     assert_intersection_equals(
         Some((713.05f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 700, 800, Some(300)),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(700),
+            SampleTime(800),
+            Some(SampleDuration(300)),
+        ),
     );
 }
 
@@ -202,7 +254,13 @@ fn narrowly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 800, 1000, Some(500)),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(800),
+            SampleTime(1000),
+            Some(SampleDuration(500)),
+        ),
     );
 }
 
@@ -220,8 +278,14 @@ fn clearly_miss_static_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 700, 900, Some(400)),
-    )
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(700),
+            SampleTime(900),
+            Some(SampleDuration(400)),
+        ),
+    );
 }
 
 #[test]
@@ -229,15 +293,17 @@ fn hit_receiver_moving_towards_ray() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(-10f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: 20,
+                time: SampleTime(20),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -250,7 +316,13 @@ fn hit_receiver_moving_towards_ray() {
 
     assert_intersection_equals(
         Some((69.933f64, Vector3::new(-4.93, 0.0, 0.0))),
-        intersect_ray_and_receiver(&hitting_ray, &receiver_moving_towards_ray, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver_moving_towards_ray,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -260,15 +332,17 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
     let receiver_moving_towards_ray = Receiver::Keyframes(
         vec![
             CoordinateKeyframe {
-                time: 0,
+                time: SampleTime(0),
                 coords: Vector3::new(343.3f64, 0f64, 0f64),
             },
             CoordinateKeyframe {
-                time: loop_dur,
+                time: SampleTime(loop_dur),
                 coords: Vector3::new(0f64, 0f64, 0f64),
             },
         ],
         0.1f64,
+        None,
+        false,
     );
 
     let hitting_ray: Ray = Ray::new(
@@ -280,13 +354,16 @@ fn hit_receiver_moving_towards_ray_after_later_start() {
     );
 
     assert_intersection_equals(
-        Some((39689.74f64 + loop_dur as f64, Vector3::new(308.87, 0.0, 0.0))),
+        Some((
+            39689.74f64 + f64::from(loop_dur),
+            Vector3::new(308.87, 0.0, 0.0),
+        )),
         intersect_ray_and_receiver(
             &hitting_ray,
             &receiver_moving_towards_ray,
-            loop_dur,
-            10000000,
-            Some(loop_dur),
+            SampleTime(loop_dur),
+            SampleTime(10000000),
+            Some(SampleDuration(loop_dur)),
         ),
     );
 }
@@ -305,7 +382,13 @@ fn narrowly_hit_moving_receiver() {
 
     assert_intersection_equals(
         Some((110f64, Vector3::new(10.1f64, 10f64, 1f64))),
-        intersect_ray_and_receiver(&narrowly_hitting_ray, &receiver, 0, 200, Some(20)),
+        intersect_ray_and_receiver(
+            &narrowly_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(200),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -323,7 +406,13 @@ fn narrowly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&narrowly_missing_ray, &receiver, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &narrowly_missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -341,7 +430,13 @@ fn clearly_miss_moving_receiver() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&missing_ray, &receiver, 0, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &missing_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -359,7 +454,13 @@ fn miss_moving_receiver_because_timing() {
 
     assert_intersection_equals(
         None,
-        intersect_ray_and_receiver(&too_late_ray, &receiver, 2, 100, Some(20)),
+        intersect_ray_and_receiver(
+            &too_late_ray,
+            &receiver,
+            SampleTime(2),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -376,7 +477,13 @@ fn hit_moving_receiver_after_movement_finished() {
 
     assert_intersection_equals(
         Some((74.042f64, Vector3::new(19.93f64, -0.07f64, 1f64))),
-        intersect_ray_and_receiver(&late_hitting_ray, &receiver, 0, 100, Some(40)),
+        intersect_ray_and_receiver(
+            &late_hitting_ray,
+            &receiver,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(40)),
+        ),
     );
 }
 
@@ -392,9 +499,15 @@ fn clearly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((207f64, Vector3::new(5f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 300, Some(100)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(300),
+            Some(SampleDuration(100)),
+        ),
     );
 }
 
@@ -410,9 +523,15 @@ fn miss_static_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 201, 205, Some(120)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(201),
+            SampleTime(205),
+            Some(SampleDuration(120)),
+        ),
     );
 }
 
@@ -428,9 +547,15 @@ fn narrowly_hit_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((803f64, Vector3::new(0f64, 3f64, 0f64))),
-        intersect_ray_and_surface(&narrowly_hitting_ray, &surface, 800, 900, Some(400)),
+        intersect_ray_and_surface(
+            &narrowly_hitting_ray,
+            &surface,
+            SampleTime(800),
+            SampleTime(900),
+            Some(SampleDuration(400)),
+        ),
     );
 }
 
@@ -446,9 +571,15 @@ fn narrowly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 1000, 1100, Some(500)),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(1000),
+            SampleTime(1100),
+            Some(SampleDuration(500)),
+        ),
     );
 }
 
@@ -464,10 +595,16 @@ fn clearly_miss_static_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&missing_ray, &surface, 700, 800, Some(700)),
-    )
+        intersect_ray_and_surface(
+            &missing_ray,
+            &surface,
+            SampleTime(700),
+            SampleTime(800),
+            Some(SampleDuration(700)),
+        ),
+    );
 }
 
 #[test]
@@ -482,9 +619,15 @@ fn clearly_hit_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((90f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -500,9 +643,15 @@ fn miss_moving_surface_because_time() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&hitting_ray, &surface, 61, 65, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(61),
+            SampleTime(65),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -518,9 +667,15 @@ fn hit_moving_surface_with_ray_starting_late() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         Some((50f64, Vector3::new(1f64, 3f64, 2f64))),
-        intersect_ray_and_surface(&hitting_ray_with_later_start, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &hitting_ray_with_later_start,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -536,9 +691,15 @@ fn narrowly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&narrowly_missing_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &narrowly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -554,9 +715,15 @@ fn clearly_miss_moving_surface() {
         1f64,
     );
 
-    assert_intersection_equals(
+    assert_surface_intersection_equals(
         None,
-        intersect_ray_and_surface(&clearly_missing_ray, &surface, 0, 100, Some(20)),
+        intersect_ray_and_surface(
+            &clearly_missing_ray,
+            &surface,
+            SampleTime(0),
+            SampleTime(100),
+            Some(SampleDuration(20)),
+        ),
     );
 }
 
@@ -582,3 +749,135 @@ assert_intersection_equal(
     intersect_ray_and_surface(&missing_ray, &surface, 0, 100),
 )
 */
+
+/// A receiver approaching the origin along the X axis for 40 samples,
+/// then holding still at x = 10 until the loop ends after 100 samples.
+fn approaching_then_holding_receiver() -> Receiver {
+    Receiver::Keyframes(
+        vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(30f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(40),
+                coords: Vector3::new(10f64, 0f64, 0f64),
+            },
+        ],
+        0.1f64,
+        None,
+        false,
+    )
+}
+
+#[test]
+fn hit_approaching_receiver_in_first_partial_loop() {
+    let receiver = approaching_then_holding_receiver();
+    // starts mid-loop, long after the scene started
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+        Vector3::new(0f64, 0f64, 0f64),
+        1f64,
+        10_020,
+        1f64,
+    );
+
+    assert_intersection_equals(
+        Some((10_033.267f64, Vector3::new(13.267f64, 0f64, 0f64))),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(10_020),
+            SampleTime(10_100),
+            Some(SampleDuration(100)),
+        ),
+    );
+}
+
+#[test]
+fn only_hit_holding_receiver_within_time_window() {
+    let receiver = approaching_then_holding_receiver();
+    // reaches the holding receiver at 10_059.9, the loop started at 10_000
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+        Vector3::new(0f64, 0f64, 0f64),
+        1f64,
+        10_050,
+        1f64,
+    );
+
+    assert_intersection_equals(
+        Some((10_059.9f64, Vector3::new(9.9f64, 0f64, 0f64))),
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(10_050),
+            SampleTime(10_100),
+            Some(SampleDuration(100)),
+        ),
+    );
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(10_070),
+            SampleTime(10_100),
+            Some(SampleDuration(100)),
+        ),
+    );
+    assert_intersection_equals(
+        None,
+        intersect_ray_and_receiver(
+            &hitting_ray,
+            &receiver,
+            SampleTime(10_050),
+            SampleTime(10_055),
+            Some(SampleDuration(100)),
+        ),
+    );
+}
+
+#[test]
+fn only_hit_holding_surface_within_time_window() {
+    let surface = moving_surface();
+    // reaches the surface holding its final keyframe at y = 5 at 10_027, the loop started at 10_000
+    let hitting_ray: Ray = Ray::new(
+        Unit::new_normalize(Vector3::new(0f64, 1f64, 0f64)),
+        Vector3::new(5f64, 0f64, 1f64),
+        1f64,
+        10_022,
+        1f64,
+    );
+
+    assert_surface_intersection_equals(
+        Some((10_027f64, Vector3::new(5f64, 5f64, 1f64))),
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(10_022),
+            SampleTime(10_039),
+            Some(SampleDuration(40)),
+        ),
+    );
+    assert_surface_intersection_equals(
+        None,
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(10_030),
+            SampleTime(10_039),
+            Some(SampleDuration(40)),
+        ),
+    );
+    assert_surface_intersection_equals(
+        None,
+        intersect_ray_and_surface(
+            &hitting_ray,
+            &surface,
+            SampleTime(10_022),
+            SampleTime(10_025),
+            Some(SampleDuration(40)),
+        ),
+    );
+}
@@ -0,0 +1,78 @@
+use approx::assert_abs_diff_eq;
+use demo::ir_diff::diff_impulse_responses;
+
+const SAMPLE_RATE: f64 = 1000f64;
+
+/// An exponentially decaying impulse response with its peak at sample 5.
+fn decaying_impulse_response() -> Vec<f64> {
+    let mut impulse_response = vec![0f64; 5];
+    impulse_response.extend((0..200).map(|idx| 0.97f64.powi(idx)));
+    impulse_response
+}
+
+#[test]
+fn identical_impulse_responses_have_no_difference() {
+    let impulse_response = decaying_impulse_response();
+    let diff = diff_impulse_responses(&impulse_response, &impulse_response, SAMPLE_RATE);
+    assert_eq!(impulse_response.len(), diff.length);
+    assert_abs_diff_eq!(0f64, diff.l2);
+    assert_abs_diff_eq!(0f64, diff.l_infinity);
+    assert_eq!(Some(0), diff.peak_offset);
+    assert_abs_diff_eq!(1f64, diff.correlation.unwrap(), epsilon = 1e-12);
+    for decade in &diff.decades {
+        if decade.first != 0f64 {
+            assert_abs_diff_eq!(1f64, decade.ratio().unwrap(), epsilon = 1e-12);
+        }
+    }
+}
+
+#[test]
+fn scaled_impulse_response_changes_energy_but_not_shape() {
+    let impulse_response = decaying_impulse_response();
+    let scaled: Vec<f64> = impulse_response.iter().map(|value| value * 0.5).collect();
+    let diff = diff_impulse_responses(&impulse_response, &scaled, SAMPLE_RATE);
+
+    assert_abs_diff_eq!(0.5f64, diff.l_infinity, epsilon = 1e-12);
+    let l2: f64 = impulse_response
+        .iter()
+        .map(|value| value * value)
+        .sum::<f64>()
+        .sqrt();
+    assert_abs_diff_eq!(0.5 * l2, diff.l2, epsilon = 1e-12);
+    assert_eq!(Some(0), diff.peak_offset);
+    assert_abs_diff_eq!(1f64, diff.correlation.unwrap(), epsilon = 1e-12);
+    // at 1 kHz, the decades cover sample 0, samples 1 to 9, 10 to 99 and 100 to 204
+    assert_eq!(
+        vec![(0f64, 0.001f64), (0.001, 0.01), (0.01, 0.1), (0.1, 1.0)],
+        diff.decades
+            .iter()
+            .map(|decade| (decade.start, decade.end))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(None, diff.decades[0].ratio());
+    for decade in &diff.decades[1..] {
+        assert_abs_diff_eq!(0.5f64, decade.ratio().unwrap(), epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn shifted_impulse_response_is_padded_and_arrives_later() {
+    let impulse_response = decaying_impulse_response();
+    let mut shifted = vec![0f64; 20];
+    shifted.extend(&impulse_response);
+    let diff = diff_impulse_responses(&impulse_response, &shifted, SAMPLE_RATE);
+
+    assert_eq!(shifted.len(), diff.length);
+    assert_eq!(Some(20), diff.peak_offset);
+    let correlation = diff.correlation.unwrap();
+    assert!(correlation < 1f64, "{correlation}");
+    assert!(diff.l_infinity > 0.4f64);
+    // the shift moves energy out of the first decades into later ones
+    assert!(diff.decades[1].ratio().unwrap() < 1f64);
+    assert!(diff.decades[3].ratio().unwrap() > 1f64);
+
+    let silence = vec![0f64; 10];
+    let diff = diff_impulse_responses(&silence, &impulse_response, SAMPLE_RATE);
+    assert_eq!(None, diff.peak_offset);
+    assert_eq!(None, diff.correlation);
+}
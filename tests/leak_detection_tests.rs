@@ -0,0 +1,222 @@
+use demo::{
+    impulse_response::PostProcessing,
+    materials::MATERIAL_CONCRETE_WALL,
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::{Scene, SceneData, SurfaceVariant},
+    scene_builder::{self, SceneBuilder},
+    simulation_config::SimulationConfig,
+    DEFAULT_SAMPLE_RATE,
+};
+use nalgebra::Vector3;
+use wav::BitDepth;
+
+fn closed_cube_scene() -> Scene {
+    SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -1.5f64),
+            (2f64, 2f64, 1.5f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_emitter_at(0f64, 0f64, 1.2f64)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn cube_missing_triangle_leaks() {
+    let mut scene = closed_cube_scene();
+    // remove one of the two triangles forming the top face
+    scene.surfaces.pop();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    let (_, report) = scene_data
+        .simulate_at_time_detecting_leaks(
+            0,
+            200,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            true,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(200, report.number_of_rays);
+    assert!(
+        report.percentage() > 25f64,
+        "Expected a significant amount of leaked rays, got {}%",
+        report.percentage()
+    );
+    // every leaked ray has to leave through the missing part of the top face
+    for leak in &report.leaked_rays {
+        assert!(leak.direction.z > 0f64, "Leaked ray moved down: {leak:?}");
+    }
+}
+
+#[test]
+fn closed_cube_barely_leaks() {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(closed_cube_scene()).unwrap();
+
+    let (_, report) = scene_data
+        .simulate_at_time_detecting_leaks(
+            0,
+            200,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            true,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    assert!(
+        report.percentage() < 5f64,
+        "Closed cube leaked {}% of rays",
+        report.percentage()
+    );
+}
+
+#[test]
+fn scene_without_surfaces_does_not_leak() {
+    let scene = SceneBuilder::new()
+        .with_receiver_at(10f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    let (_, report) = scene_data
+        .simulate_at_time_detecting_leaks(
+            0,
+            50,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(0, report.count());
+}
+
+/// The static receiver scene with a small cube of surfaces far away from it,
+/// so every ray passes through the receiver and then leaks out of the scene.
+fn directed_rays_scene() -> Scene {
+    Scene {
+        surfaces: scene_builder::static_cube(
+            Vector3::new(-5f64, -5f64, -5f64),
+            Vector3::new(-4f64, -4f64, -4f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        ..scene_builder::static_receiver_scene()
+    }
+}
+
+#[test]
+fn leak_detection_matches_regular_impulse_response_for_directed_rays() {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(directed_rays_scene()).unwrap();
+
+    let impulse_response = scene_data
+        .simulate_at_time(
+            0,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    let (leak_impulse_response, report) = scene_data
+        .simulate_at_time_detecting_leaks(
+            0,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(impulse_response, leak_impulse_response);
+    // all rays pass through the receiver, then leave the scene
+    assert_eq!(10, report.count());
+}
+
+#[test]
+fn simulating_an_input_collects_the_first_samples_leaks() {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(directed_rays_scene()).unwrap();
+    let input = BitDepth::ThirtyTwoFloat(vec![1f32; 5]);
+
+    for single_ir in [false, true] {
+        let (buffer, impulse_response, _) = scene_data
+            .simulate_for_time_span_raw(
+                &input,
+                10,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                1f64,
+                false,
+                single_ir,
+                &PostProcessing::default(),
+                &SimulationConfig::default(),
+            )
+            .unwrap();
+        let (leak_buffer, leak_impulse_response, _, report) = scene_data
+            .simulate_for_time_span_detecting_leaks(
+                &input,
+                10,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                1f64,
+                false,
+                single_ir,
+                &PostProcessing::default(),
+                &SimulationConfig::default(),
+            )
+            .unwrap();
+
+        assert_eq!(buffer, leak_buffer);
+        assert_eq!(impulse_response, leak_impulse_response);
+        // only the rays of the first sample are collected, all of which leak
+        assert_eq!(10, report.number_of_rays);
+        assert_eq!(10, report.count());
+    }
+}
+
+#[test]
+fn removing_surfaces_leaks_after_rebuilding() {
+    let scene = closed_cube_scene();
+    let last = scene.surfaces.len() - 1;
+    let without_one = scene.without_surface(last);
+    assert_eq!(scene.surfaces.len() - 1, without_one.surfaces.len());
+    assert_eq!(scene.surfaces[..last], without_one.surfaces[..]);
+    assert_eq!(scene.surfaces[1..], scene.without_surface(0).surfaces[..]);
+
+    // both triangles of the top face, with a duplicate index
+    let without_top = scene.without_surfaces(&[last, last - 1, last]);
+    assert_eq!(scene.surfaces.len() - 2, without_top.surfaces.len());
+    assert_eq!(None, without_top.bounds_cache);
+
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(without_top).unwrap();
+    let (_, report) = scene_data
+        .simulate_at_time_detecting_leaks(
+            0,
+            200,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            true,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    assert!(
+        report.percentage() > 50f64,
+        "Expected most rays to leak through the missing top face, got {}%",
+        report.percentage()
+    );
+}
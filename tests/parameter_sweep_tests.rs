@@ -0,0 +1,87 @@
+use approx::assert_abs_diff_eq;
+use demo::{
+    parameter_sweep::{run_sweep, sweep_values, SweepParameter},
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::{Receiver, Scene},
+    scene_builder,
+    simulation_config::SimulationConfig,
+    DEFAULT_SAMPLE_RATE,
+};
+use nalgebra::{Rotation3, Vector3};
+
+#[test]
+fn sweep_values_are_evenly_spaced() {
+    let values = sweep_values(0.05, 0.5, 10);
+    assert_eq!(10, values.len());
+    for (index, value) in values.iter().enumerate() {
+        assert_abs_diff_eq!(0.05f64 * (index + 1) as f64, *value, epsilon = 1e-12);
+    }
+    assert_eq!(vec![1f64], sweep_values(1f64, 2f64, 1));
+    assert!(sweep_values(1f64, 2f64, 0).is_empty());
+}
+
+#[test]
+fn parameters_are_applied_to_scene() {
+    let scene = scene_builder::static_cube_scene();
+    assert_eq!(
+        Some(SweepParameter::AbsorptionCoefficient),
+        SweepParameter::from_name("absorption_coefficient")
+    );
+    assert_eq!(None, SweepParameter::from_name("absorption"));
+
+    let absorbing = SweepParameter::AbsorptionCoefficient.apply(&scene, 0.25);
+    for surface in &absorbing.surfaces {
+        assert_abs_diff_eq!(0.25f64, surface.data().material.absorption_coefficient);
+    }
+
+    let large = SweepParameter::ReceiverRadius.apply(&scene, 0.5);
+    assert_abs_diff_eq!(0.5f64, large.receiver.bounding_radius());
+    // ellipsoids keep their proportions
+    let ellipsoid = Receiver::Ellipsoid(
+        Vector3::zeros(),
+        Vector3::new(0.2, 0.1, 0.1),
+        Rotation3::identity(),
+        0,
+    );
+    assert_eq!(
+        Receiver::Ellipsoid(
+            Vector3::zeros(),
+            Vector3::new(0.4, 0.2, 0.2),
+            Rotation3::identity(),
+            0,
+        ),
+        ellipsoid.with_radius(0.4)
+    );
+}
+
+#[test]
+fn absorbing_more_energy_shortens_t30() {
+    // the absorption coefficient is the fraction of energy kept per bounce,
+    // so lower values absorb more energy
+    // a large receiver gets enough hits for a smooth decay
+    let base = SweepParameter::ReceiverRadius.apply(&scene_builder::static_cube_scene(), 0.5);
+    let template =
+        |value: f64| -> Scene { SweepParameter::AbsorptionCoefficient.apply(&base, value) };
+    let params = [0.9, 0.7, 0.5];
+    let results = run_sweep::<typenum::U10>(
+        &template,
+        &params,
+        0,
+        500,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        &SimulationConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(params.len(), results.len());
+    let t30s: Vec<f64> = results
+        .iter()
+        .zip(params)
+        .map(|(result, param)| {
+            assert_abs_diff_eq!(param, result.param);
+            result.t30.unwrap()
+        })
+        .collect();
+    assert!(t30s.windows(2).all(|pair| pair[0] > pair[1]), "{t30s:?}");
+}
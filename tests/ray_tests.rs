@@ -1,12 +1,17 @@
 use demo::{
-    bounce::EmissionType,
+    bounce::{EmissionType, MIN_DIFFUSE_COSINE},
+    boundary::{BoundaryBehaviour, BoundaryFace, SceneBoundary},
     materials::{Material, MATERIAL_CONCRETE_WALL},
     ray::{Ray, DEFAULT_PROPAGATION_SPEED},
-    scene::{Emitter, Receiver, Scene, SceneData, Surface, SurfaceData},
+    scene::{
+        Emitter, Receiver, Scene, SceneData, SnapshotCache, Surface, SurfaceData, SurfaceVariant,
+    },
     scene_bounds::MaximumBounds,
-    scene_builder, DEFAULT_SAMPLE_RATE,
+    scene_builder,
+    simulation_config::{DiffuseSampling, SimulationConfig, TraversalMode},
+    DEFAULT_SAMPLE_RATE,
 };
-use nalgebra::Vector3;
+use nalgebra::{Unit, Vector3};
 
 #[test]
 fn directly_hitting_receiver() {
@@ -17,15 +22,22 @@ fn directly_hitting_receiver() {
                 Vector3::new(40f64, 10f64, -10f64),
                 Vector3::new(-10f64, 10f64, 40f64),
             ],
-            0,
             SurfaceData::new(Material {
                 absorption_coefficient: 0.9,
                 diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
             }),
-        )],
-        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0),
+        )]
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0, false),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
@@ -33,6 +45,10 @@ fn directly_hitting_receiver() {
         scene,
         chunks,
         maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
     };
     let direction = Vector3::new(1f64, 0f64, 0f64);
     let result = Ray::launch(
@@ -42,6 +58,7 @@ fn directly_hitting_receiver() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected = vec![(1f64, 2557u32)];
@@ -57,15 +74,22 @@ fn hitting_receiver_after_one_bounce() {
                 Vector3::new(-10f64, 10f64, 40f64),
                 Vector3::new(40f64, 10f64, -10f64),
             ],
-            0,
             SurfaceData::new(Material {
                 absorption_coefficient: 0.9,
                 diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
             }),
-        )],
-        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0),
+        )]
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0, false),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
@@ -73,7 +97,52 @@ fn hitting_receiver_after_one_bounce() {
         scene,
         chunks,
         maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
+    };
+    let direction = Vector3::new(1f64, 1f64, 0f64);
+    let result = Ray::launch(
+        direction,
+        Vector3::new(0f64, 0f64, 0f64),
+        0,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        &scene_data,
+        &SimulationConfig::default(),
+    );
+
+    let expected = vec![(0.9f64, 3622u32)];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn hitting_receiver_after_bouncing_off_quad() {
+    let scene = Scene {
+        // the ray hits the quad in its second triangle
+        surfaces: vec![SurfaceVariant::Quad(Surface::Interpolated(
+            [
+                Vector3::new(-10f64, 10f64, -10f64),
+                Vector3::new(-10f64, 10f64, 40f64),
+                Vector3::new(40f64, 10f64, 40f64),
+                Vector3::new(40f64, 10f64, -10f64),
+            ],
+            SurfaceData::new(Material {
+                absorption_coefficient: 0.9,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            }),
+        ))],
+        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
     let direction = Vector3::new(1f64, 1f64, 0f64);
     let result = Ray::launch(
         direction,
@@ -82,6 +151,7 @@ fn hitting_receiver_after_one_bounce() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected = vec![(0.9f64, 3622u32)];
@@ -95,10 +165,17 @@ fn unreachable_receiver() {
             Vector3::new(-5f64, -5f64, -5f64),
             Vector3::new(-5f64, -5f64, -5f64),
             MATERIAL_CONCRETE_WALL,
-        ),
-        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0),
+        )
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0, false),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
@@ -106,6 +183,10 @@ fn unreachable_receiver() {
         scene,
         chunks,
         maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
     };
     let direction = Vector3::new(1f64, 1f64, 0f64);
     let result = Ray::launch(
@@ -115,6 +196,7 @@ fn unreachable_receiver() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected: Vec<(f64, u32)> = vec![];
@@ -122,7 +204,193 @@ fn unreachable_receiver() {
 }
 
 #[test]
-fn hitting_receiver_before_and_after_one_bounce() {
+fn ray_starting_in_corner_terminates() {
+    let material = Material {
+        absorption_coefficient: 0.9,
+        diffusion_coefficient: 0f64,
+        transmission_coefficient: 0f64,
+    };
+    let scene = Scene {
+        // a floor and a wall meeting along the z axis, facing into the corner between them
+        surfaces: vec![
+            SurfaceVariant::Quad(Surface::Interpolated(
+                [
+                    Vector3::new(0f64, 0f64, -10f64),
+                    Vector3::new(10f64, 0f64, -10f64),
+                    Vector3::new(10f64, 0f64, 10f64),
+                    Vector3::new(0f64, 0f64, 10f64),
+                ],
+                SurfaceData::new(material),
+            )),
+            SurfaceVariant::Quad(Surface::Interpolated(
+                [
+                    Vector3::new(0f64, 0f64, -10f64),
+                    Vector3::new(0f64, 0f64, 10f64),
+                    Vector3::new(0f64, 10f64, 10f64),
+                    Vector3::new(0f64, 10f64, -10f64),
+                ],
+                SurfaceData::new(material),
+            )),
+        ],
+        receiver: Receiver::Interpolated(Vector3::new(5f64, 5f64, 0f64), 1f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
+    };
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    // the ray starts exactly on the edge between both surfaces and points into both of them
+    let result = Ray::launch(
+        Vector3::new(-1f64, -1f64, 0f64),
+        Vector3::new(0f64, 0f64, 0f64),
+        0,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        &scene_data,
+        &SimulationConfig::default(),
+    );
+
+    // the ray bounces off both surfaces without moving, then leaves the corner towards the receiver
+    assert_eq!(vec![(0.81f64, 780u32)], result);
+}
+
+#[test]
+fn zero_area_triangle_does_not_break_rays() {
+    let material = Material {
+        absorption_coefficient: 0.9,
+        diffusion_coefficient: 1f64,
+        transmission_coefficient: 0f64,
+    };
+    let scene = Scene {
+        // a degenerate triangle whose corners all lie on a line crossing the rays' path,
+        // in front of a proper wall
+        surfaces: vec![
+            SurfaceVariant::Triangle(Surface::Interpolated(
+                [
+                    Vector3::new(5f64, -1f64, 0f64),
+                    Vector3::new(5f64, 0f64, 0f64),
+                    Vector3::new(5f64, 1f64, 0f64),
+                ],
+                SurfaceData::new(material),
+            )),
+            SurfaceVariant::Triangle(Surface::Interpolated(
+                [
+                    Vector3::new(10f64, -10f64, -10f64),
+                    Vector3::new(10f64, 10f64, -10f64),
+                    Vector3::new(10f64, 0f64, 10f64),
+                ],
+                SurfaceData::new(material),
+            )),
+        ],
+        receiver: Receiver::Interpolated(Vector3::new(2f64, 0f64, 0f64), 1f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
+    };
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    for _ in 0..100 {
+        let result = Ray::launch(
+            Vector3::new(1f64, 0f64, 0f64),
+            Vector3::new(0f64, 0f64, 0f64),
+            0,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            &scene_data,
+            &SimulationConfig::default(),
+        );
+        // the receiver is hit on the way to the wall at least
+        assert!(!result.is_empty());
+        for (energy, _time) in result {
+            assert!(energy.is_finite() && energy > 0f64);
+        }
+    }
+}
+
+/// Launch rays straight down at a fully diffuse floor with a receiver high above the hit point,
+/// then get the fraction of the rays' energy the receiver gets after the single bounce.
+fn energy_fraction_above_diffuse_floor(diffuse_sampling: DiffuseSampling) -> f64 {
+    let scene = Scene {
+        surfaces: vec![SurfaceVariant::Quad(Surface::Interpolated(
+            [
+                Vector3::new(-100f64, 0f64, -100f64),
+                Vector3::new(100f64, 0f64, -100f64),
+                Vector3::new(100f64, 0f64, 100f64),
+                Vector3::new(-100f64, 0f64, 100f64),
+            ],
+            SurfaceData::new(Material {
+                absorption_coefficient: 1f64,
+                diffusion_coefficient: 1f64,
+                transmission_coefficient: 0f64,
+            }),
+        ))],
+        receiver: Receiver::Interpolated(Vector3::new(0f64, 20f64, 0f64), 2f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 1f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
+    };
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let config = SimulationConfig {
+        diffuse_sampling,
+        ..SimulationConfig::default()
+    };
+    let number_of_rays = 100_000u32;
+    let received_energy: f64 = (0..number_of_rays)
+        .flat_map(|_| {
+            Ray::launch(
+                Vector3::new(0f64, -1f64, 0f64),
+                Vector3::new(0f64, 1f64, 0f64),
+                0,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                &scene_data,
+                &config,
+            )
+        })
+        .map(|(energy, _time)| energy)
+        .sum();
+    received_energy / f64::from(number_of_rays)
+}
+
+#[test]
+fn cosine_weighted_diffuse_bounce_sends_more_energy_along_normal() {
+    let min_cos_squared = MIN_DIFFUSE_COSINE * MIN_DIFFUSE_COSINE;
+    // the receiver covers all directions within this angle of the floor's normal
+    let sin_receiver_angle = 0.1f64;
+    let cos_receiver_angle = sin_receiver_angle.mul_add(-sin_receiver_angle, 1f64).sqrt();
+
+    // cosine weighted directions within angle θ of the normal make up a fraction of sin²(θ),
+    // uniform ones make up a fraction of 1 - cos(θ), both relative to the sampled part of the hemisphere
+    let cosine_weighted = energy_fraction_above_diffuse_floor(DiffuseSampling::CosineWeighted);
+    let expected_cosine_weighted =
+        sin_receiver_angle * sin_receiver_angle / (1f64 - min_cos_squared);
+    assert!(
+        (cosine_weighted / expected_cosine_weighted - 1f64).abs() < 0.2f64,
+        "{cosine_weighted}"
+    );
+
+    let uniform = energy_fraction_above_diffuse_floor(DiffuseSampling::Uniform);
+    let expected_uniform = (1f64 - cos_receiver_angle) / (1f64 - MIN_DIFFUSE_COSINE);
+    assert!(
+        (uniform / expected_uniform - 1f64).abs() < 0.2f64,
+        "{uniform}"
+    );
+    // close to the normal, Lambertian reflection is almost twice as bright as uniform scattering
+    assert!(cosine_weighted > 1.5f64 * uniform);
+}
+
+/// A receiver between the origin and a reflecting wall, so rays in the x direction hit it
+/// before and after bouncing off the wall.
+fn receiver_before_wall_scene_data(occluding_receiver: bool) -> SceneData<typenum::U10> {
     let scene = Scene {
         surfaces: vec![Surface::Interpolated(
             [
@@ -130,23 +398,44 @@ fn hitting_receiver_before_and_after_one_bounce() {
                 Vector3::new(40f64, 40f64, -10f64),
                 Vector3::new(40f64, -100f64, 40f64),
             ],
-            0,
             SurfaceData::new(Material {
                 absorption_coefficient: 0.9,
                 diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
             }),
-        )],
-        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0),
+        )]
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(
+            Vector3::new(20f64, 0f64, 0f64),
+            0.1f64,
+            0,
+            occluding_receiver,
+        ),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
-    let scene_data = SceneData {
+    SceneData {
         scene,
         chunks,
         maximum_bounds,
-    };
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
+    }
+}
+
+#[test]
+fn hitting_receiver_before_and_after_one_bounce() {
+    let scene_data = receiver_before_wall_scene_data(false);
     let direction = Vector3::new(1f64, 0f64, 0f64);
     let result = Ray::launch(
         direction,
@@ -155,12 +444,31 @@ fn hitting_receiver_before_and_after_one_bounce() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected = vec![(1.0f64, 2557u32), (0.9f64, 7697u32)];
     assert_eq!(expected, result);
 }
 
+#[test]
+fn occluding_receiver_absorbs_ray() {
+    let scene_data = receiver_before_wall_scene_data(true);
+    let direction = Vector3::new(1f64, 0f64, 0f64);
+    let result = Ray::launch(
+        direction,
+        Vector3::new(0f64, 0f64, 0f64),
+        0,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        &scene_data,
+        &SimulationConfig::default(),
+    );
+
+    // the ray never reaches the wall, so only the first arrival is registered
+    assert_eq!(vec![(1.0f64, 2557u32)], result);
+}
+
 #[test]
 fn not_hitting_receiver_behind_ray() {
     let scene = Scene {
@@ -171,10 +479,10 @@ fn not_hitting_receiver_behind_ray() {
                     Vector3::new(40f64, 10f64, -10f64),
                     Vector3::new(-10f64, 10f64, 40f64),
                 ],
-                0,
                 SurfaceData::new(Material {
                     absorption_coefficient: 0.9,
                     diffusion_coefficient: 0f64,
+                    transmission_coefficient: 0f64,
                 }),
             ),
             Surface::Interpolated(
@@ -183,16 +491,23 @@ fn not_hitting_receiver_behind_ray() {
                     Vector3::new(40f64, -10f64, -10f64),
                     Vector3::new(-10f64, -10f64, 40f64),
                 ],
-                0,
                 SurfaceData::new(Material {
                     absorption_coefficient: 0.9,
                     diffusion_coefficient: 0f64,
+                    transmission_coefficient: 0f64,
                 }),
             ),
-        ],
-        receiver: Receiver::Interpolated(Vector3::new(-20f64, 0f64, 0f64), 0.1f64, 0),
+        ]
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(-20f64, 0f64, 0f64), 0.1f64, 0, false),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
@@ -200,6 +515,10 @@ fn not_hitting_receiver_behind_ray() {
         scene,
         chunks,
         maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
     };
     let direction = Vector3::new(1f64, 0f64, 0f64);
     let result = Ray::launch(
@@ -209,6 +528,7 @@ fn not_hitting_receiver_behind_ray() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected: Vec<(f64, u32)> = vec![];
@@ -225,10 +545,10 @@ fn not_hitting_receiver_behind_ray_reverse() {
                     Vector3::new(40f64, 10f64, -10f64),
                     Vector3::new(-10f64, 10f64, 40f64),
                 ],
-                0,
                 SurfaceData::new(Material {
                     absorption_coefficient: 0.9,
                     diffusion_coefficient: 0f64,
+                    transmission_coefficient: 0f64,
                 }),
             ),
             Surface::Interpolated(
@@ -237,16 +557,23 @@ fn not_hitting_receiver_behind_ray_reverse() {
                     Vector3::new(40f64, -10f64, -10f64),
                     Vector3::new(-10f64, -10f64, 40f64),
                 ],
-                0,
                 SurfaceData::new(Material {
                     absorption_coefficient: 0.9,
                     diffusion_coefficient: 0f64,
+                    transmission_coefficient: 0f64,
                 }),
             ),
-        ],
-        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0),
+        ]
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(20f64, 0f64, 0f64), 0.1f64, 0, false),
         emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
         loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
     };
     let chunks = scene.chunks::<typenum::U10>();
     let maximum_bounds = scene.maximum_bounds();
@@ -254,6 +581,10 @@ fn not_hitting_receiver_behind_ray_reverse() {
         scene,
         chunks,
         maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
     };
     let direction = Vector3::new(-1f64, 0f64, 0f64);
     let result = Ray::launch(
@@ -263,8 +594,267 @@ fn not_hitting_receiver_behind_ray_reverse() {
         DEFAULT_PROPAGATION_SPEED,
         DEFAULT_SAMPLE_RATE,
         &scene_data,
+        &SimulationConfig::default(),
     );
 
     let expected: Vec<(f64, u32)> = vec![];
     assert_eq!(expected, result);
 }
+
+#[test]
+fn rays_along_chunk_boundary_planes_reach_last_chunk() {
+    let directions = [
+        Vector3::new(1f64, 0f64, 0f64),
+        Vector3::new(-1f64, 0f64, 0f64),
+        Vector3::new(0f64, 1f64, 0f64),
+        Vector3::new(0f64, -1f64, 0f64),
+        Vector3::new(0f64, 0f64, 1f64),
+        Vector3::new(0f64, 0f64, -1f64),
+    ];
+    for direction in directions {
+        let mut scene = Scene {
+            surfaces: scene_builder::static_cube(
+                Vector3::new(0f64, 0f64, 0f64),
+                Vector3::new(10f64, 10f64, 10f64),
+                Material {
+                    absorption_coefficient: 0.9,
+                    diffusion_coefficient: 0f64,
+                    transmission_coefficient: 0f64,
+                },
+            )
+            .into_iter()
+            .map(SurfaceVariant::Triangle)
+            .collect(),
+            receiver: Receiver::Interpolated(Vector3::new(5f64, 5f64, 5f64), 0.1f64, 0, false),
+            emitter: Emitter::Interpolated(Vector3::new(5f64, 5f64, 5f64), 0, EmissionType::Random),
+            loop_duration: None,
+            bounds_cache: None,
+            boundary: SceneBoundary::default(),
+            emitter_active_ranges: None,
+            statistical_tail: None,
+        };
+        let chunks = scene.chunks::<typenum::U10>();
+        // the origin lies exactly on the chunk boundary planes between chunk 4 and 5 in each dimension
+        let origin =
+            chunks.chunk_starts + Vector3::new(chunks.size_x, chunks.size_y, chunks.size_z) * 5f64;
+        // the receiver sits in the last chunk before the wall in the ray's direction,
+        // moving it inside the cube doesn't change the chunk sizes
+        scene.receiver = Receiver::Interpolated(origin + direction * 4.8f64, 0.1f64, 0, false);
+        let chunks = scene.chunks::<typenum::U10>();
+        let maximum_bounds = scene.maximum_bounds();
+        let scene_data = SceneData {
+            scene,
+            chunks,
+            maximum_bounds,
+            chunk_build_ns: 0,
+            keyframe_bounds: vec![],
+            static_normals: vec![],
+            snapshot_cache: SnapshotCache::default(),
+        };
+
+        let result = Ray::launch(
+            direction,
+            origin,
+            0,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            &scene_data,
+            &SimulationConfig::default(),
+        );
+
+        let expected_time = 4.7f64 / DEFAULT_PROPAGATION_SPEED * DEFAULT_SAMPLE_RATE;
+        assert!(
+            !result.is_empty(),
+            "Ray in direction {direction:?} didn't hit the receiver"
+        );
+        assert!(
+            (f64::from(result[0].1) - expected_time).abs() <= 1f64,
+            "Ray in direction {direction:?} hit the receiver at {} instead of {expected_time}",
+            result[0].1
+        );
+    }
+}
+
+#[test]
+fn ray_launched_outside_bounds() {
+    let scene = Scene {
+        surfaces: scene_builder::static_cube(
+            Vector3::new(-5f64, -5f64, -5f64),
+            Vector3::new(5f64, 5f64, 5f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0.1f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
+    };
+    let chunks = scene.chunks::<typenum::U10>();
+    let maximum_bounds = scene.maximum_bounds();
+    let scene_data = SceneData {
+        scene,
+        chunks,
+        maximum_bounds,
+        chunk_build_ns: 0,
+        keyframe_bounds: vec![],
+        static_normals: vec![],
+        snapshot_cache: SnapshotCache::default(),
+    };
+
+    // both beyond the upper bounds and below the lower bounds, aimed at the receiver
+    for origin in [
+        Vector3::new(100f64, 0f64, 0f64),
+        Vector3::new(-100f64, 0f64, 0f64),
+    ] {
+        let result = Ray::launch(
+            -origin,
+            origin,
+            0,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            &scene_data,
+            &SimulationConfig::default(),
+        );
+
+        let expected: Vec<(f64, u32)> = vec![];
+        assert_eq!(expected, result);
+    }
+}
+
+#[test]
+fn energy_threshold_is_relative_to_initial_energy() {
+    let scene = Scene {
+        surfaces: scene_builder::static_cube(
+            Vector3::new(-2f64, -2f64, -2f64),
+            Vector3::new(2f64, 2f64, 2f64),
+            Material {
+                absorption_coefficient: 0.5,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        )
+        .into_iter()
+        .map(SurfaceVariant::Triangle)
+        .collect(),
+        receiver: Receiver::Interpolated(Vector3::new(0f64, 0f64, 0f64), 1f64, 0, false),
+        emitter: Emitter::Interpolated(Vector3::new(0f64, 0f64, 0f64), 0, EmissionType::Random),
+        loop_duration: None,
+        bounds_cache: None,
+        boundary: SceneBoundary::default(),
+        emitter_active_ranges: None,
+        statistical_tail: None,
+    };
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let trace = |energy: f64| {
+        Ray::new(
+            Unit::new_normalize(Vector3::new(1f64, 0.1f64, 0.05f64)),
+            Vector3::new(0f64, 0f64, 0f64),
+            energy,
+            0,
+            DEFAULT_PROPAGATION_SPEED / DEFAULT_SAMPLE_RATE,
+        )
+        .trace(&scene_data, &SimulationConfig::default())
+    };
+
+    let result = trace(1f64);
+    let result_high_energy = trace(100f64);
+
+    assert!(result.len() > 1);
+    assert_eq!(result.len(), result_high_energy.len());
+    for ((energy, time), (high_energy, high_energy_time)) in result.iter().zip(&result_high_energy)
+    {
+        assert_eq!(time, high_energy_time);
+        assert!((energy * 100f64 - high_energy).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn reflective_floor_boundary_adds_delayed_arrival() {
+    let floor = Material {
+        absorption_coefficient: 0.9,
+        diffusion_coefficient: 0f64,
+        transmission_coefficient: 0f64,
+    };
+    // a small panel far away stretches the scene bounds down to the floor
+    let builder = || {
+        scene_builder::SceneBuilder::new()
+            .with_static_panel(
+                [
+                    (-20f64, -1f64, 0.1f64),
+                    (-19f64, -1f64, 0.1f64),
+                    (-19f64, 0f64, 0.1f64),
+                    (-20f64, 0f64, 0.1f64),
+                ],
+                MATERIAL_CONCRETE_WALL,
+            )
+            .with_emitter_at(0f64, 0f64, 3f64)
+            .with_receiver_at(6f64, 0f64, 3f64)
+    };
+    let open_scene_data =
+        SceneData::<typenum::U10>::create_for_scene(builder().build().unwrap()).unwrap();
+    let floor_scene_data = SceneData::<typenum::U10>::create_for_scene(
+        builder()
+            .with_boundary(BoundaryFace::MinZ, BoundaryBehaviour::Reflect(floor))
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let floor_z = floor_scene_data.maximum_bounds.0.z;
+    let emitter = Vector3::new(0f64, 0f64, 3f64);
+    let mirrored_receiver = Vector3::new(6f64, 0f64, floor_z.mul_add(2f64, -3f64));
+    let expected_delay = ((mirrored_receiver - emitter).norm() - 6f64) / DEFAULT_PROPAGATION_SPEED
+        * DEFAULT_SAMPLE_RATE;
+
+    for traversal_mode in [TraversalMode::Chunked, TraversalMode::BruteForce] {
+        let config = SimulationConfig {
+            traversal_mode,
+            ..SimulationConfig::default()
+        };
+        let launch = |direction: Vector3<f64>, scene_data: &SceneData<typenum::U10>| {
+            Ray::launch(
+                direction,
+                emitter,
+                0,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                scene_data,
+                &config,
+            )
+        };
+
+        let direct = launch(Vector3::new(1f64, 0f64, 0f64), &floor_scene_data);
+        let reflected = launch(mirrored_receiver - emitter, &floor_scene_data);
+        assert_eq!(1, direct.len());
+        assert_eq!(1, reflected.len());
+        assert!((reflected[0].0 - 0.9).abs() < 1e-9);
+        let delay = f64::from(reflected[0].1) - f64::from(direct[0].1);
+        assert!((delay - expected_delay).abs() <= 2f64);
+
+        let expected: Vec<(f64, u32)> = vec![];
+        assert_eq!(
+            expected,
+            launch(mirrored_receiver - emitter, &open_scene_data)
+        );
+    }
+}
+
+#[test]
+fn ray_debug_output_is_readable() {
+    let mut ray = Ray::new(
+        Unit::new_normalize(Vector3::new(1f64, 0f64, 0f64)),
+        Vector3::new(0f64, 0.5f64, 0f64),
+        1f64,
+        100,
+        0.5f64,
+    );
+    ray.energy = 0.25f64;
+    assert_eq!(
+        "Ray { origin: (0.0,0.5,0.0), direction: (1.0,0.0,0.0), energy: 0.250000 (25.00% of 1.0), time: 100.00 samples, velocity: 0.5 m/sample }",
+        format!("{ray:?}")
+    );
+}
@@ -0,0 +1,793 @@
+use approx::assert_abs_diff_eq;
+use demo::{
+    bounce::EmissionType,
+    error::{DemoError, InterpolationError, SceneBuildError},
+    interpolation::Interpolation,
+    materials::MATERIAL_CONCRETE_WALL,
+    ray::{Ray, DEFAULT_PROPAGATION_SPEED},
+    scene::{
+        CoordinateKeyframe, Emitter, Receiver, SceneData, Surface, SurfaceData, SurfaceKeyframe,
+        SurfaceVariant,
+    },
+    scene_bounds::MaximumBounds,
+    scene_builder::{self, BuildWarning, SceneBuilder},
+    simulation_config::SimulationConfig,
+    time::{SampleDuration, SampleTime},
+    DEFAULT_SAMPLE_RATE,
+};
+use nalgebra::{Rotation3, Vector3};
+
+fn rotating_cube_keyframes(axis: Vector3<f64>) -> Vec<Vec<SurfaceKeyframe<3>>> {
+    scene_builder::rotating_cube(
+        Vector3::new(-1f64, -1f64, -1f64),
+        Vector3::new(1f64, 2f64, 3f64),
+        Vector3::new(0f64, 0f64, 0f64),
+        axis,
+        SampleDuration(8),
+        8,
+        MATERIAL_CONCRETE_WALL,
+    )
+    .into_iter()
+    .map(|surface| match surface {
+        Surface::Keyframes(keyframes, _material, _loop_duration) => keyframes,
+        Surface::Interpolated(..) => panic!("Rotating cube contains a static surface!"),
+    })
+    .collect()
+}
+
+#[test]
+fn rotation_axis_changes_keyframes() {
+    let around_x = rotating_cube_keyframes(Vector3::new(1f64, 0f64, 0f64));
+    let around_z = rotating_cube_keyframes(Vector3::new(0f64, 0f64, 1f64));
+
+    assert_eq!(around_x.len(), around_z.len());
+    assert_ne!(around_x, around_z);
+    // both start from the unrotated cube
+    for (x_keyframes, z_keyframes) in around_x.iter().zip(&around_z) {
+        assert_eq!(x_keyframes[0], z_keyframes[0]);
+    }
+}
+
+#[test]
+fn rotation_keeps_coordinates_along_axis() {
+    for (axis, idx) in [
+        (Vector3::new(1f64, 0f64, 0f64), 0),
+        (Vector3::new(0f64, 2f64, 0f64), 1),
+        (Vector3::new(0f64, 0f64, 1f64), 2),
+    ] {
+        for keyframes in rotating_cube_keyframes(axis) {
+            for keyframe in &keyframes {
+                for (coords, initial_coords) in keyframe.coords.iter().zip(&keyframes[0].coords) {
+                    assert_abs_diff_eq!(coords[idx], initial_coords[idx], epsilon = 1e-9);
+                }
+            }
+        }
+    }
+}
+
+/// Check that every triangle is wound counter-clockwise when seen from the outside,
+/// which makes its surface normal face `center`.
+fn assert_wound_outwards(surfaces: &[Surface<3>], center: Vector3<f64>) {
+    for surface in surfaces {
+        let Surface::Interpolated(coords, _data) = surface else {
+            panic!("Static primitive contains keyframes!")
+        };
+        let winding_normal = (coords[1] - coords[0]).cross(&(coords[2] - coords[0]));
+        let to_centroid = (coords[0] + coords[1] + coords[2]) / 3f64 - center;
+        assert!(
+            winding_normal.dot(&to_centroid) > 0f64,
+            "Triangle {coords:?} isn't wound outwards"
+        );
+        assert!(surface.normal_at_time(0).unwrap().dot(&to_centroid) < 0f64);
+    }
+}
+
+#[test]
+fn rotated_cube_swaps_extents() {
+    let rotation = Rotation3::from_axis_angle(&Vector3::z_axis(), std::f64::consts::FRAC_PI_2);
+    let surfaces = scene_builder::static_cube_rotated(
+        Vector3::new(0f64, 0f64, 0f64),
+        Vector3::new(4f64, 2f64, 1f64),
+        rotation,
+        MATERIAL_CONCRETE_WALL,
+    );
+    assert_eq!(12, surfaces.len());
+    assert_wound_outwards(&surfaces, Vector3::new(2f64, 1f64, 0.5f64));
+
+    // the 4m long X extent now lies along Y, both still centred on the cube's centre
+    let (min_coords, max_coords) = surfaces
+        .iter()
+        .map(MaximumBounds::maximum_bounds)
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.inf(&min_b), max_a.sup(&max_b)))
+        .unwrap();
+    assert_abs_diff_eq!(Vector3::new(1f64, -1f64, 0f64), min_coords, epsilon = 1e-10);
+    assert_abs_diff_eq!(Vector3::new(3f64, 3f64, 1f64), max_coords, epsilon = 1e-10);
+
+    let scene = SceneBuilder::new()
+        .with_static_cube_rotated(
+            (0f64, 0f64, 0f64),
+            (4f64, 2f64, 1f64),
+            rotation,
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(2f64, 1f64, 0.5f64)
+        .with_emitter_at(2f64, 1f64, 0.5f64)
+        .build()
+        .unwrap();
+    let built: Vec<SurfaceVariant> = surfaces.into_iter().map(SurfaceVariant::Triangle).collect();
+    assert_eq!(built, scene.surfaces);
+}
+
+#[test]
+fn sphere_surface_count_matches_subdivisions() {
+    for subdivisions in 0..4 {
+        let surfaces = scene_builder::static_sphere(
+            Vector3::new(0f64, 0f64, 0f64),
+            1f64,
+            subdivisions,
+            MATERIAL_CONCRETE_WALL,
+        );
+        assert_eq!(20 * 4usize.pow(subdivisions), surfaces.len());
+    }
+}
+
+#[test]
+fn cylinder_surface_count_matches_segments() {
+    for segments in [3, 8, 17] {
+        let surfaces = scene_builder::static_cylinder(
+            Vector3::new(0f64, 0f64, 0f64),
+            1f64,
+            2f64,
+            segments,
+            MATERIAL_CONCRETE_WALL,
+        );
+        assert_eq!(4 * segments as usize, surfaces.len());
+    }
+}
+
+#[test]
+fn sphere_triangles_are_wound_outwards() {
+    let center = Vector3::new(1f64, -2f64, 0.5f64);
+    let surfaces = scene_builder::static_sphere(center, 3f64, 2, MATERIAL_CONCRETE_WALL);
+
+    assert_wound_outwards(&surfaces, center);
+    for surface in &surfaces {
+        let Surface::Interpolated(coords, _data) = surface else {
+            unreachable!()
+        };
+        for coord in coords {
+            assert_abs_diff_eq!(3f64, (coord - center).norm(), epsilon = 1e-10);
+        }
+    }
+}
+
+#[test]
+fn cylinder_triangles_are_wound_outwards() {
+    let base_center = Vector3::new(1f64, -2f64, 0.5f64);
+    let surfaces =
+        scene_builder::static_cylinder(base_center, 1.5f64, 4f64, 12, MATERIAL_CONCRETE_WALL);
+
+    assert_wound_outwards(&surfaces, base_center + Vector3::new(0f64, 0f64, 2f64));
+}
+
+#[test]
+fn ray_inside_sphere_shell_bounces() {
+    let scene = SceneBuilder::new()
+        .with_static_sphere((0f64, 0f64, 0f64), 5f64, 2, MATERIAL_CONCRETE_WALL)
+        .with_receiver_at(0f64, 0f64, 0f64)
+        .with_receiver_radius(1f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    // directions hitting triangles away from their edges
+    for direction in [
+        Vector3::new(1f64, 0.2f64, 0.1f64),
+        Vector3::new(0.3f64, -0.5f64, 0.8f64),
+        Vector3::new(-0.2f64, 0.1f64, -1f64),
+    ] {
+        let (result, leak) = Ray::launch_detecting_leaks(
+            direction.normalize(),
+            Vector3::new(0f64, 0f64, 0f64),
+            0,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            &scene_data,
+            &SimulationConfig::default(),
+        );
+        assert!(leak.is_none(), "Ray {direction:?} escaped the sphere");
+        assert!(!result.is_empty());
+    }
+}
+
+#[test]
+fn normal_of_rotating_surface_at_time() {
+    let surfaces = scene_builder::rotating_cube(
+        Vector3::new(-1f64, -1f64, -1f64),
+        Vector3::new(1f64, 1f64, 1f64),
+        Vector3::new(0f64, 0f64, 0f64),
+        Vector3::new(0f64, 0f64, 1f64),
+        SampleDuration(8),
+        8,
+        MATERIAL_CONCRETE_WALL,
+    );
+    // the first triangle is part of the cube's left face, which faces the inside of the cube
+    let left_face = &surfaces[0];
+
+    assert!((Vector3::new(1f64, 0f64, 0f64) - left_face.normal_at_time(0).unwrap()).norm() < 1e-9);
+    // a quarter rotation around the z axis
+    assert!((Vector3::new(0f64, 1f64, 0f64) - left_face.normal_at_time(2).unwrap()).norm() < 1e-9);
+    assert_eq!(
+        left_face.normal_at_time(3).unwrap(),
+        left_face.at_time(3).normal_unchecked()
+    );
+}
+
+#[test]
+fn rotating_cube_keyframe_count_is_configurable() {
+    let keyframe_times = |rotation_duration, keyframes| {
+        let surfaces = scene_builder::rotating_cube(
+            Vector3::new(-1f64, -1f64, -1f64),
+            Vector3::new(1f64, 1f64, 1f64),
+            Vector3::new(0f64, 0f64, 0f64),
+            Vector3::new(0f64, 0f64, 1f64),
+            rotation_duration,
+            keyframes,
+            MATERIAL_CONCRETE_WALL,
+        );
+        let Surface::Keyframes(keyframes, ..) = &surfaces[0] else {
+            panic!("The rotating cube should be keyframed");
+        };
+        keyframes.iter().map(|key| key.time).collect::<Vec<_>>()
+    };
+
+    // evenly spaced keyframes plus one completing the rotation
+    let times = keyframe_times(SampleDuration(44100), 100);
+    assert_eq!(101, times.len());
+    assert_eq!(SampleTime(441), times[1]);
+    assert_eq!(SampleTime(44100), times[100]);
+    // spacing that doesn't divide the duration still ends at the full rotation
+    let times = keyframe_times(SampleDuration(10), 3);
+    assert_eq!(
+        vec![SampleTime(0), SampleTime(3), SampleTime(6), SampleTime(10)],
+        times
+    );
+    // no more keyframes than samples
+    assert_eq!(9, keyframe_times(SampleDuration(8), 100).len());
+}
+
+#[test]
+fn build_invalid_scenes() {
+    assert_eq!(
+        Err(DemoError::SceneBuild(SceneBuildError::ZeroLoopDuration)),
+        SceneBuilder::new().looping(SampleDuration(0)).build()
+    );
+    assert_eq!(
+        Err(DemoError::Interpolation(InterpolationError::NoKeyframes)),
+        SceneBuilder::new().with_receiver_keyframes(vec![]).build()
+    );
+    for axis in [
+        (0f64, 0f64, 0f64),
+        (1e-20f64, 0f64, 0f64),
+        (f64::NAN, 0f64, 1f64),
+    ] {
+        assert_eq!(
+            Err(DemoError::SceneBuild(
+                SceneBuildError::DegenerateRotationAxis
+            )),
+            SceneBuilder::new()
+                .with_rotating_cube(
+                    (-1f64, -1f64, -1f64),
+                    (1f64, 1f64, 1f64),
+                    (0f64, 0f64, 0f64),
+                    axis,
+                    SampleDuration(100),
+                    scene_builder::DEFAULT_ROTATION_KEYFRAMES,
+                    MATERIAL_CONCRETE_WALL,
+                )
+                .build()
+        );
+    }
+}
+
+#[test]
+fn occluding_receiver_is_kept_when_building() {
+    let scene = SceneBuilder::new()
+        .with_occluding_receiver()
+        .with_receiver_at(1f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    assert!(scene.receiver.is_occluding());
+    assert!(!SceneBuilder::new().build().unwrap().receiver.is_occluding());
+}
+
+#[test]
+fn directed_cone_emission_is_normalised() {
+    let scene = SceneBuilder::new()
+        .with_directed_cone_emission(0f64, 2f64, 0f64, 0.1f64)
+        .build()
+        .unwrap();
+    let Emitter::Interpolated(_coords, _time, emission_type) = scene.emitter else {
+        panic!("The builder should create an interpolated emitter!")
+    };
+    assert_eq!(
+        EmissionType::DirectedCone {
+            direction: Vector3::new(0f64, 1f64, 0f64),
+            half_angle: 0.1f64
+        },
+        emission_type
+    );
+}
+
+#[test]
+fn floating_panel_is_disconnected() {
+    let room = SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -2f64),
+            (2f64, 2f64, 2f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(1f64, 0f64, 0f64);
+    assert!(room.check_connectivity().is_empty());
+
+    let panel_at_height = |height: f64| {
+        [
+            (-1f64, height, -1f64),
+            (1f64, height, -1f64),
+            (1f64, height, 1f64),
+            (-1f64, height, 1f64),
+        ]
+    };
+    // the cube's 12 triangles come first, followed by the panel
+    let with_floating_panel = SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -2f64),
+            (2f64, 2f64, 2f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(1f64, 0f64, 0f64)
+        .with_static_panel(panel_at_height(0.5f64), MATERIAL_CONCRETE_WALL);
+    assert_eq!(vec![12], with_floating_panel.check_connectivity());
+    assert!(with_floating_panel.build().is_ok());
+    assert_eq!(
+        Err(DemoError::SceneBuild(SceneBuildError::DisconnectedSurface(
+            12
+        ))),
+        with_floating_panel.with_strict_validation().build()
+    );
+
+    // a panel lying on the floor touches the walls' triangles
+    let with_floor_panel = SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -2f64),
+            (2f64, 2f64, 2f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(1f64, 0f64, 0f64)
+        .with_static_panel(panel_at_height(-2f64), MATERIAL_CONCRETE_WALL)
+        .with_strict_validation();
+    assert!(with_floor_panel.check_connectivity().is_empty());
+    assert_eq!(13, with_floor_panel.build().unwrap().surfaces.len());
+}
+
+#[test]
+fn merging_static_cubes_translates_surfaces() {
+    let mut scene = scene_builder::static_cube_scene();
+    scene.maximum_bounds_cached();
+    scene
+        .merge(
+            &scene_builder::static_cube_scene(),
+            Vector3::new(10f64, 0f64, 0f64),
+            0,
+        )
+        .unwrap();
+    assert_eq!(24, scene.surfaces.len());
+    assert_eq!(None, scene.loop_duration);
+    assert_eq!(
+        (
+            Vector3::new(-2f64, -2f64, -1.5f64),
+            Vector3::new(12f64, 2f64, 1.5f64)
+        ),
+        scene.maximum_bounds_cached()
+    );
+    assert_eq!(
+        (
+            Vector3::new(8f64, -2f64, -1.5f64),
+            Vector3::new(12f64, 2f64, 1.5f64)
+        ),
+        scene.surfaces[12..]
+            .iter()
+            .map(MaximumBounds::maximum_bounds)
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.inf(&min_b), max_a.sup(&max_b)))
+            .unwrap()
+    );
+
+    let built = SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -1.5f64),
+            (2f64, 2f64, 1.5f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_scene(scene_builder::static_cube_scene(), (10f64, 0f64, 0f64))
+        .build()
+        .unwrap();
+    assert_eq!(scene.surfaces, built.surfaces);
+}
+
+#[test]
+fn merging_shifts_keyframe_times() {
+    let mut scene = scene_builder::static_cube_scene();
+    let rotating = scene_builder::rotating_cube_scene(8);
+    scene
+        .merge(&rotating, Vector3::new(0f64, 0f64, 0f64), 3)
+        .unwrap();
+    assert_eq!(Some(SampleDuration(8)), scene.loop_duration);
+    assert!(scene.validate_keyframe_order());
+    assert!(scene.validate().is_ok());
+    for (merged, original) in scene.surfaces[12..].iter().zip(&rotating.surfaces) {
+        match (merged, original) {
+            (
+                SurfaceVariant::Triangle(Surface::Keyframes(merged, ..)),
+                SurfaceVariant::Triangle(Surface::Keyframes(original, ..)),
+            ) => {
+                let times: Vec<SampleTime> = merged.iter().map(|key| key.time).collect();
+                let expected: Vec<SampleTime> = original
+                    .iter()
+                    .map(|key| key.time + SampleDuration(3))
+                    .collect();
+                assert_eq!(expected, times);
+            }
+            _ => panic!("Rotating cube contains a static surface!"),
+        }
+    }
+}
+
+#[test]
+fn merging_scenes_with_different_loop_durations_fails() {
+    let mut scene = scene_builder::rotating_cube_scene(8);
+    let original = scene.clone();
+    assert_eq!(
+        Err(DemoError::SceneBuild(
+            SceneBuildError::LoopDurationMismatch(SampleDuration(8), SampleDuration(16))
+        )),
+        scene.merge(
+            &scene_builder::rotating_cube_scene(16),
+            Vector3::new(0f64, 0f64, 0f64),
+            0
+        )
+    );
+    assert_eq!(original, scene);
+    assert_eq!(
+        Err(DemoError::SceneBuild(
+            SceneBuildError::LoopDurationMismatch(SampleDuration(4), SampleDuration(8))
+        )),
+        SceneBuilder::new()
+            .looping(SampleDuration(4))
+            .with_scene(scene_builder::rotating_cube_scene(8), (0f64, 0f64, 0f64))
+            .build()
+    );
+}
+
+#[test]
+fn surface_areas() {
+    let triangle = Surface::Interpolated(
+        [
+            Vector3::new(0f64, 0f64, 0f64),
+            Vector3::new(3f64, 0f64, 0f64),
+            Vector3::new(0f64, 4f64, 0f64),
+        ],
+        SurfaceData::new(MATERIAL_CONCRETE_WALL),
+    );
+    assert_abs_diff_eq!(6f64, triangle.area());
+    let panel = scene_builder::static_panel(
+        [
+            Vector3::new(0f64, 0f64, 0f64),
+            Vector3::new(2f64, 0f64, 0f64),
+            Vector3::new(2f64, 0f64, 3f64),
+            Vector3::new(0f64, 0f64, 3f64),
+        ],
+        MATERIAL_CONCRETE_WALL,
+    );
+    assert_abs_diff_eq!(6f64, panel.area());
+    // keyframe surfaces use their first keyframe
+    let rotating = scene_builder::rotating_cube_scene(8);
+    assert_abs_diff_eq!(80f64, rotating.total_surface_area(), epsilon = 1e-9);
+    assert_abs_diff_eq!(
+        80f64,
+        scene_builder::static_cube_scene().total_surface_area(),
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn random_surface_points_are_weighted_by_area() {
+    let scene = SceneBuilder::new()
+        .with_static_panel(
+            [
+                (0f64, 0f64, 0f64),
+                (1f64, 0f64, 0f64),
+                (1f64, 1f64, 0f64),
+                (0f64, 1f64, 0f64),
+            ],
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_static_panel(
+            [
+                (0f64, 0f64, 5f64),
+                (3f64, 0f64, 5f64),
+                (3f64, 1f64, 5f64),
+                (0f64, 1f64, 5f64),
+            ],
+            MATERIAL_CONCRETE_WALL,
+        )
+        .build()
+        .unwrap();
+    assert_abs_diff_eq!(4f64, scene.total_surface_area(), epsilon = 1e-12);
+    let points: Vec<Vector3<f64>> = (0..20000)
+        .map(|_| scene.random_surface_point(0).unwrap())
+        .collect();
+    let on_large_panel = points.iter().filter(|point| point.z > 2.5f64).count();
+    let fraction = on_large_panel as f64 / points.len() as f64;
+    assert!((fraction - 0.75f64).abs() < 0.02, "fraction {fraction}");
+    assert!(points.iter().all(|point| (0f64..=3f64).contains(&point.x)));
+
+    assert_eq!(
+        None,
+        SceneBuilder::new().build().unwrap().random_surface_point(0)
+    );
+}
+
+#[test]
+fn emitter_keyframes_need_to_fit_into_loop() {
+    let builder = || {
+        SceneBuilder::new().with_emitter_keyframes(vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(0f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(100),
+                coords: Vector3::new(1f64, 0f64, 0f64),
+            },
+        ])
+    };
+    for scene in [
+        builder().build().unwrap(),
+        builder().looping(SampleDuration(100)).build().unwrap(),
+        builder().looping(SampleDuration(200)).build().unwrap(),
+        scene_builder::rotating_cube_scene(8),
+    ] {
+        assert!(scene.validate_emitter_loop_alignment());
+    }
+
+    assert_eq!(
+        Err(DemoError::SceneBuild(SceneBuildError::EmitterBeyondLoop(
+            SampleTime(100),
+            SampleDuration(50)
+        ))),
+        builder().looping(SampleDuration(50)).build()
+    );
+    let mut scene = builder().build().unwrap();
+    scene.loop_duration = Some(SampleDuration(50));
+    assert!(!scene.validate_emitter_loop_alignment());
+}
+
+#[test]
+fn emitter_active_ranges_repeat_with_the_loop() {
+    let builder = || SceneBuilder::new().with_emitter_active_ranges(vec![(10, 20), (50, 60)]);
+    let scene = builder().build().unwrap();
+    assert!(!scene.is_emitter_active(9));
+    assert!(scene.is_emitter_active(10));
+    assert!(!scene.is_emitter_active(20));
+    assert!(scene.is_emitter_active(55));
+    assert!(!scene.is_emitter_active(115));
+
+    let looping_scene = builder().looping(SampleDuration(100)).build().unwrap();
+    assert!(looping_scene.is_emitter_active(115));
+    assert!(!looping_scene.is_emitter_active(125));
+    assert!(looping_scene.is_emitter_active(1059));
+
+    assert!(SceneBuilder::new()
+        .build()
+        .unwrap()
+        .is_emitter_active(12345));
+}
+
+#[test]
+fn receiver_from_fn_samples_the_path() {
+    let scene = SceneBuilder::new()
+        .with_receiver_from_fn(
+            |time| Vector3::new(f64::from(time).cos(), f64::from(time).sin(), 0f64),
+            0,
+            628,
+            100,
+        )
+        .build()
+        .unwrap();
+    let Receiver::Keyframes(keyframes, ..) = scene.receiver else {
+        panic!("The receiver should be keyframed");
+    };
+    assert_eq!(100, keyframes.len());
+    assert_eq!(SampleTime(0), keyframes[0].time);
+    assert_eq!(SampleTime(628), keyframes[99].time);
+    assert!(keyframes.windows(2).all(|pair| pair[0].time < pair[1].time));
+    for keyframe in &keyframes {
+        assert_abs_diff_eq!(1f64, keyframe.coords.norm(), epsilon = 1e-9);
+        assert_abs_diff_eq!(0f64, keyframe.coords.z);
+    }
+}
+
+#[test]
+fn emitter_from_fn_drops_keyframes_on_the_same_sample() {
+    let scene = SceneBuilder::new()
+        .with_emitter_from_fn(|time| Vector3::new(f64::from(time), 0f64, 0f64), 10, 14, 9)
+        .build()
+        .unwrap();
+    let Emitter::Keyframes(keyframes, _emission_type) = scene.emitter else {
+        panic!("The emitter should be keyframed");
+    };
+    let times: Vec<u32> = keyframes
+        .iter()
+        .map(|keyframe| keyframe.time.samples())
+        .collect();
+    assert_eq!(vec![10, 11, 12, 13, 14], times);
+    assert_abs_diff_eq!(12f64, keyframes[2].coords.x);
+}
+
+#[test]
+fn triangles_and_normals_at_time() {
+    let scene = SceneBuilder::new()
+        .with_static_panel(
+            [
+                (0f64, 0f64, 0f64),
+                (1f64, 0f64, 0f64),
+                (1f64, 1f64, 0f64),
+                (0f64, 1f64, 0f64),
+            ],
+            MATERIAL_CONCRETE_WALL,
+        )
+        .build()
+        .unwrap();
+    // the quad is split into the triangles 0, 1, 2 and 0, 2, 3
+    assert_eq!(
+        vec![
+            [
+                Vector3::new(0f64, 0f64, 0f64),
+                Vector3::new(1f64, 0f64, 0f64),
+                Vector3::new(1f64, 1f64, 0f64),
+            ],
+            [
+                Vector3::new(0f64, 0f64, 0f64),
+                Vector3::new(1f64, 1f64, 0f64),
+                Vector3::new(0f64, 1f64, 0f64),
+            ],
+        ],
+        scene.triangles_at_time(0)
+    );
+    assert_eq!(
+        vec![Vector3::new(0f64, 0f64, -1f64); 2],
+        scene.normals_at_time(0)
+    );
+
+    // the rotating cube moves, but every normal stays perpendicular to its triangle
+    let rotating = scene_builder::rotating_cube_scene(8);
+    let loop_duration = rotating.loop_duration.unwrap().samples();
+    let triangles = rotating.triangles_at_time(loop_duration / 4);
+    let normals = rotating.normals_at_time(loop_duration / 4);
+    assert_eq!(rotating.surfaces.len(), triangles.len());
+    assert_eq!(triangles.len(), normals.len());
+    assert_ne!(rotating.triangles_at_time(0), triangles);
+    for (triangle, normal) in triangles.iter().zip(&normals) {
+        assert_abs_diff_eq!(1f64, normal.norm(), epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            0f64,
+            normal.dot(&(triangle[1] - triangle[0])),
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            0f64,
+            normal.dot(&(triangle[2] - triangle[0])),
+            epsilon = 1e-9
+        );
+    }
+    // the time is looped
+    assert_eq!(
+        triangles,
+        rotating.triangles_at_time(loop_duration + loop_duration / 4)
+    );
+}
+
+#[test]
+fn available_scenes_build_and_validate() {
+    let scenes = scene_builder::available_scenes();
+    for (index, descriptor) in scenes.iter().enumerate() {
+        let scene = (descriptor.build)(DEFAULT_SAMPLE_RATE as u32);
+        assert!(scene.validate().is_ok(), "{} is invalid", descriptor.slug);
+        // slugs are unique
+        assert!(scenes[..index]
+            .iter()
+            .all(|other| other.slug != descriptor.slug));
+    }
+}
+
+#[test]
+fn find_scene_by_slug_or_index() {
+    let (index, descriptor) = scene_builder::find_scene("rotating-l").unwrap();
+    assert_eq!("rotating-l", descriptor.slug);
+    assert_eq!(
+        Some(index),
+        scene_builder::find_scene("5").map(|(index, _)| index)
+    );
+    assert!(scene_builder::find_scene("nope").is_none());
+    assert!(scene_builder::find_scene("6").is_none());
+}
+
+#[test]
+fn empty_scene_builds_with_warning() {
+    let builder = SceneBuilder::new();
+    assert!(!builder.has_surfaces());
+    let result = builder.try_build().unwrap();
+    assert!(result.scene.surfaces.is_empty());
+    assert_eq!(vec![BuildWarning::NoSurfaces], result.warnings);
+    // build still succeeds and discards the warning
+    assert!(builder.build().unwrap().surfaces.is_empty());
+
+    let builder = SceneBuilder::new().with_static_cube(
+        (-1f64, -1f64, -1f64),
+        (1f64, 1f64, 1f64),
+        MATERIAL_CONCRETE_WALL,
+    );
+    assert!(builder.has_surfaces());
+    assert!(builder.try_build().unwrap().warnings.is_empty());
+}
+
+#[test]
+fn degenerate_surfaces_build_with_warning() {
+    let result = SceneBuilder::new()
+        .with_static_cube(
+            (-1f64, -1f64, -1f64),
+            (1f64, 1f64, 1f64),
+            MATERIAL_CONCRETE_WALL,
+        )
+        // all corners lie on a line, so the panel has no area
+        .with_static_panel(
+            [
+                (0f64, 0f64, 0f64),
+                (0.5f64, 0f64, 0f64),
+                (1f64, 0f64, 0f64),
+                (0.25f64, 0f64, 0f64),
+            ],
+            MATERIAL_CONCRETE_WALL,
+        )
+        .with_receiver_at(0.5f64, 0.5f64, 0.5f64)
+        .with_emitter_at(-0.5f64, -0.5f64, -0.5f64)
+        .try_build()
+        .unwrap();
+    let index = result.scene.surfaces.len() - 1;
+    assert_eq!(
+        vec![BuildWarning::DegenerateSurface(index)],
+        result.warnings
+    );
+    assert_eq!(
+        format!("surface {index} has no area, rays hitting it are discarded"),
+        result.warnings[0].to_string()
+    );
+}
+
+#[test]
+fn coordinates_can_be_given_as_vectors() {
+    let receiver = Vector3::new(1f64, -0.5f64, 0.25f64);
+    let emitter = Vector3::new(-1f64, 0.5f64, 1.25f64);
+    let from_floats = SceneBuilder::new()
+        .with_receiver_at(receiver.x, receiver.y, receiver.z)
+        .with_emitter_at(emitter.x, emitter.y, emitter.z)
+        .build()
+        .unwrap();
+    let from_vectors = SceneBuilder::new()
+        .with_receiver_at_vec(receiver)
+        .with_emitter_at_vec(emitter)
+        .build()
+        .unwrap();
+    assert_eq!(from_floats, from_vectors);
+    assert_eq!(Some(receiver), from_vectors.receiver.center());
+}
@@ -0,0 +1,201 @@
+//! End-to-end regression tests comparing simulated impulse responses of the predefined scenes
+//! against committed golden impulse responses in `tests/golden`.
+//! Only deterministic scenes are covered, i.e. ones with directed emission and specular surfaces.
+//!
+//! After intentionally changing simulation results, regenerate the golden files with
+//! `UPDATE_GOLDENS=1 cargo test --test simulation_golden_tests`.
+
+use std::{fmt::Write, path::PathBuf};
+
+use demo::{
+    bounce::EmissionType,
+    cli::options::scene_by_index,
+    materials::{Material, ABSORPTION_COEFFICIENT_CONCRETE},
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::{Emitter, Scene, SceneData},
+    simulation_config::SimulationConfig,
+    DEFAULT_SAMPLE_RATE,
+};
+
+/// The number of rays simulated per impulse response. Every ray takes the same path,
+/// so this only needs to be big enough to exercise the parallel accumulation.
+const GOLDEN_RAYS: u32 = 8;
+/// The maximum absolute difference allowed per impulse response bin.
+const BIN_EPSILON: f64 = 1e-9;
+/// The maximum relative difference allowed for the impulse response's total energy.
+const TOTAL_ENERGY_TOLERANCE: f64 = 1e-6;
+/// The maximum number of mismatching bins listed when a comparison fails.
+const MAX_REPORTED_BINS: usize = 10;
+
+/// Get the path of the golden file with the given name.
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.txt"))
+}
+
+/// Serialise an impulse response sparsely: its length on the first line,
+/// followed by the index and value of each non-zero bin.
+fn serialise(impulse_response: &[f64]) -> String {
+    let mut serialised = format!("{}\n", impulse_response.len());
+    for (index, value) in impulse_response.iter().enumerate() {
+        if *value != 0f64 {
+            writeln!(serialised, "{index} {value}").unwrap();
+        }
+    }
+    serialised
+}
+
+/// Read an impulse response serialised by `serialise`.
+fn deserialise(serialised: &str) -> Vec<f64> {
+    let mut lines = serialised.lines();
+    let length = lines
+        .next()
+        .and_then(|line| line.parse::<usize>().ok())
+        .expect("Golden files start with the impulse response's length");
+    let mut impulse_response = vec![0f64; length];
+    for line in lines {
+        let (index, value) = line
+            .split_once(' ')
+            .expect("Golden file lines hold an index and a value");
+        impulse_response[index.parse::<usize>().unwrap()] = value.parse().unwrap();
+    }
+    impulse_response
+}
+
+/// Describe the differences between the expected and actual impulse responses,
+/// or return `None` if they match within the tolerances.
+fn describe_mismatch(expected: &[f64], actual: &[f64]) -> Option<String> {
+    let mut description = String::new();
+    if expected.len() != actual.len() {
+        writeln!(
+            description,
+            "length: expected {}, got {}",
+            expected.len(),
+            actual.len()
+        )
+        .unwrap();
+    }
+    let expected_energy: f64 = expected.iter().sum();
+    let actual_energy: f64 = actual.iter().sum();
+    if (expected_energy - actual_energy).abs() > TOTAL_ENERGY_TOLERANCE * expected_energy.abs() {
+        writeln!(
+            description,
+            "total energy: expected {expected_energy}, got {actual_energy}"
+        )
+        .unwrap();
+    }
+    let bin = |impulse_response: &[f64], index: usize| {
+        impulse_response.get(index).copied().unwrap_or_default()
+    };
+    let mismatches: Vec<usize> = (0..expected.len().max(actual.len()))
+        .filter(|index| (bin(expected, *index) - bin(actual, *index)).abs() > BIN_EPSILON)
+        .collect();
+    if !mismatches.is_empty() {
+        writeln!(description, "{} mismatching bins:", mismatches.len()).unwrap();
+    }
+    for index in mismatches.iter().take(MAX_REPORTED_BINS) {
+        writeln!(
+            description,
+            "\tbin {index}: expected {}, got {}",
+            bin(expected, *index),
+            bin(actual, *index)
+        )
+        .unwrap();
+    }
+    (!description.is_empty()).then_some(description)
+}
+
+/// Simulate the given scene at time 0 and compare the impulse response against the golden file with the given name,
+/// or overwrite the golden file if `UPDATE_GOLDENS` is set.
+fn check_against_golden(name: &str, scene: Scene) {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let impulse_response = scene_data
+        .simulate_at_time(
+            0,
+            GOLDEN_RAYS,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            true,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    assert!(
+        impulse_response.iter().any(|value| *value > 0f64),
+        "{name} doesn't reach the receiver, so it can't catch regressions"
+    );
+
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serialise(&impulse_response)).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "Can't read {}: {err}. Generate it by running with UPDATE_GOLDENS=1.",
+            path.display()
+        )
+    });
+    if let Some(mismatch) = describe_mismatch(&deserialise(&golden), &impulse_response) {
+        panic!(
+            "{name} doesn't match {}:\n{mismatch}If this change is intentional, regenerate the golden files by running with UPDATE_GOLDENS=1.",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn static_cube_scene_matches_golden() {
+    // scene 0 emits randomly into concrete walls, so make it deterministic
+    let mut scene = scene_by_index(0, DEFAULT_SAMPLE_RATE as u32).unwrap();
+    for index in 0..scene.surfaces.len() {
+        scene.set_material(
+            index,
+            Material {
+                absorption_coefficient: ABSORPTION_COEFFICIENT_CONCRETE,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        );
+    }
+    let Emitter::Interpolated(coords, time, _emission_type) = scene.emitter else {
+        panic!("The static cube scene should have a static emitter");
+    };
+    scene.emitter = Emitter::Interpolated(
+        coords,
+        time,
+        EmissionType::Directed(nalgebra::Vector3::new(0.3f64, 0.2f64, -1f64).normalize()),
+    );
+    check_against_golden("scene_0_specular_directed", scene);
+}
+
+#[test]
+fn static_receiver_scene_matches_golden() {
+    check_against_golden(
+        "scene_1",
+        scene_by_index(1, DEFAULT_SAMPLE_RATE as u32).unwrap(),
+    );
+}
+
+#[test]
+fn approaching_receiver_scene_matches_golden() {
+    check_against_golden(
+        "scene_2",
+        scene_by_index(2, DEFAULT_SAMPLE_RATE as u32).unwrap(),
+    );
+}
+
+#[test]
+fn golden_comparison_reports_first_mismatching_bins() {
+    let expected = vec![0f64, 1f64, 0.5f64];
+    assert_eq!(None, describe_mismatch(&expected, &expected));
+    assert_eq!(expected, deserialise(&serialise(&expected)));
+
+    let mismatch = describe_mismatch(&expected, &[0f64, 1f64, 0.25f64, 0f64]).unwrap();
+    assert!(mismatch.contains("length: expected 3, got 4"));
+    assert!(mismatch.contains("total energy: expected 1.5, got 1.25"));
+    assert!(mismatch.contains("bin 2: expected 0.5, got 0.25"));
+}
@@ -0,0 +1,1570 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use approx::assert_abs_diff_eq;
+use demo::{
+    audio_io, bounce, echogram,
+    error::{DemoError, SceneBuildError},
+    estimate,
+    impulse_response::{self, NormalisationMode, PostProcessing, PressureSign, StatisticalTail},
+    materials::{self, Material, MATERIAL_ABSORBER},
+    ray::DEFAULT_PROPAGATION_SPEED,
+    scene::{
+        batch_simulate, CoordinateKeyframe, Emitter, Receiver, Scene, SceneData, SimulationOutput,
+        SNAPSHOT_CACHE_CAPACITY,
+    },
+    scene_builder::{self, SceneBuilder},
+    simulation_config::{IrPrecision, SimulationConfig, TraversalMode},
+    time::{SampleDuration, SampleTime},
+    DEFAULT_SAMPLE_RATE,
+};
+use nalgebra::{Rotation3, Vector3};
+use wav::BitDepth;
+
+fn static_scene_data() -> SceneData<typenum::U10> {
+    // directed emission without any surfaces makes every ray's path deterministic
+    let scene = SceneBuilder::new()
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    SceneData::create_for_scene(scene).unwrap()
+}
+
+fn simulate(
+    input: &BitDepth,
+    single_ir: bool,
+    post_processing: &PostProcessing,
+) -> (Vec<f32>, Vec<f64>) {
+    let SimulationOutput {
+        ir: impulse_response,
+        audio: result,
+        ..
+    } = static_scene_data()
+        .simulate_for_time_span(
+            input,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            0.5f64,
+            false,
+            single_ir,
+            post_processing,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    let BitDepth::ThirtyTwoFloat(result) = result else {
+        panic!("Simulation changed the output's bit depth!")
+    };
+    (result, impulse_response)
+}
+
+#[test]
+fn static_scene_single_ir_matches_multiple_irs() {
+    let samples: Vec<f32> = (0..300u16)
+        .map(|idx| (f32::from(idx) * 0.1f32).sin())
+        .collect();
+    let input = BitDepth::ThirtyTwoFloat(samples.clone());
+
+    let (single_ir_result, impulse_response) = simulate(&input, true, &PostProcessing::default());
+    let (multiple_irs_result, _) = simulate(&input, false, &PostProcessing::default());
+
+    assert!(!impulse_response.is_empty());
+    assert_eq!(
+        samples.len() + impulse_response.len() - 1,
+        single_ir_result.len()
+    );
+    assert_eq!(single_ir_result.len(), multiple_irs_result.len());
+    for (idx, (single, multiple)) in single_ir_result
+        .iter()
+        .zip(&multiple_irs_result)
+        .enumerate()
+    {
+        assert!(
+            (single - multiple).abs() < 1e-6,
+            "Outputs differ at index {idx}: {single} vs {multiple}"
+        );
+    }
+    // the output actually contains the scaled input
+    assert!(single_ir_result.iter().any(|value| value.abs() > 1e-3));
+}
+
+#[test]
+fn post_processing_is_applied_in_both_paths() {
+    let mut samples = vec![0f32; 50];
+    samples[10] = 1f32;
+    let input = BitDepth::ThirtyTwoFloat(samples);
+    let post_processing = PostProcessing {
+        pressure: Some(PressureSign::Alternating),
+        normalise_peak: Some(1f64),
+    };
+
+    let (single_ir_result, impulse_response) = simulate(&input, true, &post_processing);
+    let (multiple_irs_result, _) = simulate(&input, false, &post_processing);
+
+    let peak = impulse_response
+        .iter()
+        .fold(0f64, |peak, value| peak.max(value.abs()));
+    assert!((peak - 1f64).abs() < 1e-9);
+    for result in [single_ir_result, multiple_irs_result] {
+        // the single input sample is scaled by 0.5, with the normalised peak at 1
+        let peak = result
+            .iter()
+            .fold(0f32, |peak, value| peak.max(value.abs()));
+        assert!((peak - 0.5f32).abs() < 1e-6, "Unexpected peak {peak}");
+        // the DC offset is removed from the pressure response
+        assert!(result.iter().sum::<f32>().abs() < 1e-3);
+    }
+}
+
+#[test]
+fn looping_emitter_moves_again_in_second_loop() {
+    const LOOP_DURATION: u32 = 100;
+    let mut scene = SceneBuilder::new()
+        .with_emitter_keyframes(vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(0f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(LOOP_DURATION),
+                coords: Vector3::new(5f64, 0f64, 0f64),
+            },
+        ])
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(20f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    scene.loop_duration = Some(SampleDuration(LOOP_DURATION));
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    let arrival_time = |time: u32| {
+        scene_data
+            .simulate_at_time(
+                time,
+                1,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                false,
+                &SimulationConfig::default(),
+            )
+            .unwrap()
+            .iter()
+            .position(|value| *value != 0f64)
+            .unwrap()
+    };
+
+    for time in [10, 50, 90] {
+        // the emitter is at the same position one loop later, so the arrival is shifted by exactly one loop
+        assert_eq!(
+            arrival_time(time) + LOOP_DURATION as usize,
+            arrival_time(time + LOOP_DURATION)
+        );
+    }
+    // the emitter does move within the loop
+    assert!(arrival_time(10) > arrival_time(90));
+}
+
+#[test]
+fn absorbent_floor_shortens_impulse_response() {
+    // purely specular surfaces and directed emission keep every ray on the same deterministic path
+    let specular = |material: Material| Material {
+        diffusion_coefficient: 0f64,
+        ..material
+    };
+    let impulse_response_length = |absorbent_floor: bool| {
+        let mut scene = SceneBuilder::new()
+            .with_static_cube(
+                (-2f64, -2f64, -1.5f64),
+                (2f64, 2f64, 1.5f64),
+                specular(materials::MATERIAL_CONCRETE_WALL),
+            )
+            .with_emitter_at(0f64, 0f64, 1.2f64)
+            .with_directed_emission(0.3f64, 0.2f64, -1f64)
+            .build()
+            .unwrap();
+        if absorbent_floor {
+            // the bottom face consists of the 9th and 10th triangle
+            scene.set_material_range(8..10, specular(MATERIAL_ABSORBER));
+        }
+        SceneData::<typenum::U10>::create_for_scene(scene)
+            .unwrap()
+            .simulate_at_time(
+                0,
+                200,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                false,
+                &SimulationConfig::default(),
+            )
+            .unwrap()
+            .len()
+    };
+
+    let concrete_length = impulse_response_length(false);
+    let absorbent_length = impulse_response_length(true);
+    assert!(
+        absorbent_length * 2 < concrete_length,
+        "Absorbent floor didn't shorten the impulse response: {absorbent_length} vs {concrete_length}"
+    );
+}
+
+#[test]
+fn looping_simulation_is_deterministic() {
+    const LOOP_DURATION: u32 = 250;
+    let scene = SceneBuilder::new()
+        .with_receiver_keyframes(vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(5f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(LOOP_DURATION),
+                coords: Vector3::new(2f64, 0f64, 0f64),
+            },
+        ])
+        // specular walls make the ray pass the receiver repeatedly, without any randomness
+        .with_static_cube(
+            (-1f64, -3f64, -3f64),
+            (10f64, 3f64, 3f64),
+            Material {
+                absorption_coefficient: 0.5f64,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        )
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .looping(SampleDuration(LOOP_DURATION))
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let input = BitDepth::ThirtyTwoFloat(
+        (0..600u16)
+            .map(|idx| (f32::from(idx) * 0.1f32).sin())
+            .collect(),
+    );
+    let simulate = || {
+        let SimulationOutput {
+            ir: impulse_response,
+            audio: result,
+            ..
+        } = scene_data
+            .simulate_for_time_span(
+                &input,
+                1,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                0.5f64,
+                false,
+                false,
+                &PostProcessing::default(),
+                &SimulationConfig::default(),
+            )
+            .unwrap();
+        let BitDepth::ThirtyTwoFloat(result) = result else {
+            panic!("Simulation changed the output's bit depth!")
+        };
+        (
+            result
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<u32>>(),
+            impulse_response,
+        )
+    };
+
+    let (first_result, first_impulse_response) = simulate();
+    let (second_result, second_impulse_response) = simulate();
+
+    assert!(first_result.iter().any(|value| *value != 0));
+    assert_eq!(first_result, second_result);
+    assert_eq!(first_impulse_response, second_impulse_response);
+}
+
+#[test]
+fn telemetry_accumulates_over_all_impulse_responses() {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(
+        scene_builder::approaching_receiver_scene(DEFAULT_SAMPLE_RATE as u32),
+    )
+    .unwrap();
+    let config = SimulationConfig {
+        threads: Some(1),
+        ..SimulationConfig::default()
+    };
+    let simulate = |do_snapshot_method: bool| {
+        scene_data
+            .simulate_for_time_span(
+                &BitDepth::ThirtyTwoFloat(vec![1f32; 5]),
+                20,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                1f64,
+                do_snapshot_method,
+                false,
+                &PostProcessing::default(),
+                &config,
+            )
+            .unwrap()
+            .telemetry
+    };
+
+    let telemetry = simulate(false);
+    assert_eq!(scene_data.chunk_build_ns, telemetry.chunk_build_ns);
+    assert!(telemetry.ray_launch_ns > 0);
+    assert!(telemetry.ir_construction_ns > 0);
+    // running serially, the parts can't take longer than the whole simulation
+    assert!(
+        telemetry.total_ns
+            >= telemetry.chunk_build_ns + telemetry.ray_launch_ns + telemetry.ir_construction_ns
+    );
+
+    // every snapshot builds its own chunks
+    let snapshot_telemetry = simulate(true);
+    assert!(snapshot_telemetry.chunk_build_ns > scene_data.chunk_build_ns);
+    assert!(
+        snapshot_telemetry.total_ns
+            >= snapshot_telemetry.chunk_build_ns
+                + snapshot_telemetry.ray_launch_ns
+                + snapshot_telemetry.ir_construction_ns
+    );
+}
+
+#[test]
+fn snapshots_are_cached_by_time() {
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(
+        scene_builder::approaching_receiver_scene(DEFAULT_SAMPLE_RATE as u32),
+    )
+    .unwrap();
+    assert!(scene_data.snapshot_cache.is_empty());
+    let first = scene_data.cached_snapshot(100);
+    assert!(Arc::ptr_eq(&first, &scene_data.cached_snapshot(100)));
+    assert!(!Arc::ptr_eq(&first, &scene_data.cached_snapshot(101)));
+    assert_eq!(first.chunks, scene_data.snapshot(100).chunks);
+
+    // simulating with the snapshot method re-uses the cached snapshots
+    let simulate = || {
+        scene_data
+            .simulate_at_time(
+                100,
+                20,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                true,
+                false,
+                &SimulationConfig::default(),
+            )
+            .unwrap()
+    };
+    assert!(!simulate().is_empty());
+    assert_eq!(2, scene_data.snapshot_cache.len());
+
+    for time in 0..SNAPSHOT_CACHE_CAPACITY as u32 + 10 {
+        scene_data.cached_snapshot(time);
+    }
+    assert_eq!(SNAPSHOT_CACHE_CAPACITY, scene_data.snapshot_cache.len());
+}
+
+#[test]
+fn float_output_is_not_clipped() {
+    let input = BitDepth::Eight(vec![200u8; 20]);
+    let (buffer, _, _) = static_scene_data()
+        .simulate_for_time_span_raw(
+            &input,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            1000f64,
+            false,
+            true,
+            &PostProcessing::default(),
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    let (_, had_to_clip) = audio_io::to_bit_depth_of(&buffer, &input);
+    assert!(had_to_clip);
+
+    let float_result = audio_io::to_normalised_f32(&buffer, &input);
+    assert_eq!(buffer.len(), float_result.len());
+    for (raw, float) in buffer.iter().zip(&float_result) {
+        assert!((raw / 128f64 - f64::from(*float)).abs() < 1e-3);
+    }
+    assert!(float_result.iter().any(|value| value.abs() > 2f32));
+}
+
+#[test]
+fn batch_simulate_matches_individual_simulations() {
+    let input = BitDepth::ThirtyTwoFloat(vec![1f32, 0.5f32, -0.25f32]);
+    for batch_size in [2u32, 9] {
+        let configs: Vec<(Scene, SimulationConfig)> = (0..batch_size)
+            .map(|idx| {
+                let scene = SceneBuilder::new()
+                    .with_directed_emission(1f64, 0f64, 0f64)
+                    .with_receiver_at(f64::from(idx) + 2f64, 0f64, 0f64)
+                    .build()
+                    .unwrap();
+                (scene, SimulationConfig::default())
+            })
+            .collect();
+        let progress = Arc::new(Mutex::new(vec![]));
+        let callback_progress = Arc::clone(&progress);
+
+        let results = batch_simulate::<typenum::U10>(
+            &configs,
+            &input,
+            5,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            1f64,
+            false,
+            true,
+            &PostProcessing::default(),
+            Some(Arc::new(move |completed, total| {
+                callback_progress.lock().unwrap().push((completed, total));
+            })),
+        );
+
+        assert_eq!(configs.len(), results.len());
+        for ((scene, config), result) in configs.iter().zip(&results) {
+            let expected = SceneData::<typenum::U10>::create_for_scene(scene.clone())
+                .unwrap()
+                .simulate_for_time_span(
+                    &input,
+                    5,
+                    DEFAULT_PROPAGATION_SPEED,
+                    DEFAULT_SAMPLE_RATE,
+                    1f64,
+                    false,
+                    true,
+                    &PostProcessing::default(),
+                    config,
+                )
+                .unwrap();
+            assert_eq!(&Ok((expected.audio, expected.ir)), result);
+        }
+        let mut progress = progress.lock().unwrap().clone();
+        progress.sort_unstable();
+        let expected_progress: Vec<(usize, usize)> = (1..=configs.len())
+            .map(|completed| (completed, configs.len()))
+            .collect();
+        assert_eq!(expected_progress, progress);
+    }
+}
+
+#[test]
+fn thread_count_does_not_change_result() {
+    let scene = SceneBuilder::new()
+        .with_static_cube(
+            (-1f64, -3f64, -3f64),
+            (10f64, 3f64, 3f64),
+            Material {
+                absorption_coefficient: 0.5f64,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        )
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let input = BitDepth::ThirtyTwoFloat(
+        (0..250u16)
+            .map(|idx| (f32::from(idx) * 0.1f32).sin())
+            .collect(),
+    );
+    let simulate = |threads: usize| {
+        let config = SimulationConfig {
+            threads: Some(threads),
+            samples_per_task: 7,
+            ..SimulationConfig::default()
+        };
+        let SimulationOutput {
+            ir: impulse_response,
+            audio: result,
+            ..
+        } = scene_data
+            .simulate_for_time_span(
+                &input,
+                5,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                0.5f64,
+                false,
+                false,
+                &PostProcessing::default(),
+                &config,
+            )
+            .unwrap();
+        let BitDepth::ThirtyTwoFloat(result) = result else {
+            panic!("Simulation changed the output's bit depth!")
+        };
+        let impulse_response_at_time = scene_data
+            .simulate_at_time(
+                20,
+                5,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                true,
+                &config,
+            )
+            .unwrap();
+        (
+            result
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<u32>>(),
+            impulse_response,
+            impulse_response_at_time,
+        )
+    };
+
+    let serial = simulate(1);
+    let parallel = simulate(4);
+
+    assert!(serial.0.iter().any(|value| *value != 0));
+    assert!(!serial.2.is_empty());
+    assert_eq!(serial, parallel);
+}
+
+#[test]
+fn higher_capture_decay_lengthens_impulse_response() {
+    let scene = SceneBuilder::new()
+        .with_static_cube(
+            (-1f64, -3f64, -3f64),
+            (10f64, 3f64, 3f64),
+            Material {
+                absorption_coefficient: 0.5f64,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        )
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let impulse_response_length = |capture_decay_db: Option<f64>| {
+        scene_data
+            .simulate_at_time(
+                0,
+                250,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                false,
+                &SimulationConfig {
+                    capture_decay_db,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap()
+            .len()
+    };
+
+    let full_length = impulse_response_length(None);
+    let lengths: Vec<usize> = [5f64, 10f64, 20f64, 30f64, 40f64]
+        .into_iter()
+        .map(|decay_db| impulse_response_length(Some(decay_db)))
+        .collect();
+
+    for pair in lengths.windows(2) {
+        assert!(pair[0] < pair[1], "IR lengths didn't increase: {lengths:?}");
+    }
+    assert!(lengths[lengths.len() - 1] < full_length);
+    // the rays are discarded 60 dB below their initial energy, so a higher target can't be reached
+    assert_eq!(full_length, impulse_response_length(Some(100f64)));
+}
+
+/// Simulate the given impulses in a static scene with the given loop duration,
+/// returning the indices of all non-zero output values.
+fn looped_output_indices(
+    loop_duration: Option<SampleDuration>,
+    input_len: usize,
+    impulses: &[usize],
+) -> Vec<usize> {
+    let mut scene = static_scene_data().scene;
+    scene.loop_duration = loop_duration;
+    let mut input = vec![0f32; input_len];
+    for idx in impulses {
+        input[*idx] = 1f32;
+    }
+    let SimulationOutput { audio: result, .. } = SceneData::<typenum::U10>::create_for_scene(scene)
+        .unwrap()
+        .simulate_for_time_span(
+            &BitDepth::ThirtyTwoFloat(input),
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            0.5f64,
+            false,
+            false,
+            &PostProcessing::default(),
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    let BitDepth::ThirtyTwoFloat(result) = result else {
+        panic!("Simulation changed the output's bit depth!")
+    };
+    result
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value != 0f32)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+#[test]
+fn looping_scene_places_samples_at_their_own_index() {
+    const LOOP_DURATION: u32 = 100;
+    let impulse_response = static_scene_data()
+        .simulate_at_time(
+            0,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    let arrivals: Vec<usize> = impulse_response
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value != 0f64)
+        .map(|(idx, _)| idx)
+        .collect();
+    assert!(!arrivals.is_empty());
+    let expected_indices = |impulses: &[usize]| {
+        let mut indices: Vec<usize> = impulses
+            .iter()
+            .flat_map(|impulse| arrivals.iter().map(move |arrival| impulse + arrival))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    };
+
+    // 1.5 loops: samples 30 and 130 share a group, sample 120 is alone in its group
+    let impulses = [30, 120, 130];
+    let looped = looped_output_indices(Some(SampleDuration(LOOP_DURATION)), 150, &impulses);
+    assert_eq!(expected_indices(&impulses), looped);
+    assert_eq!(looped_output_indices(None, 150, &impulses), looped);
+
+    // 0.5 loops
+    let impulses = [10, 40];
+    let looped = looped_output_indices(Some(SampleDuration(LOOP_DURATION)), 50, &impulses);
+    assert_eq!(expected_indices(&impulses), looped);
+    assert_eq!(looped_output_indices(None, 50, &impulses), looped);
+}
+
+#[test]
+fn batch_simulate_reports_invalid_scenes_in_place() {
+    let input = BitDepth::ThirtyTwoFloat(vec![1f32, 0.5f32]);
+    let invalid_scene = Scene {
+        loop_duration: Some(SampleDuration(0)),
+        ..scene_builder::static_receiver_scene()
+    };
+    let configs = vec![
+        (invalid_scene, SimulationConfig::default()),
+        (
+            scene_builder::static_receiver_scene(),
+            SimulationConfig::default(),
+        ),
+    ];
+
+    let results = batch_simulate::<typenum::U10>(
+        &configs,
+        &input,
+        5,
+        DEFAULT_PROPAGATION_SPEED,
+        DEFAULT_SAMPLE_RATE,
+        1f64,
+        false,
+        true,
+        &PostProcessing::default(),
+        None,
+    );
+
+    assert_eq!(
+        Some(&Err(DemoError::SceneBuild(
+            SceneBuildError::ZeroLoopDuration
+        ))),
+        results.first()
+    );
+    assert!(results[1].is_ok());
+}
+
+#[test]
+fn ambisonic_impulse_response_follows_receiver_orientation() {
+    // every ray travels along +X into the receiver, so all sound arrives from -X
+    let scene_data = static_scene_data();
+    let simulate = |orientation: &Rotation3<f64>| {
+        scene_data
+            .simulate_ambisonic_at_time(
+                0,
+                5,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                orientation,
+                false,
+                &SimulationConfig::default(),
+            )
+            .unwrap()
+    };
+
+    let result = simulate(&Rotation3::identity());
+    let total_energy: f64 = result.w.iter().sum();
+    assert!(total_energy > 0f64);
+    assert_abs_diff_eq!(-total_energy, result.x.iter().sum::<f64>(), epsilon = 1e-9);
+    assert!(result
+        .y
+        .iter()
+        .chain(&result.z)
+        .all(|value| value.abs() < 1e-9));
+
+    // turning the receiver by 90 degrees to the left makes it face +Y, so the sound arrives from its left, i.e. +Y
+    let turned = simulate(&Rotation3::from_axis_angle(
+        &Vector3::z_axis(),
+        std::f64::consts::FRAC_PI_2,
+    ));
+    assert_eq!(result.w, turned.w);
+    assert_abs_diff_eq!(total_energy, turned.y.iter().sum::<f64>(), epsilon = 1e-9);
+    assert!(turned
+        .x
+        .iter()
+        .chain(&turned.z)
+        .all(|value| value.abs() < 1e-9));
+}
+
+#[test]
+fn estimate_for_static_receiver_scene() {
+    let scene_data =
+        SceneData::<typenum::U10>::create_for_scene(scene_builder::static_receiver_scene())
+            .unwrap();
+    let estimate = scene_data
+        .estimate(
+            1000,
+            10,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(estimate::PILOT_RAYS, estimate.pilot_rays);
+    // the receiver is one second of sound travel away from the emitter
+    assert!(
+        estimate.impulse_response_length.abs_diff(44100) < 100,
+        "{estimate:?}"
+    );
+    assert!(estimate.mean_bounces.abs() < f64::EPSILON);
+    // there are no surfaces for the rays to leak through
+    assert!(estimate.leaked_fraction.abs() < f64::EPSILON);
+    assert!(estimate.pilot_duration > Duration::ZERO);
+    // the full run traces 20 times as many rays as the pilot
+    assert!(estimate.estimated_duration > estimate.pilot_duration);
+}
+
+#[test]
+fn area_weighted_emission_aims_rays_at_surfaces() {
+    // a single small panel far above the emitter, facing it
+    let scene = SceneBuilder::new()
+        .with_static_panel(
+            [
+                (0f64, 0f64, 20f64),
+                (1f64, 0f64, 20f64),
+                (1f64, 1f64, 20f64),
+                (0f64, 1f64, 20f64),
+            ],
+            MATERIAL_ABSORBER,
+        )
+        .with_receiver_at(50f64, 50f64, 50f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let mean_bounces = |use_area_weighted_emission: bool| {
+        scene_data
+            .estimate(
+                1000,
+                1,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                &SimulationConfig {
+                    use_area_weighted_emission,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap()
+            .mean_bounces
+    };
+
+    assert!(mean_bounces(false) < 0.1);
+    assert!(mean_bounces(true) >= 1f64);
+}
+
+#[test]
+fn dry_wet_mix_blends_input_and_convolved_signal() {
+    let samples: Vec<f32> = (0..200u16)
+        .map(|idx| (f32::from(idx) * 0.1f32).sin())
+        .collect();
+    let input = BitDepth::ThirtyTwoFloat(samples.clone());
+    let simulate_mixed = |dry_wet: f64| {
+        let (buffer, _ir, _telemetry) = static_scene_data()
+            .simulate_for_time_span_raw(
+                &input,
+                10,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                0.5f64,
+                false,
+                true,
+                &PostProcessing::default(),
+                &SimulationConfig {
+                    dry_wet,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap();
+        buffer
+    };
+
+    let dry = simulate_mixed(0f64);
+    let wet = simulate_mixed(1f64);
+    let half = simulate_mixed(0.5f64);
+    let (unmixed, _ir) = simulate(&input, true, &PostProcessing::default());
+
+    assert_eq!(dry.len(), wet.len());
+    // only the input, which is restored exactly when cast back
+    let dry_audio: Vec<f32> = dry.iter().map(|value| *value as f32).collect();
+    assert_eq!(samples, dry_audio[..samples.len()]);
+    assert!(dry_audio[samples.len()..]
+        .iter()
+        .all(|value| value.abs() < f32::EPSILON));
+    // only the convolved signal, like without mixing
+    let wet_audio: Vec<f32> = wet.iter().map(|value| *value as f32).collect();
+    assert_eq!(unmixed, wet_audio);
+    for ((dry, wet), half) in dry.iter().zip(&wet).zip(&half) {
+        assert!((f64::midpoint(*dry, *wet) - half).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn receiver_importance_keeps_direct_sound_unbiased() {
+    // no surfaces, so the only arrival is the direct sound of a distant receiver
+    let scene = SceneBuilder::new()
+        .with_receiver_at(10f64, 0f64, 0f64)
+        .with_receiver_radius(0.5f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let direct_energy = |number_of_rays: u32, receiver_importance: f64| -> f64 {
+        scene_data
+            .simulate_at_time(
+                0,
+                number_of_rays,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                true,
+                &SimulationConfig {
+                    receiver_importance,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap()
+            .iter()
+            .sum()
+    };
+    // the share of randomly emitted rays hitting the receiver
+    let half_angle = (0.5f64 / 10f64).asin();
+    let expected =
+        bounce::random_direction_density(&Vector3::x()) * bounce::cone_solid_angle(half_angle);
+
+    let uniform = direct_energy(1_000_000, 0f64);
+    let importance = direct_energy(20_000, 0.2f64);
+
+    assert!(
+        (importance - expected).abs() < 0.06 * expected,
+        "{importance} vs. {expected}"
+    );
+    // about 300 of the uniformly emitted rays hit the receiver, so this is well above their noise
+    assert!(
+        (uniform - importance).abs() < 0.25 * importance,
+        "{uniform} vs. {importance}"
+    );
+}
+
+#[test]
+fn streaming_simulation_matches_collecting_simulation() {
+    // specular walls and directed emission make every ray bounce back and forth through the receiver
+    let scene = SceneBuilder::new()
+        .with_static_cube(
+            (-2f64, -2f64, -2f64),
+            (2f64, 2f64, 2f64),
+            Material {
+                absorption_coefficient: 0.8,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        )
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(1f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    for config in [
+        SimulationConfig::default(),
+        SimulationConfig {
+            threads: Some(1),
+            normalisation_mode: NormalisationMode::PerReceivedEnergy,
+            ..SimulationConfig::default()
+        },
+    ] {
+        let collected = scene_data
+            .simulate_at_time(
+                0,
+                100,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                true,
+                &config,
+            )
+            .unwrap();
+        let streamed = scene_data
+            .simulate_at_time_streaming(
+                0,
+                100,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                &config,
+            )
+            .unwrap();
+
+        assert!(collected.iter().filter(|value| **value > 0f64).count() > 1);
+        assert_eq!(collected.len(), streamed.len());
+        for (collected, streamed) in collected.iter().zip(&streamed) {
+            assert_abs_diff_eq!(collected, streamed, epsilon = 1e-9);
+        }
+    }
+}
+
+#[test]
+fn transmissive_wall_attenuates_direct_sound() {
+    // a wall in the y-z plane at the given x coordinate, facing the emitter
+    let wall = |x: f64| {
+        [
+            (x, -1f64, -1f64),
+            (x, 1f64, -1f64),
+            (x, 1f64, 1f64),
+            (x, -1f64, 1f64),
+        ]
+    };
+    let window = Material {
+        absorption_coefficient: 0.9,
+        diffusion_coefficient: 0f64,
+        transmission_coefficient: 0.4,
+    };
+    let back_wall = Material {
+        transmission_coefficient: 0f64,
+        ..window
+    };
+    let scene = SceneBuilder::new()
+        .with_static_panel(wall(1f64), window)
+        .with_static_panel(wall(5f64), back_wall)
+        .with_emitter_at(0f64, 0f64, 0f64)
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(3f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+
+    for traversal_mode in [TraversalMode::Chunked, TraversalMode::BruteForce] {
+        let impulse_response = scene_data
+            .simulate_at_time(
+                0,
+                4000,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                true,
+                &SimulationConfig {
+                    traversal_mode,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap();
+
+        // rays reflected by the window leave the scene, transmitted ones pass the receiver,
+        // bounce off the back wall and pass the receiver again, then pass the window's back side
+        let arrivals: Vec<(usize, f64)> = impulse_response
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_time, energy)| *energy > 0f64)
+            .collect();
+        assert_eq!(2, arrivals.len(), "{arrivals:?}");
+        let (direct_time, direct_energy) = arrivals[0];
+        let (reflected_time, reflected_energy) = arrivals[1];
+        assert!((direct_energy - 0.4).abs() < 0.03, "{direct_energy}");
+        assert!(0.9f64.mul_add(-direct_energy, reflected_energy).abs() < 1e-9);
+        // the reflected path is 4m longer
+        let expected_delay = 4f64 / DEFAULT_PROPAGATION_SPEED * DEFAULT_SAMPLE_RATE;
+        assert!(((reflected_time - direct_time) as f64 - expected_delay).abs() <= 1f64);
+    }
+}
+
+#[test]
+fn emitter_only_emits_during_active_ranges() {
+    let active_start = 100usize;
+    let scene = SceneBuilder::new()
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64)
+        .with_emitter_active_ranges(vec![(active_start as u32, 200)])
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let simulate_at = |time: usize| {
+        scene_data
+            .simulate_at_time(
+                time as u32,
+                10,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                true,
+                &SimulationConfig::default(),
+            )
+            .unwrap()
+    };
+
+    assert_eq!(Vec::<f64>::new(), simulate_at(0));
+    let active_impulse_response = simulate_at(active_start);
+    let arrival = active_impulse_response
+        .iter()
+        .position(|value| *value != 0f64)
+        .unwrap();
+    assert!(arrival > active_start);
+
+    let input = BitDepth::ThirtyTwoFloat(vec![1f32; 200]);
+    for single_ir in [false, true] {
+        let (buffer, _ir, _telemetry) = scene_data
+            .simulate_for_time_span_raw(
+                &input,
+                10,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                1f64,
+                false,
+                single_ir,
+                &PostProcessing::default(),
+                &SimulationConfig::default(),
+            )
+            .unwrap();
+        let first_output = buffer.iter().position(|value| *value != 0f64);
+        let last_output = buffer.iter().rposition(|value| *value != 0f64);
+        // the second half of the input arrives after the direct sound's delay, the first half is silent
+        assert_eq!(Some(arrival), first_output, "{single_ir}");
+        assert_eq!(
+            Some(arrival - active_start + 199),
+            last_output,
+            "{single_ir}"
+        );
+        assert_eq!(
+            200 - active_start,
+            buffer.iter().filter(|value| **value != 0f64).count(),
+            "{single_ir}"
+        );
+    }
+}
+
+#[test]
+fn echogram_records_bounces_and_receiver_hits_in_order() {
+    let wall = Material {
+        absorption_coefficient: 0.5,
+        diffusion_coefficient: 0f64,
+        transmission_coefficient: 0f64,
+    };
+    // the ray passes the receiver, bounces off the wall and passes the receiver again
+    let scene = SceneBuilder::new()
+        .with_static_panel(
+            [
+                (2f64, -1f64, -1f64),
+                (2f64, 1f64, -1f64),
+                (2f64, 1f64, 1f64),
+                (2f64, -1f64, 1f64),
+            ],
+            wall,
+        )
+        .with_emitter_at(0f64, 0f64, 0f64)
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(1f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let simulate = |parallel: bool, record_echogram: bool| {
+        scene_data
+            .simulate_at_time_with_echogram(
+                0,
+                2,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                parallel,
+                &SimulationConfig {
+                    record_echogram,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap()
+    };
+
+    let (impulse_response, records) = simulate(false, true);
+    let records = records.unwrap();
+    assert_eq!(6, records.len());
+    let first_ray: Vec<(Option<usize>, f64)> = records[..3]
+        .iter()
+        .map(|record| (record.surface_index, record.energy))
+        .collect();
+    assert_eq!(
+        vec![(None, 1f64), (Some(0), 1f64), (None, 0.5f64)],
+        first_ray
+    );
+    assert!(records[..3]
+        .windows(2)
+        .all(|pair| pair[0].hit_time < pair[1].hit_time));
+    assert_abs_diff_eq!(2f64, records[1].position.x, epsilon = 1e-9);
+    assert_eq!(records[..3], records[3..]);
+    assert_eq!(simulate(false, false).0, impulse_response);
+
+    let csv = echogram::echogram_to_csv(&records, DEFAULT_SAMPLE_RATE);
+    assert_eq!(7, csv.lines().count());
+    assert!(csv.lines().nth(3).unwrap().contains(";0;1;"));
+
+    assert_eq!(None, simulate(false, false).1);
+    assert_eq!(None, simulate(true, true).1);
+}
+
+/// The static cube with specular concrete walls and directed emission, so every ray takes the same path.
+fn specular_cube_scene_data() -> SceneData<typenum::U10> {
+    let mut scene = scene_builder::static_cube_scene();
+    for index in 0..scene.surfaces.len() {
+        scene.set_material(
+            index,
+            Material {
+                absorption_coefficient: materials::ABSORPTION_COEFFICIENT_CONCRETE,
+                diffusion_coefficient: 0f64,
+                transmission_coefficient: 0f64,
+            },
+        );
+    }
+    let Emitter::Interpolated(coords, time, _emission_type) = scene.emitter else {
+        panic!("The static cube scene should have a static emitter");
+    };
+    scene.emitter = Emitter::Interpolated(
+        coords,
+        time,
+        bounce::EmissionType::Directed(Vector3::new(0.3f64, 0.2f64, -1f64).normalize()),
+    );
+    SceneData::create_for_scene(scene).unwrap()
+}
+
+/// Simulate the static cube with specular concrete walls, see `specular_cube_scene_data`.
+fn simulate_specular_cube(velocity: f64, relative_energy_threshold: f64) -> Vec<f64> {
+    specular_cube_scene_data()
+        .simulate_at_time(
+            0,
+            8,
+            velocity,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            true,
+            &SimulationConfig {
+                relative_energy_threshold,
+                ..SimulationConfig::default()
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn higher_energy_threshold_shortens_impulse_response() {
+    let lengths: Vec<usize> = [1e-6, 1e-4, 1e-2]
+        .into_iter()
+        .map(|threshold| simulate_specular_cube(DEFAULT_PROPAGATION_SPEED, threshold).len())
+        .collect();
+    assert!(
+        lengths.windows(2).all(|pair| pair[0] > pair[1]),
+        "{lengths:?}"
+    );
+}
+
+#[test]
+fn faster_propagation_speed_arrives_earlier() {
+    let speed_of_water = 1482f64;
+    let in_air = simulate_specular_cube(DEFAULT_PROPAGATION_SPEED, 1e-6);
+    let in_water = simulate_specular_cube(speed_of_water, 1e-6);
+    let arrival_in_air = impulse_response::direct_sound_arrival(&in_air).unwrap() as f64;
+    let arrival_in_water = impulse_response::direct_sound_arrival(&in_water).unwrap() as f64;
+    // arrivals are rounded to whole samples
+    assert_abs_diff_eq!(
+        arrival_in_air * DEFAULT_PROPAGATION_SPEED / speed_of_water,
+        arrival_in_water,
+        epsilon = 1f64
+    );
+    assert!(in_water.len() < in_air.len());
+}
+
+#[test]
+fn f32_precision_matches_f64_output() {
+    let scene_data = specular_cube_scene_data();
+    let input = BitDepth::Sixteen(
+        (0..40)
+            .map(|idx| (idx * 523 % 2000) as i16 - 1000)
+            .collect(),
+    );
+    for single_ir in [false, true] {
+        let simulate = |ir_precision: IrPrecision| {
+            scene_data
+                .simulate_for_time_span_raw(
+                    &input,
+                    8,
+                    DEFAULT_PROPAGATION_SPEED,
+                    DEFAULT_SAMPLE_RATE,
+                    10000f64,
+                    false,
+                    single_ir,
+                    &PostProcessing::default(),
+                    &SimulationConfig {
+                        relative_energy_threshold: 1e-3,
+                        samples_per_task: 7,
+                        ir_precision,
+                        ..SimulationConfig::default()
+                    },
+                )
+                .unwrap()
+                .0
+        };
+        let in_f64 = simulate(IrPrecision::F64);
+        let in_f32 = simulate(IrPrecision::F32);
+        assert_eq!(in_f64.len(), in_f32.len());
+        let peak = in_f64
+            .iter()
+            .fold(0f64, |peak, value| peak.max(value.abs()));
+        assert!(peak > 0f64);
+        let max_difference = in_f64
+            .iter()
+            .zip(&in_f32)
+            .fold(0f64, |max, (value, other)| max.max((value - other).abs()));
+        assert!(
+            max_difference < 1e-5 * peak,
+            "{max_difference} vs. peak {peak}"
+        );
+    }
+}
+
+#[test]
+fn launch_jitter_spreads_direct_sound_of_moving_emitter() {
+    // the emitter moves away from the receiver at about two thirds of the speed of sound,
+    // so jittered rays arrive up to 1.6 samples apart
+    let scene = SceneBuilder::new()
+        .with_emitter_keyframes(vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(0f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(100),
+                coords: Vector3::new(-0.5f64, 0f64, 0f64),
+            },
+        ])
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(2f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let occupied_bins = |launch_jitter: bool| {
+        scene_data
+            .simulate_at_time(
+                50,
+                200,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                false,
+                &SimulationConfig {
+                    launch_jitter,
+                    ..SimulationConfig::default()
+                },
+            )
+            .unwrap()
+            .iter()
+            .filter(|energy| **energy > 0f64)
+            .count()
+    };
+    assert_eq!(1, occupied_bins(false));
+    assert!(occupied_bins(true) >= 2);
+}
+
+#[test]
+fn ellipsoid_receiver_is_hit_at_its_tip() {
+    let mut scene = SceneBuilder::new()
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64)
+        .build()
+        .unwrap();
+    scene.receiver = Receiver::Ellipsoid(
+        Vector3::new(5f64, 0f64, 0f64),
+        Vector3::new(2f64, 0.1f64, 0.1f64),
+        Rotation3::identity(),
+        0,
+    );
+    let impulse_response = SceneData::<typenum::U10>::create_for_scene(scene)
+        .unwrap()
+        .simulate_at_time(
+            0,
+            1,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            false,
+            false,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+    let arrival = impulse_response.iter().position(|energy| *energy > 0f64);
+    // the ellipsoid's tip is 3m from the emitter
+    let expected = (3f64 * DEFAULT_SAMPLE_RATE / DEFAULT_PROPAGATION_SPEED).round() as usize;
+    assert_eq!(Some(expected), arrival);
+}
+
+#[test]
+fn statistical_tail_extends_geometric_impulse_response() {
+    let builder = SceneBuilder::new()
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .with_receiver_at(5f64, 0f64, 0f64);
+    let geometric = SceneData::<typenum::U10>::create_for_scene(builder.build().unwrap()).unwrap();
+    let tail = StatisticalTail {
+        t60: 0.5f64,
+        level_db: -40f64,
+        onset_time: 0.05f64,
+    };
+    let with_tail = SceneData::<typenum::U10>::create_for_scene(
+        builder.with_statistical_tail(tail).build().unwrap(),
+    )
+    .unwrap();
+    let config = SimulationConfig {
+        threads: Some(1),
+        seed: Some(42),
+        ..SimulationConfig::default()
+    };
+    let simulate = |scene_data: &SceneData<typenum::U10>| {
+        scene_data
+            .simulate_at_time(
+                10,
+                100,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                false,
+                &config,
+            )
+            .unwrap()
+    };
+
+    let geometric_ir = simulate(&geometric);
+    let tail_ir = simulate(&with_tail);
+    let tail_end = 10 + ((0.05f64 + 0.5f64) * DEFAULT_SAMPLE_RATE).round() as usize;
+    assert!(geometric_ir.len() < tail_end);
+    assert_eq!(tail_end, tail_ir.len());
+    assert!(tail_ir[geometric_ir.len()..]
+        .iter()
+        .any(|value| *value > 0f64));
+    // the direct sound is untouched, since the tail starts later
+    assert_eq!(geometric_ir, tail_ir[..geometric_ir.len()].to_vec());
+
+    // the same seed gives the same tail, also when streaming
+    assert_eq!(tail_ir, simulate(&with_tail));
+    assert_eq!(
+        tail_ir,
+        with_tail
+            .simulate_at_time_streaming(
+                10,
+                100,
+                DEFAULT_PROPAGATION_SPEED,
+                DEFAULT_SAMPLE_RATE,
+                false,
+                &config,
+            )
+            .unwrap()
+    );
+}
+
+#[test]
+fn stratified_emission_reduces_direct_sound_variance() {
+    let direct_sound_variance = |builder: SceneBuilder| {
+        let scene_data = SceneData::<typenum::U10>::create_for_scene(
+            builder
+                .with_receiver_at(3f64, 0f64, 0f64)
+                .with_receiver_radius(1f64)
+                .with_emitter_at(0f64, 0f64, 0f64)
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        // without any surfaces, all received energy is direct sound
+        let energies: Vec<f64> = (0..20)
+            .map(|_| {
+                scene_data
+                    .simulate_at_time(
+                        0,
+                        10000,
+                        DEFAULT_PROPAGATION_SPEED,
+                        DEFAULT_SAMPLE_RATE,
+                        false,
+                        false,
+                        &SimulationConfig::default(),
+                    )
+                    .unwrap()
+                    .iter()
+                    .sum::<f64>()
+            })
+            .collect();
+        let mean = energies.iter().sum::<f64>() / energies.len() as f64;
+        assert!(mean > 0f64);
+        energies
+            .iter()
+            .map(|energy| (energy - mean) * (energy - mean))
+            .sum::<f64>()
+            / energies.len() as f64
+    };
+
+    let random_variance = direct_sound_variance(SceneBuilder::new().with_random_emission());
+    let stratified_variance =
+        direct_sound_variance(SceneBuilder::new().with_stratified_emission(1f64));
+    assert!(
+        stratified_variance < random_variance / 4f64,
+        "{stratified_variance} vs. {random_variance}"
+    );
+}
+
+/// Simulate the given input in the given scene with one ray per impulse response,
+/// with the input's first sample simulated at `time_offset`.
+fn simulate_from_time_offset(scene: &Scene, input: &[f32], time_offset: u32) -> Vec<f64> {
+    simulate_from_time_offset_with(scene, input, time_offset, false)
+}
+
+/// Like `simulate_from_time_offset`, optionally only simulating a single impulse response.
+fn simulate_from_time_offset_with(
+    scene: &Scene,
+    input: &[f32],
+    time_offset: u32,
+    single_ir: bool,
+) -> Vec<f64> {
+    let (result, ..) = SceneData::<typenum::U10>::create_for_scene(scene.clone())
+        .unwrap()
+        .simulate_for_time_span_raw(
+            &BitDepth::ThirtyTwoFloat(input.to_vec()),
+            1,
+            DEFAULT_PROPAGATION_SPEED,
+            DEFAULT_SAMPLE_RATE,
+            1f64,
+            false,
+            single_ir,
+            &PostProcessing::default(),
+            &SimulationConfig {
+                time_offset,
+                ..SimulationConfig::default()
+            },
+        )
+        .unwrap();
+    result
+}
+
+#[test]
+fn windowed_simulation_matches_slice_of_full_simulation() {
+    const WINDOW_START: usize = 150;
+    // the directed emission makes every impulse response deterministic
+    let scene = scene_builder::approaching_receiver_scene(DEFAULT_SAMPLE_RATE as u32);
+    let window: Vec<f32> = (0..100u16)
+        .map(|idx| (f32::from(idx) * 0.1f32).sin())
+        .collect();
+    let mut full_input = vec![0f32; WINDOW_START];
+    full_input.extend(&window);
+
+    let full = simulate_from_time_offset(&scene, &full_input, 0);
+    let windowed = simulate_from_time_offset(&scene, &window, WINDOW_START as u32);
+
+    assert!(windowed.iter().any(|value| *value != 0f64));
+    // the silent samples before the window still pad the full run's output to their
+    // (longer) impulse responses' lengths, so only the window's part of it is compared
+    let full_window = &full[WINDOW_START..];
+    assert!(full_window.len() >= windowed.len());
+    assert_eq!(full_window[..windowed.len()].to_vec(), windowed);
+    assert!(full_window[windowed.len()..]
+        .iter()
+        .all(|value| *value == 0f64));
+}
+
+#[test]
+fn windowed_single_ir_simulation_starts_at_time_offset() {
+    const WINDOW_START: u32 = 150;
+    let scene = scene_builder::approaching_receiver_scene(DEFAULT_SAMPLE_RATE as u32);
+    // only the first sample is non-zero, so both paths apply the impulse response at the window's start
+    let mut window = vec![0f32; 100];
+    window[0] = 1f32;
+
+    let single_ir = simulate_from_time_offset_with(&scene, &window, WINDOW_START, true);
+    let per_sample = simulate_from_time_offset_with(&scene, &window, WINDOW_START, false);
+
+    assert!(single_ir.iter().any(|value| *value != 0f64));
+    assert_eq!(per_sample, single_ir);
+    // the receiver is closer at the window's start, so the direct sound arrives earlier than at time 0
+    let arrival = |result: &[f64]| result.iter().position(|value| *value != 0f64);
+    assert!(
+        arrival(&single_ir) < arrival(&simulate_from_time_offset_with(&scene, &window, 0, true))
+    );
+}
+
+#[test]
+fn time_offsets_wrap_around_in_looping_scenes() {
+    const LOOP_DURATION: u32 = 50;
+    let scene = SceneBuilder::new()
+        .with_receiver_keyframes(vec![
+            CoordinateKeyframe {
+                time: SampleTime(0),
+                coords: Vector3::new(5f64, 0f64, 0f64),
+            },
+            CoordinateKeyframe {
+                time: SampleTime(LOOP_DURATION),
+                coords: Vector3::new(2f64, 0f64, 0f64),
+            },
+        ])
+        .with_directed_emission(1f64, 0f64, 0f64)
+        .looping(SampleDuration(LOOP_DURATION))
+        .build()
+        .unwrap();
+    let input: Vec<f32> = (0..120u16)
+        .map(|idx| (f32::from(idx) * 0.1f32).sin())
+        .collect();
+
+    let in_first_loop = simulate_from_time_offset(&scene, &input, 20);
+    assert_eq!(
+        in_first_loop,
+        simulate_from_time_offset(&scene, &input, 20 + 3 * LOOP_DURATION)
+    );
+    // shorter inputs don't take the grouped looping path, but still wrap
+    assert_eq!(
+        simulate_from_time_offset(&scene, &input[..30], 20),
+        simulate_from_time_offset(&scene, &input[..30], 20 + 3 * LOOP_DURATION)
+    );
+    assert_ne!(in_first_loop, simulate_from_time_offset(&scene, &input, 0));
+}
@@ -0,0 +1,143 @@
+use demo::{
+    materials::Material,
+    scene::SceneData,
+    scene_builder,
+    simulation_config::SimulationConfig,
+    sound_field::{GridPoint, GridResult, ReceiverGrid},
+    DEFAULT_SAMPLE_RATE,
+};
+
+/// A 3x3 grid around the static cube's emitter, one meter apart and 1.2m below it.
+const GRID: ReceiverGrid = ReceiverGrid {
+    z: 0f64,
+    x_range: (-1f64, 1f64),
+    y_range: (-1f64, 1f64),
+    step: 1f64,
+};
+
+const fn point(x: f64, y: f64, total_energy: f64, first_arrival: Option<usize>) -> GridPoint {
+    GridPoint {
+        x,
+        y,
+        total_energy,
+        first_arrival,
+    }
+}
+
+#[test]
+fn grid_points_are_listed_row_by_row() {
+    assert_eq!(
+        vec![
+            (-1f64, -1f64),
+            (0f64, -1f64),
+            (1f64, -1f64),
+            (-1f64, 0f64),
+            (0f64, 0f64),
+            (1f64, 0f64),
+            (-1f64, 1f64),
+            (0f64, 1f64),
+            (1f64, 1f64),
+        ],
+        GRID.points()
+    );
+    // the maximum is kept despite rounding errors
+    let grid = ReceiverGrid {
+        x_range: (-2f64, 2f64),
+        step: 0.1f64,
+        ..GRID
+    };
+    assert_eq!(41, grid.xs().len());
+    assert!(ReceiverGrid { step: 0f64, ..GRID }.points().is_empty());
+}
+
+#[test]
+fn grid_results_are_written_as_csv_and_pgm() {
+    let grid = ReceiverGrid {
+        y_range: (0f64, 1f64),
+        ..GRID
+    };
+    let result = GridResult {
+        grid,
+        points: vec![
+            point(-1f64, 0f64, 1f64, Some(3)),
+            point(0f64, 0f64, 1e-2, Some(2)),
+            point(1f64, 0f64, 0f64, None),
+            point(-1f64, 1f64, 1e-3, Some(4)),
+            point(0f64, 1f64, 1e-9, Some(5)),
+            point(1f64, 1f64, 1f64, Some(3)),
+        ],
+    };
+
+    let mut csv = vec![];
+    result.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!("x;y;total_energy;first_arrival_sample", lines[0]);
+    assert_eq!("-1;0;1;3", lines[1]);
+    assert_eq!("1;0;0;", lines[3]);
+    assert_eq!(7, lines.len());
+
+    let mut pgm = vec![];
+    result.write_pgm(&mut pgm).unwrap();
+    // the top row is the one with the largest y, each level is 10 dB apart
+    assert_eq!(
+        "P2\n3 2\n255\n128 0 255\n255 170 0\n",
+        String::from_utf8(pgm).unwrap()
+    );
+}
+
+#[test]
+fn static_cube_sound_field_is_symmetric_about_the_emitter() {
+    // fully absorbing walls and launching rays towards the receiver keep this fast
+    // and limit the energies to the direct sound, so they aren't too noisy
+    let mut scene = scene_builder::static_cube_scene();
+    scene.set_material_range(
+        0..scene.surfaces.len(),
+        Material {
+            absorption_coefficient: 0f64,
+            diffusion_coefficient: 0f64,
+            transmission_coefficient: 0f64,
+        },
+    );
+    let scene_data = SceneData::<typenum::U10>::create_for_scene(scene).unwrap();
+    let config = SimulationConfig {
+        relative_energy_threshold: 1e-2,
+        receiver_importance: 0.5f64,
+        ..SimulationConfig::default()
+    };
+    let result = scene_data
+        .sweep_receiver_grid(&GRID, 0, 4000, 343.2f64, DEFAULT_SAMPLE_RATE, &config)
+        .unwrap();
+    assert_eq!(GRID, result.grid);
+    assert_eq!(9, result.points.len());
+
+    // the points are listed row by row, see `grid_points_are_listed_row_by_row`
+    let at = |x: i32, y: i32| result.points[((y + 1) * 3 + x + 1) as usize];
+    let mirrored_groups = [
+        vec![at(-1, 0), at(1, 0), at(0, -1), at(0, 1)],
+        vec![at(-1, -1), at(-1, 1), at(1, -1), at(1, 1)],
+    ];
+    for group in mirrored_groups {
+        let reference = group[0];
+        assert!(reference.total_energy > 0f64);
+        for point in &group[1..] {
+            // rays hit the receiver's sphere at slightly different distances
+            assert!(
+                reference
+                    .first_arrival
+                    .unwrap()
+                    .abs_diff(point.first_arrival.unwrap())
+                    <= 1
+            );
+            let ratio = point.total_energy / reference.total_energy;
+            assert!(
+                (0.8f64..1.25f64).contains(&ratio),
+                "{point:?} vs. {reference:?}"
+            );
+        }
+    }
+    // the point right below the emitter is the first to receive the direct sound
+    let centre = at(0, 0);
+    assert!(centre.first_arrival < at(1, 0).first_arrival);
+    assert!(at(1, 0).first_arrival < at(1, 1).first_arrival);
+}